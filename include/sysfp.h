@@ -0,0 +1,77 @@
+/*
+ * The C interface of sysfp, built with the `capi` feature.
+ *
+ * Values pass as their bit patterns. The flags and the status are integers
+ * with the encodings below, and every operation writes its status through
+ * its last argument unless it is null.
+ */
+
+#ifndef SYSFP_H
+#define SYSFP_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef uint32_t sysfp_flags;
+typedef uint32_t sysfp_status;
+
+#define SYSFP_ROUND_NEAREST 0u
+#define SYSFP_ROUND_ZERO 1u
+#define SYSFP_ROUND_UP 2u
+#define SYSFP_ROUND_DOWN 3u
+#define SYSFP_ROUND_NEAREST_AWAY 4u
+#define SYSFP_ROUND_ODD 5u
+
+#define SYSFP_FLAGS_ROUNDING 0x7u
+#define SYSFP_FLAGS_FTZ (1u << 3)
+#define SYSFP_FLAGS_DAZ (1u << 4)
+#define SYSFP_FLAGS_DEFAULT_NAN (1u << 5)
+#define SYSFP_FLAGS_TININESS_BEFORE (1u << 6)
+
+#define SYSFP_INVALID (1u << 0)
+#define SYSFP_DENORM (1u << 1)
+#define SYSFP_DIV_ZERO (1u << 2)
+#define SYSFP_OVERFLOW (1u << 3)
+#define SYSFP_UNDERFLOW (1u << 4)
+#define SYSFP_INEXACT (1u << 5)
+
+sysfp_flags sysfp_flags_new(void);
+sysfp_flags sysfp_flags_with_rounding(sysfp_flags flags, uint32_t rounding);
+sysfp_flags sysfp_flags_with_ftz(sysfp_flags flags, bool enabled);
+sysfp_flags sysfp_flags_with_daz(sysfp_flags flags, bool enabled);
+sysfp_flags sysfp_flags_with_default_nan(sysfp_flags flags, bool enabled);
+sysfp_flags sysfp_flags_with_tininess_before(sysfp_flags flags, bool enabled);
+
+uint32_t sysfp_f32_add(sysfp_flags flags, uint32_t l, uint32_t r, sysfp_status *status);
+uint32_t sysfp_f32_sub(sysfp_flags flags, uint32_t l, uint32_t r, sysfp_status *status);
+uint32_t sysfp_f32_mul(sysfp_flags flags, uint32_t l, uint32_t r, sysfp_status *status);
+uint32_t sysfp_f32_div(sysfp_flags flags, uint32_t l, uint32_t r, sysfp_status *status);
+/* With the `fma` feature. */
+uint32_t sysfp_f32_madd(sysfp_flags flags, uint32_t a, uint32_t b, uint32_t c,
+                        sysfp_status *status);
+uint32_t sysfp_f32_round(sysfp_flags flags, uint32_t x, sysfp_status *status);
+uint32_t sysfp_f32_sqrt(sysfp_flags flags, uint32_t x, sysfp_status *status);
+/* With the `convert` feature. */
+uint64_t sysfp_f32_to_f64(sysfp_flags flags, uint32_t x, sysfp_status *status);
+
+uint64_t sysfp_f64_add(sysfp_flags flags, uint64_t l, uint64_t r, sysfp_status *status);
+uint64_t sysfp_f64_sub(sysfp_flags flags, uint64_t l, uint64_t r, sysfp_status *status);
+uint64_t sysfp_f64_mul(sysfp_flags flags, uint64_t l, uint64_t r, sysfp_status *status);
+uint64_t sysfp_f64_div(sysfp_flags flags, uint64_t l, uint64_t r, sysfp_status *status);
+/* With the `fma` feature. */
+uint64_t sysfp_f64_madd(sysfp_flags flags, uint64_t a, uint64_t b, uint64_t c,
+                        sysfp_status *status);
+uint64_t sysfp_f64_round(sysfp_flags flags, uint64_t x, sysfp_status *status);
+uint64_t sysfp_f64_sqrt(sysfp_flags flags, uint64_t x, sysfp_status *status);
+/* With the `convert` feature. */
+uint32_t sysfp_f64_to_f32(sysfp_flags flags, uint64_t x, sysfp_status *status);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,579 @@
+use core::arch::asm;
+
+use crate::{soft, Tininess};
+
+const ROUND_MASK: u32 = 0b11 << 22;
+const FLUSH_ZERO: u32 = 1 << 24;
+const DENORMALS_ZERO: u32 = 1 << 5;
+const DEFAULT_NAN: u32 = 1 << 25;
+const TININESS_BEFORE: u32 = 1 << 6;
+const TIES_AWAY: u32 = 1 << 7;
+const TO_ODD: u32 = 1 << 8;
+const EXCEPT_MASK: u32 = 0x9F;
+
+/// A rounding mode, laid out as the `u32` of its bits in the flags.
+#[repr(u32)]
+pub enum Rounding {
+    /// Rounds towards zero.
+    Zero = 0b11 << 22,
+    /// Rounds towards positive infinity.
+    Up = 0b01 << 22,
+    /// Rounds towards negative infinity.
+    Down = 0b10 << 22,
+    /// Rounds towards nearest.
+    Nearest = 0b00 << 22,
+    /// Rounds towards nearest, ties away from zero, which the hardware lacks.
+    NearestAway = TIES_AWAY,
+    /// Rounds towards zero, setting the last bit of inexact results, which
+    /// the hardware lacks.
+    Odd = TO_ODD,
+}
+
+/// The flags set for the operation.
+///
+/// The layout is stable: a `u32` holding the bits the operations load into
+/// FPSCR, with the settings it lacks in bits it leaves free.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Flags {
+    inner: u32,
+}
+
+impl Default for Flags {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Flags {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: TININESS_BEFORE,
+        }
+    }
+
+    #[inline]
+    pub const fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.set_rounding(rounding);
+        self
+    }
+
+    #[inline]
+    pub fn with_ftz(mut self, enabled: bool) -> Self {
+        self.set_ftz(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_daz(mut self, enabled: bool) -> Self {
+        self.set_daz(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_default_nan(mut self, enabled: bool) -> Self {
+        self.set_default_nan(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_tininess(mut self, tininess: Tininess) -> Self {
+        self.set_tininess(tininess);
+        self
+    }
+
+    #[inline]
+    pub const fn set_rounding(&mut self, rounding: Rounding) {
+        self.inner = (self.inner & !(ROUND_MASK | TIES_AWAY | TO_ODD)) | rounding as u32;
+    }
+
+    #[inline]
+    pub fn rounding(self) -> Rounding {
+        match self.inner & (ROUND_MASK | TIES_AWAY | TO_ODD) {
+            b if b == Rounding::Zero as u32 => Rounding::Zero,
+            b if b == Rounding::Up as u32 => Rounding::Up,
+            b if b == Rounding::Down as u32 => Rounding::Down,
+            b if b == Rounding::NearestAway as u32 => Rounding::NearestAway,
+            b if b == Rounding::Odd as u32 => Rounding::Odd,
+            _ => Rounding::Nearest,
+        }
+    }
+
+    #[inline]
+    pub fn set_ftz(&mut self, enabled: bool) {
+        self.inner = (self.inner & !FLUSH_ZERO) | if enabled { FLUSH_ZERO } else { 0 }
+    }
+
+    #[inline]
+    pub fn ftz(self) -> bool {
+        self.inner & FLUSH_ZERO != 0
+    }
+
+    #[inline]
+    pub fn set_daz(&mut self, enabled: bool) {
+        self.inner = (self.inner & !DENORMALS_ZERO) | if enabled { DENORMALS_ZERO } else { 0 }
+    }
+
+    #[inline]
+    pub fn daz(self) -> bool {
+        self.inner & DENORMALS_ZERO != 0
+    }
+
+    /// Makes every NaN result the default NaN, regardless of the operands.
+    #[inline]
+    pub fn set_default_nan(&mut self, enabled: bool) {
+        self.inner = (self.inner & !DEFAULT_NAN) | if enabled { DEFAULT_NAN } else { 0 }
+    }
+
+    #[inline]
+    pub fn default_nan(self) -> bool {
+        self.inner & DEFAULT_NAN != 0
+    }
+
+    /// Selects when results are tiny, which the hardware detects
+    /// before rounding.
+    #[inline]
+    pub fn set_tininess(&mut self, tininess: Tininess) {
+        self.inner = (self.inner & !TININESS_BEFORE)
+            | match tininess {
+                Tininess::BeforeRounding => TININESS_BEFORE,
+                Tininess::AfterRounding => 0,
+            }
+    }
+
+    #[inline]
+    pub fn tininess(self) -> Tininess {
+        if self.inner & TININESS_BEFORE != 0 {
+            Tininess::BeforeRounding
+        } else {
+            Tininess::AfterRounding
+        }
+    }
+
+    /// Whether the rounding mode is one the hardware lacks, so that the
+    /// operations run in software.
+    #[inline]
+    pub(crate) fn soft_rounding(self) -> bool {
+        self.inner & (TIES_AWAY | TO_ODD) != 0
+    }
+
+    /// The environment emulating the FPU for operations it lacks.
+    #[inline]
+    pub(crate) fn soft_env(self) -> soft::Env {
+        soft::Env {
+            mode: match self.rounding() {
+                Rounding::Zero => soft::Mode::Zero,
+                Rounding::Up => soft::Mode::Up,
+                Rounding::Down => soft::Mode::Down,
+                Rounding::Nearest => soft::Mode::Nearest,
+                Rounding::NearestAway => soft::Mode::NearestAway,
+                Rounding::Odd => soft::Mode::Odd,
+            },
+            // FPSCR.FZ flushes both inputs and results.
+            ftz: self.ftz(),
+            daz: self.ftz() || self.daz(),
+            default_nan: self.default_nan(),
+            sem: soft::Semantics {
+                tininess_before_rounding: self.tininess() == Tininess::BeforeRounding,
+                ..soft::Semantics::ARM
+            },
+        }
+    }
+
+    /// The FPSCR image, without the bits only tracked in software.
+    #[inline]
+    fn fpscr(self) -> u32 {
+        self.inner & !(DENORMALS_ZERO | TININESS_BEFORE | TIES_AWAY | TO_ODD)
+    }
+}
+
+/// The status from the operations.
+///
+/// The layout is stable: a `u32` holding the exception bits of FPSCR.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Status {
+    inner: u32,
+}
+
+impl Status {
+    pub const INVALID: Self = Self { inner: 1 << 0 };
+    pub const OVERFLOW: Self = Self { inner: 1 << 2 };
+    pub const UNDERFLOW: Self = Self { inner: 1 << 3 };
+    pub const INEXACT: Self = Self { inner: 1 << 4 };
+    pub const DENORM: Self = Self { inner: 1 << 7 };
+    pub const DIV_ZERO: Self = Self { inner: 1 << 1 };
+
+    #[inline]
+    pub fn empty() -> Self {
+        Self { inner: 0 }
+    }
+
+    #[inline]
+    pub fn has_exceptions(self) -> bool {
+        self.inner & EXCEPT_MASK != 0
+    }
+
+    #[inline]
+    pub fn invalid(self) -> bool {
+        self.has(Self::INVALID)
+    }
+
+    #[inline]
+    pub fn overflow(self) -> bool {
+        self.has(Self::OVERFLOW)
+    }
+
+    #[inline]
+    pub fn underflow(self) -> bool {
+        self.has(Self::UNDERFLOW)
+    }
+
+    #[inline]
+    pub fn inexact(self) -> bool {
+        self.has(Self::INEXACT)
+    }
+
+    #[inline]
+    pub fn denorm(self) -> bool {
+        self.has(Self::DENORM)
+    }
+
+    #[inline]
+    pub fn div_zero(self) -> bool {
+        self.has(Self::DIV_ZERO)
+    }
+
+    #[inline]
+    pub fn has(self, status: Self) -> bool {
+        self.inner & status.inner == status.inner
+    }
+
+    #[inline]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            inner: self.inner | other.inner,
+        }
+    }
+
+    #[inline]
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            inner: self.inner & other.inner,
+        }
+    }
+
+    /// Translates exceptions raised by the software implementation.
+    #[inline]
+    pub(crate) fn from_soft(exc: u32) -> Self {
+        [
+            (soft::INVALID, Self::INVALID),
+            (soft::DENORM, Self::DENORM),
+            (soft::DIV_ZERO, Self::DIV_ZERO),
+            (soft::OVERFLOW, Self::OVERFLOW),
+            (soft::UNDERFLOW, Self::UNDERFLOW),
+            (soft::INEXACT, Self::INEXACT),
+        ]
+        .iter()
+        .filter(|(bit, _)| exc & bit != 0)
+        .fold(Self::empty(), |status, &(_, flag)| status.or(flag))
+    }
+}
+
+// The single-precision-only FPUs lack the `vfp2` feature that `sreg`
+// operands require, so values travel through core registers and the scratch
+// registers are saved around the operation.
+macro_rules! host_op {
+    ($flags:ident; $asm:literal; $($end:tt)* ) => {
+        unsafe {
+            let status: u32;
+            asm!(
+                "vpush {{s0-s3}}",
+                "vmsr fpscr, {flags}",
+                $asm,
+                "vmrs {status}, fpscr",
+                "vpop {{s0-s3}}",
+                flags = in(reg) $flags.fpscr(),
+                status = out(reg) status,
+                $($end)*
+            );
+            status
+        }
+    };
+}
+
+/// Runs an operation in software when only denormal inputs should be
+/// flushed, which the flush-to-zero bit can't express on its own.
+macro_rules! soft_op {
+    ($flags:ident; $op:path; $ty:ident; $($arg:expr),+) => {
+        if $flags.daz() && !$flags.ftz() {
+            let (bits, exc) = $op(&$flags.soft_env(), $($arg.to_bits() as u64),+);
+            return ($ty::from_bits(bits as _), Status::from_soft(exc));
+        }
+    };
+}
+
+/// Whether `madd` runs on a fused instruction, which double precision
+/// lacks here.
+pub(crate) const FUSED_MADD: bool = false;
+
+pub mod f32 {
+    use super::*;
+
+    pub use crate::bits::f32::*;
+    pub use crate::bounds::f32::*;
+    pub use crate::functions::f32::*;
+    pub use crate::sign::f32::*;
+    #[cfg(feature = "batch")]
+    pub use crate::slice::f32 as slice;
+    pub use crate::sticky::f32::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::add::<soft::Binary32>; f32; l, r);
+        let mut l = l.to_bits();
+        let status = host_op!(
+            flags;
+            "vmov s0, {l}\nvmov s1, {r}\nvadd.f32 s0, s0, s1\nvmov {l}, s0";
+            l = inout(reg) l,
+            r = in(reg) r.to_bits()
+        );
+        (f32::from_bits(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::sub::<soft::Binary32>; f32; l, r);
+        let mut l = l.to_bits();
+        let status = host_op!(
+            flags;
+            "vmov s0, {l}\nvmov s1, {r}\nvsub.f32 s0, s0, s1\nvmov {l}, s0";
+            l = inout(reg) l,
+            r = in(reg) r.to_bits()
+        );
+        (f32::from_bits(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::mul::<soft::Binary32>; f32; l, r);
+        let mut l = l.to_bits();
+        let status = host_op!(
+            flags;
+            "vmov s0, {l}\nvmov s1, {r}\nvmul.f32 s0, s0, s1\nvmov {l}, s0";
+            l = inout(reg) l,
+            r = in(reg) r.to_bits(),
+        );
+        (f32::from_bits(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::div::<soft::Binary32>; f32; l, r);
+        let mut l = l.to_bits();
+        let status = host_op!(
+            flags;
+            "vmov s0, {l}\nvmov s1, {r}\nvdiv.f32 s0, s0, s1\nvmov {l}, s0";
+            l = inout(reg) l,
+            r = in(reg) r.to_bits(),
+        );
+        (f32::from_bits(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f32, b: f32, c: f32) -> (f32, Status) {
+        soft_op!(flags; soft::fma::<soft::Binary32>; f32; a, b, c);
+        let mut c = c.to_bits();
+        let status = host_op!(
+            flags;
+            "vmov s0, {a}\nvmov s1, {b}\nvmov s2, {c}\nvfma.f32 s2, s0, s1\nvmov {c}, s2";
+            a = in(reg) a.to_bits(),
+            b = in(reg) b.to_bits(),
+            c = inout(reg) c,
+        );
+        (f32::from_bits(c), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f32) -> (f32, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary32>(&flags.soft_env(), x.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f32) -> (f32, Status) {
+        soft_op!(flags; soft::sqrt::<soft::Binary32>; f32; x);
+        let mut x = x.to_bits();
+        let status = host_op!(
+            flags;
+            "vmov s0, {x}\nvsqrt.f32 s0, s0\nvmov {x}, s0";
+            x = inout(reg) x,
+        );
+        (f32::from_bits(x), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, single: f32) -> (f64, Status) {
+        let (bits, exc) = soft::convert::<soft::Binary32, soft::Binary64>(
+            &flags.soft_env(),
+            single.to_bits() as u64,
+        );
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status) {
+        let (bits, exc) = soft::convert::<soft::Binary32, soft::Binary16>(
+            &flags.soft_env(),
+            single.to_bits() as u64,
+        );
+        (bits as u16, Status::from_soft(exc))
+    }
+}
+
+/// The FPU only implements single precision, so double precision is
+/// emulated with the same rounding, flushing and exception behavior.
+pub mod f64 {
+    use super::*;
+
+    pub use crate::bits::f64::*;
+    pub use crate::bounds::f64::*;
+    pub use crate::functions::f64::*;
+    pub use crate::sign::f64::*;
+    #[cfg(feature = "batch")]
+    pub use crate::slice::f64 as slice;
+    pub use crate::sticky::f64::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let (bits, exc) = soft::add::<soft::Binary64>(&flags.soft_env(), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let (bits, exc) = soft::sub::<soft::Binary64>(&flags.soft_env(), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let (bits, exc) = soft::mul::<soft::Binary64>(&flags.soft_env(), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let (bits, exc) = soft::div::<soft::Binary64>(&flags.soft_env(), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f64, b: f64, c: f64) -> (f64, Status) {
+        let (bits, exc) =
+            soft::fma::<soft::Binary64>(&flags.soft_env(), a.to_bits(), b.to_bits(), c.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f64) -> (f64, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary64>(&flags.soft_env(), x.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f64) -> (f64, Status) {
+        let (bits, exc) = soft::sqrt::<soft::Binary64>(&flags.soft_env(), x.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, double: f64) -> (f32, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary64, soft::Binary32>(&flags.soft_env(), double.to_bits());
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary64, soft::Binary16>(&flags.soft_env(), double.to_bits());
+        (bits as u16, Status::from_soft(exc))
+    }
+}
+
+/// Half-precision operations, taking and returning the bit patterns of the
+/// values since `f16` isn't a stable Rust type.
+pub mod f16 {
+    use super::*;
+
+    pub use crate::sticky::f16::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::add::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::sub::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::mul::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::div::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: u16, b: u16, c: u16) -> (u16, Status) {
+        let (bits, exc) =
+            soft::fma::<soft::Binary16>(&flags.soft_env(), a as u64, b as u64, c as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: u16) -> (u16, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary16>(&flags.soft_env(), x as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: u16) -> (u16, Status) {
+        let (bits, exc) = soft::sqrt::<soft::Binary16>(&flags.soft_env(), x as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, half: u16) -> (f32, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary16, soft::Binary32>(&flags.soft_env(), half as u64);
+        (f32::from_bits(bits as _), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, half: u16) -> (f64, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary16, soft::Binary64>(&flags.soft_env(), half as u64);
+        (f64::from_bits(bits as _), Status::from_soft(exc))
+    }
+}
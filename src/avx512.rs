@@ -0,0 +1,411 @@
+//! The special-case instructions of AVX-512, `vfixupimm` and `vrange`, so
+//! emulators can patch special results, such as applying a guest's NaN
+//! rules, through a table instead of branching.
+//!
+//! They run on the instructions where the CPU has them, `vfixupimm` needing
+//! AVX-512F and `vrange` AVX-512DQ, and otherwise in software with the
+//! results and exceptions of the hardware, on any host.
+//!
+//! Denormal operands are zeros when the flags set `daz`. The results are
+//! never rounded, so the other flags don't apply.
+
+use crate::soft::{Binary32, Binary64, Format};
+use crate::{Flags, Status};
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "x86"),
+        target_feature = "sse2",
+        not(any(feature = "soft-float", feature = "x87", miri))
+    ))] {
+        use crate::imp::avx512 as imp;
+    } else {
+        use soft as imp;
+    }
+}
+
+/// The class of the operand `fixup` looks up in its table.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    QuietNan,
+    SignalingNan,
+    Zero,
+    PosOne,
+    NegInfinity,
+    PosInfinity,
+    /// Any other negative value.
+    Negative,
+    /// Any other positive value.
+    Positive,
+}
+
+/// The result `fixup` gives for a token.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Response {
+    /// The destination, unchanged.
+    Dest,
+    /// The operand, after `daz` applied.
+    Src,
+    /// The operand with every exponent bit and the quiet bit set, which
+    /// quiets NaNs.
+    QuietSrc,
+    /// The QNaN floating-point indefinite.
+    DefaultNan,
+    NegInfinity,
+    PosInfinity,
+    /// The infinity with the sign of the operand.
+    Infinity,
+    NegZero,
+    PosZero,
+    NegOne,
+    PosOne,
+    Half,
+    Ninety,
+    HalfPi,
+    Max,
+    NegMax,
+}
+
+/// An exception `fixup` raises when it looks up a token.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    ZeroDivZero,
+    ZeroInvalid,
+    OneDivZero,
+    OneInvalid,
+    SignalingNanInvalid,
+    NegInfinityInvalid,
+    NegativeInvalid,
+    PosInfinityInvalid,
+}
+
+/// The response of every token and the faults they raise, as `vfixupimm`
+/// takes them in its table operand and immediate.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FixupTable {
+    table: u32,
+    imm8: u8,
+}
+
+impl Default for FixupTable {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixupTable {
+    /// The table keeping the destination for every token, raising nothing.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { table: 0, imm8: 0 }
+    }
+
+    #[inline]
+    pub const fn from_bits(table: u32, imm8: u8) -> Self {
+        Self { table, imm8 }
+    }
+
+    /// The low 32 bits of the table operand.
+    #[inline]
+    pub const fn table(self) -> u32 {
+        self.table
+    }
+
+    /// The immediate selecting the faults.
+    #[inline]
+    pub const fn imm8(self) -> u8 {
+        self.imm8
+    }
+
+    #[inline]
+    pub const fn with_response(mut self, token: Token, response: Response) -> Self {
+        let shift = 4 * token as u32;
+        self.table = self.table & !(0xF << shift) | (response as u32) << shift;
+        self
+    }
+
+    #[inline]
+    pub const fn with_fault(mut self, fault: Fault) -> Self {
+        self.imm8 |= 1 << fault as u8;
+        self
+    }
+
+    #[inline]
+    pub fn response(self, token: Token) -> Response {
+        const RESPONSES: [Response; 16] = [
+            Response::Dest,
+            Response::Src,
+            Response::QuietSrc,
+            Response::DefaultNan,
+            Response::NegInfinity,
+            Response::PosInfinity,
+            Response::Infinity,
+            Response::NegZero,
+            Response::PosZero,
+            Response::NegOne,
+            Response::PosOne,
+            Response::Half,
+            Response::Ninety,
+            Response::HalfPi,
+            Response::Max,
+            Response::NegMax,
+        ];
+        RESPONSES[(self.table >> (4 * token as u32) & 0xF) as usize]
+    }
+
+    #[inline]
+    pub fn has_fault(self, fault: Fault) -> bool {
+        self.imm8 & 1 << fault as u8 != 0
+    }
+}
+
+/// Which operand `range` selects.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RangeOp {
+    /// The smaller, `-0` being below `+0`.
+    Min,
+    /// The larger, `+0` being above `-0`.
+    Max,
+    /// The one of smaller magnitude, or the smaller if they are equal.
+    MinAbs,
+    /// The one of larger magnitude, or the larger if they are equal.
+    MaxAbs,
+}
+
+/// The sign `range` gives its result.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RangeSign {
+    /// The sign of the first operand.
+    First,
+    /// The sign of the selected operand.
+    Selected,
+    Clear,
+    Set,
+}
+
+/// The formats and the constants their responses need.
+trait Special: Format {
+    const HALF_PI: u64;
+}
+
+impl Special for Binary32 {
+    const HALF_PI: u64 = 0x3FC9_0FDB;
+}
+
+impl Special for Binary64 {
+    const HALF_PI: u64 = 0x3FF9_21FB_5444_2D18;
+}
+
+#[inline]
+fn is_nan<F: Format>(x: u64) -> bool {
+    x & !F::SIGN > F::EXP_MASK
+}
+
+#[inline]
+fn is_signaling<F: Format>(x: u64) -> bool {
+    is_nan::<F>(x) && x & F::QUIET == 0
+}
+
+#[inline]
+fn is_denormal<F: Format>(x: u64) -> bool {
+    x & F::EXP_MASK == 0 && x & F::FRAC_MASK != 0
+}
+
+/// `x` as zero if it is denormal and the flags set `daz`.
+#[inline]
+fn operand<F: Format>(flags: Flags, x: u64) -> u64 {
+    if flags.daz() && is_denormal::<F>(x) {
+        x & F::SIGN
+    } else {
+        x
+    }
+}
+
+#[inline]
+fn token<F: Format>(x: u64) -> Token {
+    let one = (F::BIAS as u64) << F::FRAC_BITS;
+    match x {
+        _ if is_signaling::<F>(x) => Token::SignalingNan,
+        _ if is_nan::<F>(x) => Token::QuietNan,
+        _ if x & !F::SIGN == 0 => Token::Zero,
+        _ if x == one => Token::PosOne,
+        _ if x == F::SIGN | F::EXP_MASK => Token::NegInfinity,
+        _ if x == F::EXP_MASK => Token::PosInfinity,
+        _ if x & F::SIGN != 0 => Token::Negative,
+        _ => Token::Positive,
+    }
+}
+
+#[inline]
+fn fixup<F: Special>(flags: Flags, dest: u64, src: u64, table: FixupTable) -> (u64, Status) {
+    let src = operand::<F>(flags, src);
+    let token = token::<F>(src);
+    let one = (F::BIAS as u64) << F::FRAC_BITS;
+    let max = F::EXP_MASK - 1;
+    let value = match table.response(token) {
+        Response::Dest => dest,
+        Response::Src => src,
+        Response::QuietSrc => src | F::EXP_MASK | F::QUIET,
+        Response::DefaultNan => F::SIGN | F::EXP_MASK | F::QUIET,
+        Response::NegInfinity => F::SIGN | F::EXP_MASK,
+        Response::PosInfinity => F::EXP_MASK,
+        Response::Infinity => src & F::SIGN | F::EXP_MASK,
+        Response::NegZero => F::SIGN,
+        Response::PosZero => 0,
+        Response::NegOne => F::SIGN | one,
+        Response::PosOne => one,
+        Response::Half => one - (1 << F::FRAC_BITS),
+        Response::Ninety => one + (6 << F::FRAC_BITS) + (13 << (F::FRAC_BITS - 5)),
+        Response::HalfPi => F::HALF_PI,
+        Response::Max => max,
+        Response::NegMax => F::SIGN | max,
+    };
+    let (div_zero, invalid) = match token {
+        Token::Zero => (Some(Fault::ZeroDivZero), Some(Fault::ZeroInvalid)),
+        Token::PosOne => (Some(Fault::OneDivZero), Some(Fault::OneInvalid)),
+        Token::SignalingNan => (None, Some(Fault::SignalingNanInvalid)),
+        Token::NegInfinity => (None, Some(Fault::NegInfinityInvalid)),
+        Token::Negative => (None, Some(Fault::NegativeInvalid)),
+        Token::PosInfinity => (None, Some(Fault::PosInfinityInvalid)),
+        Token::QuietNan | Token::Positive => (None, None),
+    };
+    let raised = |fault: Option<Fault>| fault.is_some_and(|fault| table.has_fault(fault));
+    let mut status = Status::empty();
+    if raised(div_zero) {
+        status = status.or(Status::DIV_ZERO);
+    }
+    if raised(invalid) {
+        status = status.or(Status::INVALID);
+    }
+    (value, status)
+}
+
+#[inline]
+fn range<F: Format>(flags: Flags, l: u64, r: u64, op: RangeOp, sign: RangeSign) -> (u64, Status) {
+    let (l, r) = (operand::<F>(flags, l), operand::<F>(flags, r));
+    if is_signaling::<F>(l) {
+        return (l | F::QUIET, Status::INVALID);
+    }
+    if is_signaling::<F>(r) {
+        return (r | F::QUIET, Status::INVALID);
+    }
+    let mut status = Status::empty();
+    let selected = if is_nan::<F>(r) {
+        l
+    } else if is_nan::<F>(l) {
+        r
+    } else {
+        if is_denormal::<F>(l) || is_denormal::<F>(r) {
+            status = Status::DENORM;
+        }
+        // Orders the values with `-0` below `+0`.
+        let key = |x: u64| {
+            let magnitude = (x & !F::SIGN) as i64;
+            if x & F::SIGN != 0 {
+                -magnitude - 1
+            } else {
+                magnitude
+            }
+        };
+        let magnitude = |x: u64| x & !F::SIGN;
+        let smaller = if key(l) <= key(r) { l } else { r };
+        let larger = if key(l) <= key(r) { r } else { l };
+        match op {
+            RangeOp::Min => smaller,
+            RangeOp::Max => larger,
+            RangeOp::MinAbs if magnitude(l) == magnitude(r) => smaller,
+            RangeOp::MinAbs if magnitude(l) < magnitude(r) => l,
+            RangeOp::MinAbs => r,
+            RangeOp::MaxAbs if magnitude(l) == magnitude(r) => larger,
+            RangeOp::MaxAbs if magnitude(l) > magnitude(r) => l,
+            RangeOp::MaxAbs => r,
+        }
+    };
+    let value = match sign {
+        RangeSign::First => selected & !F::SIGN | l & F::SIGN,
+        RangeSign::Selected => selected,
+        RangeSign::Clear => selected & !F::SIGN,
+        RangeSign::Set => selected | F::SIGN,
+    };
+    (value, status)
+}
+
+/// The instructions run in software.
+pub(crate) mod soft {
+    use super::*;
+
+    #[inline]
+    pub(crate) fn f32_fixup(flags: Flags, dest: f32, src: f32, table: FixupTable) -> (f32, Status) {
+        let (bits, status) =
+            fixup::<Binary32>(flags, dest.to_bits() as u64, src.to_bits() as u64, table);
+        (f32::from_bits(bits as u32), status)
+    }
+
+    #[inline]
+    pub(crate) fn f32_range(
+        flags: Flags,
+        l: f32,
+        r: f32,
+        op: RangeOp,
+        sign: RangeSign,
+    ) -> (f32, Status) {
+        let (bits, status) =
+            range::<Binary32>(flags, l.to_bits() as u64, r.to_bits() as u64, op, sign);
+        (f32::from_bits(bits as u32), status)
+    }
+
+    #[inline]
+    pub(crate) fn f64_fixup(flags: Flags, dest: f64, src: f64, table: FixupTable) -> (f64, Status) {
+        let (bits, status) = fixup::<Binary64>(flags, dest.to_bits(), src.to_bits(), table);
+        (f64::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub(crate) fn f64_range(
+        flags: Flags,
+        l: f64,
+        r: f64,
+        op: RangeOp,
+        sign: RangeSign,
+    ) -> (f64, Status) {
+        let (bits, status) = range::<Binary64>(flags, l.to_bits(), r.to_bits(), op, sign);
+        (f64::from_bits(bits), status)
+    }
+}
+
+pub mod f32 {
+    use super::*;
+
+    /// Replaces `dest` by the response the table gives for the class of
+    /// `src`, as `vfixupimmss` does.
+    #[inline]
+    pub fn fixup(flags: Flags, dest: f32, src: f32, table: FixupTable) -> (f32, Status) {
+        imp::f32_fixup(flags, dest, src, table)
+    }
+
+    /// Selects `l` or `r` by `op`, as `vrangess` does. A signaling NaN
+    /// operand gives itself quieted, and a quiet NaN the other operand.
+    #[inline]
+    pub fn range(flags: Flags, l: f32, r: f32, op: RangeOp, sign: RangeSign) -> (f32, Status) {
+        imp::f32_range(flags, l, r, op, sign)
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    /// Replaces `dest` by the response the table gives for the class of
+    /// `src`, as `vfixupimmsd` does.
+    #[inline]
+    pub fn fixup(flags: Flags, dest: f64, src: f64, table: FixupTable) -> (f64, Status) {
+        imp::f64_fixup(flags, dest, src, table)
+    }
+
+    /// Selects `l` or `r` by `op`, as `vrangesd` does. A signaling NaN
+    /// operand gives itself quieted, and a quiet NaN the other operand.
+    #[inline]
+    pub fn range(flags: Flags, l: f64, r: f64, op: RangeOp, sign: RangeSign) -> (f64, Status) {
+        imp::f64_range(flags, l, r, op, sign)
+    }
+}
@@ -0,0 +1,136 @@
+//! The operations on raw bit patterns, for emulators holding guest registers
+//! as integers.
+//!
+//! The patterns only ever move through integers and memory on their way to
+//! and from the operations, so no NaN is canonicalized or quieted outside of
+//! what the operation itself does.
+
+pub(crate) mod f32 {
+    use crate::{Flags, Status};
+
+    #[inline]
+    pub fn add_bits(flags: Flags, l: u32, r: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::add(flags, f32::from_bits(l), f32::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn sub_bits(flags: Flags, l: u32, r: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::sub(flags, f32::from_bits(l), f32::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn mul_bits(flags: Flags, l: u32, r: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::mul(flags, f32::from_bits(l), f32::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn div_bits(flags: Flags, l: u32, r: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::div(flags, f32::from_bits(l), f32::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_bits(flags: Flags, a: u32, b: u32, c: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::madd(
+            flags,
+            f32::from_bits(a),
+            f32::from_bits(b),
+            f32::from_bits(c),
+        );
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn round_bits(flags: Flags, x: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::round(flags, f32::from_bits(x));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn sqrt_bits(flags: Flags, x: u32) -> (u32, Status) {
+        let (value, status) = crate::f32::sqrt(flags, f32::from_bits(x));
+        (value.to_bits(), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double_bits(flags: Flags, single: u32) -> (u64, Status) {
+        let (value, status) = crate::f32::to_double(flags, f32::from_bits(single));
+        (value.to_bits(), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half_bits(flags: Flags, single: u32) -> (u16, Status) {
+        crate::f32::to_half(flags, f32::from_bits(single))
+    }
+}
+
+pub(crate) mod f64 {
+    use crate::{Flags, Status};
+
+    #[inline]
+    pub fn add_bits(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::add(flags, f64::from_bits(l), f64::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn sub_bits(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::sub(flags, f64::from_bits(l), f64::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn mul_bits(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::mul(flags, f64::from_bits(l), f64::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn div_bits(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::div(flags, f64::from_bits(l), f64::from_bits(r));
+        (value.to_bits(), status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_bits(flags: Flags, a: u64, b: u64, c: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::madd(
+            flags,
+            f64::from_bits(a),
+            f64::from_bits(b),
+            f64::from_bits(c),
+        );
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn round_bits(flags: Flags, x: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::round(flags, f64::from_bits(x));
+        (value.to_bits(), status)
+    }
+
+    #[inline]
+    pub fn sqrt_bits(flags: Flags, x: u64) -> (u64, Status) {
+        let (value, status) = crate::f64::sqrt(flags, f64::from_bits(x));
+        (value.to_bits(), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single_bits(flags: Flags, double: u64) -> (u32, Status) {
+        let (value, status) = crate::f64::to_single(flags, f64::from_bits(double));
+        (value.to_bits(), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half_bits(flags: Flags, double: u64) -> (u16, Status) {
+        crate::f64::to_half(flags, f64::from_bits(double))
+    }
+}
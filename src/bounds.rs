@@ -0,0 +1,170 @@
+//! Operations rounded several ways in one call: both down and up, giving the
+//! bounds of the exact result for interval arithmetic, or in every rounding
+//! mode, for studying and validating rounding errors.
+//!
+//! Each runs the operation once in every direction, under the other flags of
+//! the caller. The bounds combine the status of both directions, while every
+//! mode keeps its own.
+
+use crate::{Flags, Rounding, Status};
+
+#[inline]
+fn bounds<T>(flags: Flags, op: impl Fn(Flags) -> (T, Status)) -> (T, T, Status) {
+    let (down, low) = op(flags.with_rounding(Rounding::Down));
+    let (up, high) = op(flags.with_rounding(Rounding::Up));
+    (down, up, low.or(high))
+}
+
+/// Runs `op` to nearest, down, up and toward zero, in that order.
+#[inline]
+fn roundings<T>(flags: Flags, op: impl Fn(Flags) -> (T, Status)) -> [(T, Status); 4] {
+    [
+        Rounding::Nearest,
+        Rounding::Down,
+        Rounding::Up,
+        Rounding::Zero,
+    ]
+    .map(|rounding| op(flags.with_rounding(rounding)))
+}
+
+pub(crate) mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add_bounds(flags: Flags, l: f32, r: f32) -> (f32, f32, Status) {
+        bounds(flags, |flags| crate::f32::add(flags, l, r))
+    }
+
+    #[inline]
+    pub fn sub_bounds(flags: Flags, l: f32, r: f32) -> (f32, f32, Status) {
+        bounds(flags, |flags| crate::f32::sub(flags, l, r))
+    }
+
+    #[inline]
+    pub fn mul_bounds(flags: Flags, l: f32, r: f32) -> (f32, f32, Status) {
+        bounds(flags, |flags| crate::f32::mul(flags, l, r))
+    }
+
+    #[inline]
+    pub fn div_bounds(flags: Flags, l: f32, r: f32) -> (f32, f32, Status) {
+        bounds(flags, |flags| crate::f32::div(flags, l, r))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_bounds(flags: Flags, a: f32, b: f32, c: f32) -> (f32, f32, Status) {
+        bounds(flags, |flags| crate::f32::madd(flags, a, b, c))
+    }
+
+    #[inline]
+    pub fn sqrt_bounds(flags: Flags, x: f32) -> (f32, f32, Status) {
+        bounds(flags, |flags| crate::f32::sqrt(flags, x))
+    }
+
+    #[inline]
+    pub fn add_roundings(flags: Flags, l: f32, r: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::add(flags, l, r))
+    }
+
+    #[inline]
+    pub fn sub_roundings(flags: Flags, l: f32, r: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::sub(flags, l, r))
+    }
+
+    #[inline]
+    pub fn mul_roundings(flags: Flags, l: f32, r: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::mul(flags, l, r))
+    }
+
+    #[inline]
+    pub fn div_roundings(flags: Flags, l: f32, r: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::div(flags, l, r))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_roundings(flags: Flags, a: f32, b: f32, c: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::madd(flags, a, b, c))
+    }
+
+    #[inline]
+    pub fn sqrt_roundings(flags: Flags, x: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::sqrt(flags, x))
+    }
+
+    #[inline]
+    pub fn round_roundings(flags: Flags, x: f32) -> [(f32, Status); 4] {
+        roundings(flags, |flags| crate::f32::round(flags, x))
+    }
+}
+
+pub(crate) mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add_bounds(flags: Flags, l: f64, r: f64) -> (f64, f64, Status) {
+        bounds(flags, |flags| crate::f64::add(flags, l, r))
+    }
+
+    #[inline]
+    pub fn sub_bounds(flags: Flags, l: f64, r: f64) -> (f64, f64, Status) {
+        bounds(flags, |flags| crate::f64::sub(flags, l, r))
+    }
+
+    #[inline]
+    pub fn mul_bounds(flags: Flags, l: f64, r: f64) -> (f64, f64, Status) {
+        bounds(flags, |flags| crate::f64::mul(flags, l, r))
+    }
+
+    #[inline]
+    pub fn div_bounds(flags: Flags, l: f64, r: f64) -> (f64, f64, Status) {
+        bounds(flags, |flags| crate::f64::div(flags, l, r))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_bounds(flags: Flags, a: f64, b: f64, c: f64) -> (f64, f64, Status) {
+        bounds(flags, |flags| crate::f64::madd(flags, a, b, c))
+    }
+
+    #[inline]
+    pub fn sqrt_bounds(flags: Flags, x: f64) -> (f64, f64, Status) {
+        bounds(flags, |flags| crate::f64::sqrt(flags, x))
+    }
+
+    #[inline]
+    pub fn add_roundings(flags: Flags, l: f64, r: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::add(flags, l, r))
+    }
+
+    #[inline]
+    pub fn sub_roundings(flags: Flags, l: f64, r: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::sub(flags, l, r))
+    }
+
+    #[inline]
+    pub fn mul_roundings(flags: Flags, l: f64, r: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::mul(flags, l, r))
+    }
+
+    #[inline]
+    pub fn div_roundings(flags: Flags, l: f64, r: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::div(flags, l, r))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_roundings(flags: Flags, a: f64, b: f64, c: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::madd(flags, a, b, c))
+    }
+
+    #[inline]
+    pub fn sqrt_roundings(flags: Flags, x: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::sqrt(flags, x))
+    }
+
+    #[inline]
+    pub fn round_roundings(flags: Flags, x: f64) -> [(f64, Status); 4] {
+        roundings(flags, |flags| crate::f64::round(flags, x))
+    }
+}
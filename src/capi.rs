@@ -0,0 +1,198 @@
+//! The scalar operations as C functions, for the `capi` feature, declared in
+//! `include/sysfp.h`.
+//!
+//! The functions take the flags and give the status as integers with the
+//! fixed encodings below, which don't depend on the backend, and pass values
+//! as their bit patterns, as the C calling convention returns floats on the
+//! x87 stack on some targets, which quiets signaling NaNs. Every operation
+//! writes its status through the last argument unless it is null.
+
+use crate::{Flags, Rounding, Status, Tininess};
+
+pub const SYSFP_ROUND_NEAREST: u32 = 0;
+pub const SYSFP_ROUND_ZERO: u32 = 1;
+pub const SYSFP_ROUND_UP: u32 = 2;
+pub const SYSFP_ROUND_DOWN: u32 = 3;
+pub const SYSFP_ROUND_NEAREST_AWAY: u32 = 4;
+pub const SYSFP_ROUND_ODD: u32 = 5;
+
+/// The bits of the flags holding the rounding mode.
+pub const SYSFP_FLAGS_ROUNDING: u32 = 0b111;
+pub const SYSFP_FLAGS_FTZ: u32 = 1 << 3;
+pub const SYSFP_FLAGS_DAZ: u32 = 1 << 4;
+pub const SYSFP_FLAGS_DEFAULT_NAN: u32 = 1 << 5;
+/// Results are tiny before rounding rather than after.
+pub const SYSFP_FLAGS_TININESS_BEFORE: u32 = 1 << 6;
+
+pub const SYSFP_INVALID: u32 = 1 << 0;
+pub const SYSFP_DENORM: u32 = 1 << 1;
+pub const SYSFP_DIV_ZERO: u32 = 1 << 2;
+pub const SYSFP_OVERFLOW: u32 = 1 << 3;
+pub const SYSFP_UNDERFLOW: u32 = 1 << 4;
+pub const SYSFP_INEXACT: u32 = 1 << 5;
+
+/// The exceptions with their encodings.
+const EXCEPTIONS: [(Status, u32); 6] = [
+    (Status::INVALID, SYSFP_INVALID),
+    (Status::DENORM, SYSFP_DENORM),
+    (Status::DIV_ZERO, SYSFP_DIV_ZERO),
+    (Status::OVERFLOW, SYSFP_OVERFLOW),
+    (Status::UNDERFLOW, SYSFP_UNDERFLOW),
+    (Status::INEXACT, SYSFP_INEXACT),
+];
+
+/// Decodes `flags`, an unknown rounding mode rounding to nearest.
+#[inline]
+fn decode(flags: u32) -> Flags {
+    let rounding = match flags & SYSFP_FLAGS_ROUNDING {
+        SYSFP_ROUND_ZERO => Rounding::Zero,
+        SYSFP_ROUND_UP => Rounding::Up,
+        SYSFP_ROUND_DOWN => Rounding::Down,
+        SYSFP_ROUND_NEAREST_AWAY => Rounding::NearestAway,
+        SYSFP_ROUND_ODD => Rounding::Odd,
+        _ => Rounding::Nearest,
+    };
+    let tininess = if flags & SYSFP_FLAGS_TININESS_BEFORE != 0 {
+        Tininess::BeforeRounding
+    } else {
+        Tininess::AfterRounding
+    };
+    Flags::new()
+        .with_rounding(rounding)
+        .with_ftz(flags & SYSFP_FLAGS_FTZ != 0)
+        .with_daz(flags & SYSFP_FLAGS_DAZ != 0)
+        .with_default_nan(flags & SYSFP_FLAGS_DEFAULT_NAN != 0)
+        .with_tininess(tininess)
+}
+
+#[inline]
+fn encode(flags: Flags) -> u32 {
+    let rounding = match flags.rounding() {
+        Rounding::Nearest => SYSFP_ROUND_NEAREST,
+        Rounding::Zero => SYSFP_ROUND_ZERO,
+        Rounding::Up => SYSFP_ROUND_UP,
+        Rounding::Down => SYSFP_ROUND_DOWN,
+        Rounding::NearestAway => SYSFP_ROUND_NEAREST_AWAY,
+        Rounding::Odd => SYSFP_ROUND_ODD,
+    };
+    let before = flags.tininess() == Tininess::BeforeRounding;
+    let flags_bits = with_bit(rounding, SYSFP_FLAGS_FTZ, flags.ftz());
+    let flags_bits = with_bit(flags_bits, SYSFP_FLAGS_DAZ, flags.daz());
+    let flags_bits = with_bit(flags_bits, SYSFP_FLAGS_DEFAULT_NAN, flags.default_nan());
+    with_bit(flags_bits, SYSFP_FLAGS_TININESS_BEFORE, before)
+}
+
+#[inline]
+fn report(out: Option<&mut u32>, status: Status) {
+    if let Some(out) = out {
+        *out = EXCEPTIONS
+            .iter()
+            .filter(|(exception, _)| status.has(*exception))
+            .fold(0, |bits, (_, bit)| bits | bit);
+    }
+}
+
+/// The default flags of the backend: rounding to nearest, without flushing,
+/// and with the tininess of the host.
+#[no_mangle]
+pub extern "C" fn sysfp_flags_new() -> u32 {
+    encode(Flags::new())
+}
+
+#[no_mangle]
+pub extern "C" fn sysfp_flags_with_rounding(flags: u32, rounding: u32) -> u32 {
+    flags & !SYSFP_FLAGS_ROUNDING | rounding & SYSFP_FLAGS_ROUNDING
+}
+
+#[no_mangle]
+pub extern "C" fn sysfp_flags_with_ftz(flags: u32, enabled: bool) -> u32 {
+    with_bit(flags, SYSFP_FLAGS_FTZ, enabled)
+}
+
+#[no_mangle]
+pub extern "C" fn sysfp_flags_with_daz(flags: u32, enabled: bool) -> u32 {
+    with_bit(flags, SYSFP_FLAGS_DAZ, enabled)
+}
+
+#[no_mangle]
+pub extern "C" fn sysfp_flags_with_default_nan(flags: u32, enabled: bool) -> u32 {
+    with_bit(flags, SYSFP_FLAGS_DEFAULT_NAN, enabled)
+}
+
+#[no_mangle]
+pub extern "C" fn sysfp_flags_with_tininess_before(flags: u32, enabled: bool) -> u32 {
+    with_bit(flags, SYSFP_FLAGS_TININESS_BEFORE, enabled)
+}
+
+#[inline]
+fn with_bit(flags: u32, bit: u32, enabled: bool) -> u32 {
+    flags & !bit | if enabled { bit } else { 0 }
+}
+
+macro_rules! unary {
+    ($name:ident, $float:ident, $bits:ty, $op:ident, $to_bits:ty) => {
+        #[no_mangle]
+        pub extern "C" fn $name(flags: u32, x: $bits, status: Option<&mut u32>) -> $to_bits {
+            let (value, op_status) = crate::$float::$op(decode(flags), $float::from_bits(x));
+            report(status, op_status);
+            value.to_bits()
+        }
+    };
+}
+
+macro_rules! binary {
+    ($name:ident, $float:ident, $bits:ty, $op:ident) => {
+        #[no_mangle]
+        pub extern "C" fn $name(flags: u32, l: $bits, r: $bits, status: Option<&mut u32>) -> $bits {
+            let (value, op_status) =
+                crate::$float::$op(decode(flags), $float::from_bits(l), $float::from_bits(r));
+            report(status, op_status);
+            value.to_bits()
+        }
+    };
+}
+
+#[cfg(feature = "fma")]
+macro_rules! ternary {
+    ($name:ident, $float:ident, $bits:ty, $op:ident) => {
+        #[no_mangle]
+        pub extern "C" fn $name(
+            flags: u32,
+            a: $bits,
+            b: $bits,
+            c: $bits,
+            status: Option<&mut u32>,
+        ) -> $bits {
+            let (value, op_status) = crate::$float::$op(
+                decode(flags),
+                $float::from_bits(a),
+                $float::from_bits(b),
+                $float::from_bits(c),
+            );
+            report(status, op_status);
+            value.to_bits()
+        }
+    };
+}
+
+binary!(sysfp_f32_add, f32, u32, add);
+binary!(sysfp_f32_sub, f32, u32, sub);
+binary!(sysfp_f32_mul, f32, u32, mul);
+binary!(sysfp_f32_div, f32, u32, div);
+#[cfg(feature = "fma")]
+ternary!(sysfp_f32_madd, f32, u32, madd);
+unary!(sysfp_f32_round, f32, u32, round, u32);
+unary!(sysfp_f32_sqrt, f32, u32, sqrt, u32);
+#[cfg(feature = "convert")]
+unary!(sysfp_f32_to_f64, f32, u32, to_double, u64);
+
+binary!(sysfp_f64_add, f64, u64, add);
+binary!(sysfp_f64_sub, f64, u64, sub);
+binary!(sysfp_f64_mul, f64, u64, mul);
+binary!(sysfp_f64_div, f64, u64, div);
+#[cfg(feature = "fma")]
+ternary!(sysfp_f64_madd, f64, u64, madd);
+unary!(sysfp_f64_round, f64, u64, round, u64);
+unary!(sysfp_f64_sqrt, f64, u64, sqrt, u64);
+#[cfg(feature = "convert")]
+unary!(sysfp_f64_to_f32, f64, u64, to_single, u32);
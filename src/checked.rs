@@ -0,0 +1,161 @@
+//! Operations failing with an `FpError` when they raise any exception of a
+//! mask, for treating exceptions such as overflow or invalid operations as
+//! errors.
+
+use core::fmt;
+
+use crate::{Flags, Status};
+
+/// The exceptions an operation raised, at least one of them in the mask it
+/// was given.
+#[derive(Clone, Copy)]
+pub struct FpError {
+    status: Status,
+}
+
+impl FpError {
+    /// Every exception the operation raised, including those outside the
+    /// mask.
+    #[inline]
+    pub fn status(self) -> Status {
+        self.status
+    }
+}
+
+impl fmt::Display for FpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let exceptions = [
+            (self.status.invalid(), "invalid operation"),
+            (self.status.div_zero(), "division by zero"),
+            (self.status.overflow(), "overflow"),
+            (self.status.underflow(), "underflow"),
+            (self.status.inexact(), "inexact result"),
+            (self.status.denorm(), "denormal operand"),
+        ];
+        f.write_str("floating-point exception")?;
+        let mut separator = ": ";
+        for (_, name) in exceptions.iter().filter(|&&(raised, _)| raised) {
+            f.write_str(separator)?;
+            f.write_str(name)?;
+            separator = ", ";
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FpError")
+            .field("invalid", &self.status.invalid())
+            .field("div_zero", &self.status.div_zero())
+            .field("overflow", &self.status.overflow())
+            .field("underflow", &self.status.underflow())
+            .field("inexact", &self.status.inexact())
+            .field("denorm", &self.status.denorm())
+            .finish()
+    }
+}
+
+impl core::error::Error for FpError {}
+
+/// `value`, or the error if `status` raised an exception in `mask`.
+#[inline]
+fn check<T>(mask: Status, (value, status): (T, Status)) -> Result<T, FpError> {
+    if status.and(mask).has_exceptions() {
+        Err(FpError { status })
+    } else {
+        Ok(value)
+    }
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, mask: Status, l: f32, r: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::add(flags, l, r))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, mask: Status, l: f32, r: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::sub(flags, l, r))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, mask: Status, l: f32, r: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::mul(flags, l, r))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, mask: Status, l: f32, r: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::div(flags, l, r))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, mask: Status, a: f32, b: f32, c: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::madd(flags, a, b, c))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, mask: Status, x: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::round(flags, x))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, mask: Status, x: f32) -> Result<f32, FpError> {
+        check(mask, crate::f32::sqrt(flags, x))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, mask: Status, single: f32) -> Result<f64, FpError> {
+        check(mask, crate::f32::to_double(flags, single))
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, mask: Status, l: f64, r: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::add(flags, l, r))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, mask: Status, l: f64, r: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::sub(flags, l, r))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, mask: Status, l: f64, r: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::mul(flags, l, r))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, mask: Status, l: f64, r: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::div(flags, l, r))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, mask: Status, a: f64, b: f64, c: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::madd(flags, a, b, c))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, mask: Status, x: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::round(flags, x))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, mask: Status, x: f64) -> Result<f64, FpError> {
+        check(mask, crate::f64::sqrt(flags, x))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, mask: Status, double: f64) -> Result<f32, FpError> {
+        check(mask, crate::f64::to_single(flags, double))
+    }
+}
@@ -0,0 +1,273 @@
+//! Complex arithmetic with the special values of Annex G of the C standard:
+//! a product or quotient with an infinite part is infinite even where the
+//! plain formulas give a NaN, and a quotient by zero is infinite.
+//!
+//! The products and quotients follow the sample implementations of the
+//! annex, but compute `a * b ± c * d` with Kahan's algorithm, rounding the
+//! second product, recovering its error with a fused multiply-add and
+//! fusing the first product into it, where the plain formulas cancel. That
+//! keeps each part of a product within an ulp, and a quotient within a few
+//! ulps of its larger part, rounding to nearest. Every step rounds under the
+//! flags, and the status gathers their exceptions but for the roundings
+//! the algorithm recovers, and for the NaNs of the plain formulas where the
+//! annex recovers an infinite or zero result instead.
+
+use crate::{Flags, Status};
+
+/// The complex number `re + im * i`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Complex<T> {
+    pub re: T,
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    #[inline]
+    pub fn new(re: T, im: T) -> Self {
+        Self { re, im }
+    }
+}
+
+macro_rules! complex {
+    ($float:ident, $bits:ty) => {
+        pub mod $float {
+            use super::*;
+
+            /// The steps of an operation, gathering their status.
+            struct Steps {
+                flags: Flags,
+                status: Status,
+            }
+
+            impl Steps {
+                #[inline]
+                fn new(flags: Flags) -> Self {
+                    Self {
+                        flags,
+                        status: Status::empty(),
+                    }
+                }
+
+                #[inline]
+                fn raise(&mut self, (value, status): ($float, Status)) -> $float {
+                    self.status = self.status.or(status);
+                    value
+                }
+
+                /// Raises the exceptions of a rounding the following steps
+                /// recover, which are lost only below the normal range.
+                #[inline]
+                fn raise_exact(&mut self, (value, status): ($float, Status)) -> $float {
+                    if !value.is_finite() || status.underflow() {
+                        return self.raise((value, status));
+                    }
+                    let exceptions = Status::INVALID
+                        .or(Status::DIV_ZERO)
+                        .or(Status::OVERFLOW)
+                        .or(Status::DENORM);
+                    self.raise((value, status.and(exceptions)))
+                }
+
+                #[inline]
+                fn mul(&mut self, l: $float, r: $float) -> $float {
+                    self.raise(crate::$float::mul(self.flags, l, r))
+                }
+
+                #[inline]
+                fn div(&mut self, l: $float, r: $float) -> $float {
+                    self.raise(crate::$float::div(self.flags, l, r))
+                }
+
+                /// Computes `a * b + c * d`.
+                fn dot(&mut self, a: $float, b: $float, c: $float, d: $float) -> $float {
+                    let product = self.raise_exact(crate::$float::mul(self.flags, c, d));
+                    if !product.is_finite() {
+                        return self.raise(crate::$float::madd(self.flags, a, b, product));
+                    }
+                    let error = self.raise(crate::$float::madd(self.flags, c, d, -product));
+                    let sum = self.raise(crate::$float::madd(self.flags, a, b, product));
+                    // A zero error leaves the sign of a zero sum as it is.
+                    if error == 0.0 {
+                        return sum;
+                    }
+                    self.raise(crate::$float::add(self.flags, sum, error))
+                }
+
+                /// Multiplies `x` by `2^n`.
+                fn scale(&mut self, x: $float, n: i32) -> $float {
+                    // Powers below the subnormal range are never needed, and
+                    // above the normal range take two steps.
+                    if n >= $float::MAX_EXP {
+                        let half = self.mul(x, pow2(n / 2));
+                        return self.mul(half, pow2(n - n / 2));
+                    }
+                    self.mul(x, pow2(n))
+                }
+            }
+
+            /// The power of two `2^n`, for `n` from the exponent of the
+            /// smallest subnormal to that of the largest normal.
+            #[inline]
+            fn pow2(n: i32) -> $float {
+                let frac_bits = $float::MANTISSA_DIGITS - 1;
+                let min_exp = $float::MIN_EXP - 1;
+                if n >= min_exp {
+                    $float::from_bits(((n + $float::MAX_EXP - 1) as $bits) << frac_bits)
+                } else {
+                    $float::from_bits(1 << (n - min_exp + frac_bits as i32))
+                }
+            }
+
+            /// The exponent of a finite nonzero `x`, the floor of `log2 |x|`.
+            #[inline]
+            fn logb(x: $float) -> i32 {
+                let frac_bits = $float::MANTISSA_DIGITS - 1;
+                let bits = x.abs().to_bits();
+                let biased = (bits >> frac_bits) as i32;
+                if biased == 0 {
+                    let top = <$bits>::BITS - 1 - bits.leading_zeros();
+                    top as i32 - frac_bits as i32 + $float::MIN_EXP - 1
+                } else {
+                    biased - ($float::MAX_EXP - 1)
+                }
+            }
+
+            /// One with the sign of `x` if it is infinite, or zero with the
+            /// sign of `x`.
+            #[inline]
+            fn boxed(x: $float) -> $float {
+                let magnitude: $float = if x.is_infinite() { 1.0 } else { 0.0 };
+                magnitude.copysign(x)
+            }
+
+            /// Zero with the sign of `x` if it is a NaN, or `x`.
+            #[inline]
+            fn unnan(x: $float) -> $float {
+                if x.is_nan() {
+                    (0.0 as $float).copysign(x)
+                } else {
+                    x
+                }
+            }
+
+            #[inline]
+            pub fn add(
+                flags: Flags,
+                l: Complex<$float>,
+                r: Complex<$float>,
+            ) -> (Complex<$float>, Status) {
+                let mut steps = Steps::new(flags);
+                let re = steps.raise(crate::$float::add(flags, l.re, r.re));
+                let im = steps.raise(crate::$float::add(flags, l.im, r.im));
+                (Complex::new(re, im), steps.status)
+            }
+
+            #[inline]
+            pub fn sub(
+                flags: Flags,
+                l: Complex<$float>,
+                r: Complex<$float>,
+            ) -> (Complex<$float>, Status) {
+                let mut steps = Steps::new(flags);
+                let re = steps.raise(crate::$float::sub(flags, l.re, r.re));
+                let im = steps.raise(crate::$float::sub(flags, l.im, r.im));
+                (Complex::new(re, im), steps.status)
+            }
+
+            pub fn mul(
+                flags: Flags,
+                l: Complex<$float>,
+                r: Complex<$float>,
+            ) -> (Complex<$float>, Status) {
+                let (mut a, mut b, mut c, mut d) = (l.re, l.im, r.re, r.im);
+                let mut steps = Steps::new(flags);
+                let re = steps.dot(a, c, -b, d);
+                let im = steps.dot(a, d, b, c);
+                if !(re.is_nan() && im.is_nan()) {
+                    return (Complex::new(re, im), steps.status);
+                }
+                // Recovers the infinities the formulas lost to NaNs.
+                let mut recover = false;
+                if a.is_infinite() || b.is_infinite() {
+                    (a, b, c, d) = (boxed(a), boxed(b), unnan(c), unnan(d));
+                    recover = true;
+                }
+                if c.is_infinite() || d.is_infinite() {
+                    (a, b, c, d) = (unnan(a), unnan(b), boxed(c), boxed(d));
+                    recover = true;
+                }
+                if !recover && [a * c, b * d, a * d, b * c].iter().any(|p| p.is_infinite()) {
+                    (a, b, c, d) = (unnan(a), unnan(b), unnan(c), unnan(d));
+                    recover = true;
+                }
+                if !recover {
+                    return (Complex::new(re, im), steps.status);
+                }
+                let mut steps = Steps::new(flags);
+                let (re, im) = (steps.dot(a, c, -b, d), steps.dot(a, d, b, c));
+                let re = steps.mul($float::INFINITY, re);
+                let im = steps.mul($float::INFINITY, im);
+                (Complex::new(re, im), steps.status)
+            }
+
+            /// Divides `l` by `r`, scaling `r` by a power of two near its
+            /// magnitude first so that its squared magnitude can't overflow
+            /// or underflow.
+            pub fn div(
+                flags: Flags,
+                l: Complex<$float>,
+                r: Complex<$float>,
+            ) -> (Complex<$float>, Status) {
+                let (mut a, mut b, mut c, mut d) = (l.re, l.im, r.re, r.im);
+                let mut steps = Steps::new(flags);
+                let magnitude = c.abs().max(d.abs());
+                let exp = if magnitude.is_finite() && magnitude != 0.0 {
+                    logb(magnitude)
+                } else {
+                    0
+                };
+                if exp != 0 {
+                    (c, d) = (steps.scale(c, -exp), steps.scale(d, -exp));
+                }
+                let denom = steps.dot(c, c, d, d);
+                let re = steps.dot(a, c, b, d);
+                let re = steps.div(re, denom);
+                let re = steps.scale(re, -exp);
+                let im = steps.dot(b, c, -a, d);
+                let im = steps.div(im, denom);
+                let im = steps.scale(im, -exp);
+                if !(re.is_nan() && im.is_nan()) {
+                    return (Complex::new(re, im), steps.status);
+                }
+                // Recovers the infinite and zero quotients the formulas lost
+                // to NaNs.
+                let status = steps.status;
+                let mut steps = Steps::new(flags);
+                let (re, im) = if denom == 0.0 && !(a.is_nan() && b.is_nan()) {
+                    if [a, b].iter().any(|x| x.is_finite() && *x != 0.0) {
+                        steps.status = Status::DIV_ZERO;
+                    }
+                    let inf = $float::INFINITY.copysign(c);
+                    (steps.mul(inf, a), steps.mul(inf, b))
+                } else if (a.is_infinite() || b.is_infinite()) && c.is_finite() && d.is_finite() {
+                    (a, b) = (boxed(a), boxed(b));
+                    let (re, im) = (steps.dot(a, c, b, d), steps.dot(b, c, -a, d));
+                    (
+                        steps.mul($float::INFINITY, re),
+                        steps.mul($float::INFINITY, im),
+                    )
+                } else if magnitude.is_infinite() && a.is_finite() && b.is_finite() {
+                    (c, d) = (boxed(c), boxed(d));
+                    let (re, im) = (steps.dot(a, c, b, d), steps.dot(b, c, -a, d));
+                    (steps.mul(0.0, re), steps.mul(0.0, im))
+                } else {
+                    return (Complex::new(re, im), status);
+                };
+                (Complex::new(re, im), steps.status)
+            }
+        }
+    };
+}
+
+complex!(f32, u32);
+complex!(f64, u64);
@@ -0,0 +1,120 @@
+//! The bit patterns of the special values each architecture produces, the
+//! NaNs its invalid operations return and the integers its conversions
+//! return for NaNs and out-of-range values.
+
+pub mod x86 {
+    //! SSE, AVX and the x87, whose invalid operations return the negative
+    //! "indefinite" values.
+
+    use crate::f80::F80;
+
+    /// The QNaN floating-point indefinite in half precision.
+    pub const F16_NAN: u16 = 0xFE00;
+    /// The QNaN floating-point indefinite in single precision.
+    pub const F32_NAN: u32 = 0xFFC0_0000;
+    /// The QNaN floating-point indefinite in double precision.
+    pub const F64_NAN: u64 = 0xFFF8_0000_0000_0000;
+    /// The real indefinite of the x87.
+    pub const F80_NAN: F80 = F80::NAN;
+    /// The integer indefinite of conversions to 32-bit signed integers.
+    pub const I32_INDEFINITE: u32 = 0x8000_0000;
+    /// The integer indefinite of conversions to 64-bit signed integers.
+    pub const I64_INDEFINITE: u64 = 0x8000_0000_0000_0000;
+    /// The integer indefinite of the AVX-512 conversions to 32-bit unsigned
+    /// integers.
+    pub const U32_INDEFINITE: u32 = 0xFFFF_FFFF;
+    /// The integer indefinite of the AVX-512 conversions to 64-bit unsigned
+    /// integers.
+    pub const U64_INDEFINITE: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+}
+
+pub mod arm {
+    //! AArch64 and AArch32 VFP and NEON, whose conversions saturate and
+    //! turn NaNs into zeros.
+
+    /// The default NaN in half precision.
+    pub const F16_NAN: u16 = 0x7E00;
+    /// The default NaN in single precision.
+    pub const F32_NAN: u32 = 0x7FC0_0000;
+    /// The default NaN in double precision.
+    pub const F64_NAN: u64 = 0x7FF8_0000_0000_0000;
+    /// The default NaN in half precision under the alternate handling of
+    /// FEAT_AFP.
+    pub const ALTERNATE_F16_NAN: u16 = 0xFE00;
+    /// The default NaN in single precision under the alternate handling of
+    /// FEAT_AFP.
+    pub const ALTERNATE_F32_NAN: u32 = 0xFFC0_0000;
+    /// The default NaN in double precision under the alternate handling of
+    /// FEAT_AFP.
+    pub const ALTERNATE_F64_NAN: u64 = 0xFFF8_0000_0000_0000;
+}
+
+pub mod riscv {
+    //! RISC-V, whose conversions saturate and turn NaNs into the largest
+    //! integer.
+
+    /// The canonical NaN in half precision.
+    pub const F16_NAN: u16 = 0x7E00;
+    /// The canonical NaN in single precision.
+    pub const F32_NAN: u32 = 0x7FC0_0000;
+    /// The canonical NaN in double precision.
+    pub const F64_NAN: u64 = 0x7FF8_0000_0000_0000;
+    /// The canonical NaN in quad precision.
+    #[cfg(feature = "f128")]
+    pub const F128_NAN: u128 = crate::f128::NAN;
+}
+
+pub mod mips {
+    //! MIPS under both NaN encodings. The legacy one sets the quiet bit of
+    //! signaling NaNs, so its default NaNs have it clear.
+
+    /// The default NaN in single precision under the IEEE 754-2008
+    /// encoding.
+    pub const F32_NAN: u32 = 0x7FC0_0000;
+    /// The default NaN in double precision under the IEEE 754-2008
+    /// encoding.
+    pub const F64_NAN: u64 = 0x7FF8_0000_0000_0000;
+    /// The default NaN in single precision under the legacy encoding.
+    pub const LEGACY_F32_NAN: u32 = 0x7FBF_FFFF;
+    /// The default NaN in double precision under the legacy encoding.
+    pub const LEGACY_F64_NAN: u64 = 0x7FF7_FFFF_FFFF_FFFF;
+    /// The default result of invalid conversions to 32-bit integers before
+    /// the IEEE 754-2008 encoding, which later ones saturate instead.
+    pub const LEGACY_I32_INVALID: u32 = 0x7FFF_FFFF;
+    /// The default result of invalid conversions to 64-bit integers before
+    /// the IEEE 754-2008 encoding, which later ones saturate instead.
+    pub const LEGACY_I64_INVALID: u64 = 0x7FFF_FFFF_FFFF_FFFF;
+}
+
+pub mod ppc {
+    //! Power, whose conversions saturate and turn NaNs into the smallest
+    //! integer.
+
+    /// The default NaN in single precision.
+    pub const F32_NAN: u32 = 0x7FC0_0000;
+    /// The default NaN in double precision.
+    pub const F64_NAN: u64 = 0x7FF8_0000_0000_0000;
+    /// The default NaN in quad precision.
+    #[cfg(feature = "f128")]
+    pub const F128_NAN: u128 = crate::f128::NAN;
+}
+
+pub mod sparc {
+    //! SPARC, whose invalid operations return NaNs with every bit of the
+    //! significand set.
+
+    /// The default NaN in single precision.
+    pub const F32_NAN: u32 = 0x7FFF_FFFF;
+    /// The default NaN in double precision.
+    pub const F64_NAN: u64 = 0x7FFF_FFFF_FFFF_FFFF;
+}
+
+pub mod sh4 {
+    //! SH-4, which sets the quiet bit of signaling NaNs, as the legacy MIPS
+    //! encoding does, so its default NaNs have it clear.
+
+    /// The default NaN in single precision.
+    pub const F32_NAN: u32 = 0x7FBF_FFFF;
+    /// The default NaN in double precision.
+    pub const F64_NAN: u64 = 0x7FF7_FFFF_FFFF_FFFF;
+}
@@ -0,0 +1,241 @@
+//! Double-double arithmetic, representing a value as the unevaluated sum of
+//! two doubles for about 106 bits of precision at a fraction of the cost of
+//! `f128`.
+//!
+//! The operations follow the accurate algorithms of the QD library, built
+//! on error-free transformations, which compute the rounding error of a sum
+//! or product exactly, the latter with a fused multiply-add. Those need
+//! rounding to nearest without flushing, so the rounding mode and flushing
+//! controls of the flags don't apply, and results are not correctly
+//! rounded: the relative error of a sum or product is within about 2^-104
+//! and of a quotient within 2^-103.
+//!
+//! The status gathers the exceptions of the steps but for the roundings the
+//! transformations recover. Inexact is raised whenever part of the exact
+//! result was lost, but may also be raised on an exact result, as the steps
+//! adding the low doubles can round. Infinite and NaN operands, zero
+//! divisors and results overflowing the high double are computed on the
+//! high doubles alone, with a zero low double.
+
+use core::ops::Neg;
+
+use crate::{Flags, Rounding, Status};
+
+/// The value `hi + lo`, with `lo` below half an ulp of `hi` in magnitude.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DoubleDouble {
+    hi: f64,
+    lo: f64,
+}
+
+impl DoubleDouble {
+    pub const ZERO: Self = Self { hi: 0.0, lo: 0.0 };
+    pub const ONE: Self = Self { hi: 1.0, lo: 0.0 };
+
+    /// The value `hi + lo`, exactly.
+    #[inline]
+    pub fn new(hi: f64, lo: f64) -> Self {
+        let mut ops = Ops::new(Flags::new());
+        let (hi, lo) = ops.two_sum(hi, lo);
+        Self { hi, lo }
+    }
+
+    /// The value `x`, exactly.
+    #[inline]
+    pub fn from_f64(x: f64) -> Self {
+        Self { hi: x, lo: 0.0 }
+    }
+
+    /// The high double, which is the value rounded to nearest.
+    #[inline]
+    pub fn hi(self) -> f64 {
+        self.hi
+    }
+
+    #[inline]
+    pub fn lo(self) -> f64 {
+        self.lo
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+/// The steps of an operation, gathering their status.
+struct Ops {
+    flags: Flags,
+    status: Status,
+}
+
+impl Ops {
+    #[inline]
+    fn new(flags: Flags) -> Self {
+        Self {
+            flags: flags
+                .with_rounding(Rounding::Nearest)
+                .with_ftz(false)
+                .with_daz(false),
+            status: Status::empty(),
+        }
+    }
+
+    #[inline]
+    fn raise(&mut self, (value, status): (f64, Status)) -> f64 {
+        self.status = self.status.or(status);
+        value
+    }
+
+    /// Raises the exceptions of a rounding the following steps recover.
+    #[inline]
+    fn raise_exact(&mut self, (value, status): (f64, Status)) -> f64 {
+        if !value.is_finite() {
+            return self.raise((value, status));
+        }
+        let exceptions = Status::INVALID
+            .or(Status::DIV_ZERO)
+            .or(Status::OVERFLOW)
+            .or(Status::UNDERFLOW)
+            .or(Status::DENORM);
+        self.raise((value, status.and(exceptions)))
+    }
+
+    #[inline]
+    fn add(&mut self, l: f64, r: f64) -> f64 {
+        self.raise(crate::f64::add(self.flags, l, r))
+    }
+
+    #[inline]
+    fn sub(&mut self, l: f64, r: f64) -> f64 {
+        self.raise(crate::f64::sub(self.flags, l, r))
+    }
+
+    #[inline]
+    fn madd(&mut self, a: f64, b: f64, c: f64) -> f64 {
+        self.raise(crate::f64::madd(self.flags, a, b, c))
+    }
+
+    /// The rounded sum and its error.
+    #[inline]
+    fn two_sum(&mut self, a: f64, b: f64) -> (f64, f64) {
+        let sum = self.raise_exact(crate::f64::add(self.flags, a, b));
+        if !sum.is_finite() {
+            return (sum, 0.0);
+        }
+        let b_virtual = self.sub(sum, a);
+        let a_virtual = self.sub(sum, b_virtual);
+        let (a_err, b_err) = (self.sub(a, a_virtual), self.sub(b, b_virtual));
+        (sum, self.add(a_err, b_err))
+    }
+
+    /// The rounded sum and its error, for `a` at least as large as `b` in
+    /// magnitude.
+    #[inline]
+    fn quick_two_sum(&mut self, a: f64, b: f64) -> (f64, f64) {
+        let sum = self.raise_exact(crate::f64::add(self.flags, a, b));
+        if !sum.is_finite() {
+            return (sum, 0.0);
+        }
+        let b_virtual = self.sub(sum, a);
+        (sum, self.sub(b, b_virtual))
+    }
+
+    /// The rounded product and its error.
+    #[inline]
+    fn two_prod(&mut self, a: f64, b: f64) -> (f64, f64) {
+        let product = self.raise_exact(crate::f64::mul(self.flags, a, b));
+        if !product.is_finite() {
+            return (product, 0.0);
+        }
+        (product, self.madd(a, b, -product))
+    }
+
+    fn add_dd(&mut self, l: DoubleDouble, r: DoubleDouble) -> DoubleDouble {
+        let (hi, lo) = self.two_sum(l.hi, r.hi);
+        let (lo_hi, lo_lo) = self.two_sum(l.lo, r.lo);
+        let lo = self.add(lo, lo_hi);
+        let (hi, lo) = self.quick_two_sum(hi, lo);
+        let lo = self.add(lo, lo_lo);
+        let (hi, lo) = self.quick_two_sum(hi, lo);
+        DoubleDouble { hi, lo }
+    }
+
+    fn mul_dd(&mut self, l: DoubleDouble, r: DoubleDouble) -> DoubleDouble {
+        let (hi, lo) = self.two_prod(l.hi, r.hi);
+        if !hi.is_finite() {
+            return DoubleDouble { hi, lo };
+        }
+        let lo = self.madd(l.hi, r.lo, lo);
+        let lo = self.madd(l.lo, r.hi, lo);
+        // The product of the low doubles is below the precision kept.
+        if l.lo != 0.0 && r.lo != 0.0 {
+            self.status = self.status.or(Status::INEXACT);
+        }
+        let (hi, lo) = self.quick_two_sum(hi, lo);
+        DoubleDouble { hi, lo }
+    }
+}
+
+/// Whether the operands are handled on their high doubles alone.
+#[inline]
+fn special(l: DoubleDouble, r: DoubleDouble) -> bool {
+    !(l.hi.is_finite() && r.hi.is_finite())
+}
+
+#[inline]
+pub fn add(flags: Flags, l: DoubleDouble, r: DoubleDouble) -> (DoubleDouble, Status) {
+    let mut ops = Ops::new(flags);
+    if special(l, r) {
+        let hi = ops.add(l.hi, r.hi);
+        return (DoubleDouble { hi, lo: 0.0 }, ops.status);
+    }
+    (ops.add_dd(l, r), ops.status)
+}
+
+#[inline]
+pub fn sub(flags: Flags, l: DoubleDouble, r: DoubleDouble) -> (DoubleDouble, Status) {
+    add(flags, l, -r)
+}
+
+#[inline]
+pub fn mul(flags: Flags, l: DoubleDouble, r: DoubleDouble) -> (DoubleDouble, Status) {
+    let mut ops = Ops::new(flags);
+    if special(l, r) {
+        let hi = ops.raise(crate::f64::mul(ops.flags, l.hi, r.hi));
+        return (DoubleDouble { hi, lo: 0.0 }, ops.status);
+    }
+    (ops.mul_dd(l, r), ops.status)
+}
+
+/// Divides `l` by `r`, refining the quotient of the high doubles by two
+/// correction terms.
+#[inline]
+pub fn div(flags: Flags, l: DoubleDouble, r: DoubleDouble) -> (DoubleDouble, Status) {
+    let mut ops = Ops::new(flags);
+    let q1 = ops.raise_exact(crate::f64::div(ops.flags, l.hi, r.hi));
+    if special(l, r) || !q1.is_finite() {
+        return (DoubleDouble { hi: q1, lo: 0.0 }, ops.status);
+    }
+    // The remainders are exact but for the low parts of the products.
+    let product = ops.mul_dd(DoubleDouble::from_f64(q1), r);
+    let rem = ops.add_dd(l, -product);
+    let q2 = ops.raise_exact(crate::f64::div(ops.flags, rem.hi, r.hi));
+    let product = ops.mul_dd(DoubleDouble::from_f64(q2), r);
+    let rem = ops.add_dd(rem, -product);
+    let q3 = ops.raise(crate::f64::div(ops.flags, rem.hi, r.hi));
+    // The last term leaves out the low doubles of the remainder and divisor.
+    if rem.lo != 0.0 || rem.hi != 0.0 && r.lo != 0.0 {
+        ops.status = ops.status.or(Status::INEXACT);
+    }
+    let (hi, lo) = ops.quick_two_sum(q1, q2);
+    let quotient = ops.add_dd(DoubleDouble { hi, lo }, DoubleDouble::from_f64(q3));
+    (quotient, ops.status)
+}
@@ -0,0 +1,525 @@
+//! Operations on IEEE 754 decimal64 values, held as their binary integer
+//! decimal (BID) encoding, computed in software for flag-aware decimal
+//! arithmetic and for emulating the decimal floating-point instructions of
+//! POWER and z/Architecture, whose densely packed decimal (DPD) encoding
+//! `to_dpd` and `from_dpd` convert to and from.
+//!
+//! A value is a coefficient of up to 16 digits times a power of ten, and
+//! exact results keep the exponent closest to the one IEEE 754 prefers. The
+//! flags apply as to the binary formats but for flushing to zero, tininess
+//! being detected before rounding as IEEE 754 requires for decimal formats.
+//! `Rounding::Odd` rounds to prepare for shorter precision, its decimal
+//! counterpart, incrementing inexact results ending in a zero or a five.
+//!
+//! NaN results propagate the first signaling NaN operand, else the first
+//! quiet one, quieted with their sign and payload.
+
+use core::cmp::Ordering;
+
+use crate::soft::{self, Mode, INEXACT, INVALID, OVERFLOW, UNDERFLOW};
+use crate::{Flags, Status};
+
+/// Positive zero, with an exponent of zero.
+pub const ZERO: u64 = (BIAS as u64) << 53;
+/// One, with an exponent of zero.
+pub const ONE: u64 = ZERO | 1;
+/// Positive infinity.
+pub const INFINITY: u64 = 0x7800_0000_0000_0000;
+/// The quiet NaN without a payload.
+pub const NAN: u64 = 0x7C00_0000_0000_0000;
+/// The largest coefficient, of 16 nines.
+pub const MAX_COEFFICIENT: u64 = 9_999_999_999_999_999;
+/// The smallest exponent of the coefficient.
+pub const MIN_EXPONENT: i32 = -BIAS;
+/// The largest exponent of the coefficient.
+pub const MAX_EXPONENT: i32 = 369;
+
+const SIGN: u64 = 1 << 63;
+const SIGNALING: u64 = 0x7E00_0000_0000_0000;
+const BIAS: i32 = 398;
+const DIGITS: i32 = 16;
+/// The payloads of NaNs, which are canonical below 10^15.
+const PAYLOAD: u64 = (1 << 50) - 1;
+
+/// The powers of ten up to 10^38, the largest below 2^128.
+const POW10: [u128; 39] = {
+    let mut pow = [1; 39];
+    let mut i = 1;
+    while i < 39 {
+        pow[i] = pow[i - 1] * 10;
+        i += 1;
+    }
+    pow
+};
+
+#[derive(Clone, Copy)]
+enum Class {
+    Nan,
+    Inf,
+    /// The coefficient and exponent.
+    Finite(u128, i32),
+}
+
+#[inline]
+fn result<T>((value, exc): (T, u32)) -> (T, Status) {
+    (value, Status::from_soft(exc))
+}
+
+/// The number of digits of `coeff`, zero having none.
+#[inline]
+fn digits(coeff: u128) -> i32 {
+    POW10.iter().take_while(|&&pow| pow <= coeff).count() as i32
+}
+
+#[inline]
+fn sign_bit(sign: bool) -> u64 {
+    if sign {
+        SIGN
+    } else {
+        0
+    }
+}
+
+#[inline]
+fn is_nan(x: u64) -> bool {
+    x & NAN == NAN
+}
+
+#[inline]
+fn is_signaling(x: u64) -> bool {
+    x & SIGNALING == SIGNALING
+}
+
+/// Decodes `x`, taking non-canonical coefficients as zero.
+#[inline]
+fn unpack(x: u64) -> (bool, Class) {
+    let class = if is_nan(x) {
+        Class::Nan
+    } else if x & NAN == INFINITY {
+        Class::Inf
+    } else {
+        let (biased, coeff) = if x >> 61 & 3 == 3 {
+            (x >> 51 & 0x3FF, 0b100 << 51 | x & ((1 << 51) - 1))
+        } else {
+            (x >> 53 & 0x3FF, x & ((1 << 53) - 1))
+        };
+        let coeff = if coeff > MAX_COEFFICIENT { 0 } else { coeff };
+        Class::Finite(coeff as u128, biased as i32 - BIAS)
+    };
+    (x & SIGN != 0, class)
+}
+
+/// Encodes a coefficient and exponent within the range.
+#[inline]
+fn pack(sign: bool, coeff: u64, exp: i32) -> u64 {
+    let biased = (exp + BIAS) as u64;
+    if coeff >> 53 == 0 {
+        sign_bit(sign) | biased << 53 | coeff
+    } else {
+        sign_bit(sign) | 3 << 61 | biased << 51 | coeff & ((1 << 51) - 1)
+    }
+}
+
+#[inline]
+fn inf(sign: bool) -> u64 {
+    sign_bit(sign) | INFINITY
+}
+
+#[inline]
+fn invalid(exc: &mut u32) -> u64 {
+    *exc |= INVALID;
+    NAN
+}
+
+/// The NaN result of an operation on `ops`, if any is a NaN.
+#[inline]
+fn propagate(env: &soft::Env, ops: &[u64], exc: &mut u32) -> Option<u64> {
+    if ops.iter().any(|&op| is_signaling(op)) {
+        *exc |= INVALID;
+    }
+    let nan = ops
+        .iter()
+        .copied()
+        .find(|&op| is_signaling(op))
+        .or_else(|| ops.iter().copied().find(|&op| is_nan(op)))?;
+    if env.default_nan {
+        return Some(NAN);
+    }
+    Some(nan & SIGN | NAN | payload(nan))
+}
+
+/// The canonical payload of a NaN.
+#[inline]
+fn payload(x: u64) -> u64 {
+    let payload = x & PAYLOAD;
+    if payload >= POW10[15] as u64 {
+        0
+    } else {
+        payload
+    }
+}
+
+/// Removes the last `drop` digits of `coeff`, none or more, returning the rest, how the
+/// removed part compares with half a unit of the rest and whether it is
+/// nonzero.
+#[inline]
+fn split(coeff: u128, drop: i32) -> (u128, Ordering, bool) {
+    if drop >= POW10.len() as i32 {
+        return (0, Ordering::Less, coeff != 0);
+    }
+    if drop == 0 {
+        return (coeff, Ordering::Less, false);
+    }
+    let pow = POW10[drop as usize];
+    let rem = coeff % pow;
+    (coeff / pow, rem.cmp(&(pow / 2)), rem != 0)
+}
+
+#[inline]
+fn round_increment(mode: Mode, sign: bool, kept: u128, half: Ordering, inexact: bool) -> bool {
+    match mode {
+        Mode::Nearest => half == Ordering::Greater || half == Ordering::Equal && kept & 1 != 0,
+        Mode::NearestAway => half != Ordering::Less,
+        Mode::Zero => false,
+        Mode::Up => !sign && inexact,
+        Mode::Down => sign && inexact,
+        Mode::Odd => inexact && kept.is_multiple_of(5),
+    }
+}
+
+/// Rounds the value `coeff * 10^exp` to the format. Inexact coefficients
+/// must have a nonzero last digit standing in for the lost ones and at
+/// least two digits more than the format.
+fn round_pack(env: &soft::Env, sign: bool, coeff: u128, exp: i32, exc: &mut u32) -> u64 {
+    let count = digits(coeff);
+    let drop = (count - DIGITS).max(MIN_EXPONENT - exp);
+    let (mut coeff, mut exp) = (coeff, exp);
+    if drop > 0 {
+        let (kept, half, inexact) = split(coeff, drop);
+        if inexact {
+            *exc |= INEXACT;
+            if exp + count - 1 < MIN_EXPONENT + DIGITS - 1 {
+                *exc |= UNDERFLOW;
+            }
+        }
+        coeff = kept + round_increment(env.mode, sign, kept, half, inexact) as u128;
+        exp += drop;
+        if coeff == POW10[DIGITS as usize] {
+            coeff /= 10;
+            exp += 1;
+        }
+    }
+    if exp > MAX_EXPONENT {
+        // Zeros padding the coefficient bring the exponent into the range
+        // as long as it has room for them.
+        let pad = exp - MAX_EXPONENT;
+        if coeff == 0 {
+            exp = MAX_EXPONENT;
+        } else if digits(coeff) + pad <= DIGITS {
+            coeff *= POW10[pad as usize];
+            exp = MAX_EXPONENT;
+        } else {
+            *exc |= OVERFLOW | INEXACT;
+            let to_inf = match env.mode {
+                Mode::Nearest | Mode::NearestAway => true,
+                Mode::Zero | Mode::Odd => false,
+                Mode::Up => !sign,
+                Mode::Down => sign,
+            };
+            return if to_inf {
+                inf(sign)
+            } else {
+                pack(sign, MAX_COEFFICIENT, MAX_EXPONENT)
+            };
+        }
+    }
+    pack(sign, coeff as u64, exp)
+}
+
+fn add_impl(env: &soft::Env, a: u64, b: u64, negate: bool) -> (u64, u32) {
+    let mut exc = 0;
+    if let Some(nan) = propagate(env, &[a, b], &mut exc) {
+        return (nan, exc);
+    }
+    let (sign_a, a) = unpack(a);
+    let (sign_b, b) = unpack(b);
+    let sign_b = sign_b ^ negate;
+    let res = match (a, b) {
+        (Class::Inf, Class::Inf) if sign_a != sign_b => invalid(&mut exc),
+        (Class::Inf, _) => inf(sign_a),
+        (_, Class::Inf) => inf(sign_b),
+        (Class::Finite(coeff_a, exp_a), Class::Finite(coeff_b, exp_b)) => {
+            // `x` has the larger exponent, and the sum takes the other.
+            let ((sign_x, coeff_x, exp_x), (sign_y, coeff_y, exp_y)) = if exp_a >= exp_b {
+                ((sign_a, coeff_a, exp_a), (sign_b, coeff_b, exp_b))
+            } else {
+                ((sign_b, coeff_b, exp_b), (sign_a, coeff_a, exp_a))
+            };
+            let diff = exp_x - exp_y;
+            let (coeff_x, coeff_y, exp) = if coeff_y == 0 && coeff_x != 0 {
+                // Only as many zeros as the coefficient has room for.
+                let pad = diff.min(DIGITS - digits(coeff_x));
+                (coeff_x * POW10[pad as usize], 0, exp_x - pad)
+            } else if diff <= 19 {
+                (coeff_x * POW10[diff as usize], coeff_y, exp_y)
+            } else if coeff_x == 0 {
+                (0, coeff_y, exp_y)
+            } else {
+                // `y` is below a unit of `x` scaled to 21 digits or more, and
+                // those below a tenth of that unit only count as a last digit.
+                let (kept, _, lost) = split(coeff_y, diff - 19);
+                (coeff_x * POW10[20], kept * 10 + lost as u128, exp_x - 20)
+            };
+            if sign_x == sign_y {
+                round_pack(env, sign_x, coeff_x + coeff_y, exp, &mut exc)
+            } else if coeff_x > coeff_y {
+                round_pack(env, sign_x, coeff_x - coeff_y, exp, &mut exc)
+            } else if coeff_x < coeff_y {
+                round_pack(env, sign_y, coeff_y - coeff_x, exp, &mut exc)
+            } else {
+                round_pack(env, env.mode == Mode::Down, 0, exp, &mut exc)
+            }
+        }
+        _ => unreachable!(),
+    };
+    (res, exc)
+}
+
+#[inline]
+pub fn add(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    result(add_impl(&flags.soft_env(), l, r, false))
+}
+
+#[inline]
+pub fn sub(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    result(add_impl(&flags.soft_env(), l, r, true))
+}
+
+#[inline]
+pub fn mul(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let env = flags.soft_env();
+    let mut exc = 0;
+    if let Some(nan) = propagate(&env, &[l, r], &mut exc) {
+        return result((nan, exc));
+    }
+    let (sign_l, l) = unpack(l);
+    let (sign_r, r) = unpack(r);
+    let sign = sign_l != sign_r;
+    let res = match (l, r) {
+        (Class::Inf, Class::Finite(0, _)) | (Class::Finite(0, _), Class::Inf) => invalid(&mut exc),
+        (Class::Inf, _) | (_, Class::Inf) => inf(sign),
+        (Class::Finite(coeff_l, exp_l), Class::Finite(coeff_r, exp_r)) => {
+            round_pack(&env, sign, coeff_l * coeff_r, exp_l + exp_r, &mut exc)
+        }
+        _ => unreachable!(),
+    };
+    result((res, exc))
+}
+
+/// Divides `l` by `r`. Quotients of infinitely many digits keep the full
+/// precision.
+#[inline]
+pub fn div(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let env = flags.soft_env();
+    let mut exc = 0;
+    if let Some(nan) = propagate(&env, &[l, r], &mut exc) {
+        return result((nan, exc));
+    }
+    let (sign_l, l) = unpack(l);
+    let (sign_r, r) = unpack(r);
+    let sign = sign_l != sign_r;
+    let res = match (l, r) {
+        (Class::Inf, Class::Inf) | (Class::Finite(0, _), Class::Finite(0, _)) => invalid(&mut exc),
+        (Class::Inf, _) => inf(sign),
+        (_, Class::Inf) => pack(sign, 0, MIN_EXPONENT),
+        (_, Class::Finite(0, _)) => {
+            exc |= soft::DIV_ZERO;
+            inf(sign)
+        }
+        (Class::Finite(0, exp_l), Class::Finite(_, exp_r)) => {
+            round_pack(&env, sign, 0, exp_l - exp_r, &mut exc)
+        }
+        (Class::Finite(coeff_l, exp_l), Class::Finite(coeff_r, exp_r)) => {
+            // Scaled so that the quotient has at least 17 digits.
+            let scale = DIGITS + 1 + digits(coeff_r) - digits(coeff_l);
+            let num = coeff_l * POW10[scale as usize];
+            let (mut quo, rem) = (num / coeff_r, num % coeff_r);
+            let (ideal, mut exp) = (exp_l - exp_r, exp_l - exp_r - scale);
+            if rem != 0 {
+                quo = quo * 10 + 1;
+                exp -= 1;
+            } else {
+                while exp < ideal && quo % 10 == 0 {
+                    quo /= 10;
+                    exp += 1;
+                }
+            }
+            round_pack(&env, sign, quo, exp, &mut exc)
+        }
+        _ => unreachable!(),
+    };
+    result((res, exc))
+}
+
+/// Rounds `x` to the exponent of `exp`, raising invalid if the coefficient
+/// would need more than 16 digits, as only one of them being infinite
+/// does. Overflow and underflow are never raised.
+#[inline]
+pub fn quantize(flags: Flags, x: u64, exp: u64) -> (u64, Status) {
+    let env = flags.soft_env();
+    let mut exc = 0;
+    if let Some(nan) = propagate(&env, &[x, exp], &mut exc) {
+        return result((nan, exc));
+    }
+    let (sign, x_class) = unpack(x);
+    let res = match (x_class, unpack(exp).1) {
+        (Class::Inf, Class::Inf) => inf(sign),
+        (Class::Finite(coeff, from), Class::Finite(_, to)) => {
+            if to >= from {
+                let (kept, half, inexact) = split(coeff, to - from);
+                let coeff = kept + round_increment(env.mode, sign, kept, half, inexact) as u128;
+                if coeff == POW10[DIGITS as usize] {
+                    invalid(&mut exc)
+                } else {
+                    if inexact {
+                        exc |= INEXACT;
+                    }
+                    pack(sign, coeff as u64, to)
+                }
+            } else if coeff == 0 {
+                pack(sign, 0, to)
+            } else if digits(coeff) + from - to <= DIGITS {
+                pack(sign, (coeff * POW10[(from - to) as usize]) as u64, to)
+            } else {
+                invalid(&mut exc)
+            }
+        }
+        _ => invalid(&mut exc),
+    };
+    result((res, exc))
+}
+
+/// The value `coefficient * 10^exponent`, rounded to the format.
+#[inline]
+pub fn from_parts(flags: Flags, negative: bool, coefficient: u64, exponent: i32) -> (u64, Status) {
+    // Exponents beyond these round as they do.
+    let exponent = exponent.clamp(-(1 << 16), 1 << 16);
+    let mut exc = 0;
+    let res = round_pack(
+        &flags.soft_env(),
+        negative,
+        coefficient as u128,
+        exponent,
+        &mut exc,
+    );
+    result((res, exc))
+}
+
+/// The sign, coefficient and exponent of a finite `x`.
+#[inline]
+pub fn to_parts(x: u64) -> Option<(bool, u64, i32)> {
+    match unpack(x) {
+        (sign, Class::Finite(coeff, exp)) => Some((sign, coeff as u64, exp)),
+        _ => None,
+    }
+}
+
+/// Encodes three digits as a declet.
+#[inline]
+fn declet(n: u64) -> u64 {
+    let (d1, d2, d3) = (n / 100, n / 10 % 10, n % 10);
+    let big = |d: u64| d >> 3 != 0;
+    // Digits of eight and nine keep their last bit, and the indicator bits
+    // `v` and `wx` (or `st`) spell out which ones they are.
+    let (pqr, stu, v, wxy) = match (big(d1), big(d2), big(d3)) {
+        (false, false, false) => (d1, d2, 0, d3),
+        (false, false, true) => (d1, d2, 1, d3 & 1),
+        (false, true, false) => (d1, d3 & 6 | d2 & 1, 1, 0b010 | d3 & 1),
+        (false, true, true) => (d1, 0b100 | d2 & 1, 1, 0b110 | d3 & 1),
+        (true, false, false) => (d3 & 6 | d1 & 1, d2, 1, 0b100 | d3 & 1),
+        (true, false, true) => (d2 & 6 | d1 & 1, 0b010 | d2 & 1, 1, 0b110 | d3 & 1),
+        (true, true, false) => (d3 & 6 | d1 & 1, d2 & 1, 1, 0b110 | d3 & 1),
+        (true, true, true) => (d1 & 1, 0b110 | d2 & 1, 1, 0b110 | d3 & 1),
+    };
+    pqr << 7 | stu << 4 | v << 3 | wxy
+}
+
+/// Decodes a declet, canonical or not, into the three digits it encodes.
+#[inline]
+fn undeclet(bits: u64) -> u64 {
+    let (pqr, stu, wxy) = (bits >> 7 & 7, bits >> 4 & 7, bits & 7);
+    let (pq, st) = (pqr & 6, stu & 6);
+    let (r, u, y) = (pqr & 1, stu & 1, wxy & 1);
+    let (d1, d2, d3) = if bits & 8 == 0 {
+        (pqr, stu, wxy)
+    } else {
+        match (wxy >> 1, st >> 1) {
+            (0b00, _) => (pqr, stu, 8 | y),
+            (0b01, _) => (pqr, 8 | u, st | y),
+            (0b10, _) => (8 | r, stu, pq | y),
+            (_, 0b00) => (8 | r, 8 | u, pq | y),
+            (_, 0b01) => (8 | r, pq | u, 8 | y),
+            (_, 0b10) => (pqr, 8 | u, 8 | y),
+            _ => (8 | r, 8 | u, 8 | y),
+        }
+    };
+    d1 * 100 + d2 * 10 + d3
+}
+
+/// The five declets encoding the last 15 digits of `n`.
+#[inline]
+fn declets(n: u64) -> u64 {
+    (0..5).fold(0, |bits, i| {
+        bits | declet(n / POW10[3 * i] as u64 % 1000) << (10 * i)
+    })
+}
+
+/// The 15 digits of five declets.
+#[inline]
+fn undeclets(bits: u64) -> u64 {
+    (0..5).fold(0, |n, i| {
+        n + undeclet(bits >> (10 * i) & 0x3FF) * POW10[3 * i] as u64
+    })
+}
+
+/// Converts `x` to the densely packed decimal encoding, which holds the
+/// same values.
+#[inline]
+pub fn to_dpd(x: u64) -> u64 {
+    let sign = x & SIGN;
+    match unpack(x).1 {
+        Class::Nan => x & (SIGN | SIGNALING) | declets(payload(x)),
+        Class::Inf => sign | INFINITY,
+        Class::Finite(coeff, exp) => {
+            let (coeff, biased) = (coeff as u64, (exp + BIAS) as u64);
+            let lead = coeff / POW10[15] as u64;
+            let combination = if lead < 8 {
+                (biased >> 8) << 3 | lead
+            } else {
+                0b11 << 3 | (biased >> 8) << 1 | lead & 1
+            };
+            sign | combination << 58 | (biased & 0xFF) << 50 | declets(coeff)
+        }
+    }
+}
+
+/// Converts `x` from the densely packed decimal encoding.
+#[inline]
+pub fn from_dpd(x: u64) -> u64 {
+    let sign = x & SIGN != 0;
+    let combination = x >> 58 & 0x1F;
+    if combination == 0x1F {
+        return x & (SIGN | SIGNALING) | undeclets(x);
+    }
+    if combination == 0x1E {
+        return inf(sign);
+    }
+    let (high, lead) = if combination >> 3 == 0b11 {
+        (combination >> 1 & 3, 8 | combination & 1)
+    } else {
+        (combination >> 3, combination & 7)
+    };
+    let biased = high << 8 | x >> 50 & 0xFF;
+    let coeff = lead * POW10[15] as u64 + undeclets(x);
+    pack(sign, coeff, biased as i32 - BIAS)
+}
@@ -0,0 +1,307 @@
+//! Operations reporting which way their result was rounded, as the FR and FI
+//! bits of PowerPC record and interval arithmetic can exploit.
+//!
+//! Only inexact results in the modes rounding to nearest or to odd run again
+//! towards zero to find out, so exact results cost one check.
+
+use crate::{Flags, Rounding, Status};
+
+/// Where the delivered result lies relative to the exact one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The result is exact.
+    Exact,
+    /// The result is above the exact one.
+    Up,
+    /// The result is below the exact one.
+    Down,
+}
+
+impl Direction {
+    /// Whether the magnitude of a result with the sign `negative` was
+    /// rounded up, as FR records.
+    #[inline]
+    pub fn is_away_from_zero(self, negative: bool) -> bool {
+        match self {
+            Self::Exact => false,
+            Self::Up => !negative,
+            Self::Down => negative,
+        }
+    }
+}
+
+/// The direction of a result with the sign `negative` that an operation
+/// returned with `status`, where `truncated` tells whether running it again
+/// towards zero gives the same result. Flushed results are zeros, which lie
+/// towards zero in every mode.
+#[inline]
+fn direction(
+    flags: Flags,
+    status: Status,
+    negative: bool,
+    zero: bool,
+    truncated: impl FnOnce() -> bool,
+) -> Direction {
+    if !(status.inexact() || status.underflow()) {
+        return Direction::Exact;
+    }
+    let away = !zero
+        && match flags.rounding() {
+            Rounding::Zero => false,
+            Rounding::Up => !negative,
+            Rounding::Down => negative,
+            Rounding::Nearest | Rounding::NearestAway | Rounding::Odd => !truncated(),
+        };
+    if away != negative {
+        Direction::Up
+    } else {
+        Direction::Down
+    }
+}
+
+/// Whether the magnitude of `value`, which an operation returned with
+/// `status`, was rounded up, where `truncated` runs it again towards zero.
+/// Unlike `Direction`, this needs no sign, so it serves any result type.
+#[inline]
+pub(crate) fn away_from_zero<T: PartialEq>(
+    flags: Flags,
+    status: Status,
+    value: &T,
+    truncated: impl FnOnce() -> T,
+) -> bool {
+    status.inexact() && !matches!(flags.rounding(), Rounding::Zero) && truncated() != *value
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f32, r: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::add(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::add(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f32, r: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::sub(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::sub(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f32, r: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::mul(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::mul(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f32, r: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::div(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::div(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f32, b: f32, c: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::madd(flags, a, b, c);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::madd(flags.with_rounding(Rounding::Zero), a, b, c).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::round(flags, x);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::round(flags.with_rounding(Rounding::Zero), x).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f32) -> (f32, Status, Direction) {
+        let (value, status) = crate::f32::sqrt(flags, x);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f32::sqrt(flags.with_rounding(Rounding::Zero), x).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status, Direction) {
+        let (value, status) = crate::f32::to_half(flags, single);
+        let direction = direction(
+            flags,
+            status,
+            single.is_sign_negative(),
+            value & 0x7FFF == 0,
+            || crate::f32::to_half(flags.with_rounding(Rounding::Zero), single).0 == value,
+        );
+        (value, status, direction)
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f64, r: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::add(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::add(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f64, r: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::sub(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::sub(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f64, r: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::mul(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::mul(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f64, r: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::div(flags, l, r);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::div(flags.with_rounding(Rounding::Zero), l, r).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f64, b: f64, c: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::madd(flags, a, b, c);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::madd(flags.with_rounding(Rounding::Zero), a, b, c).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::round(flags, x);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::round(flags.with_rounding(Rounding::Zero), x).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f64) -> (f64, Status, Direction) {
+        let (value, status) = crate::f64::sqrt(flags, x);
+        let direction = direction(
+            flags,
+            status,
+            value.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::sqrt(flags.with_rounding(Rounding::Zero), x).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, double: f64) -> (f32, Status, Direction) {
+        let (value, status) = crate::f64::to_single(flags, double);
+        let direction = direction(
+            flags,
+            status,
+            double.is_sign_negative(),
+            value == 0.0,
+            || crate::f64::to_single(flags.with_rounding(Rounding::Zero), double).0 == value,
+        );
+        (value, status, direction)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status, Direction) {
+        let (value, status) = crate::f64::to_half(flags, double);
+        let direction = direction(
+            flags,
+            status,
+            double.is_sign_negative(),
+            value & 0x7FFF == 0,
+            || crate::f64::to_half(flags.with_rounding(Rounding::Zero), double).0 == value,
+        );
+        (value, status, direction)
+    }
+}
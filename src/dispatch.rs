@@ -0,0 +1,93 @@
+//! A dispatcher over the operations by their kind and width, so decoders in
+//! emulators can stay data-driven.
+
+use crate::{Flags, Status};
+
+/// An operation, taking its operands in the order of the function of the same
+/// name.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FpOpKind {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    #[cfg(feature = "fma")]
+    Madd,
+    Sqrt,
+    Round,
+}
+
+impl FpOpKind {
+    /// The number of operands the operation takes.
+    #[inline]
+    pub fn arity(self) -> usize {
+        match self {
+            Self::Sqrt | Self::Round => 1,
+            #[cfg(feature = "fma")]
+            Self::Madd => 3,
+            _ => 2,
+        }
+    }
+}
+
+/// The format of the values an operation works on.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FpWidth {
+    Half,
+    Single,
+    Double,
+}
+
+/// Runs `kind` on the bit patterns of `operands`, ignoring those past its
+/// arity. Values narrower than 64 bits are taken from the low bits, and
+/// results are zero extended.
+#[inline]
+pub fn dispatch(kind: FpOpKind, width: FpWidth, flags: Flags, operands: [u64; 3]) -> (u64, Status) {
+    match width {
+        FpWidth::Half => {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [a, b, c] = operands.map(|op| op as u16);
+            let (value, status) = match kind {
+                FpOpKind::Add => crate::f16::add(flags, a, b),
+                FpOpKind::Sub => crate::f16::sub(flags, a, b),
+                FpOpKind::Mul => crate::f16::mul(flags, a, b),
+                FpOpKind::Div => crate::f16::div(flags, a, b),
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => crate::f16::madd(flags, a, b, c),
+                FpOpKind::Sqrt => crate::f16::sqrt(flags, a),
+                FpOpKind::Round => crate::f16::round(flags, a),
+            };
+            (value as u64, status)
+        }
+        FpWidth::Single => {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [a, b, c] = operands.map(|op| f32::from_bits(op as u32));
+            let (value, status) = match kind {
+                FpOpKind::Add => crate::f32::add(flags, a, b),
+                FpOpKind::Sub => crate::f32::sub(flags, a, b),
+                FpOpKind::Mul => crate::f32::mul(flags, a, b),
+                FpOpKind::Div => crate::f32::div(flags, a, b),
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => crate::f32::madd(flags, a, b, c),
+                FpOpKind::Sqrt => crate::f32::sqrt(flags, a),
+                FpOpKind::Round => crate::f32::round(flags, a),
+            };
+            (value.to_bits() as u64, status)
+        }
+        FpWidth::Double => {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [a, b, c] = operands.map(f64::from_bits);
+            let (value, status) = match kind {
+                FpOpKind::Add => crate::f64::add(flags, a, b),
+                FpOpKind::Sub => crate::f64::sub(flags, a, b),
+                FpOpKind::Mul => crate::f64::mul(flags, a, b),
+                FpOpKind::Div => crate::f64::div(flags, a, b),
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => crate::f64::madd(flags, a, b, c),
+                FpOpKind::Sqrt => crate::f64::sqrt(flags, a),
+                FpOpKind::Round => crate::f64::round(flags, a),
+            };
+            (value.to_bits(), status)
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Operations on IEEE 754 binary128 values, held as their bits, computed in
+//! software for emulating the RISC-V Q extension and the quad-precision
+//! instructions of POWER.
+//!
+//! The flags apply as to the other formats, and NaN results, flushing and
+//! the reporting of denormal operands follow the host, as its software
+//! fallbacks do.
+
+use crate::{soft, Flags, Status};
+
+/// Positive zero.
+pub const ZERO: u128 = 0;
+/// One.
+pub const ONE: u128 = 0x3FFF << 112;
+/// Positive infinity.
+pub const INFINITY: u128 = 0x7FFF << 112;
+/// The quiet NaN with only the quiet bit set.
+pub const NAN: u128 = 0x7FFF_8000 << 96;
+
+#[inline]
+fn result<T>((value, exc): (T, u32)) -> (T, Status) {
+    (value, Status::from_soft(exc))
+}
+
+#[inline]
+pub fn add(flags: Flags, l: u128, r: u128) -> (u128, Status) {
+    result(soft::quad::add(&flags.soft_env(), l, r))
+}
+
+#[inline]
+pub fn sub(flags: Flags, l: u128, r: u128) -> (u128, Status) {
+    result(soft::quad::sub(&flags.soft_env(), l, r))
+}
+
+#[inline]
+pub fn mul(flags: Flags, l: u128, r: u128) -> (u128, Status) {
+    result(soft::quad::mul(&flags.soft_env(), l, r))
+}
+
+#[inline]
+pub fn div(flags: Flags, l: u128, r: u128) -> (u128, Status) {
+    result(soft::quad::div(&flags.soft_env(), l, r))
+}
+
+/// Computes `a * b + c` with a single rounding.
+#[cfg(feature = "fma")]
+#[inline]
+pub fn madd(flags: Flags, a: u128, b: u128, c: u128) -> (u128, Status) {
+    result(soft::quad::fma(&flags.soft_env(), a, b, c))
+}
+
+#[inline]
+pub fn round(flags: Flags, x: u128) -> (u128, Status) {
+    result(soft::quad::round_int(&flags.soft_env(), x))
+}
+
+#[inline]
+pub fn sqrt(flags: Flags, x: u128) -> (u128, Status) {
+    result(soft::quad::sqrt(&flags.soft_env(), x))
+}
+
+#[cfg(feature = "convert")]
+/// Widens `half` exactly, quieting signaling NaNs.
+#[inline]
+pub fn from_half(flags: Flags, half: u16) -> (u128, Status) {
+    result(soft::quad::from::<soft::Binary16>(
+        &flags.soft_env(),
+        half as u64,
+    ))
+}
+
+#[cfg(feature = "convert")]
+/// Widens `single` exactly, quieting signaling NaNs.
+#[inline]
+pub fn from_single(flags: Flags, single: f32) -> (u128, Status) {
+    result(soft::quad::from::<soft::Binary32>(
+        &flags.soft_env(),
+        single.to_bits() as u64,
+    ))
+}
+
+#[cfg(feature = "convert")]
+/// Widens `double` exactly, quieting signaling NaNs.
+#[inline]
+pub fn from_double(flags: Flags, double: f64) -> (u128, Status) {
+    result(soft::quad::from::<soft::Binary64>(
+        &flags.soft_env(),
+        double.to_bits(),
+    ))
+}
+
+#[cfg(feature = "convert")]
+#[inline]
+pub fn to_half(flags: Flags, x: u128) -> (u16, Status) {
+    let (bits, status) = result(soft::quad::to::<soft::Binary16>(&flags.soft_env(), x));
+    (bits as u16, status)
+}
+
+#[cfg(feature = "convert")]
+#[inline]
+pub fn to_single(flags: Flags, x: u128) -> (f32, Status) {
+    let (bits, status) = result(soft::quad::to::<soft::Binary32>(&flags.soft_env(), x));
+    (f32::from_bits(bits as u32), status)
+}
+
+#[cfg(feature = "convert")]
+#[inline]
+pub fn to_double(flags: Flags, x: u128) -> (f64, Status) {
+    let (bits, status) = result(soft::quad::to::<soft::Binary64>(&flags.soft_env(), x));
+    (f64::from_bits(bits), status)
+}
@@ -0,0 +1,293 @@
+//! Operations on the x87 double extended format, computed in software with
+//! its full 64-bit significand, so emulated x87 and m68k code doesn't round
+//! twice through `f64`.
+//!
+//! Results follow the x87: NaNs propagate the one with the larger
+//! significand, unnormals and the pseudo encodings are invalid operands, and
+//! denormal operands raise `Status::DENORM`. Flushing to zero doesn't apply
+//! to the format, but the rounding mode, default NaN and tininess in the
+//! flags do. `status_word` maps the status to the exception bits of the x87
+//! status word.
+//!
+//! The arithmetic rounds to the significand width of its `Precision`, as under
+//! the x87 precision control, but keeps the exponent range of the format, so
+//! storing a result of `Precision::Double` to `f64` rounds again where it is
+//! below the normal range of `f64` and can overflow there, as on hardware.
+//!
+//! The transcendental functions round correctly to the full precision, where
+//! the x87 is only within an ulp, so they can differ from it in the last bit.
+//! `Accuracy::HardwareReduction` reproduces its argument reduction, not its
+//! rounding.
+
+use core::cmp::Ordering;
+
+use crate::transcendental::{self, Trig};
+use crate::{soft, Flags, Status, Tininess};
+
+/// A double extended value, as the sign and biased exponent followed by the
+/// significand with its explicit integer bit.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct F80 {
+    pub sign_exp: u16,
+    pub sig: u64,
+}
+
+impl F80 {
+    /// Positive zero.
+    pub const ZERO: Self = Self::from_bits(0);
+    /// One.
+    pub const ONE: Self = Self::from_bits(0x3FFF_8000_0000_0000_0000);
+    /// Positive infinity.
+    pub const INFINITY: Self = Self::from_bits(0x7FFF_8000_0000_0000_0000);
+    /// The default NaN the x87 produces for invalid operations.
+    pub const NAN: Self = Self::from_bits(0xFFFF_C000_0000_0000_0000);
+
+    /// The value whose 80 bits are the low bits of `bits`.
+    #[inline]
+    pub const fn from_bits(bits: u128) -> Self {
+        Self {
+            sign_exp: (bits >> 64) as u16,
+            sig: bits as u64,
+        }
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u128 {
+        (self.sign_exp as u128) << 64 | self.sig as u128
+    }
+
+    /// The value stored in memory as `bytes`, as `fld` loads it.
+    #[inline]
+    pub const fn from_le_bytes(bytes: [u8; 10]) -> Self {
+        let mut buf = [0; 16];
+        let mut i = 0;
+        while i < 10 {
+            buf[i] = bytes[i];
+            i += 1;
+        }
+        Self::from_bits(u128::from_le_bytes(buf))
+    }
+
+    /// The bytes `fstp` stores for the value.
+    #[inline]
+    pub const fn to_le_bytes(self) -> [u8; 10] {
+        let buf = self.to_bits().to_le_bytes();
+        let mut bytes = [0; 10];
+        let mut i = 0;
+        while i < 10 {
+            bytes[i] = buf[i];
+            i += 1;
+        }
+        bytes
+    }
+
+    #[inline]
+    pub const fn is_sign_negative(self) -> bool {
+        self.sign_exp >> 15 != 0
+    }
+
+    /// Whether the value is a NaN, which needs the integer bit set.
+    #[inline]
+    pub const fn is_nan(self) -> bool {
+        self.sign_exp & 0x7FFF == 0x7FFF && self.sig & 1 << 63 != 0 && self.sig << 1 != 0
+    }
+
+    /// Whether the value is an unnormal, pseudo-NaN or pseudo-infinity, which
+    /// the 387 and later reject as invalid operands.
+    #[inline]
+    pub const fn is_unsupported(self) -> bool {
+        self.sign_exp & 0x7FFF != 0 && self.sig & 1 << 63 == 0
+    }
+}
+
+/// The precision control of the x87, which is the width of the significands
+/// the arithmetic rounds to, keeping the exponent range of the format.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// 24 bits, as in `f32`.
+    Single,
+    /// 53 bits, as in `f64`.
+    Double,
+    /// All 64 bits of the format.
+    Extended,
+}
+
+impl Precision {
+    #[inline]
+    pub(crate) fn bits(self) -> i32 {
+        match self {
+            Self::Single => 24,
+            Self::Double => 53,
+            Self::Extended => 64,
+        }
+    }
+}
+
+/// How the trigonometric functions reduce their arguments by multiples of
+/// `π / 2`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Accuracy {
+    /// By the 66-bit `π` of the 387 and later, as they do, so results near
+    /// multiples of `π` keep the error of that `π` in place of the few bits
+    /// the exact remainder has. Only the reduction and the flags match the
+    /// hardware, as the function of the reduced argument rounds correctly,
+    /// which can leave results an ulp from those of `fsin` and the like.
+    HardwareReduction,
+    /// By `π` to 330 bits, so results round correctly across the range.
+    Correct,
+}
+
+/// The exception bits of the x87 status word that `status` raises.
+#[inline]
+pub fn status_word(status: Status) -> u16 {
+    (status.invalid() as u16)
+        | (status.denorm() as u16) << 1
+        | (status.div_zero() as u16) << 2
+        | (status.overflow() as u16) << 3
+        | (status.underflow() as u16) << 4
+        | (status.inexact() as u16) << 5
+}
+
+#[inline]
+pub(crate) fn env(flags: Flags) -> soft::Env {
+    soft::Env {
+        ftz: false,
+        daz: false,
+        sem: soft::Semantics {
+            tininess_before_rounding: flags.tininess() == Tininess::BeforeRounding,
+            ..soft::Semantics::X87
+        },
+        ..flags.soft_env()
+    }
+}
+
+#[inline]
+fn result<T>((value, exc): (T, u32)) -> (T, Status) {
+    (value, Status::from_soft(exc))
+}
+
+#[inline]
+pub fn add(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+    result(soft::extended::add(&env(flags), l, r, precision.bits()))
+}
+
+#[inline]
+pub fn sub(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+    result(soft::extended::sub(&env(flags), l, r, precision.bits()))
+}
+
+#[inline]
+pub fn mul(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+    result(soft::extended::mul(&env(flags), l, r, precision.bits()))
+}
+
+#[inline]
+pub fn div(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+    result(soft::extended::div(&env(flags), l, r, precision.bits()))
+}
+
+#[inline]
+pub fn sqrt(flags: Flags, precision: Precision, x: F80) -> (F80, Status) {
+    result(soft::extended::sqrt(&env(flags), x, precision.bits()))
+}
+
+/// Rounds `x` to an integral value, as `frndint` does, which the precision
+/// control doesn't apply to.
+#[inline]
+pub fn round(flags: Flags, x: F80) -> (F80, Status) {
+    result(soft::extended::round_int(&env(flags), x))
+}
+
+/// Compares `l` with `r`, as `fcom` does, raising invalid for any NaN.
+#[inline]
+pub fn compare(l: F80, r: F80) -> (Option<Ordering>, Status) {
+    result(soft::extended::compare(l, r, false))
+}
+
+/// Compares `l` with `r`, as `fucom` does, raising invalid only for
+/// signaling NaNs.
+#[inline]
+pub fn compare_quiet(l: F80, r: F80) -> (Option<Ordering>, Status) {
+    result(soft::extended::compare(l, r, true))
+}
+
+#[cfg(feature = "convert")]
+/// Widens `single` exactly, quieting signaling NaNs.
+#[inline]
+pub fn from_single(flags: Flags, single: f32) -> (F80, Status) {
+    result(soft::extended::from::<soft::Binary32>(
+        &env(flags),
+        single.to_bits() as u64,
+    ))
+}
+
+#[cfg(feature = "convert")]
+/// Widens `double` exactly, quieting signaling NaNs.
+#[inline]
+pub fn from_double(flags: Flags, double: f64) -> (F80, Status) {
+    result(soft::extended::from::<soft::Binary64>(
+        &env(flags),
+        double.to_bits(),
+    ))
+}
+
+#[cfg(feature = "convert")]
+#[inline]
+pub fn to_single(flags: Flags, x: F80) -> (f32, Status) {
+    let (bits, status) = result(soft::extended::to::<soft::Binary32>(&env(flags), x));
+    (f32::from_bits(bits as u32), status)
+}
+
+#[cfg(feature = "convert")]
+#[inline]
+pub fn to_double(flags: Flags, x: F80) -> (f64, Status) {
+    let (bits, status) = result(soft::extended::to::<soft::Binary64>(&env(flags), x));
+    (f64::from_bits(bits), status)
+}
+
+#[inline]
+fn trig(flags: Flags, accuracy: Accuracy, x: F80, trig: Trig) -> Option<(F80, Status)> {
+    let hardware = accuracy == Accuracy::HardwareReduction;
+    transcendental::trig(&env(flags), x, trig, hardware).map(result)
+}
+
+/// Computes the sine of `x`, as `fsin` does, returning `None` for finite
+/// magnitudes of 2^63 and over, which it leaves as they are, setting C2.
+#[inline]
+pub fn sin(flags: Flags, accuracy: Accuracy, x: F80) -> Option<(F80, Status)> {
+    trig(flags, accuracy, x, Trig::Sin)
+}
+
+/// Computes the cosine of `x`, as `fcos` does, returning `None` like `sin`.
+#[inline]
+pub fn cos(flags: Flags, accuracy: Accuracy, x: F80) -> Option<(F80, Status)> {
+    trig(flags, accuracy, x, Trig::Cos)
+}
+
+/// Computes the tangent of `x`, as `fptan` does before pushing one,
+/// returning `None` like `sin`.
+#[inline]
+pub fn tan(flags: Flags, accuracy: Accuracy, x: F80) -> Option<(F80, Status)> {
+    trig(flags, accuracy, x, Trig::Tan)
+}
+
+/// Computes the angle of the point `(x, y)` from the positive x-axis, as
+/// `fpatan` does, which follows IEEE 754 `atan2` for zeros and infinities,
+/// raising nothing for them.
+#[inline]
+pub fn atan2(flags: Flags, y: F80, x: F80) -> (F80, Status) {
+    result(transcendental::atan2(&env(flags), y, x))
+}
+
+/// Computes `2^x - 1`, as `f2xm1` does for `x` from -1 to 1, and beyond where
+/// the result of the x87 is undefined.
+#[inline]
+pub fn exp2_m1(flags: Flags, x: F80) -> (F80, Status) {
+    result(transcendental::exp2_m1(&env(flags), x))
+}
+
+/// Computes `y * log2(x)` with a single rounding, as `fyl2x` does.
+#[inline]
+pub fn y_log2_x(flags: Flags, y: F80, x: F80) -> (F80, Status) {
+    result(transcendental::y_log2_x(&env(flags), y, x))
+}
@@ -0,0 +1,140 @@
+/// The floating-point extensions of the CPU the program runs on.
+#[derive(Clone, Copy)]
+pub struct Features {
+    inner: u32,
+}
+
+impl Features {
+    /// Fused multiply-add, reported where `madd` runs on it.
+    pub const FMA: Self = Self { inner: 1 << 0 };
+    /// SSE4.1 on x86.
+    pub const SSE4_1: Self = Self { inner: 1 << 1 };
+    /// Half-precision conversions on x86.
+    pub const F16C: Self = Self { inner: 1 << 2 };
+    /// AVX on x86, including the operating system saving its state.
+    pub const AVX: Self = Self { inner: 1 << 3 };
+    /// AVX-512 Foundation on x86, including the operating system saving its
+    /// state.
+    pub const AVX512F: Self = Self { inner: 1 << 4 };
+    /// AVX-512 half-precision arithmetic on x86.
+    pub const AVX512FP16: Self = Self { inner: 1 << 5 };
+    /// Half-precision arithmetic on RISC-V.
+    pub const ZFH: Self = Self { inner: 1 << 6 };
+    /// AVX-512 Doubleword and Quadword on x86.
+    pub const AVX512DQ: Self = Self { inner: 1 << 7 };
+
+    #[inline]
+    pub fn empty() -> Self {
+        Self { inner: 0 }
+    }
+
+    #[inline]
+    pub fn has(self, features: Self) -> bool {
+        self.inner & features.inner == features.inner
+    }
+
+    #[inline]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            inner: self.inner | other.inner,
+        }
+    }
+
+    #[inline]
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            inner: self.inner & other.inner,
+        }
+    }
+
+    #[inline]
+    fn when(self, enabled: bool) -> Self {
+        if enabled {
+            self
+        } else {
+            Self::empty()
+        }
+    }
+}
+
+/// Detects the floating-point extensions of the CPU.
+///
+/// Queries CPUID on x86, only once, and falls back to the features enabled
+/// at compile time elsewhere, as other architectures offer no unprivileged
+/// way to ask.
+#[inline]
+pub fn features() -> Features {
+    detect()
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(all(any(target_arch = "x86_64", target_arch = "x86"), not(miri)))] {
+        #[cfg(target_arch = "x86")]
+        use core::arch::x86 as arch;
+        #[cfg(target_arch = "x86_64")]
+        use core::arch::x86_64 as arch;
+
+        /// The features as first queried, for dispatching without querying
+        /// CPUID every time.
+        fn detect() -> Features {
+            use core::sync::atomic::{AtomicU32, Ordering};
+
+            // Detected features never include this bit, so it marks the cache
+            // as empty.
+            const UNKNOWN: u32 = 1 << 31;
+            static CACHE: AtomicU32 = AtomicU32::new(UNKNOWN);
+            let inner = CACHE.load(Ordering::Relaxed);
+            if inner != UNKNOWN {
+                return Features { inner };
+            }
+            let features = query();
+            CACHE.store(features.inner, Ordering::Relaxed);
+            features
+        }
+
+        fn query() -> Features {
+            let leaf1 = arch::__cpuid(1);
+            let leaf7 = if arch::__cpuid(0).eax >= 7 {
+                arch::__cpuid_count(7, 0)
+            } else {
+                arch::CpuidResult { eax: 0, ebx: 0, ecx: 0, edx: 0 }
+            };
+            let bit = |reg: u32, bit: u32| reg & 1 << bit != 0;
+            // The registers are only usable once the operating system saves
+            // them, which it reports through XCR0.
+            let xcr0 = if bit(leaf1.ecx, 27) { xgetbv() } else { 0 };
+            let avx = bit(leaf1.ecx, 28) && xcr0 & 0x06 == 0x06;
+            let avx512 = avx && xcr0 & 0xE0 == 0xE0 && bit(leaf7.ebx, 16);
+            // The other backends on x86 fuse in software.
+            Features::FMA
+                .when(crate::imp::FUSED_MADD && avx && bit(leaf1.ecx, 12))
+                .or(Features::SSE4_1.when(bit(leaf1.ecx, 19)))
+                .or(Features::F16C.when(avx && bit(leaf1.ecx, 29)))
+                .or(Features::AVX.when(avx))
+                .or(Features::AVX512F.when(avx512))
+                .or(Features::AVX512FP16.when(avx512 && bit(leaf7.edx, 23)))
+                .or(Features::AVX512DQ.when(avx512 && bit(leaf7.ebx, 17)))
+        }
+
+        #[inline]
+        fn xgetbv() -> u64 {
+            let (low, high): (u32, u32);
+            unsafe {
+                core::arch::asm!(
+                    "xgetbv",
+                    in("ecx") 0,
+                    out("eax") low,
+                    out("edx") high,
+                    options(nomem, nostack, preserves_flags),
+                );
+            }
+            (high as u64) << 32 | low as u64
+        }
+    } else {
+        fn detect() -> Features {
+            Features::FMA
+                .when(crate::imp::FUSED_MADD)
+                .or(Features::ZFH.when(cfg!(target_feature = "zfh")))
+        }
+    }
+}
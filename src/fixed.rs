@@ -0,0 +1,107 @@
+//! Operations whose rounding mode is a constant, as in
+//! `fixed::f64::add::<{ Rounding::Down as u32 }>(l, r)`, so the flags are
+//! built at compile time instead of for every operation.
+//!
+//! Every other flag keeps its default. Anything but a `Rounding` cast to
+//! `u32` fails to compile.
+
+use crate::{Flags, Rounding, Status};
+
+/// The flags rounding in mode `R`.
+struct Fixed<const R: u32>;
+
+impl<const R: u32> Fixed<R> {
+    const FLAGS: Flags = Flags::new().with_rounding(rounding(R));
+}
+
+const fn rounding(bits: u32) -> Rounding {
+    match bits {
+        b if b == Rounding::Zero as u32 => Rounding::Zero,
+        b if b == Rounding::Up as u32 => Rounding::Up,
+        b if b == Rounding::Down as u32 => Rounding::Down,
+        b if b == Rounding::Nearest as u32 => Rounding::Nearest,
+        b if b == Rounding::NearestAway as u32 => Rounding::NearestAway,
+        b if b == Rounding::Odd as u32 => Rounding::Odd,
+        _ => panic!("not a rounding mode"),
+    }
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add<const R: u32>(l: f32, r: f32) -> (f32, Status) {
+        crate::f32::add(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[inline]
+    pub fn sub<const R: u32>(l: f32, r: f32) -> (f32, Status) {
+        crate::f32::sub(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[inline]
+    pub fn mul<const R: u32>(l: f32, r: f32) -> (f32, Status) {
+        crate::f32::mul(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[inline]
+    pub fn div<const R: u32>(l: f32, r: f32) -> (f32, Status) {
+        crate::f32::div(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd<const R: u32>(a: f32, b: f32, c: f32) -> (f32, Status) {
+        crate::f32::madd(Fixed::<R>::FLAGS, a, b, c)
+    }
+
+    #[inline]
+    pub fn round<const R: u32>(x: f32) -> (f32, Status) {
+        crate::f32::round(Fixed::<R>::FLAGS, x)
+    }
+
+    #[inline]
+    pub fn sqrt<const R: u32>(x: f32) -> (f32, Status) {
+        crate::f32::sqrt(Fixed::<R>::FLAGS, x)
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add<const R: u32>(l: f64, r: f64) -> (f64, Status) {
+        crate::f64::add(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[inline]
+    pub fn sub<const R: u32>(l: f64, r: f64) -> (f64, Status) {
+        crate::f64::sub(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[inline]
+    pub fn mul<const R: u32>(l: f64, r: f64) -> (f64, Status) {
+        crate::f64::mul(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[inline]
+    pub fn div<const R: u32>(l: f64, r: f64) -> (f64, Status) {
+        crate::f64::div(Fixed::<R>::FLAGS, l, r)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd<const R: u32>(a: f64, b: f64, c: f64) -> (f64, Status) {
+        crate::f64::madd(Fixed::<R>::FLAGS, a, b, c)
+    }
+
+    #[inline]
+    pub fn round<const R: u32>(x: f64) -> (f64, Status) {
+        crate::f64::round(Fixed::<R>::FLAGS, x)
+    }
+
+    #[inline]
+    pub fn sqrt<const R: u32>(x: f64) -> (f64, Status) {
+        crate::f64::sqrt(Fixed::<R>::FLAGS, x)
+    }
+}
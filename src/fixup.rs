@@ -0,0 +1,301 @@
+//! The scalar operations as the crate exports them, emulating around the
+//! backend the modes its hardware lacks.
+//!
+//! Rounding modes the hardware lacks, such as ties away from zero outside
+//! RISC-V or to odd, run every operation in software.
+//!
+//! A result can only be tiny under one convention of detecting tininess if
+//! it raises underflow or rounds to the smallest normal magnitude, so only
+//! those run again in software when the flags select the convention the
+//! hardware doesn't use.
+
+use crate::{soft, Flags, Status};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "outline")] {
+        use crate::outline as base;
+    } else {
+        use crate::imp as base;
+    }
+}
+
+/// When a result is tiny, which decides whether it underflows and, when
+/// flushing to zero, whether it is flushed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tininess {
+    /// The result is tiny if it is nonzero and below the normal range before
+    /// rounding, as on ARM, Power and s390x.
+    BeforeRounding,
+    /// The result is tiny if it is nonzero and below the normal range after
+    /// rounding to unbounded exponent range, as on x86, RISC-V and
+    /// LoongArch.
+    AfterRounding,
+}
+
+/// The result of `host`, or of `soft` if the flags select a rounding mode
+/// the hardware lacks, or the convention of tininess it doesn't use and the
+/// result may differ under it.
+#[inline]
+fn fixup<T: soft::Value>(
+    flags: Flags,
+    host: impl FnOnce() -> (T, Status),
+    soft: impl FnOnce(&soft::Env) -> (u64, u32),
+) -> (T, Status) {
+    if !flags.soft_rounding() {
+        let (value, status) = host();
+        if flags.tininess() == Flags::new().tininess()
+            || !(status.underflow() || status.inexact() && value.is_min_normal())
+        {
+            return (value, status);
+        }
+    }
+    let (bits, exc) = soft(&flags.soft_env());
+    (T::from_soft(bits), Status::from_soft(exc))
+}
+
+pub mod f32 {
+    use super::*;
+
+    pub use base::f32::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::add(flags, l, r),
+            |env| soft::add::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::sub(flags, l, r),
+            |env| soft::sub::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::mul(flags, l, r),
+            |env| soft::mul::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::div(flags, l, r),
+            |env| soft::div::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f32, b: f32, c: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::madd(flags, a, b, c),
+            |env| {
+                soft::fma::<soft::Binary32>(
+                    env,
+                    a.to_bits() as u64,
+                    b.to_bits() as u64,
+                    c.to_bits() as u64,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::round(flags, x),
+            |env| soft::round_int::<soft::Binary32>(env, x.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f32) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f32::sqrt(flags, x),
+            |env| soft::sqrt::<soft::Binary32>(env, x.to_bits() as u64),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f32::to_half(flags, single),
+            |env| soft::convert::<soft::Binary32, soft::Binary16>(env, single.to_bits() as u64),
+        )
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    pub use base::f64::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::add(flags, l, r),
+            |env| soft::add::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::sub(flags, l, r),
+            |env| soft::sub::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::mul(flags, l, r),
+            |env| soft::mul::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::div(flags, l, r),
+            |env| soft::div::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f64, b: f64, c: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::madd(flags, a, b, c),
+            |env| soft::fma::<soft::Binary64>(env, a.to_bits(), b.to_bits(), c.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::round(flags, x),
+            |env| soft::round_int::<soft::Binary64>(env, x.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f64) -> (f64, Status) {
+        fixup(
+            flags,
+            || base::f64::sqrt(flags, x),
+            |env| soft::sqrt::<soft::Binary64>(env, x.to_bits()),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, double: f64) -> (f32, Status) {
+        fixup(
+            flags,
+            || base::f64::to_single(flags, double),
+            |env| soft::convert::<soft::Binary64, soft::Binary32>(env, double.to_bits()),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f64::to_half(flags, double),
+            |env| soft::convert::<soft::Binary64, soft::Binary16>(env, double.to_bits()),
+        )
+    }
+}
+
+pub mod f16 {
+    use super::*;
+
+    pub use base::f16::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::add(flags, l, r),
+            |env| soft::add::<soft::Binary16>(env, l as u64, r as u64),
+        )
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::sub(flags, l, r),
+            |env| soft::sub::<soft::Binary16>(env, l as u64, r as u64),
+        )
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::mul(flags, l, r),
+            |env| soft::mul::<soft::Binary16>(env, l as u64, r as u64),
+        )
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::div(flags, l, r),
+            |env| soft::div::<soft::Binary16>(env, l as u64, r as u64),
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: u16, b: u16, c: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::madd(flags, a, b, c),
+            |env| soft::fma::<soft::Binary16>(env, a as u64, b as u64, c as u64),
+        )
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::round(flags, x),
+            |env| soft::round_int::<soft::Binary16>(env, x as u64),
+        )
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: u16) -> (u16, Status) {
+        fixup(
+            flags,
+            || base::f16::sqrt(flags, x),
+            |env| soft::sqrt::<soft::Binary16>(env, x as u64),
+        )
+    }
+}
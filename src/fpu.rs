@@ -0,0 +1,286 @@
+use crate::{Flags, NanRule, Status};
+
+/// The floating-point unit of an emulated architecture, as seen through its
+/// control and status registers.
+///
+/// Every operation mirrors the function of the same name in the `f32` and
+/// `f64` modules, taking the `flags` that `flags` derives from `registers`
+/// and returning the registers with the raised exceptions added. The
+/// provided ones choose NaN results by `NAN_RULE` and `accumulate` the
+/// status; architectures whose results differ from the host's in other ways
+/// run their routines in `guest` instead, which read the registers
+/// themselves.
+pub trait Isa {
+    /// The image of the control and status registers.
+    type Registers: Copy;
+
+    /// The rule NaN results follow, or `None` for the host's.
+    const NAN_RULE: Option<NanRule> = None;
+
+    /// The flags the registers select. Only the control fields may be read,
+    /// as `accumulate` leaves them alone.
+    fn flags(registers: Self::Registers) -> Flags;
+
+    /// `registers` with the exceptions raised by `status` added to their
+    /// sticky flags.
+    fn accumulate(registers: Self::Registers, status: Status) -> Self::Registers;
+
+    #[inline]
+    fn f32_add(registers: Self::Registers, flags: Flags, l: f32, r: f32) -> (f32, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f32::add(flags, rule, l, r),
+            None => crate::f32::add(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f32_sub(registers: Self::Registers, flags: Flags, l: f32, r: f32) -> (f32, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f32::sub(flags, rule, l, r),
+            None => crate::f32::sub(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f32_mul(registers: Self::Registers, flags: Flags, l: f32, r: f32) -> (f32, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f32::mul(flags, rule, l, r),
+            None => crate::f32::mul(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f32_div(registers: Self::Registers, flags: Flags, l: f32, r: f32) -> (f32, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f32::div(flags, rule, l, r),
+            None => crate::f32::div(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    fn f32_madd(
+        registers: Self::Registers,
+        flags: Flags,
+        a: f32,
+        b: f32,
+        c: f32,
+    ) -> (f32, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f32::madd(flags, rule, a, b, c),
+            None => crate::f32::madd(flags, a, b, c),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f32_sqrt(registers: Self::Registers, flags: Flags, x: f32) -> (f32, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f32::sqrt(flags, rule, x),
+            None => crate::f32::sqrt(flags, x),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f64_add(registers: Self::Registers, flags: Flags, l: f64, r: f64) -> (f64, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f64::add(flags, rule, l, r),
+            None => crate::f64::add(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f64_sub(registers: Self::Registers, flags: Flags, l: f64, r: f64) -> (f64, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f64::sub(flags, rule, l, r),
+            None => crate::f64::sub(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f64_mul(registers: Self::Registers, flags: Flags, l: f64, r: f64) -> (f64, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f64::mul(flags, rule, l, r),
+            None => crate::f64::mul(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f64_div(registers: Self::Registers, flags: Flags, l: f64, r: f64) -> (f64, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f64::div(flags, rule, l, r),
+            None => crate::f64::div(flags, l, r),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    fn f64_madd(
+        registers: Self::Registers,
+        flags: Flags,
+        a: f64,
+        b: f64,
+        c: f64,
+    ) -> (f64, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f64::madd(flags, rule, a, b, c),
+            None => crate::f64::madd(flags, a, b, c),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+
+    #[inline]
+    fn f64_sqrt(registers: Self::Registers, flags: Flags, x: f64) -> (f64, Self::Registers) {
+        let (value, status) = match Self::NAN_RULE {
+            Some(rule) => crate::nan::f64::sqrt(flags, rule, x),
+            None => crate::f64::sqrt(flags, x),
+        };
+        (value, Self::accumulate(registers, status))
+    }
+}
+
+/// The floating-point state of a guest, holding its control and status
+/// registers and the host flags they select.
+///
+/// The flags are derived on the first operation after the registers are set
+/// and kept until they are set again, which the sticky flags the operations
+/// add to never require.
+#[derive(Clone, Copy)]
+pub struct GuestFpu<I: Isa> {
+    registers: I::Registers,
+    flags: Option<Flags>,
+}
+
+impl<I: Isa> GuestFpu<I> {
+    #[inline]
+    pub fn new(registers: I::Registers) -> Self {
+        Self {
+            registers,
+            flags: None,
+        }
+    }
+
+    /// The registers, with every exception raised so far.
+    #[inline]
+    pub fn registers(&self) -> I::Registers {
+        self.registers
+    }
+
+    /// Replaces the registers, as a guest write to them does.
+    #[inline]
+    pub fn set_registers(&mut self, registers: I::Registers) {
+        self.registers = registers;
+        self.flags = None;
+    }
+
+    /// The flags the registers select.
+    #[inline]
+    pub fn flags(&mut self) -> Flags {
+        *self.flags.get_or_insert_with(|| I::flags(self.registers))
+    }
+
+    /// Performs `op` under the flags and adds the exceptions it raised, for
+    /// the operations `Isa` does not cover. Instructions with a static
+    /// rounding mode change it in the flags `op` is given.
+    #[inline]
+    pub fn run<T>(&mut self, op: impl FnOnce(Flags) -> (T, Status)) -> T {
+        let (value, status) = op(self.flags());
+        self.registers = I::accumulate(self.registers, status);
+        value
+    }
+
+    #[inline]
+    pub fn f32_add(&mut self, l: f32, r: f32) -> f32 {
+        let (value, registers) = I::f32_add(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f32_sub(&mut self, l: f32, r: f32) -> f32 {
+        let (value, registers) = I::f32_sub(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f32_mul(&mut self, l: f32, r: f32) -> f32 {
+        let (value, registers) = I::f32_mul(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f32_div(&mut self, l: f32, r: f32) -> f32 {
+        let (value, registers) = I::f32_div(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn f32_madd(&mut self, a: f32, b: f32, c: f32) -> f32 {
+        let (value, registers) = I::f32_madd(self.registers, self.flags(), a, b, c);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f32_sqrt(&mut self, x: f32) -> f32 {
+        let (value, registers) = I::f32_sqrt(self.registers, self.flags(), x);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f64_add(&mut self, l: f64, r: f64) -> f64 {
+        let (value, registers) = I::f64_add(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f64_sub(&mut self, l: f64, r: f64) -> f64 {
+        let (value, registers) = I::f64_sub(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f64_mul(&mut self, l: f64, r: f64) -> f64 {
+        let (value, registers) = I::f64_mul(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f64_div(&mut self, l: f64, r: f64) -> f64 {
+        let (value, registers) = I::f64_div(self.registers, self.flags(), l, r);
+        self.registers = registers;
+        value
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn f64_madd(&mut self, a: f64, b: f64, c: f64) -> f64 {
+        let (value, registers) = I::f64_madd(self.registers, self.flags(), a, b, c);
+        self.registers = registers;
+        value
+    }
+
+    #[inline]
+    pub fn f64_sqrt(&mut self, x: f64) -> f64 {
+        let (value, registers) = I::f64_sqrt(self.registers, self.flags(), x);
+        self.registers = registers;
+        value
+    }
+}
@@ -0,0 +1,109 @@
+//! Functions of the C library built on the operations, which need neither
+//! the `math` feature nor its evaluation.
+//!
+//! The Euclidean norm runs in software on every backend, correctly rounded
+//! and raising overflow and underflow only for the result. The powers
+//! multiply by repeated squaring, rounding every product under the flags,
+//! so they are not correctly rounded, and raise the exceptions of the steps
+//! they keep.
+
+pub(crate) mod f32 {
+    use crate::{soft, Flags, Status};
+
+    /// Computes `sqrt(x^2 + y^2)`. An infinity gives positive infinity even
+    /// with a quiet NaN.
+    #[inline]
+    pub fn hypot(flags: Flags, x: f32, y: f32) -> (f32, Status) {
+        let (bits, exc) = soft::hypot::<soft::Binary32>(
+            &flags.soft_env(),
+            x.to_bits() as u64,
+            y.to_bits() as u64,
+        );
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    /// Computes `x^n` by repeated squaring. Negative powers are the
+    /// reciprocals of the positive ones, or the powers of the reciprocal of
+    /// `x` where those overflow or fall below the normal range.
+    #[inline]
+    pub fn powi(flags: Flags, x: f32, n: i32) -> (f32, Status) {
+        let (power, status) = unsigned_power(flags, x, n.unsigned_abs());
+        if n >= 0 {
+            return (power, status);
+        }
+        let tiny = power == 0.0 || power.is_subnormal() || status.underflow();
+        if (power.is_infinite() || tiny) && x.is_finite() && x != 0.0 {
+            let (reciprocal, raised) = crate::f32::div(flags, 1.0, x);
+            let (power, status) = unsigned_power(flags, reciprocal, n.unsigned_abs());
+            return (power, status.or(raised));
+        }
+        let (reciprocal, raised) = crate::f32::div(flags, 1.0, power);
+        (reciprocal, status.or(raised))
+    }
+
+    fn unsigned_power(flags: Flags, x: f32, n: u32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let (mut power, mut square, mut bits) = (1.0, x, n);
+        while bits != 0 {
+            if bits & 1 != 0 {
+                let (product, raised) = crate::f32::mul(flags, power, square);
+                (power, status) = (product, status.or(raised));
+            }
+            bits >>= 1;
+            if bits != 0 {
+                let (product, raised) = crate::f32::mul(flags, square, square);
+                (square, status) = (product, status.or(raised));
+            }
+        }
+        (power, status)
+    }
+}
+
+pub(crate) mod f64 {
+    use crate::{soft, Flags, Status};
+
+    /// Computes `sqrt(x^2 + y^2)`. An infinity gives positive infinity even
+    /// with a quiet NaN.
+    #[inline]
+    pub fn hypot(flags: Flags, x: f64, y: f64) -> (f64, Status) {
+        let (bits, exc) =
+            soft::hypot::<soft::Binary64>(&flags.soft_env(), x.to_bits(), y.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    /// Computes `x^n` by repeated squaring. Negative powers are the
+    /// reciprocals of the positive ones, or the powers of the reciprocal of
+    /// `x` where those overflow or fall below the normal range.
+    #[inline]
+    pub fn powi(flags: Flags, x: f64, n: i32) -> (f64, Status) {
+        let (power, status) = unsigned_power(flags, x, n.unsigned_abs());
+        if n >= 0 {
+            return (power, status);
+        }
+        let tiny = power == 0.0 || power.is_subnormal() || status.underflow();
+        if (power.is_infinite() || tiny) && x.is_finite() && x != 0.0 {
+            let (reciprocal, raised) = crate::f64::div(flags, 1.0, x);
+            let (power, status) = unsigned_power(flags, reciprocal, n.unsigned_abs());
+            return (power, status.or(raised));
+        }
+        let (reciprocal, raised) = crate::f64::div(flags, 1.0, power);
+        (reciprocal, status.or(raised))
+    }
+
+    fn unsigned_power(flags: Flags, x: f64, n: u32) -> (f64, Status) {
+        let mut status = Status::empty();
+        let (mut power, mut square, mut bits) = (1.0, x, n);
+        while bits != 0 {
+            if bits & 1 != 0 {
+                let (product, raised) = crate::f64::mul(flags, power, square);
+                (power, status) = (product, status.or(raised));
+            }
+            bits >>= 1;
+            if bits != 0 {
+                let (product, raised) = crate::f64::mul(flags, square, square);
+                (square, status) = (product, status.or(raised));
+            }
+        }
+        (power, status)
+    }
+}
@@ -0,0 +1,3068 @@
+//! Translations between the floating-point control and status registers of
+//! emulated CPUs and the host `Flags` and `Status`.
+//!
+//! Each module takes the raw register image the guest state stores, so an
+//! emulator builds the flags for an operation from it and folds the status
+//! the operation returned back into it. The status follows the host, so
+//! flags only some architectures report, such as the denormal operand flag,
+//! are only as precise as the host's.
+
+pub mod x86 {
+    //! The SSE control and status register, MXCSR, and the compare
+    //! predicates of SSE and AVX, which run in software under it.
+
+    use core::cmp::Ordering;
+
+    use crate::{soft, Flags, NanRule, Rounding, Status};
+
+    /// The invalid operation flag.
+    pub const IE: u32 = 1 << 0;
+    /// The denormal operand flag.
+    pub const DE: u32 = 1 << 1;
+    /// The divide-by-zero flag.
+    pub const ZE: u32 = 1 << 2;
+    /// The overflow flag.
+    pub const OE: u32 = 1 << 3;
+    /// The underflow flag.
+    pub const UE: u32 = 1 << 4;
+    /// The precision flag.
+    pub const PE: u32 = 1 << 5;
+    /// Every exception flag.
+    pub const FLAGS: u32 = 0x3F;
+    /// Treats denormal operands as zero.
+    pub const DAZ: u32 = 1 << 6;
+    /// Every exception mask, each at its flag shifted by `MASK_SHIFT`.
+    pub const MASKS: u32 = FLAGS << MASK_SHIFT;
+    /// The distance between an exception flag and its mask.
+    pub const MASK_SHIFT: u32 = 7;
+    /// The rounding control field.
+    pub const RC: u32 = 0b11 << 13;
+    /// Flushes tiny results to zero.
+    pub const FTZ: u32 = 1 << 15;
+    /// The value after reset, with every exception masked.
+    pub const DEFAULT: u32 = 0x1F80;
+
+    /// The flags selected by `mxcsr`.
+    #[inline]
+    pub fn flags(mxcsr: u32) -> Flags {
+        let rounding = match (mxcsr & RC) >> 13 {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::Down,
+            0b10 => Rounding::Up,
+            _ => Rounding::Zero,
+        };
+        Flags::new()
+            .with_rounding(rounding)
+            .with_ftz(mxcsr & FTZ != 0)
+            .with_daz(mxcsr & DAZ != 0)
+    }
+
+    /// The exception flags raised by `status`.
+    #[inline]
+    pub fn exceptions(status: Status) -> u32 {
+        [
+            (status.invalid(), IE),
+            (status.denorm(), DE),
+            (status.div_zero(), ZE),
+            (status.overflow(), OE),
+            (status.underflow(), UE),
+            (status.inexact(), PE),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit)
+    }
+
+    /// `mxcsr` with the exceptions raised by `status` added to its sticky
+    /// flags.
+    #[inline]
+    pub fn accumulate(mxcsr: u32, status: Status) -> u32 {
+        mxcsr | exceptions(status)
+    }
+
+    /// The exceptions raised by `status` that `mxcsr` leaves unmasked, which
+    /// the guest expects to trap on instead of taking the result.
+    ///
+    /// The operations run with every exception masked, so underflow is only
+    /// raised for inexact tiny results, while an unmasked `UE` also traps on
+    /// exact ones.
+    #[inline]
+    pub fn unmasked(mxcsr: u32, status: Status) -> u32 {
+        exceptions(status) & !(mxcsr >> MASK_SHIFT)
+    }
+
+    /// Whether the compare predicate `imm` holds for operands that compare
+    /// as `ordering`, and whether it raises invalid for quiet NaNs.
+    ///
+    /// The low three bits select the predicates of SSE, and AVX adds the
+    /// ones that invert their result for unordered operands under bit 3 and
+    /// their opposite signaling under bit 4.
+    #[inline]
+    fn predicate(imm: u8, ordering: Option<Ordering>) -> (bool, bool) {
+        let holds = match ordering {
+            None => matches!(imm & 0xF, 3 | 4 | 5 | 6 | 8 | 9 | 10 | 15),
+            Some(ordering) => match imm & 0xF {
+                0 | 8 => ordering == Ordering::Equal,
+                1 | 9 => ordering == Ordering::Less,
+                2 | 10 => ordering != Ordering::Greater,
+                3 | 11 => false,
+                4 | 12 => ordering != Ordering::Equal,
+                5 | 13 => ordering != Ordering::Less,
+                6 | 14 => ordering == Ordering::Greater,
+                _ => true,
+            },
+        };
+        let signaling = matches!(imm & 0xF, 1 | 2 | 5 | 6 | 9 | 10 | 13 | 14) != (imm & 0x10 != 0);
+        (holds, signaling)
+    }
+
+    /// Runs the comparison of `l` with `r` under `imm` as the hardware does
+    /// under `mxcsr`, returning whether it holds.
+    #[inline]
+    fn compare<F: soft::Format>(mxcsr: u32, imm: u8, l: u64, r: u64) -> (bool, Status) {
+        let env = soft::Env {
+            sem: soft::Semantics::X86,
+            ..flags(mxcsr).soft_env()
+        };
+        let (ordering, mut exc) = soft::compare::<F>(&env, l, r);
+        let (holds, signaling) = predicate(imm, ordering);
+        if ordering.is_none() && signaling {
+            exc |= soft::INVALID;
+        }
+        (holds, Status::from_soft(exc))
+    }
+
+    /// The SSE unit, whose registers are the MXCSR. NaN results follow x86
+    /// on any host.
+    #[derive(Clone, Copy)]
+    pub struct Sse;
+
+    impl crate::Isa for Sse {
+        type Registers = u32;
+
+        const NAN_RULE: Option<NanRule> = Some(NanRule::FirstOperand);
+
+        #[inline]
+        fn flags(mxcsr: u32) -> Flags {
+            flags(mxcsr)
+        }
+
+        #[inline]
+        fn accumulate(mxcsr: u32, status: Status) -> u32 {
+            accumulate(mxcsr, status)
+        }
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        /// Compares `l` with `r` under the predicate in the low five bits of
+        /// `imm`, as `cmpss` and `vcmpss` do for the immediate, returning
+        /// the mask of all ones where it holds and zero elsewhere.
+        #[inline]
+        pub fn cmp(mxcsr: u32, imm: u8, l: f32, r: f32) -> (u32, Status) {
+            let (l, r) = (l.to_bits() as u64, r.to_bits() as u64);
+            let (holds, status) = compare::<soft::Binary32>(mxcsr, imm, l, r);
+            (if holds { !0 } else { 0 }, status)
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        /// Compares `l` with `r` like `f32::cmp`, as `cmpsd` and `vcmpsd` do.
+        #[inline]
+        pub fn cmp(mxcsr: u32, imm: u8, l: f64, r: f64) -> (u64, Status) {
+            let (holds, status) = compare::<soft::Binary64>(mxcsr, imm, l.to_bits(), r.to_bits());
+            (if holds { !0 } else { 0 }, status)
+        }
+    }
+}
+
+pub mod arm {
+    //! The AArch32 FPSCR and its AArch64 split into FPCR and FPSR.
+    //!
+    //! Every field keeps its FPSCR position in FPCR and FPSR, so the same
+    //! functions take either an FPSCR or the matching half. The fields
+    //! FEAT_AFP adds to the FPCR overlap the cumulative flags of an FPSCR, so
+    //! only `control_afp` and the operations here read them.
+    //!
+    //! The operations perform what a core with FEAT_AFP does under an FPCR,
+    //! down to the flags they add to the FPSR, running again in software
+    //! whenever the host might differ.
+
+    use crate::{soft, Flags, Rounding, Status, Tininess};
+
+    /// Flushes denormal inputs to zero, with FEAT_AFP.
+    pub const FIZ: u32 = 1 << 0;
+    /// Selects the alternate handling of FEAT_AFP, which follows x86: the
+    /// first NaN operand is returned, the default NaN is negative, `FZ` only
+    /// flushes results, tininess is detected after rounding and flushing is
+    /// inexact. Inputs flushed under it raise no `IDC`.
+    pub const AH: u32 = 1 << 1;
+    /// Makes scalar operations keep the other elements of their destination
+    /// vector, with FEAT_AFP.
+    pub const NEP: u32 = 1 << 2;
+    /// The fields FEAT_AFP adds to the FPCR.
+    pub const AFP: u32 = FIZ | AH | NEP;
+
+    /// The invalid operation cumulative flag.
+    pub const IOC: u32 = 1 << 0;
+    /// The divide-by-zero cumulative flag.
+    pub const DZC: u32 = 1 << 1;
+    /// The overflow cumulative flag.
+    pub const OFC: u32 = 1 << 2;
+    /// The underflow cumulative flag.
+    pub const UFC: u32 = 1 << 3;
+    /// The inexact cumulative flag.
+    pub const IXC: u32 = 1 << 4;
+    /// The input denormal cumulative flag.
+    pub const IDC: u32 = 1 << 7;
+    /// Every cumulative exception flag.
+    pub const FLAGS: u32 = IOC | DZC | OFC | UFC | IXC | IDC;
+    /// Every trap enable, each at its flag shifted by `ENABLE_SHIFT`.
+    pub const ENABLES: u32 = FLAGS << ENABLE_SHIFT;
+    /// The distance between an exception flag and its trap enable.
+    pub const ENABLE_SHIFT: u32 = 8;
+    /// The vector length field, which only AArch32 VFP uses.
+    pub const LEN: u32 = 0b111 << 16;
+    /// Flushes half-precision denormals to zero.
+    pub const FZ16: u32 = 1 << 19;
+    /// The vector stride field, which only AArch32 VFP uses.
+    pub const STRIDE: u32 = 0b11 << 20;
+    /// The rounding mode field.
+    pub const RMODE: u32 = 0b11 << 22;
+    /// Flushes single and double-precision denormals to zero.
+    pub const FZ: u32 = 1 << 24;
+    /// Replaces every NaN result with the default NaN.
+    pub const DN: u32 = 1 << 25;
+    /// Selects the alternative half-precision format, without infinities and
+    /// NaNs.
+    pub const AHP: u32 = 1 << 26;
+    /// The saturation flag of the integer vector instructions.
+    pub const QC: u32 = 1 << 27;
+    /// The comparison flags, which only AArch32 keeps in the FPSCR.
+    pub const NZCV: u32 = 0xF << 28;
+    /// The FPSCR fields that AArch64 moves to the FPCR.
+    pub const FPCR: u32 = ENABLES | LEN | FZ16 | STRIDE | RMODE | FZ | DN | AHP;
+    /// The FPSCR fields that AArch64 moves to the FPSR.
+    pub const FPSR: u32 = FLAGS | QC | NZCV;
+
+    /// The settings an FPCR selects.
+    #[derive(Clone, Copy)]
+    pub struct Control {
+        /// The flags for single and double-precision operations.
+        pub flags: Flags,
+        /// The flags for half-precision operations, which flush under `FZ16`
+        /// instead of `FZ`.
+        pub half_flags: Flags,
+        /// Whether NaN results must be replaced with the default NaN. The
+        /// `nan` operations under `NanRule::Canonical` return the positive
+        /// one ARM does, while `Flags::with_default_nan` returns the host's.
+        pub default_nan: bool,
+        /// Whether half-precision conversions use the alternative format,
+        /// which the `half` conversions take as `HalfFormat::Alternative`.
+        pub alternative_half: bool,
+        /// Whether `AH` selects the alternate handling, which `flags` only
+        /// follows in flushing and tininess.
+        pub alternate: bool,
+        /// Whether `NEP` keeps the other elements of the destination vector,
+        /// which is up to the emulator writing the result.
+        pub preserve_elements: bool,
+    }
+
+    /// The settings selected by `fpcr`, ignoring the fields of FEAT_AFP.
+    #[inline]
+    pub fn control(fpcr: u32) -> Control {
+        control_afp(fpcr & !AFP)
+    }
+
+    /// The settings selected by an AArch64 `fpcr` on a core with FEAT_AFP.
+    #[inline]
+    pub fn control_afp(fpcr: u32) -> Control {
+        let rounding = match (fpcr & RMODE) >> 22 {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::Up,
+            0b10 => Rounding::Down,
+            _ => Rounding::Zero,
+        };
+        let alternate = fpcr & AH != 0;
+        let flags = Flags::new()
+            .with_rounding(rounding)
+            .with_tininess(if alternate {
+                Tininess::AfterRounding
+            } else {
+                Tininess::BeforeRounding
+            });
+        // Flushing applies to both the inputs and the results, unless the
+        // alternate handling leaves the inputs to `FIZ`.
+        let fz = fpcr & FZ != 0;
+        let fz16 = fpcr & FZ16 != 0;
+        let fiz = fpcr & FIZ != 0;
+        Control {
+            flags: flags.with_ftz(fz).with_daz(fiz || fz && !alternate),
+            half_flags: flags.with_ftz(fz16).with_daz(fz16 && !alternate),
+            default_nan: fpcr & DN != 0,
+            alternative_half: fpcr & AHP != 0,
+            alternate,
+            preserve_elements: fpcr & NEP != 0,
+        }
+    }
+
+    /// The FPSCR combining an AArch64 `fpcr` and `fpsr`.
+    #[inline]
+    pub fn fpscr(fpcr: u32, fpsr: u32) -> u32 {
+        fpcr & FPCR | fpsr & FPSR
+    }
+
+    /// The AArch64 FPCR and FPSR an `fpscr` splits into.
+    #[inline]
+    pub fn split(fpscr: u32) -> (u32, u32) {
+        (fpscr & FPCR, fpscr & FPSR)
+    }
+
+    /// The cumulative exception flags raised by `status`.
+    #[inline]
+    pub fn exceptions(status: Status) -> u32 {
+        [
+            (status.invalid(), IOC),
+            (status.div_zero(), DZC),
+            (status.overflow(), OFC),
+            (status.underflow(), UFC),
+            (status.inexact(), IXC),
+            (status.denorm(), IDC),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit)
+    }
+
+    /// `fpsr` with the exceptions raised by `status` added to its cumulative
+    /// flags.
+    #[inline]
+    pub fn accumulate(fpsr: u32, status: Status) -> u32 {
+        fpsr | exceptions(status)
+    }
+
+    /// The exceptions raised by `status` whose traps `fpcr` enables.
+    #[inline]
+    pub fn enabled(fpcr: u32, status: Status) -> u32 {
+        exceptions(status) & fpcr >> ENABLE_SHIFT
+    }
+    /// The result of `host` under `fpcr`, or of `soft` where the host may
+    /// differ from the core, and `fpsr` with the raised exceptions added.
+    #[inline]
+    fn finish<T: soft::Value>(
+        fpcr: u32,
+        fpsr: u32,
+        denormal: bool,
+        host: impl FnOnce(Flags) -> (T, Status),
+        soft: impl FnOnce(&soft::Env) -> (u64, u32),
+    ) -> (T, u32) {
+        let control = control_afp(fpcr);
+        if !denormal {
+            let (value, status) = host(control.flags);
+            if !(value.is_nan() || status.underflow() || status.inexact() && value.is_min_normal())
+            {
+                return (value, accumulate(fpsr, status));
+            }
+        }
+        let env = soft::Env {
+            default_nan: control.default_nan,
+            sem: if control.alternate {
+                soft::Semantics::ARM_ALTERNATE
+            } else {
+                soft::Semantics::ARM
+            },
+            ..control.flags.soft_env()
+        };
+        let (bits, exc) = soft(&env);
+        let mut exceptions = exceptions(Status::from_soft(exc));
+        if control.alternate {
+            exceptions &= !IDC;
+        }
+        (T::from_soft(bits), fpsr | exceptions)
+    }
+
+    /// An AArch64 core with FEAT_AFP, whose registers are the FPCR and the
+    /// FPSR, in that order. AArch32 keeps both in the FPSCR, which `split`
+    /// separates.
+    #[derive(Clone, Copy)]
+    pub struct AArch64;
+
+    impl crate::Isa for AArch64 {
+        type Registers = (u32, u32);
+
+        #[inline]
+        fn flags((fpcr, _): (u32, u32)) -> Flags {
+            control_afp(fpcr).flags
+        }
+
+        #[inline]
+        fn accumulate((fpcr, fpsr): (u32, u32), status: Status) -> (u32, u32) {
+            (fpcr, accumulate(fpsr, status))
+        }
+
+        #[inline]
+        fn f32_add((fpcr, fpsr): (u32, u32), _: Flags, l: f32, r: f32) -> (f32, (u32, u32)) {
+            let (value, fpsr) = f32::add(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f32_sub((fpcr, fpsr): (u32, u32), _: Flags, l: f32, r: f32) -> (f32, (u32, u32)) {
+            let (value, fpsr) = f32::sub(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f32_mul((fpcr, fpsr): (u32, u32), _: Flags, l: f32, r: f32) -> (f32, (u32, u32)) {
+            let (value, fpsr) = f32::mul(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f32_div((fpcr, fpsr): (u32, u32), _: Flags, l: f32, r: f32) -> (f32, (u32, u32)) {
+            let (value, fpsr) = f32::div(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f32_madd(
+            (fpcr, fpsr): (u32, u32),
+            _: Flags,
+            a: f32,
+            b: f32,
+            c: f32,
+        ) -> (f32, (u32, u32)) {
+            let (value, fpsr) = f32::madd(fpcr, fpsr, a, b, c);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f32_sqrt((fpcr, fpsr): (u32, u32), _: Flags, x: f32) -> (f32, (u32, u32)) {
+            let (value, fpsr) = f32::sqrt(fpcr, fpsr, x);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f64_add((fpcr, fpsr): (u32, u32), _: Flags, l: f64, r: f64) -> (f64, (u32, u32)) {
+            let (value, fpsr) = f64::add(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f64_sub((fpcr, fpsr): (u32, u32), _: Flags, l: f64, r: f64) -> (f64, (u32, u32)) {
+            let (value, fpsr) = f64::sub(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f64_mul((fpcr, fpsr): (u32, u32), _: Flags, l: f64, r: f64) -> (f64, (u32, u32)) {
+            let (value, fpsr) = f64::mul(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f64_div((fpcr, fpsr): (u32, u32), _: Flags, l: f64, r: f64) -> (f64, (u32, u32)) {
+            let (value, fpsr) = f64::div(fpcr, fpsr, l, r);
+            (value, (fpcr, fpsr))
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f64_madd(
+            (fpcr, fpsr): (u32, u32),
+            _: Flags,
+            a: f64,
+            b: f64,
+            c: f64,
+        ) -> (f64, (u32, u32)) {
+            let (value, fpsr) = f64::madd(fpcr, fpsr, a, b, c);
+            (value, (fpcr, fpsr))
+        }
+
+        #[inline]
+        fn f64_sqrt((fpcr, fpsr): (u32, u32), _: Flags, x: f64) -> (f64, (u32, u32)) {
+            let (value, fpsr) = f64::sqrt(fpcr, fpsr, x);
+            (value, (fpcr, fpsr))
+        }
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        #[inline]
+        pub fn add(fpcr: u32, fpsr: u32, l: f32, r: f32) -> (f32, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f32::add(flags, l, r),
+                |env| soft::add::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+            )
+        }
+
+        #[inline]
+        pub fn sub(fpcr: u32, fpsr: u32, l: f32, r: f32) -> (f32, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f32::sub(flags, l, r),
+                |env| soft::sub::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+            )
+        }
+
+        #[inline]
+        pub fn mul(fpcr: u32, fpsr: u32, l: f32, r: f32) -> (f32, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f32::mul(flags, l, r),
+                |env| soft::mul::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+            )
+        }
+
+        #[inline]
+        pub fn div(fpcr: u32, fpsr: u32, l: f32, r: f32) -> (f32, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f32::div(flags, l, r),
+                |env| soft::div::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+            )
+        }
+
+        /// The fused `a * b + c`.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpcr: u32, fpsr: u32, a: f32, b: f32, c: f32) -> (f32, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                a.is_subnormal() || b.is_subnormal() || c.is_subnormal(),
+                |flags| crate::f32::madd(flags, a, b, c),
+                |env| {
+                    soft::fma::<soft::Binary32>(
+                        env,
+                        a.to_bits() as u64,
+                        b.to_bits() as u64,
+                        c.to_bits() as u64,
+                    )
+                },
+            )
+        }
+
+        #[inline]
+        pub fn sqrt(fpcr: u32, fpsr: u32, x: f32) -> (f32, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                x.is_subnormal(),
+                |flags| crate::f32::sqrt(flags, x),
+                |env| soft::sqrt::<soft::Binary32>(env, x.to_bits() as u64),
+            )
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        #[inline]
+        pub fn add(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f64::add(flags, l, r),
+                |env| soft::add::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+            )
+        }
+
+        #[inline]
+        pub fn sub(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f64::sub(flags, l, r),
+                |env| soft::sub::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+            )
+        }
+
+        #[inline]
+        pub fn mul(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f64::mul(flags, l, r),
+                |env| soft::mul::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+            )
+        }
+
+        #[inline]
+        pub fn div(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                l.is_subnormal() || r.is_subnormal(),
+                |flags| crate::f64::div(flags, l, r),
+                |env| soft::div::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+            )
+        }
+
+        /// The fused `a * b + c`.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpcr: u32, fpsr: u32, a: f64, b: f64, c: f64) -> (f64, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                a.is_subnormal() || b.is_subnormal() || c.is_subnormal(),
+                |flags| crate::f64::madd(flags, a, b, c),
+                |env| soft::fma::<soft::Binary64>(env, a.to_bits(), b.to_bits(), c.to_bits()),
+            )
+        }
+
+        #[inline]
+        pub fn sqrt(fpcr: u32, fpsr: u32, x: f64) -> (f64, u32) {
+            finish(
+                fpcr,
+                fpsr,
+                x.is_subnormal(),
+                |flags| crate::f64::sqrt(flags, x),
+                |env| soft::sqrt::<soft::Binary64>(env, x.to_bits()),
+            )
+        }
+    }
+}
+
+pub mod riscv {
+    //! The `fcsr` register, holding the dynamic rounding mode `frm` and the
+    //! accrued exceptions `fflags`, and the `rm` field of the instructions.
+
+    use crate::{Flags, NanRule, Rounding, Status};
+
+    /// The inexact flag.
+    pub const NX: u32 = 1 << 0;
+    /// The underflow flag.
+    pub const UF: u32 = 1 << 1;
+    /// The overflow flag.
+    pub const OF: u32 = 1 << 2;
+    /// The divide-by-zero flag.
+    pub const DZ: u32 = 1 << 3;
+    /// The invalid operation flag.
+    pub const NV: u32 = 1 << 4;
+    /// The accrued exceptions, `fflags`.
+    pub const FFLAGS: u32 = 0x1F;
+    /// The dynamic rounding mode, `frm`.
+    pub const FRM: u32 = 0b111 << FRM_SHIFT;
+    /// The position of `frm` in `fcsr`.
+    pub const FRM_SHIFT: u32 = 5;
+
+    /// Rounds to nearest, ties to even.
+    pub const RNE: u32 = 0b000;
+    /// Rounds towards zero.
+    pub const RTZ: u32 = 0b001;
+    /// Rounds down.
+    pub const RDN: u32 = 0b010;
+    /// Rounds up.
+    pub const RUP: u32 = 0b011;
+    /// Rounds to nearest, ties to max magnitude.
+    pub const RMM: u32 = 0b100;
+    /// Selects the rounding mode in `frm`, only valid in instructions.
+    pub const DYN: u32 = 0b111;
+
+    /// The rounding mode an instruction with `rm` uses under `fcsr`, or
+    /// `None` for reserved encodings, including a `DYN` resolving to one,
+    /// which are illegal instructions.
+    #[inline]
+    pub fn rounding(fcsr: u32, rm: u32) -> Option<u32> {
+        let rm = if rm == DYN {
+            (fcsr & FRM) >> FRM_SHIFT
+        } else {
+            rm
+        };
+        (rm <= RMM).then_some(rm)
+    }
+
+    /// The flags for the rounding mode `rm` returned by `rounding`.
+    #[inline]
+    pub fn flags(rm: u32) -> Flags {
+        let rounding = match rm {
+            RNE => Rounding::Nearest,
+            RTZ => Rounding::Zero,
+            RDN => Rounding::Down,
+            RUP => Rounding::Up,
+            _ => Rounding::NearestAway,
+        };
+        Flags::new().with_rounding(rounding)
+    }
+
+    /// The accrued exceptions raised by `status`.
+    #[inline]
+    pub fn exceptions(status: Status) -> u32 {
+        [
+            (status.inexact(), NX),
+            (status.underflow(), UF),
+            (status.overflow(), OF),
+            (status.div_zero(), DZ),
+            (status.invalid(), NV),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit)
+    }
+
+    /// `fcsr` with the exceptions raised by `status` added to its accrued
+    /// exceptions.
+    #[inline]
+    pub fn accumulate(fcsr: u32, status: Status) -> u32 {
+        fcsr | exceptions(status)
+    }
+
+    /// A RISC-V hart, whose register is the `fcsr`. Operations run under the
+    /// dynamic rounding mode, and a reserved `frm`, which makes them illegal
+    /// instructions, runs them to nearest.
+    #[derive(Clone, Copy)]
+    pub struct RiscV;
+
+    impl crate::Isa for RiscV {
+        type Registers = u32;
+
+        const NAN_RULE: Option<NanRule> = Some(NanRule::Canonical);
+
+        #[inline]
+        fn flags(fcsr: u32) -> Flags {
+            flags(rounding(fcsr, DYN).unwrap_or(RNE))
+        }
+
+        #[inline]
+        fn accumulate(fcsr: u32, status: Status) -> u32 {
+            accumulate(fcsr, status)
+        }
+    }
+
+    /// The canonical NaN of single precision, which unboxing an improperly
+    /// boxed value gives.
+    pub const CANONICAL_SINGLE: u32 = crate::consts::riscv::F32_NAN;
+    /// The canonical NaN of half precision, which unboxing an improperly
+    /// boxed value gives.
+    pub const CANONICAL_HALF: u16 = crate::consts::riscv::F16_NAN;
+
+    /// `single` NaN-boxed into a 64-bit register, with every upper bit set.
+    #[inline]
+    pub fn box_single(single: f32) -> u64 {
+        !0 << 32 | single.to_bits() as u64
+    }
+
+    /// The single in the 64-bit register `reg`, or the canonical NaN if it
+    /// isn't properly NaN-boxed.
+    #[inline]
+    pub fn unbox_single(reg: u64) -> f32 {
+        f32::from_bits(if reg >> 32 == !0 >> 32 {
+            reg as u32
+        } else {
+            CANONICAL_SINGLE
+        })
+    }
+
+    /// The bit pattern of a half NaN-boxed into a 64-bit register, with
+    /// every upper bit set.
+    #[inline]
+    pub fn box_half(half: u16) -> u64 {
+        !0 << 16 | half as u64
+    }
+
+    /// The bit pattern of the half in the 64-bit register `reg`, or the
+    /// canonical NaN if it isn't properly NaN-boxed.
+    #[inline]
+    pub fn unbox_half(reg: u64) -> u16 {
+        if reg >> 16 == !0 >> 16 {
+            reg as u16
+        } else {
+            CANONICAL_HALF
+        }
+    }
+}
+
+pub mod ppc {
+    //! The FPSCR, which besides the sticky exceptions records the rounding
+    //! of every result in FR and FI, its class in FPRF and the cause of
+    //! every invalid operation.
+    //!
+    //! `Status` carries none of those, so the operations here classify their
+    //! operands and results and round again towards zero when FR depends on
+    //! it. Invalid operations and divisions by zero with their exceptions
+    //! enabled leave FR, FI and FPRF as the hardware does, and the caller
+    //! must keep the target register. The arithmetic runs under
+    //! `Preset::POWER`, so NaN results are those of the hardware.
+
+    use core::num::FpCategory;
+
+    use crate::preset::{self, Preset};
+    use crate::{Flags, Rounding, Status};
+
+    /// The rounding mode field.
+    pub const RN: u32 = 0b11;
+    /// Selects the non-IEEE mode, which flushes denormal results to zero.
+    pub const NI: u32 = 1 << 2;
+    /// The inexact exception enable.
+    pub const XE: u32 = 1 << 3;
+    /// The divide-by-zero exception enable.
+    pub const ZE: u32 = 1 << 4;
+    /// The underflow exception enable.
+    pub const UE: u32 = 1 << 5;
+    /// The overflow exception enable.
+    pub const OE: u32 = 1 << 6;
+    /// The invalid operation exception enable.
+    pub const VE: u32 = 1 << 7;
+    /// Every exception enable, each at its summary shifted by
+    /// `ENABLE_SHIFT`.
+    pub const ENABLES: u32 = XE | ZE | UE | OE | VE;
+    /// The distance between an exception enable and its summary.
+    pub const ENABLE_SHIFT: u32 = 22;
+    /// An invalid integer conversion.
+    pub const VXCVI: u32 = 1 << 8;
+    /// An invalid square root.
+    pub const VXSQRT: u32 = 1 << 9;
+    /// An invalid operation requested by software.
+    pub const VXSOFT: u32 = 1 << 10;
+    /// The result class and condition field.
+    pub const FPRF: u32 = 0x1F << 12;
+    /// An invalid comparison.
+    pub const VXVC: u32 = 1 << 19;
+    /// An infinity multiplied by zero.
+    pub const VXIMZ: u32 = 1 << 20;
+    /// Zero divided by zero.
+    pub const VXZDZ: u32 = 1 << 21;
+    /// Infinity divided by infinity.
+    pub const VXIDI: u32 = 1 << 22;
+    /// Infinity subtracted from infinity.
+    pub const VXISI: u32 = 1 << 23;
+    /// A signaling NaN operand.
+    pub const VXSNAN: u32 = 1 << 24;
+    /// The last result was inexact.
+    pub const FI: u32 = 1 << 17;
+    /// The last result was rounded up in magnitude.
+    pub const FR: u32 = 1 << 18;
+    /// The inexact exception.
+    pub const XX: u32 = 1 << 25;
+    /// The divide-by-zero exception.
+    pub const ZX: u32 = 1 << 26;
+    /// The underflow exception.
+    pub const UX: u32 = 1 << 27;
+    /// The overflow exception.
+    pub const OX: u32 = 1 << 28;
+    /// The summary of every invalid operation cause.
+    pub const VX: u32 = 1 << 29;
+    /// The summary of every enabled exception.
+    pub const FEX: u32 = 1 << 30;
+    /// Set whenever an exception is newly raised.
+    pub const FX: u32 = 1 << 31;
+    /// Every invalid operation cause.
+    pub const VX_CAUSES: u32 =
+        VXSNAN | VXISI | VXIDI | VXZDZ | VXIMZ | VXVC | VXSOFT | VXSQRT | VXCVI;
+
+    /// The FPRF of a quiet NaN.
+    pub const QNAN: u32 = 0b10001 << 12;
+    /// The FPRF of negative infinity.
+    pub const NEG_INFINITY: u32 = 0b01001 << 12;
+    /// The FPRF of a negative normal number.
+    pub const NEG_NORMAL: u32 = 0b01000 << 12;
+    /// The FPRF of a negative denormal number.
+    pub const NEG_DENORMAL: u32 = 0b11000 << 12;
+    /// The FPRF of negative zero.
+    pub const NEG_ZERO: u32 = 0b10010 << 12;
+    /// The FPRF of positive zero.
+    pub const POS_ZERO: u32 = 0b00010 << 12;
+    /// The FPRF of a positive denormal number.
+    pub const POS_DENORMAL: u32 = 0b10100 << 12;
+    /// The FPRF of a positive normal number.
+    pub const POS_NORMAL: u32 = 0b00100 << 12;
+    /// The FPRF of positive infinity.
+    pub const POS_INFINITY: u32 = 0b00101 << 12;
+
+    /// The flags selected by `fpscr`.
+    #[inline]
+    pub fn flags(fpscr: u32) -> Flags {
+        let rounding = match fpscr & RN {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::Zero,
+            0b10 => Rounding::Up,
+            _ => Rounding::Down,
+        };
+        Flags::new()
+            .with_rounding(rounding)
+            .with_ftz(fpscr & NI != 0)
+    }
+
+    /// The class of an operand, as far as the invalid operation causes need.
+    #[derive(Clone, Copy)]
+    struct Operand {
+        nan: bool,
+        snan: bool,
+        infinite: bool,
+        zero: bool,
+        negative: bool,
+    }
+
+    impl Operand {
+        #[inline]
+        fn f32(x: f32) -> Self {
+            Self {
+                nan: x.is_nan(),
+                snan: x.is_nan() && x.to_bits() & 1 << 22 == 0,
+                infinite: x.is_infinite(),
+                zero: x.to_bits() << 1 == 0,
+                negative: x.is_sign_negative(),
+            }
+        }
+
+        #[inline]
+        fn f64(x: f64) -> Self {
+            Self {
+                nan: x.is_nan(),
+                snan: x.is_nan() && x.to_bits() & 1 << 51 == 0,
+                infinite: x.is_infinite(),
+                zero: x.to_bits() << 1 == 0,
+                negative: x.is_sign_negative(),
+            }
+        }
+    }
+
+    #[inline]
+    fn snan(operands: &[Operand]) -> u32 {
+        if operands.iter().any(|x| x.snan) {
+            VXSNAN
+        } else {
+            0
+        }
+    }
+
+    /// The causes of `l + r`, or of `l - r` when `negate`.
+    #[inline]
+    fn add_causes(l: Operand, r: Operand, negate: bool) -> u32 {
+        let isi = l.infinite && r.infinite && l.negative != (r.negative != negate);
+        snan(&[l, r]) | if isi { VXISI } else { 0 }
+    }
+
+    #[inline]
+    fn mul_causes(l: Operand, r: Operand) -> u32 {
+        let imz = l.infinite && r.zero || l.zero && r.infinite;
+        snan(&[l, r]) | if imz { VXIMZ } else { 0 }
+    }
+
+    #[inline]
+    fn div_causes(l: Operand, r: Operand) -> u32 {
+        let idi = l.infinite && r.infinite;
+        let zdz = l.zero && r.zero;
+        snan(&[l, r]) | if idi { VXIDI } else { 0 } | if zdz { VXZDZ } else { 0 }
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    fn madd_causes(a: Operand, b: Operand, c: Operand) -> u32 {
+        let imz = a.infinite && b.zero || a.zero && b.infinite;
+        let product = !a.nan && !b.nan && (a.infinite || b.infinite) && !imz;
+        let isi = product && c.infinite && (a.negative != b.negative) != c.negative;
+        snan(&[a, b, c]) | if imz { VXIMZ } else { 0 } | if isi { VXISI } else { 0 }
+    }
+
+    #[inline]
+    fn sqrt_causes(x: Operand) -> u32 {
+        let sqrt = x.negative && !x.nan && !x.zero;
+        snan(&[x]) | if sqrt { VXSQRT } else { 0 }
+    }
+
+    /// The FPRF of a value of class `category`.
+    #[inline]
+    fn fprf(category: FpCategory, negative: bool) -> u32 {
+        match (category, negative) {
+            (FpCategory::Nan, _) => QNAN,
+            (FpCategory::Infinite, true) => NEG_INFINITY,
+            (FpCategory::Normal, true) => NEG_NORMAL,
+            (FpCategory::Subnormal, true) => NEG_DENORMAL,
+            (FpCategory::Zero, true) => NEG_ZERO,
+            (FpCategory::Zero, false) => POS_ZERO,
+            (FpCategory::Subnormal, false) => POS_DENORMAL,
+            (FpCategory::Normal, false) => POS_NORMAL,
+            (FpCategory::Infinite, false) => POS_INFINITY,
+        }
+    }
+
+    /// Whether the result with `bits` was rounded up in magnitude, where
+    /// `truncated` performs the operation again rounding towards zero.
+    #[inline]
+    fn rounded(
+        flags: Flags,
+        status: Status,
+        negative: bool,
+        bits: u64,
+        truncated: impl FnOnce(Flags) -> u64,
+    ) -> bool {
+        if !status.inexact() {
+            return false;
+        }
+        match flags.rounding() {
+            Rounding::Zero => false,
+            Rounding::Up => !negative,
+            Rounding::Down => negative,
+            Rounding::Nearest | Rounding::NearestAway | Rounding::Odd => {
+                truncated(flags.with_rounding(Rounding::Zero)) != bits
+            }
+        }
+    }
+
+    /// `fpscr` updated with the exceptions of `status`, the invalid
+    /// operation `causes`, and the `fr` and `fprf` of the result.
+    #[inline]
+    fn update(fpscr: u32, status: Status, causes: u32, fr: bool, fprf: u32) -> u32 {
+        let exceptions = [
+            (status.overflow(), OX),
+            (status.underflow(), UX),
+            (status.div_zero(), ZX),
+            (status.inexact(), XX),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(causes, |bits, &(_, bit)| bits | bit);
+        let mut next = if causes != 0 && fpscr & VE != 0 || status.div_zero() && fpscr & ZE != 0 {
+            fpscr & !(FR | FI)
+        } else {
+            let fr = if fr { FR } else { 0 };
+            let fi = if status.inexact() { FI } else { 0 };
+            fpscr & !(FR | FI | FPRF) | fr | fi | fprf
+        };
+        next |= exceptions;
+        if exceptions & !fpscr != 0 {
+            next |= FX;
+        }
+        next &= !(VX | FEX);
+        if next & VX_CAUSES != 0 {
+            next |= VX;
+        }
+        if next >> ENABLE_SHIFT & next & ENABLES != 0 {
+            next |= FEX;
+        }
+        next
+    }
+
+    /// The base and slope of the estimates of `fres` on the 750 family, the
+    /// Gekko and Broadway, by the top five fraction bits of the operand.
+    const RECIPROCAL: [(u32, u32); 32] = [
+        (0x7FF800, 0x3E1),
+        (0x783800, 0x3A7),
+        (0x70EA00, 0x371),
+        (0x6A0800, 0x340),
+        (0x638800, 0x313),
+        (0x5D6200, 0x2EA),
+        (0x579000, 0x2C4),
+        (0x520800, 0x2A0),
+        (0x4CC800, 0x27F),
+        (0x47CA00, 0x261),
+        (0x430800, 0x245),
+        (0x3E8000, 0x22A),
+        (0x3A2C00, 0x212),
+        (0x360800, 0x1FB),
+        (0x321400, 0x1E5),
+        (0x2E4A00, 0x1D1),
+        (0x2AA800, 0x1BE),
+        (0x272C00, 0x1AC),
+        (0x23D600, 0x19B),
+        (0x209E00, 0x18B),
+        (0x1D8800, 0x17C),
+        (0x1A9000, 0x16E),
+        (0x17AE00, 0x15B),
+        (0x14F800, 0x15B),
+        (0x124400, 0x143),
+        (0x0FBE00, 0x143),
+        (0x0D3800, 0x12D),
+        (0x0ADE00, 0x12D),
+        (0x088400, 0x11A),
+        (0x065000, 0x11A),
+        (0x041C00, 0x108),
+        (0x020C00, 0x106),
+    ];
+
+    /// The base and slope of the estimates of `frsqrte` on the 750 family,
+    /// by the top four fraction bits of the operand, with those of operands
+    /// with an odd unbiased exponent following.
+    const RECIPROCAL_SQRT: [(u32, u32); 32] = [
+        (0x3FFA000, 0x7A4),
+        (0x3C29000, 0x700),
+        (0x38AA000, 0x670),
+        (0x3572000, 0x5F2),
+        (0x3279000, 0x584),
+        (0x2FB7000, 0x524),
+        (0x2D26000, 0x4CC),
+        (0x2AC0000, 0x47E),
+        (0x2881000, 0x43A),
+        (0x2665000, 0x3FA),
+        (0x2468000, 0x3C2),
+        (0x2287000, 0x38E),
+        (0x20C1000, 0x35E),
+        (0x1F12000, 0x332),
+        (0x1D79000, 0x30A),
+        (0x1BF4000, 0x2E6),
+        (0x1A7E800, 0x568),
+        (0x17CB800, 0x4F3),
+        (0x1552800, 0x48D),
+        (0x130C000, 0x435),
+        (0x10F2000, 0x3E7),
+        (0x0EFF000, 0x3A2),
+        (0x0D2E000, 0x365),
+        (0x0B7C000, 0x32E),
+        (0x09E5000, 0x2FC),
+        (0x0867000, 0x2D0),
+        (0x06FF000, 0x2A8),
+        (0x05AB800, 0x283),
+        (0x046A000, 0x261),
+        (0x0339800, 0x243),
+        (0x0218800, 0x226),
+        (0x0105800, 0x20B),
+    ];
+
+    /// The estimate `fres` of the 750 family gives for `x`, which is finite,
+    /// non-zero and not a NaN.
+    #[inline]
+    fn reciprocal_estimate_bits(x: u64) -> u64 {
+        let sign = x & 1 << 63;
+        let exp = (x >> 52 & 0x7FF) as i64;
+        if exp < 895 {
+            // The estimate saturates to the largest single, raising nothing.
+            return sign | 0x47EF_FFFF_E000_0000;
+        }
+        if exp >= 1149 {
+            return sign;
+        }
+        let i = (x >> 37 & 0x7FFF) as u32;
+        let (base, slope) = RECIPROCAL[i as usize >> 10];
+        let fraction = base - (slope * (i & 0x3FF)).div_ceil(2);
+        sign | ((0x7FD - exp) as u64) << 52 | (fraction as u64) << 29
+    }
+
+    /// The estimate `frsqrte` of the 750 family gives for `x`, which is
+    /// finite, positive and not zero.
+    #[inline]
+    fn reciprocal_sqrt_estimate_bits(x: u64) -> u64 {
+        let mut exp = (x >> 52) as i64;
+        let mut fraction = x & 0xF_FFFF_FFFF_FFFF;
+        if exp == 0 {
+            let shift = fraction.leading_zeros() - 11;
+            fraction = (fraction << shift) & 0xF_FFFF_FFFF_FFFF;
+            exp = 1 - shift as i64;
+        }
+        let odd = exp & 1 == 0;
+        let exp = ((0xBFC - exp) >> 1) as u64;
+        let i = (fraction >> 37) as u32;
+        let (base, slope) = RECIPROCAL_SQRT[(i >> 11) as usize + if odd { 16 } else { 0 }];
+        let fraction = base - slope * (i & 0x7FF);
+        exp << 52 | (fraction as u64) << 26
+    }
+
+    /// A PowerPC core, whose register is the FPSCR. Operations run through
+    /// `GuestFpu::run` report invalid operations as `VXSOFT` and leave `FR`
+    /// and `FPRF` alone, as their result classes are up to the emulator.
+    #[derive(Clone, Copy)]
+    pub struct PowerPc;
+
+    impl crate::Isa for PowerPc {
+        type Registers = u32;
+
+        #[inline]
+        fn flags(fpscr: u32) -> Flags {
+            flags(fpscr)
+        }
+
+        #[inline]
+        fn accumulate(fpscr: u32, status: Status) -> u32 {
+            let causes = if status.invalid() { VXSOFT } else { 0 };
+            update(fpscr, status, causes, fpscr & FR != 0, fpscr & FPRF)
+        }
+
+        #[inline]
+        fn f32_add(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::add(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f32_sub(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::sub(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f32_mul(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::mul(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f32_div(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::div(fpscr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f32_madd(fpscr: u32, _: Flags, a: f32, b: f32, c: f32) -> (f32, u32) {
+            f32::madd(fpscr, a, b, c)
+        }
+
+        #[inline]
+        fn f32_sqrt(fpscr: u32, _: Flags, x: f32) -> (f32, u32) {
+            f32::sqrt(fpscr, x)
+        }
+
+        #[inline]
+        fn f64_add(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::add(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f64_sub(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::sub(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f64_mul(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::mul(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f64_div(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::div(fpscr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f64_madd(fpscr: u32, _: Flags, a: f64, b: f64, c: f64) -> (f64, u32) {
+            f64::madd(fpscr, a, b, c)
+        }
+
+        #[inline]
+        fn f64_sqrt(fpscr: u32, _: Flags, x: f64) -> (f64, u32) {
+            f64::sqrt(fpscr, x)
+        }
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        /// The FPRF of `x`.
+        #[inline]
+        pub fn fprf(x: f32) -> u32 {
+            super::fprf(x.classify(), x.is_sign_negative())
+        }
+
+        /// `fpscr` updated after the operation `op` returned `value` and
+        /// `status`.
+        #[inline]
+        fn finish(
+            fpscr: u32,
+            status: Status,
+            causes: u32,
+            value: f32,
+            op: impl FnOnce(Flags) -> f32,
+        ) -> u32 {
+            let negative = value.is_sign_negative();
+            let fr = rounded(
+                flags(fpscr),
+                status,
+                negative,
+                value.to_bits() as u64,
+                |flags| op(flags).to_bits() as u64,
+            );
+            update(fpscr, status, causes, fr, fprf(value))
+        }
+
+        #[inline]
+        pub fn add(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            let (value, status) = preset::f32::add(flags(fpscr), Preset::POWER, l, r);
+            let causes = add_causes(Operand::f32(l), Operand::f32(r), false);
+            let op = |flags| preset::f32::add(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn sub(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            let (value, status) = preset::f32::sub(flags(fpscr), Preset::POWER, l, r);
+            let causes = add_causes(Operand::f32(l), Operand::f32(r), true);
+            let op = |flags| preset::f32::sub(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn mul(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            let (value, status) = preset::f32::mul(flags(fpscr), Preset::POWER, l, r);
+            let causes = mul_causes(Operand::f32(l), Operand::f32(r));
+            let op = |flags| preset::f32::mul(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn div(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            let (value, status) = preset::f32::div(flags(fpscr), Preset::POWER, l, r);
+            let causes = div_causes(Operand::f32(l), Operand::f32(r));
+            let op = |flags| preset::f32::div(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpscr: u32, a: f32, b: f32, c: f32) -> (f32, u32) {
+            let (value, status) = preset::f32::madd(flags(fpscr), Preset::POWER, a, b, c);
+            let causes = madd_causes(Operand::f32(a), Operand::f32(b), Operand::f32(c));
+            let op = |flags| preset::f32::madd(flags, Preset::POWER, a, b, c).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn sqrt(fpscr: u32, x: f32) -> (f32, u32) {
+            let (value, status) = preset::f32::sqrt(flags(fpscr), Preset::POWER, x);
+            let causes = sqrt_causes(Operand::f32(x));
+            let op = |flags| preset::f32::sqrt(flags, Preset::POWER, x).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        /// The FPRF of `x`.
+        #[inline]
+        pub fn fprf(x: f64) -> u32 {
+            super::fprf(x.classify(), x.is_sign_negative())
+        }
+
+        /// `fpscr` updated after the operation `op` returned `value` and
+        /// `status`.
+        #[inline]
+        fn finish(
+            fpscr: u32,
+            status: Status,
+            causes: u32,
+            value: f64,
+            op: impl FnOnce(Flags) -> f64,
+        ) -> u32 {
+            let negative = value.is_sign_negative();
+            let fr = rounded(flags(fpscr), status, negative, value.to_bits(), |flags| {
+                op(flags).to_bits()
+            });
+            update(fpscr, status, causes, fr, fprf(value))
+        }
+
+        #[inline]
+        pub fn add(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = preset::f64::add(flags(fpscr), Preset::POWER, l, r);
+            let causes = add_causes(Operand::f64(l), Operand::f64(r), false);
+            let op = |flags| preset::f64::add(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn sub(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = preset::f64::sub(flags(fpscr), Preset::POWER, l, r);
+            let causes = add_causes(Operand::f64(l), Operand::f64(r), true);
+            let op = |flags| preset::f64::sub(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn mul(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = preset::f64::mul(flags(fpscr), Preset::POWER, l, r);
+            let causes = mul_causes(Operand::f64(l), Operand::f64(r));
+            let op = |flags| preset::f64::mul(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn div(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = preset::f64::div(flags(fpscr), Preset::POWER, l, r);
+            let causes = div_causes(Operand::f64(l), Operand::f64(r));
+            let op = |flags| preset::f64::div(flags, Preset::POWER, l, r).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpscr: u32, a: f64, b: f64, c: f64) -> (f64, u32) {
+            let (value, status) = preset::f64::madd(flags(fpscr), Preset::POWER, a, b, c);
+            let causes = madd_causes(Operand::f64(a), Operand::f64(b), Operand::f64(c));
+            let op = |flags| preset::f64::madd(flags, Preset::POWER, a, b, c).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        #[inline]
+        pub fn sqrt(fpscr: u32, x: f64) -> (f64, u32) {
+            let (value, status) = preset::f64::sqrt(flags(fpscr), Preset::POWER, x);
+            let causes = sqrt_causes(Operand::f64(x));
+            let op = |flags| preset::f64::sqrt(flags, Preset::POWER, x).0;
+            (value, finish(fpscr, status, causes, value, op))
+        }
+
+        /// `c` if `a` is at least zero, negative zero included, and `b` if it
+        /// is negative or a NaN, as `fsel` does without raising anything or
+        /// touching the FPSCR.
+        #[inline]
+        pub fn fsel(a: f64, b: f64, c: f64) -> f64 {
+            if a >= 0.0 {
+                c
+            } else {
+                b
+            }
+        }
+
+        /// Estimates `1 / x`, as `fres` and `ps_res` do on the 750 family,
+        /// down to the bits of the estimate. The estimate is exact for zeros,
+        /// infinities and NaNs, saturates to the largest single where the
+        /// reciprocal overflows and is zero where it underflows, raising
+        /// nothing for either, and clears FR and FI.
+        #[inline]
+        pub fn reciprocal_estimate(fpscr: u32, x: f64) -> (f64, u32) {
+            let operand = Operand::f64(x);
+            let (value, status) = if operand.zero {
+                (f64::INFINITY.copysign(x), Status::DIV_ZERO)
+            } else if operand.nan {
+                (x + 0.0, Status::empty())
+            } else if operand.infinite {
+                (0f64.copysign(x), Status::empty())
+            } else {
+                let bits = reciprocal_estimate_bits(x.to_bits());
+                (f64::from_bits(bits), Status::empty())
+            };
+            let causes = snan(&[operand]);
+            (value, update(fpscr, status, causes, false, fprf(value)))
+        }
+
+        /// Estimates `1 / sqrt(x)`, as `frsqrte` and `ps_rsqrte` do on the
+        /// 750 family, down to the bits of the estimate, clearing FR and FI.
+        #[inline]
+        pub fn reciprocal_sqrt_estimate(fpscr: u32, x: f64) -> (f64, u32) {
+            let operand = Operand::f64(x);
+            let (value, status) = if operand.zero {
+                (f64::INFINITY.copysign(x), Status::DIV_ZERO)
+            } else if operand.nan {
+                (x + 0.0, Status::empty())
+            } else if operand.negative {
+                (f64::NAN, Status::INVALID)
+            } else if operand.infinite {
+                (0.0, Status::empty())
+            } else {
+                let bits = reciprocal_sqrt_estimate_bits(x.to_bits());
+                (f64::from_bits(bits), Status::empty())
+            };
+            let causes = sqrt_causes(operand);
+            (value, update(fpscr, status, causes, false, fprf(value)))
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline]
+        pub fn to_single(fpscr: u32, double: f64) -> (f32, u32) {
+            let (value, status) = crate::f64::to_single(flags(fpscr), double);
+            let causes = snan(&[Operand::f64(double)]);
+            let fr = rounded(
+                flags(fpscr),
+                status,
+                value.is_sign_negative(),
+                value.to_bits() as u64,
+                |flags| crate::f64::to_single(flags, double).0.to_bits() as u64,
+            );
+            (
+                value,
+                update(fpscr, status, causes, fr, super::f32::fprf(value)),
+            )
+        }
+    }
+
+    pub mod paired {
+        //! The paired singles of the Gekko and Broadway, two singles to a
+        //! register, and the conversions of their quantized loads and stores
+        //! under a GQR.
+        //!
+        //! Every operation performs the second slot before the first, so the
+        //! exceptions of both accumulate while FR, FI and FPRF describe the
+        //! first, as the hardware leaves them.
+
+        use super::*;
+
+        /// The type `psq_st` converts to.
+        pub const ST_TYPE: u32 = 0b111;
+        /// The scale `psq_st` multiplies by, as a signed power of two.
+        pub const ST_SCALE: u32 = 0x3F << 8;
+        /// The type `psq_l` converts from.
+        pub const LD_TYPE: u32 = 0b111 << 16;
+        /// The scale `psq_l` divides by, as a signed power of two.
+        pub const LD_SCALE: u32 = 0x3F << 24;
+
+        /// Singles, moved without conversion or scaling.
+        pub const FLOAT: u32 = 0;
+        /// Unsigned bytes.
+        pub const U8: u32 = 4;
+        /// Unsigned halfwords.
+        pub const U16: u32 = 5;
+        /// Signed bytes.
+        pub const S8: u32 = 6;
+        /// Signed halfwords.
+        pub const S16: u32 = 7;
+
+        /// Performs `op` on each slot of `l` and `r`, the second one first.
+        #[inline]
+        fn slots(
+            fpscr: u32,
+            l: [f32; 2],
+            r: [f32; 2],
+            op: impl Fn(u32, f32, f32) -> (f32, u32),
+        ) -> ([f32; 2], u32) {
+            let (ps1, fpscr) = op(fpscr, l[1], r[1]);
+            let (ps0, fpscr) = op(fpscr, l[0], r[0]);
+            ([ps0, ps1], fpscr)
+        }
+
+        #[inline]
+        pub fn add(fpscr: u32, l: [f32; 2], r: [f32; 2]) -> ([f32; 2], u32) {
+            slots(fpscr, l, r, super::f32::add)
+        }
+
+        #[inline]
+        pub fn sub(fpscr: u32, l: [f32; 2], r: [f32; 2]) -> ([f32; 2], u32) {
+            slots(fpscr, l, r, super::f32::sub)
+        }
+
+        #[inline]
+        pub fn mul(fpscr: u32, l: [f32; 2], r: [f32; 2]) -> ([f32; 2], u32) {
+            slots(fpscr, l, r, super::f32::mul)
+        }
+
+        #[inline]
+        pub fn div(fpscr: u32, l: [f32; 2], r: [f32; 2]) -> ([f32; 2], u32) {
+            slots(fpscr, l, r, super::f32::div)
+        }
+
+        /// Multiplies both slots of `l` by the first slot of `r`, as
+        /// `ps_muls0` does.
+        #[inline]
+        pub fn muls0(fpscr: u32, l: [f32; 2], r: [f32; 2]) -> ([f32; 2], u32) {
+            mul(fpscr, l, [r[0]; 2])
+        }
+
+        /// Multiplies both slots of `l` by the second slot of `r`, as
+        /// `ps_muls1` does.
+        #[inline]
+        pub fn muls1(fpscr: u32, l: [f32; 2], r: [f32; 2]) -> ([f32; 2], u32) {
+            mul(fpscr, l, [r[1]; 2])
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpscr: u32, a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> ([f32; 2], u32) {
+            let (ps1, fpscr) = super::f32::madd(fpscr, a[1], b[1], c[1]);
+            let (ps0, fpscr) = super::f32::madd(fpscr, a[0], b[0], c[0]);
+            ([ps0, ps1], fpscr)
+        }
+
+        /// Computes `a * b + c` with both slots of `b` being its first one, as
+        /// `ps_madds0` does.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madds0(fpscr: u32, a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> ([f32; 2], u32) {
+            madd(fpscr, a, [b[0]; 2], c)
+        }
+
+        /// Computes `a * b + c` with both slots of `b` being its second one,
+        /// as `ps_madds1` does.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madds1(fpscr: u32, a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> ([f32; 2], u32) {
+            madd(fpscr, a, [b[1]; 2], c)
+        }
+
+        /// The first slot of `a` plus the second of `b`, followed by the
+        /// second slot of `c`, as `ps_sum0` does.
+        #[inline]
+        pub fn sum0(fpscr: u32, a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> ([f32; 2], u32) {
+            let (ps0, fpscr) = super::f32::add(fpscr, a[0], b[1]);
+            ([ps0, c[1]], fpscr)
+        }
+
+        /// The first slot of `c`, followed by the first slot of `a` plus the
+        /// second of `b`, as `ps_sum1` does, which sets FPRF from the sum.
+        #[inline]
+        pub fn sum1(fpscr: u32, a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> ([f32; 2], u32) {
+            let (ps1, fpscr) = super::f32::add(fpscr, a[0], b[1]);
+            ([c[0], ps1], fpscr)
+        }
+
+        /// Selects, as `ps_sel` does, each slot of `c` or `b` by the same
+        /// slot of `a` in the way of `fsel`.
+        #[inline]
+        pub fn sel(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> [f32; 2] {
+            let sel = |i: usize| if a[i] >= 0.0 { c[i] } else { b[i] };
+            [sel(0), sel(1)]
+        }
+
+        /// Performs the estimate `op` on each slot of `x`, setting FPRF from
+        /// the single in the first.
+        #[inline]
+        fn estimates(fpscr: u32, x: [f32; 2], op: fn(u32, f64) -> (f64, u32)) -> ([f32; 2], u32) {
+            let (ps1, fpscr) = op(fpscr, x[1] as f64);
+            let (ps0, fpscr) = op(fpscr, x[0] as f64);
+            let (ps0, ps1) = (ps0 as f32, ps1 as f32);
+            ([ps0, ps1], fpscr & !FPRF | super::f32::fprf(ps0))
+        }
+
+        /// Estimates the reciprocal of each slot, as `ps_res` does.
+        #[inline]
+        pub fn reciprocal_estimate(fpscr: u32, x: [f32; 2]) -> ([f32; 2], u32) {
+            estimates(fpscr, x, super::f64::reciprocal_estimate)
+        }
+
+        /// Estimates the reciprocal square root of each slot, as `ps_rsqrte`
+        /// does.
+        #[inline]
+        pub fn reciprocal_sqrt_estimate(fpscr: u32, x: [f32; 2]) -> ([f32; 2], u32) {
+            estimates(fpscr, x, super::f64::reciprocal_sqrt_estimate)
+        }
+
+        /// The bytes of a quantized element of `ty`.
+        #[inline]
+        fn size(ty: u32) -> u32 {
+            match ty {
+                U8 | S8 => 1,
+                U16 | S16 => 2,
+                _ => 4,
+            }
+        }
+
+        /// `2^scale` for the signed 6-bit `scale` field.
+        #[inline]
+        fn power(scale: u32) -> f64 {
+            let scale = ((scale as i32) << 26) >> 26;
+            f64::from_bits(((1023 + scale) as u64) << 52)
+        }
+
+        /// The bytes of every element `psq_l` loads under `gqr`.
+        #[inline]
+        pub fn load_size(gqr: u32) -> u32 {
+            size((gqr & LD_TYPE) >> 16)
+        }
+
+        /// The bytes of every element `psq_st` stores under `gqr`.
+        #[inline]
+        pub fn store_size(gqr: u32) -> u32 {
+            size(gqr & ST_TYPE)
+        }
+
+        /// The single that `psq_l` loads under `gqr` from an element holding
+        /// `raw` in its low `load_size` bytes. The reserved types load zero.
+        #[inline]
+        pub fn dequantize(gqr: u32, raw: u32) -> f32 {
+            let int = match (gqr & LD_TYPE) >> 16 {
+                FLOAT => return f32::from_bits(raw),
+                U8 => raw as u8 as i32,
+                U16 => raw as u16 as i32,
+                S8 => raw as i8 as i32,
+                S16 => raw as i16 as i32,
+                _ => 0,
+            };
+            // Every quotient is exact.
+            (int as f64 / power((gqr & LD_SCALE) >> 24)) as f32
+        }
+
+        /// The element `psq_st` stores under `gqr` for `x`, in the low
+        /// `store_size` bytes: `x` scaled, truncated and saturated to the
+        /// range of the type, with NaNs and the reserved types storing zero.
+        #[inline]
+        pub fn quantize(gqr: u32, x: f32) -> u32 {
+            let (min, max) = match gqr & ST_TYPE {
+                FLOAT => return x.to_bits(),
+                U8 => (0, 0xFF),
+                U16 => (0, 0xFFFF),
+                S8 => (-0x80, 0x7F),
+                S16 => (-0x8000, 0x7FFF),
+                _ => return 0,
+            };
+            let scaled = x as f64 * power((gqr & ST_SCALE) >> 8);
+            let int = if scaled.is_nan() {
+                0
+            } else {
+                scaled.clamp(min as f64, max as f64) as i32
+            };
+            int as u32 & (!0 >> (32 - 8 * store_size(gqr)))
+        }
+    }
+}
+
+pub mod mips {
+    //! The FCSR, with its rounding and flushing controls, the cause, enable
+    //! and flag fields, and the NaN encoding it selects.
+    //!
+    //! The legacy encoding inverts the meaning of the quiet bit, so the
+    //! host can't tell the kinds apart. The operations here resolve NaN
+    //! operands and results themselves, returning the first signaling NaN
+    //! quieted, else the first quiet NaN, and the default NaN for invalid
+    //! operations. Under the legacy encoding a signaling NaN operand gives
+    //! the default NaN, as its quieted form could be an infinity.
+
+    use crate::{Flags, Rounding, Status};
+
+    /// The rounding mode field.
+    pub const RM: u32 = 0b11;
+    /// The position of the flag field.
+    pub const FLAGS_SHIFT: u32 = 2;
+    /// The position of the enable field.
+    pub const ENABLES_SHIFT: u32 = 7;
+    /// The position of the cause field.
+    pub const CAUSE_SHIFT: u32 = 12;
+    /// The inexact exception, at each field's position.
+    pub const I: u32 = 1 << 0;
+    /// The underflow exception, at each field's position.
+    pub const U: u32 = 1 << 1;
+    /// The overflow exception, at each field's position.
+    pub const O: u32 = 1 << 2;
+    /// The divide-by-zero exception, at each field's position.
+    pub const Z: u32 = 1 << 3;
+    /// The invalid operation exception, at each field's position.
+    pub const V: u32 = 1 << 4;
+    /// The unimplemented operation cause, which has no flag or enable.
+    pub const E: u32 = 1 << 5;
+    /// The flag field.
+    pub const FLAGS: u32 = 0x1F << FLAGS_SHIFT;
+    /// The enable field.
+    pub const ENABLES: u32 = 0x1F << ENABLES_SHIFT;
+    /// The cause field.
+    pub const CAUSE: u32 = 0x3F << CAUSE_SHIFT;
+    /// Selects the IEEE 754-2008 NaN encoding instead of the legacy one.
+    pub const NAN2008: u32 = 1 << 18;
+    /// Makes `abs.fmt` and `neg.fmt` arithmetic instead of bitwise.
+    pub const ABS2008: u32 = 1 << 19;
+    /// Flushes denormal operands and results to zero.
+    pub const FS: u32 = 1 << 24;
+
+    /// The flags selected by `fcsr`.
+    #[inline]
+    pub fn flags(fcsr: u32) -> Flags {
+        let rounding = match fcsr & RM {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::Zero,
+            0b10 => Rounding::Up,
+            _ => Rounding::Down,
+        };
+        let fs = fcsr & FS != 0;
+        Flags::new()
+            .with_rounding(rounding)
+            .with_ftz(fs)
+            .with_daz(fs)
+    }
+
+    /// The exceptions raised by `status`, at the position of each field.
+    #[inline]
+    pub fn exceptions(status: Status) -> u32 {
+        [
+            (status.inexact(), I),
+            (status.underflow(), U),
+            (status.overflow(), O),
+            (status.div_zero(), Z),
+            (status.invalid(), V),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit)
+    }
+
+    /// The exceptions raised by `status` that `fcsr` enables traps for.
+    #[inline]
+    pub fn enabled(fcsr: u32, status: Status) -> u32 {
+        exceptions(status) & fcsr >> ENABLES_SHIFT
+    }
+
+    /// `fcsr` after an operation raising `status`: the cause field holds its
+    /// exceptions, which the flags only accumulate when none of them traps.
+    #[inline]
+    pub fn update(fcsr: u32, status: Status) -> u32 {
+        let exceptions = exceptions(status);
+        let fcsr = fcsr & !CAUSE | exceptions << CAUSE_SHIFT;
+        if enabled(fcsr, status) != 0 {
+            fcsr
+        } else {
+            fcsr | exceptions << FLAGS_SHIFT
+        }
+    }
+
+    /// A MIPS core, whose register is the FCSR.
+    #[derive(Clone, Copy)]
+    pub struct Mips;
+
+    impl crate::Isa for Mips {
+        type Registers = u32;
+
+        #[inline]
+        fn flags(fcsr: u32) -> Flags {
+            flags(fcsr)
+        }
+
+        #[inline]
+        fn accumulate(fcsr: u32, status: Status) -> u32 {
+            update(fcsr, status)
+        }
+
+        #[inline]
+        fn f32_add(fcsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::add(fcsr, l, r)
+        }
+
+        #[inline]
+        fn f32_sub(fcsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::sub(fcsr, l, r)
+        }
+
+        #[inline]
+        fn f32_mul(fcsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::mul(fcsr, l, r)
+        }
+
+        #[inline]
+        fn f32_div(fcsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::div(fcsr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f32_madd(fcsr: u32, _: Flags, a: f32, b: f32, c: f32) -> (f32, u32) {
+            f32::madd(fcsr, a, b, c)
+        }
+
+        #[inline]
+        fn f32_sqrt(fcsr: u32, _: Flags, x: f32) -> (f32, u32) {
+            f32::sqrt(fcsr, x)
+        }
+
+        #[inline]
+        fn f64_add(fcsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::add(fcsr, l, r)
+        }
+
+        #[inline]
+        fn f64_sub(fcsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::sub(fcsr, l, r)
+        }
+
+        #[inline]
+        fn f64_mul(fcsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::mul(fcsr, l, r)
+        }
+
+        #[inline]
+        fn f64_div(fcsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::div(fcsr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f64_madd(fcsr: u32, _: Flags, a: f64, b: f64, c: f64) -> (f64, u32) {
+            f64::madd(fcsr, a, b, c)
+        }
+
+        #[inline]
+        fn f64_sqrt(fcsr: u32, _: Flags, x: f64) -> (f64, u32) {
+            f64::sqrt(fcsr, x)
+        }
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        const QUIET: u32 = 1 << 22;
+
+        /// Whether `x` is a signaling NaN under the encoding `fcsr` selects.
+        #[inline]
+        pub fn is_signaling(fcsr: u32, x: f32) -> bool {
+            x.is_nan() && (x.to_bits() & QUIET != 0) == (fcsr & NAN2008 == 0)
+        }
+
+        /// The NaN invalid operations return under the encoding `fcsr`
+        /// selects.
+        #[inline]
+        pub fn default_nan(fcsr: u32) -> f32 {
+            f32::from_bits(if fcsr & NAN2008 != 0 {
+                crate::consts::mips::F32_NAN
+            } else {
+                crate::consts::mips::LEGACY_F32_NAN
+            })
+        }
+
+        /// The result of an operation on `operands` when any is a NaN.
+        #[inline]
+        fn nan(fcsr: u32, operands: &[f32]) -> Option<(f32, Status)> {
+            if let Some(&x) = operands.iter().find(|&&x| is_signaling(fcsr, x)) {
+                let quiet = if fcsr & NAN2008 != 0 {
+                    f32::from_bits(x.to_bits() | QUIET)
+                } else {
+                    default_nan(fcsr)
+                };
+                return Some((quiet, Status::INVALID));
+            }
+            let &x = operands.iter().find(|x| x.is_nan())?;
+            Some((x, Status::empty()))
+        }
+
+        /// The result and `fcsr` after an operation without NaN operands.
+        #[inline]
+        fn finish(fcsr: u32, (value, status): (f32, Status)) -> (f32, u32) {
+            let value = if value.is_nan() {
+                default_nan(fcsr)
+            } else {
+                value
+            };
+            (value, update(fcsr, status))
+        }
+
+        #[inline]
+        pub fn add(fcsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f32::add(flags(fcsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn sub(fcsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f32::sub(flags(fcsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn mul(fcsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f32::mul(flags(fcsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn div(fcsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f32::div(flags(fcsr), l, r)),
+            }
+        }
+
+        /// The fused `maddf.fmt` of Release 6, computing `a * b + c`.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fcsr: u32, a: f32, b: f32, c: f32) -> (f32, u32) {
+            match nan(fcsr, &[a, b, c]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f32::madd(flags(fcsr), a, b, c)),
+            }
+        }
+
+        #[inline]
+        pub fn sqrt(fcsr: u32, x: f32) -> (f32, u32) {
+            match nan(fcsr, &[x]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f32::sqrt(flags(fcsr), x)),
+            }
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline]
+        pub fn to_double(fcsr: u32, single: f32) -> (f64, u32) {
+            match nan(fcsr, &[single]) {
+                // The payload keeps its position below the quiet bit.
+                Some((value, status)) => {
+                    let bits = value.to_bits() as u64;
+                    let double = (bits & 1 << 31) << 32 | 0x7FF << 52 | (bits & 0x7F_FFFF) << 29;
+                    (f64::from_bits(double), update(fcsr, status))
+                }
+                None => {
+                    let (double, status) = crate::f32::to_double(flags(fcsr), single);
+                    (double, update(fcsr, status))
+                }
+            }
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        const QUIET: u64 = 1 << 51;
+
+        /// Whether `x` is a signaling NaN under the encoding `fcsr` selects.
+        #[inline]
+        pub fn is_signaling(fcsr: u32, x: f64) -> bool {
+            x.is_nan() && (x.to_bits() & QUIET != 0) == (fcsr & NAN2008 == 0)
+        }
+
+        /// The NaN invalid operations return under the encoding `fcsr`
+        /// selects.
+        #[inline]
+        pub fn default_nan(fcsr: u32) -> f64 {
+            f64::from_bits(if fcsr & NAN2008 != 0 {
+                crate::consts::mips::F64_NAN
+            } else {
+                crate::consts::mips::LEGACY_F64_NAN
+            })
+        }
+
+        /// The result of an operation on `operands` when any is a NaN.
+        #[inline]
+        fn nan(fcsr: u32, operands: &[f64]) -> Option<(f64, Status)> {
+            if let Some(&x) = operands.iter().find(|&&x| is_signaling(fcsr, x)) {
+                let quiet = if fcsr & NAN2008 != 0 {
+                    f64::from_bits(x.to_bits() | QUIET)
+                } else {
+                    default_nan(fcsr)
+                };
+                return Some((quiet, Status::INVALID));
+            }
+            let &x = operands.iter().find(|x| x.is_nan())?;
+            Some((x, Status::empty()))
+        }
+
+        /// The result and `fcsr` after an operation without NaN operands.
+        #[inline]
+        fn finish(fcsr: u32, (value, status): (f64, Status)) -> (f64, u32) {
+            let value = if value.is_nan() {
+                default_nan(fcsr)
+            } else {
+                value
+            };
+            (value, update(fcsr, status))
+        }
+
+        #[inline]
+        pub fn add(fcsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f64::add(flags(fcsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn sub(fcsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f64::sub(flags(fcsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn mul(fcsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f64::mul(flags(fcsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn div(fcsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(fcsr, &[l, r]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f64::div(flags(fcsr), l, r)),
+            }
+        }
+
+        /// The fused `maddf.fmt` of Release 6, computing `a * b + c`.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fcsr: u32, a: f64, b: f64, c: f64) -> (f64, u32) {
+            match nan(fcsr, &[a, b, c]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f64::madd(flags(fcsr), a, b, c)),
+            }
+        }
+
+        #[inline]
+        pub fn sqrt(fcsr: u32, x: f64) -> (f64, u32) {
+            match nan(fcsr, &[x]) {
+                Some((value, status)) => (value, update(fcsr, status)),
+                None => finish(fcsr, crate::f64::sqrt(flags(fcsr), x)),
+            }
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline]
+        pub fn to_single(fcsr: u32, double: f64) -> (f32, u32) {
+            match nan(fcsr, &[double]) {
+                // The payload keeps its upper bits, unless it would vanish
+                // into an infinity.
+                Some((value, status)) => {
+                    let bits = value.to_bits();
+                    let payload = (bits >> 29) as u32 & 0x7F_FFFF;
+                    let single = if payload == 0 {
+                        super::f32::default_nan(fcsr)
+                    } else {
+                        f32::from_bits((bits >> 32) as u32 & 1 << 31 | 0xFF << 23 | payload)
+                    };
+                    (single, update(fcsr, status))
+                }
+                None => {
+                    let (single, status) = crate::f64::to_single(flags(fcsr), double);
+                    (single, update(fcsr, status))
+                }
+            }
+        }
+    }
+}
+
+pub mod m68k {
+    //! The 68881 and 68882 FPCR and FPSR.
+    //!
+    //! The guest registers hold extended precision, which `f64` stands in
+    //! for here, so the extended and double rounding precisions both round
+    //! to double precision. Rounding to single precision keeps the exponent
+    //! range, so the operations round towards zero, jamming any lost bits
+    //! into the last one, and round that result again to single precision,
+    //! which gives the correctly rounded result. Results below the normal
+    //! range keep the precision the host rounds them to.
+
+    use crate::{Flags, Rounding, Status};
+
+    /// The inexact decimal input exception, in the exception byte of both.
+    pub const INEX1: u32 = 1 << 8;
+    /// The inexact operation exception, in the exception byte of both.
+    pub const INEX2: u32 = 1 << 9;
+    /// The divide-by-zero exception, in the exception byte of both.
+    pub const DZ: u32 = 1 << 10;
+    /// The underflow exception, in the exception byte of both.
+    pub const UNFL: u32 = 1 << 11;
+    /// The overflow exception, in the exception byte of both.
+    pub const OVFL: u32 = 1 << 12;
+    /// The operand error exception, in the exception byte of both.
+    pub const OPERR: u32 = 1 << 13;
+    /// The signaling NaN exception, in the exception byte of both.
+    pub const SNAN: u32 = 1 << 14;
+    /// The branch on unordered exception, in the exception byte of both.
+    pub const BSUN: u32 = 1 << 15;
+    /// The exception byte.
+    pub const EXCEPTIONS: u32 = 0xFF << 8;
+
+    /// The rounding mode field of the FPCR.
+    pub const RND: u32 = 0b11 << 4;
+    /// The rounding precision field of the FPCR.
+    pub const PREC: u32 = 0b11 << 6;
+
+    /// The accrued inexact exception of the FPSR.
+    pub const AINEX: u32 = 1 << 3;
+    /// The accrued divide-by-zero exception of the FPSR.
+    pub const ADZ: u32 = 1 << 4;
+    /// The accrued underflow exception of the FPSR.
+    pub const AUNFL: u32 = 1 << 5;
+    /// The accrued overflow exception of the FPSR.
+    pub const AOVFL: u32 = 1 << 6;
+    /// The accrued invalid operation exception of the FPSR.
+    pub const AIOP: u32 = 1 << 7;
+    /// The quotient byte of the FPSR, with the quotient's sign in the top
+    /// bit and its lowest 7 bits below.
+    pub const QUOTIENT: u32 = 0xFF << 16;
+    /// The NaN condition code of the FPSR.
+    pub const NAN: u32 = 1 << 24;
+    /// The infinity condition code of the FPSR.
+    pub const I: u32 = 1 << 25;
+    /// The zero condition code of the FPSR.
+    pub const Z: u32 = 1 << 26;
+    /// The negative condition code of the FPSR.
+    pub const N: u32 = 1 << 27;
+    /// The condition code byte of the FPSR.
+    pub const CONDITION: u32 = 0xFF << 24;
+
+    /// The precision results are rounded to.
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub enum Precision {
+        Extended,
+        Single,
+        Double,
+    }
+
+    /// The flags selected by `fpcr`.
+    #[inline]
+    pub fn flags(fpcr: u32) -> Flags {
+        let rounding = match (fpcr & RND) >> 4 {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::Zero,
+            0b10 => Rounding::Down,
+            _ => Rounding::Up,
+        };
+        Flags::new().with_rounding(rounding)
+    }
+
+    /// The rounding precision selected by `fpcr`. The reserved encoding
+    /// rounds to double precision, as on the 68040.
+    #[inline]
+    pub fn precision(fpcr: u32) -> Precision {
+        match (fpcr & PREC) >> 6 {
+            0b00 => Precision::Extended,
+            0b01 => Precision::Single,
+            _ => Precision::Double,
+        }
+    }
+
+    /// The condition codes of a result `x`.
+    #[inline]
+    pub fn condition(x: f64) -> u32 {
+        let n = if x.is_sign_negative() { N } else { 0 };
+        if x.is_nan() {
+            n | NAN
+        } else if x.is_infinite() {
+            n | I
+        } else if x == 0.0 {
+            n | Z
+        } else {
+            n
+        }
+    }
+
+    /// `fpsr` with its quotient byte holding the sign and the lowest 7 bits
+    /// of the quotient magnitude, as `fmod` and `frem` leave them.
+    #[inline]
+    pub fn with_quotient(fpsr: u32, negative: bool, quotient: u64) -> u32 {
+        let sign = if negative { 1 << 23 } else { 0 };
+        fpsr & !QUOTIENT | sign | (quotient as u32 & 0x7F) << 16
+    }
+
+    /// The accrued exceptions the exception byte `exceptions` adds.
+    #[inline]
+    pub fn accrued(exceptions: u32) -> u32 {
+        [
+            (BSUN | SNAN | OPERR, AIOP),
+            (OVFL, AOVFL),
+            (DZ, ADZ),
+            (INEX1 | INEX2 | OVFL, AINEX),
+        ]
+        .iter()
+        .filter(|&&(bits, _)| exceptions & bits != 0)
+        .fold(
+            // Underflow only accrues when the result is also inexact.
+            if exceptions & (UNFL | INEX2) == UNFL | INEX2 {
+                AUNFL
+            } else {
+                0
+            },
+            |bits, &(_, bit)| bits | bit,
+        )
+    }
+
+    /// The exceptions in the exception byte of `fpsr` that `fpcr` enables
+    /// traps for.
+    #[inline]
+    pub fn enabled(fpcr: u32, fpsr: u32) -> u32 {
+        fpcr & fpsr & EXCEPTIONS
+    }
+
+    /// `fpsr` after an operation returning `x` and raising `status`, which
+    /// was signaling if `snan`.
+    #[inline]
+    fn update(fpsr: u32, x: f64, status: Status, snan: bool) -> u32 {
+        let exceptions = [
+            (status.invalid() && snan, SNAN),
+            (status.invalid() && !snan, OPERR),
+            (status.overflow(), OVFL),
+            (status.underflow(), UNFL),
+            (status.div_zero(), DZ),
+            (status.inexact(), INEX2),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit);
+        fpsr & QUOTIENT | fpsr & 0xFF | condition(x) | exceptions | accrued(exceptions)
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        const SIGN: u64 = 1 << 63;
+        /// The significand bits single precision drops from double precision.
+        const DROPPED: u32 = 29;
+
+        #[inline]
+        fn is_signaling(x: f64) -> bool {
+            x.is_nan() && x.to_bits() & 1 << 51 == 0
+        }
+
+        /// Performs `op` rounded to the precision `fpcr` selects.
+        #[inline]
+        fn rounded(fpcr: u32, op: impl Fn(Flags) -> (f64, Status)) -> (f64, Status) {
+            let flags = flags(fpcr);
+            if precision(fpcr) != Precision::Single {
+                return op(flags);
+            }
+            let (value, status) = op(flags.with_rounding(Rounding::Zero));
+            if !value.is_normal() {
+                return op(flags);
+            }
+            let bits = value.to_bits();
+            let negative = bits & SIGN != 0;
+            // Jamming the lost bits leaves no ties where there were none.
+            let magnitude = bits & !SIGN | status.inexact() as u64;
+            let rest = magnitude & ((1 << DROPPED) - 1);
+            let half = 1 << (DROPPED - 1);
+            let up = match flags.rounding() {
+                Rounding::Nearest => rest > half || rest == half && magnitude >> DROPPED & 1 != 0,
+                Rounding::NearestAway => rest >= half,
+                Rounding::Odd => rest != 0 && magnitude >> DROPPED & 1 == 0,
+                Rounding::Zero => false,
+                Rounding::Up => rest != 0 && !negative,
+                Rounding::Down => rest != 0 && negative,
+            };
+            let magnitude = ((magnitude >> DROPPED) + up as u64) << DROPPED;
+            let mut status = status;
+            if rest != 0 {
+                status = status.or(Status::INEXACT);
+            }
+            if magnitude >> 52 == 0x7FF {
+                status = status.or(Status::OVERFLOW);
+            }
+            (f64::from_bits(bits & SIGN | magnitude), status)
+        }
+
+        #[inline]
+        pub fn add(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = rounded(fpcr, |flags| crate::f64::add(flags, l, r));
+            let snan = is_signaling(l) || is_signaling(r);
+            (value, update(fpsr, value, status, snan))
+        }
+
+        #[inline]
+        pub fn sub(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = rounded(fpcr, |flags| crate::f64::sub(flags, l, r));
+            let snan = is_signaling(l) || is_signaling(r);
+            (value, update(fpsr, value, status, snan))
+        }
+
+        #[inline]
+        pub fn mul(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = rounded(fpcr, |flags| crate::f64::mul(flags, l, r));
+            let snan = is_signaling(l) || is_signaling(r);
+            (value, update(fpsr, value, status, snan))
+        }
+
+        #[inline]
+        pub fn div(fpcr: u32, fpsr: u32, l: f64, r: f64) -> (f64, u32) {
+            let (value, status) = rounded(fpcr, |flags| crate::f64::div(flags, l, r));
+            let snan = is_signaling(l) || is_signaling(r);
+            (value, update(fpsr, value, status, snan))
+        }
+
+        #[inline]
+        pub fn sqrt(fpcr: u32, fpsr: u32, x: f64) -> (f64, u32) {
+            let (value, status) = rounded(fpcr, |flags| crate::f64::sqrt(flags, x));
+            (value, update(fpsr, value, status, is_signaling(x)))
+        }
+    }
+}
+
+pub mod sparc {
+    //! The FSR, with its rounding direction, trap enables and the accrued
+    //! and current exception fields.
+    //!
+    //! The operations here resolve NaN operands and results themselves,
+    //! returning the signaling NaN of the later operand quieted, else its
+    //! quiet NaN, and the default NaN for invalid operations.
+
+    use crate::{Flags, Rounding, Status};
+
+    /// The inexact exception, at each field's position.
+    pub const NX: u32 = 1 << 0;
+    /// The divide-by-zero exception, at each field's position.
+    pub const DZ: u32 = 1 << 1;
+    /// The underflow exception, at each field's position.
+    pub const UF: u32 = 1 << 2;
+    /// The overflow exception, at each field's position.
+    pub const OF: u32 = 1 << 3;
+    /// The invalid operation exception, at each field's position.
+    pub const NV: u32 = 1 << 4;
+    /// The position of the current exception field, `cexc`.
+    pub const CEXC_SHIFT: u32 = 0;
+    /// The position of the accrued exception field, `aexc`.
+    pub const AEXC_SHIFT: u32 = 5;
+    /// The position of the trap enable field, `TEM`.
+    pub const TEM_SHIFT: u32 = 23;
+    /// The current exception field.
+    pub const CEXC: u32 = 0x1F << CEXC_SHIFT;
+    /// The accrued exception field.
+    pub const AEXC: u32 = 0x1F << AEXC_SHIFT;
+    /// The floating-point trap type field.
+    pub const FTT: u32 = 0b111 << 14;
+    /// The trap enable field.
+    pub const TEM: u32 = 0x1F << TEM_SHIFT;
+    /// The non-standard floating-point mode, which may flush denormals.
+    pub const NS: u32 = 1 << 22;
+    /// The rounding direction field.
+    pub const RD: u32 = 0b11 << 30;
+    /// The trap type of an IEEE 754 exception.
+    pub const IEEE_754_EXCEPTION: u32 = 1 << 14;
+
+    /// The flags selected by `fsr`. `NS` is ignored, since whether it
+    /// flushes is up to the implementation.
+    #[inline]
+    pub fn flags(fsr: u32) -> Flags {
+        let rounding = match (fsr & RD) >> 30 {
+            0b00 => Rounding::Nearest,
+            0b01 => Rounding::Zero,
+            0b10 => Rounding::Up,
+            _ => Rounding::Down,
+        };
+        Flags::new().with_rounding(rounding)
+    }
+
+    /// The exceptions raised by `status`, at the position of each field.
+    #[inline]
+    pub fn exceptions(status: Status) -> u32 {
+        [
+            (status.inexact(), NX),
+            (status.div_zero(), DZ),
+            (status.underflow(), UF),
+            (status.overflow(), OF),
+            (status.invalid(), NV),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit)
+    }
+
+    /// The exceptions raised by `status` that `fsr` enables traps for.
+    #[inline]
+    pub fn enabled(fsr: u32, status: Status) -> u32 {
+        exceptions(status) & fsr >> TEM_SHIFT
+    }
+
+    /// `fsr` after an operation raising `status`. `cexc` holds its
+    /// exceptions, which accrue into `aexc` unless one of them traps, in
+    /// which case `ftt` records an IEEE 754 exception instead.
+    #[inline]
+    pub fn update(fsr: u32, status: Status) -> u32 {
+        let exceptions = exceptions(status);
+        let fsr = fsr & !CEXC | exceptions << CEXC_SHIFT;
+        if enabled(fsr, status) != 0 {
+            fsr & !FTT | IEEE_754_EXCEPTION
+        } else {
+            fsr | exceptions << AEXC_SHIFT
+        }
+    }
+
+    /// A SPARC core, whose register is the FSR.
+    #[derive(Clone, Copy)]
+    pub struct Sparc;
+
+    impl crate::Isa for Sparc {
+        type Registers = u32;
+
+        #[inline]
+        fn flags(fsr: u32) -> Flags {
+            flags(fsr)
+        }
+
+        #[inline]
+        fn accumulate(fsr: u32, status: Status) -> u32 {
+            update(fsr, status)
+        }
+
+        #[inline]
+        fn f32_add(fsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::add(fsr, l, r)
+        }
+
+        #[inline]
+        fn f32_sub(fsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::sub(fsr, l, r)
+        }
+
+        #[inline]
+        fn f32_mul(fsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::mul(fsr, l, r)
+        }
+
+        #[inline]
+        fn f32_div(fsr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::div(fsr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f32_madd(fsr: u32, _: Flags, a: f32, b: f32, c: f32) -> (f32, u32) {
+            f32::madd(fsr, a, b, c)
+        }
+
+        #[inline]
+        fn f32_sqrt(fsr: u32, _: Flags, x: f32) -> (f32, u32) {
+            f32::sqrt(fsr, x)
+        }
+
+        #[inline]
+        fn f64_add(fsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::add(fsr, l, r)
+        }
+
+        #[inline]
+        fn f64_sub(fsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::sub(fsr, l, r)
+        }
+
+        #[inline]
+        fn f64_mul(fsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::mul(fsr, l, r)
+        }
+
+        #[inline]
+        fn f64_div(fsr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::div(fsr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f64_madd(fsr: u32, _: Flags, a: f64, b: f64, c: f64) -> (f64, u32) {
+            f64::madd(fsr, a, b, c)
+        }
+
+        #[inline]
+        fn f64_sqrt(fsr: u32, _: Flags, x: f64) -> (f64, u32) {
+            f64::sqrt(fsr, x)
+        }
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        const QUIET: u32 = 1 << 22;
+
+        /// Whether `x` is a signaling NaN.
+        #[inline]
+        pub fn is_signaling(x: f32) -> bool {
+            x.is_nan() && x.to_bits() & QUIET == 0
+        }
+
+        /// The NaN invalid operations return.
+        #[inline]
+        pub fn default_nan() -> f32 {
+            f32::from_bits(crate::consts::sparc::F32_NAN)
+        }
+
+        /// The result of an operation on `operands` when any is a NaN: the
+        /// last signaling NaN quieted, else the last quiet NaN, as `rs2` wins
+        /// over `rs1`.
+        #[inline]
+        fn nan(operands: &[f32]) -> Option<(f32, Status)> {
+            if let Some(&x) = operands.iter().rev().find(|&&x| is_signaling(x)) {
+                return Some((f32::from_bits(x.to_bits() | QUIET), Status::INVALID));
+            }
+            let &x = operands.iter().rev().find(|x| x.is_nan())?;
+            Some((x, Status::empty()))
+        }
+
+        /// The result and `fsr` after an operation without NaN operands.
+        #[inline]
+        fn finish(fsr: u32, (value, status): (f32, Status)) -> (f32, u32) {
+            let value = if value.is_nan() { default_nan() } else { value };
+            (value, update(fsr, status))
+        }
+
+        #[inline]
+        pub fn add(fsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f32::add(flags(fsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn sub(fsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f32::sub(flags(fsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn mul(fsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f32::mul(flags(fsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn div(fsr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f32::div(flags(fsr), l, r)),
+            }
+        }
+
+        /// The fused `fmadd` of SPARC64, computing `a * b + c`.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fsr: u32, a: f32, b: f32, c: f32) -> (f32, u32) {
+            match nan(&[a, b, c]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f32::madd(flags(fsr), a, b, c)),
+            }
+        }
+
+        #[inline]
+        pub fn sqrt(fsr: u32, x: f32) -> (f32, u32) {
+            match nan(&[x]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f32::sqrt(flags(fsr), x)),
+            }
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        const QUIET: u64 = 1 << 51;
+
+        /// Whether `x` is a signaling NaN.
+        #[inline]
+        pub fn is_signaling(x: f64) -> bool {
+            x.is_nan() && x.to_bits() & QUIET == 0
+        }
+
+        /// The NaN invalid operations return.
+        #[inline]
+        pub fn default_nan() -> f64 {
+            f64::from_bits(crate::consts::sparc::F64_NAN)
+        }
+
+        /// The result of an operation on `operands` when any is a NaN: the
+        /// last signaling NaN quieted, else the last quiet NaN, as `rs2` wins
+        /// over `rs1`.
+        #[inline]
+        fn nan(operands: &[f64]) -> Option<(f64, Status)> {
+            if let Some(&x) = operands.iter().rev().find(|&&x| is_signaling(x)) {
+                return Some((f64::from_bits(x.to_bits() | QUIET), Status::INVALID));
+            }
+            let &x = operands.iter().rev().find(|x| x.is_nan())?;
+            Some((x, Status::empty()))
+        }
+
+        /// The result and `fsr` after an operation without NaN operands.
+        #[inline]
+        fn finish(fsr: u32, (value, status): (f64, Status)) -> (f64, u32) {
+            let value = if value.is_nan() { default_nan() } else { value };
+            (value, update(fsr, status))
+        }
+
+        #[inline]
+        pub fn add(fsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f64::add(flags(fsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn sub(fsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f64::sub(flags(fsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn mul(fsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f64::mul(flags(fsr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn div(fsr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f64::div(flags(fsr), l, r)),
+            }
+        }
+
+        /// The fused `fmadd` of SPARC64, computing `a * b + c`.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fsr: u32, a: f64, b: f64, c: f64) -> (f64, u32) {
+            match nan(&[a, b, c]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f64::madd(flags(fsr), a, b, c)),
+            }
+        }
+
+        #[inline]
+        pub fn sqrt(fsr: u32, x: f64) -> (f64, u32) {
+            match nan(&[x]) {
+                Some((value, status)) => (value, update(fsr, status)),
+                None => finish(fsr, crate::f64::sqrt(flags(fsr), x)),
+            }
+        }
+    }
+}
+
+pub mod sh4 {
+    //! The SH-4 FPSCR, with its rounding, denormalization and precision
+    //! modes and the cause, enable and flag fields.
+    //!
+    //! SH-4 inverts the meaning of the quiet bit, as the legacy MIPS
+    //! encoding does, so the host can't tell the kinds apart. The operations
+    //! here resolve NaN operands and results themselves, returning the first
+    //! quiet NaN, and the default NaN for signaling NaN operands and invalid
+    //! operations.
+
+    use crate::{Flags, Rounding, Status};
+
+    /// The rounding mode field.
+    pub const RM: u32 = 0b11;
+    /// The position of the flag field.
+    pub const FLAGS_SHIFT: u32 = 2;
+    /// The position of the enable field.
+    pub const ENABLES_SHIFT: u32 = 7;
+    /// The position of the cause field.
+    pub const CAUSE_SHIFT: u32 = 12;
+    /// The inexact exception, at each field's position.
+    pub const I: u32 = 1 << 0;
+    /// The underflow exception, at each field's position.
+    pub const U: u32 = 1 << 1;
+    /// The overflow exception, at each field's position.
+    pub const O: u32 = 1 << 2;
+    /// The divide-by-zero exception, at each field's position.
+    pub const Z: u32 = 1 << 3;
+    /// The invalid operation exception, at each field's position.
+    pub const V: u32 = 1 << 4;
+    /// The FPU error cause, which has no flag or enable and always traps.
+    pub const E: u32 = 1 << 5;
+    /// The flag field.
+    pub const FLAGS: u32 = 0x1F << FLAGS_SHIFT;
+    /// The enable field.
+    pub const ENABLES: u32 = 0x1F << ENABLES_SHIFT;
+    /// The cause field.
+    pub const CAUSE: u32 = 0x3F << CAUSE_SHIFT;
+    /// Treats denormal operands and results as zero.
+    pub const DN: u32 = 1 << 18;
+    /// Selects double precision instead of single precision.
+    pub const PR: u32 = 1 << 19;
+    /// Makes `fmov` transfer register pairs.
+    pub const SZ: u32 = 1 << 20;
+    /// Selects the register bank.
+    pub const FR: u32 = 1 << 21;
+
+    /// The flags selected by `fpscr`. The encodings of `RM` besides round
+    /// to nearest and towards zero are reserved and round towards zero.
+    #[inline]
+    pub fn flags(fpscr: u32) -> Flags {
+        let rounding = match fpscr & RM {
+            0b00 => Rounding::Nearest,
+            _ => Rounding::Zero,
+        };
+        let dn = fpscr & DN != 0;
+        Flags::new()
+            .with_rounding(rounding)
+            .with_ftz(dn)
+            .with_daz(dn)
+    }
+
+    /// Whether `fpscr` selects double precision for the arithmetic.
+    #[inline]
+    pub fn double(fpscr: u32) -> bool {
+        fpscr & PR != 0
+    }
+
+    /// The exceptions raised by `status`, at the position of each field.
+    #[inline]
+    pub fn exceptions(status: Status) -> u32 {
+        [
+            (status.inexact(), I),
+            (status.underflow(), U),
+            (status.overflow(), O),
+            (status.div_zero(), Z),
+            (status.invalid(), V),
+        ]
+        .iter()
+        .filter(|&&(raised, _)| raised)
+        .fold(0, |bits, &(_, bit)| bits | bit)
+    }
+
+    /// The exceptions raised by `status` that `fpscr` enables traps for.
+    #[inline]
+    pub fn enabled(fpscr: u32, status: Status) -> u32 {
+        exceptions(status) & fpscr >> ENABLES_SHIFT
+    }
+
+    /// `fpscr` after an operation raising `status`: every operation replaces
+    /// the cause field with its exceptions, which the flags only accumulate
+    /// when none of them traps.
+    #[inline]
+    pub fn update(fpscr: u32, status: Status) -> u32 {
+        let exceptions = exceptions(status);
+        let fpscr = fpscr & !CAUSE | exceptions << CAUSE_SHIFT;
+        if enabled(fpscr, status) != 0 {
+            fpscr
+        } else {
+            fpscr | exceptions << FLAGS_SHIFT
+        }
+    }
+
+    /// `fpscr` after an operation the FPU leaves to software, such as one
+    /// on a denormal operand while `DN` is clear, which only sets cause `E`.
+    #[inline]
+    pub fn error(fpscr: u32) -> u32 {
+        fpscr & !CAUSE | E << CAUSE_SHIFT
+    }
+
+    /// An SH-4 core, whose register is the FPSCR.
+    #[derive(Clone, Copy)]
+    pub struct Sh4;
+
+    impl crate::Isa for Sh4 {
+        type Registers = u32;
+
+        #[inline]
+        fn flags(fpscr: u32) -> Flags {
+            flags(fpscr)
+        }
+
+        #[inline]
+        fn accumulate(fpscr: u32, status: Status) -> u32 {
+            update(fpscr, status)
+        }
+
+        #[inline]
+        fn f32_add(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::add(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f32_sub(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::sub(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f32_mul(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::mul(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f32_div(fpscr: u32, _: Flags, l: f32, r: f32) -> (f32, u32) {
+            f32::div(fpscr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f32_madd(fpscr: u32, _: Flags, a: f32, b: f32, c: f32) -> (f32, u32) {
+            f32::madd(fpscr, a, b, c)
+        }
+
+        #[inline]
+        fn f32_sqrt(fpscr: u32, _: Flags, x: f32) -> (f32, u32) {
+            f32::sqrt(fpscr, x)
+        }
+
+        #[inline]
+        fn f64_add(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::add(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f64_sub(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::sub(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f64_mul(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::mul(fpscr, l, r)
+        }
+
+        #[inline]
+        fn f64_div(fpscr: u32, _: Flags, l: f64, r: f64) -> (f64, u32) {
+            f64::div(fpscr, l, r)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        fn f64_madd(fpscr: u32, _: Flags, a: f64, b: f64, c: f64) -> (f64, u32) {
+            f64::madd(fpscr, a, b, c)
+        }
+
+        #[inline]
+        fn f64_sqrt(fpscr: u32, _: Flags, x: f64) -> (f64, u32) {
+            f64::sqrt(fpscr, x)
+        }
+    }
+
+    pub mod f32 {
+        use super::*;
+
+        const QUIET: u32 = 1 << 22;
+
+        /// Whether `x` is a signaling NaN.
+        #[inline]
+        pub fn is_signaling(x: f32) -> bool {
+            x.is_nan() && x.to_bits() & QUIET != 0
+        }
+
+        /// The NaN invalid operations return.
+        #[inline]
+        pub fn default_nan() -> f32 {
+            f32::from_bits(crate::consts::sh4::F32_NAN)
+        }
+
+        /// The result of an operation on `operands` when any is a NaN: the
+        /// first quiet NaN, or the default NaN for a signaling one, whose
+        /// quieted form could be an infinity.
+        #[inline]
+        fn nan(operands: &[f32]) -> Option<(f32, Status)> {
+            if operands.iter().any(|&x| is_signaling(x)) {
+                return Some((default_nan(), Status::INVALID));
+            }
+            let &x = operands.iter().find(|x| x.is_nan())?;
+            Some((x, Status::empty()))
+        }
+
+        /// The result and `fpscr` after an operation without NaN operands.
+        #[inline]
+        fn finish(fpscr: u32, (value, status): (f32, Status)) -> (f32, u32) {
+            let value = if value.is_nan() { default_nan() } else { value };
+            (value, update(fpscr, status))
+        }
+
+        #[inline]
+        pub fn add(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f32::add(flags(fpscr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn sub(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f32::sub(flags(fpscr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn mul(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f32::mul(flags(fpscr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn div(fpscr: u32, l: f32, r: f32) -> (f32, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f32::div(flags(fpscr), l, r)),
+            }
+        }
+
+        /// The `fmac` instruction, computing `a * b + c` with one rounding.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpscr: u32, a: f32, b: f32, c: f32) -> (f32, u32) {
+            match nan(&[a, b, c]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f32::madd(flags(fpscr), a, b, c)),
+            }
+        }
+
+        #[inline]
+        pub fn sqrt(fpscr: u32, x: f32) -> (f32, u32) {
+            match nan(&[x]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f32::sqrt(flags(fpscr), x)),
+            }
+        }
+    }
+
+    pub mod f64 {
+        use super::*;
+
+        const QUIET: u64 = 1 << 51;
+
+        /// Whether `x` is a signaling NaN.
+        #[inline]
+        pub fn is_signaling(x: f64) -> bool {
+            x.is_nan() && x.to_bits() & QUIET != 0
+        }
+
+        /// The NaN invalid operations return.
+        #[inline]
+        pub fn default_nan() -> f64 {
+            f64::from_bits(crate::consts::sh4::F64_NAN)
+        }
+
+        /// The result of an operation on `operands` when any is a NaN: the
+        /// first quiet NaN, or the default NaN for a signaling one, whose
+        /// quieted form could be an infinity.
+        #[inline]
+        fn nan(operands: &[f64]) -> Option<(f64, Status)> {
+            if operands.iter().any(|&x| is_signaling(x)) {
+                return Some((default_nan(), Status::INVALID));
+            }
+            let &x = operands.iter().find(|x| x.is_nan())?;
+            Some((x, Status::empty()))
+        }
+
+        /// The result and `fpscr` after an operation without NaN operands.
+        #[inline]
+        fn finish(fpscr: u32, (value, status): (f64, Status)) -> (f64, u32) {
+            let value = if value.is_nan() { default_nan() } else { value };
+            (value, update(fpscr, status))
+        }
+
+        #[inline]
+        pub fn add(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f64::add(flags(fpscr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn sub(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f64::sub(flags(fpscr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn mul(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f64::mul(flags(fpscr), l, r)),
+            }
+        }
+
+        #[inline]
+        pub fn div(fpscr: u32, l: f64, r: f64) -> (f64, u32) {
+            match nan(&[l, r]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f64::div(flags(fpscr), l, r)),
+            }
+        }
+
+        /// Computes `a * b + c` with one rounding, which `fmac` only offers
+        /// in single precision.
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(fpscr: u32, a: f64, b: f64, c: f64) -> (f64, u32) {
+            match nan(&[a, b, c]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f64::madd(flags(fpscr), a, b, c)),
+            }
+        }
+
+        #[inline]
+        pub fn sqrt(fpscr: u32, x: f64) -> (f64, u32) {
+            match nan(&[x]) {
+                Some((value, status)) => (value, update(fpscr, status)),
+                None => finish(fpscr, crate::f64::sqrt(flags(fpscr), x)),
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Conversions to and from half precision in a selectable format, for
+//! emulating ARM cores running with `FPCR.AHP` set.
+//!
+//! The alternative format has no infinities or NaNs and uses their encodings
+//! for another binade of normal numbers, up to 131008. The conversions to it
+//! turn NaNs into zeros and infinities and overflowing values into the
+//! largest magnitude, raising invalid for each of them and nothing else, and
+//! always run in software.
+
+use crate::{soft, Flags, Status};
+
+/// The format of half-precision values.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HalfFormat {
+    /// IEEE 754 binary16.
+    Ieee,
+    /// The alternative half-precision format of ARM.
+    Alternative,
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn to_half(flags: Flags, format: HalfFormat, single: f32) -> (u16, Status) {
+        match format {
+            HalfFormat::Ieee => crate::f32::to_half(flags, single),
+            HalfFormat::Alternative => {
+                let (bits, exc) = soft::to_alternative_half::<soft::Binary32>(
+                    &flags.soft_env(),
+                    single.to_bits() as u64,
+                );
+                (bits as u16, Status::from_soft(exc))
+            }
+        }
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn to_half(flags: Flags, format: HalfFormat, double: f64) -> (u16, Status) {
+        match format {
+            HalfFormat::Ieee => crate::f64::to_half(flags, double),
+            HalfFormat::Alternative => {
+                let (bits, exc) = soft::to_alternative_half::<soft::Binary64>(
+                    &flags.soft_env(),
+                    double.to_bits(),
+                );
+                (bits as u16, Status::from_soft(exc))
+            }
+        }
+    }
+}
+
+pub mod f16 {
+    use super::*;
+
+    #[inline]
+    pub fn to_single(flags: Flags, format: HalfFormat, half: u16) -> (f32, Status) {
+        match format {
+            HalfFormat::Ieee => crate::f16::to_single(flags, half),
+            HalfFormat::Alternative => {
+                let (bits, exc) =
+                    soft::from_alternative_half::<soft::Binary32>(&flags.soft_env(), half as u64);
+                (f32::from_bits(bits as u32), Status::from_soft(exc))
+            }
+        }
+    }
+
+    #[inline]
+    pub fn to_double(flags: Flags, format: HalfFormat, half: u16) -> (f64, Status) {
+        match format {
+            HalfFormat::Ieee => crate::f16::to_double(flags, half),
+            HalfFormat::Alternative => {
+                let (bits, exc) =
+                    soft::from_alternative_half::<soft::Binary64>(&flags.soft_env(), half as u64);
+                (f64::from_bits(bits), Status::from_soft(exc))
+            }
+        }
+    }
+}
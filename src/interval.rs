@@ -0,0 +1,360 @@
+//! Interval arithmetic on doubles, rounding the lower bound down and the
+//! upper bound up so that every result encloses the exact one.
+//!
+//! Bounds may be infinite, and the empty interval results from operations
+//! defined nowhere on their operands, such as dividing by `[0, 0]` or the
+//! square root of a negative interval. Division by an interval containing
+//! zero in its interior gives the entire line.
+//!
+//! The square root rounds each bound in the direction it needs. The
+//! exponential, logarithm, sine and cosine are evaluated in double extended
+//! precision, whose error is below an ulp of that format, and widened by an
+//! ulp of `f64` past its directed roundings unless exact. The sine and cosine
+//! take the extrema of the quadrants they cross, and are `[-1, 1]` for bounds
+//! of magnitude 2^63 and over.
+
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+#[cfg(feature = "convert")]
+use crate::f80::F80;
+#[cfg(feature = "convert")]
+use crate::transcendental::{self, Trig};
+#[cfg(feature = "convert")]
+use crate::{soft, Status};
+use crate::{Flags, Rounding};
+
+const DOWN: Flags = Flags::new().with_rounding(Rounding::Down);
+const UP: Flags = Flags::new().with_rounding(Rounding::Up);
+
+/// A closed interval of reals, or the empty set.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Interval {
+    // The empty interval is held as `[+inf, -inf]`.
+    lo: f64,
+    hi: f64,
+}
+
+impl Interval {
+    pub const EMPTY: Self = Self {
+        lo: f64::INFINITY,
+        hi: f64::NEG_INFINITY,
+    };
+    pub const ENTIRE: Self = Self {
+        lo: f64::NEG_INFINITY,
+        hi: f64::INFINITY,
+    };
+
+    /// The interval `[lo, hi]`, or the empty interval if it holds no real,
+    /// as when `lo` is above `hi`, either is a NaN or both are the same
+    /// infinity.
+    #[inline]
+    pub fn new(lo: f64, hi: f64) -> Self {
+        if lo <= hi && lo != f64::INFINITY && hi != f64::NEG_INFINITY {
+            Self { lo, hi }
+        } else {
+            Self::EMPTY
+        }
+    }
+
+    /// The interval holding only `x`.
+    #[inline]
+    pub fn point(x: f64) -> Self {
+        Self::new(x, x)
+    }
+
+    /// The lower bound, `+inf` for the empty interval.
+    #[inline]
+    pub fn lo(self) -> f64 {
+        self.lo
+    }
+
+    /// The upper bound, `-inf` for the empty interval.
+    #[inline]
+    pub fn hi(self) -> f64 {
+        self.hi
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.lo > self.hi
+    }
+
+    #[inline]
+    pub fn is_entire(self) -> bool {
+        self.lo == f64::NEG_INFINITY && self.hi == f64::INFINITY
+    }
+
+    #[inline]
+    pub fn contains(self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    /// The smallest interval holding both.
+    #[inline]
+    pub fn hull(self, other: Self) -> Self {
+        Self {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    #[inline]
+    pub fn intersection(self, other: Self) -> Self {
+        Self::new(self.lo.max(other.lo), self.hi.min(other.hi))
+    }
+
+    /// The square roots of the nonnegative part.
+    #[inline]
+    pub fn sqrt(self) -> Self {
+        let x = self.intersection(Self::new(0.0, f64::INFINITY));
+        if x.is_empty() {
+            return Self::EMPTY;
+        }
+        Self {
+            lo: crate::f64::sqrt(DOWN, x.lo).0,
+            hi: crate::f64::sqrt(UP, x.hi).0,
+        }
+    }
+
+    #[cfg(feature = "convert")]
+    /// The exponentials of the values.
+    #[inline]
+    pub fn exp(self) -> Self {
+        if self.is_empty() {
+            return Self::EMPTY;
+        }
+        let exp = |x| extended(x, transcendental::exp);
+        Self {
+            lo: exp(self.lo).lo.max(0.0),
+            hi: exp(self.hi).hi,
+        }
+    }
+
+    #[cfg(feature = "convert")]
+    /// The natural logarithms of the positive part.
+    #[inline]
+    pub fn log(self) -> Self {
+        let x = self.intersection(Self::new(0.0, f64::INFINITY));
+        if x.is_empty() || x.hi == 0.0 {
+            return Self::EMPTY;
+        }
+        let ln = |x: f64| match x {
+            0.0 => Self {
+                lo: f64::NEG_INFINITY,
+                hi: f64::NEG_INFINITY,
+            },
+            f64::INFINITY => Self { lo: x, hi: x },
+            _ => extended(x, transcendental::ln),
+        };
+        Self {
+            lo: ln(x.lo).lo,
+            hi: ln(x.hi).hi,
+        }
+    }
+
+    #[cfg(feature = "convert")]
+    /// The sines of the values.
+    #[inline]
+    pub fn sin(self) -> Self {
+        // The maximum starts the second quadrant and the minimum the fourth.
+        self.periodic(Trig::Sin, 1)
+    }
+
+    #[cfg(feature = "convert")]
+    /// The cosines of the values.
+    #[inline]
+    pub fn cos(self) -> Self {
+        self.periodic(Trig::Cos, 0)
+    }
+
+    #[cfg(feature = "convert")]
+    /// The sines or cosines of the values, whose maximum starts the quadrant
+    /// `peak` and minimum the one two after it.
+    fn periodic(self, trig: Trig, peak: u32) -> Self {
+        const UNIT: Interval = Interval { lo: -1.0, hi: 1.0 };
+        if self.is_empty() {
+            return Self::EMPTY;
+        }
+        if !(self.lo.abs() < TRIG_LIMIT && self.hi.abs() < TRIG_LIMIT)
+            || crate::f64::sub(DOWN, self.hi, self.lo).0 >= 6.5
+        {
+            return UNIT;
+        }
+        if crate::f64::sub(UP, self.hi, self.lo).0 >= 4.5 {
+            // Halves narrower than three quadrants, which the quadrants of
+            // their bounds tell apart.
+            let mid = self.lo + (self.hi - self.lo) / 2.0;
+            return Self::new(self.lo, mid)
+                .periodic(trig, peak)
+                .hull(Self::new(mid, self.hi).periodic(trig, peak));
+        }
+        let quadrant = |x: f64| match x {
+            0.0 => 0,
+            _ => transcendental::quadrant(extend(x)),
+        };
+        let (first, last) = (quadrant(self.lo), quadrant(self.hi));
+        let crosses =
+            |target: u32| (1..=last.wrapping_sub(first) & 3).any(|j| (first + j) & 3 == target);
+        let value = |x| {
+            extended(x, |env, x| {
+                transcendental::trig(env, x, trig, false).unwrap()
+            })
+        };
+        let (lo, hi) = (value(self.lo), value(self.hi));
+        Self {
+            lo: if crosses(peak + 2) {
+                -1.0
+            } else {
+                lo.lo.min(hi.lo).max(-1.0)
+            },
+            hi: if crosses(peak) {
+                1.0
+            } else {
+                lo.hi.max(hi.hi).min(1.0)
+            },
+        }
+    }
+
+    /// The interval bounded by the smallest and largest of the products or
+    /// quotients of the bounds, computed by `op` under each rounding. Bounds
+    /// giving NaNs, zeros times infinities or infinities over infinities,
+    /// contribute `nan`.
+    #[inline]
+    fn corners(self, other: Self, nan: f64, op: impl Fn(Flags, f64, f64) -> f64) -> Self {
+        let corner = |flags, l, r| {
+            let x = op(flags, l, r);
+            if x.is_nan() {
+                nan
+            } else {
+                x
+            }
+        };
+        let pairs = [
+            (self.lo, other.lo),
+            (self.lo, other.hi),
+            (self.hi, other.lo),
+            (self.hi, other.hi),
+        ];
+        let (mut lo, mut hi) = (f64::INFINITY, f64::NEG_INFINITY);
+        for (l, r) in pairs {
+            lo = lo.min(corner(DOWN, l, r));
+            hi = hi.max(corner(UP, l, r));
+        }
+        Self { lo, hi }
+    }
+}
+
+impl Neg for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return Self::EMPTY;
+        }
+        Self {
+            lo: crate::f64::add(DOWN, self.lo, other.lo).0,
+            hi: crate::f64::add(UP, self.hi, other.hi).0,
+        }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return Self::EMPTY;
+        }
+        // A zero bound times an infinite one stands for zero times the
+        // finite values next to it.
+        self.corners(other, 0.0, |flags, l, r| crate::f64::mul(flags, l, r).0)
+    }
+}
+
+impl Div for Interval {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, other: Self) -> Self {
+        if self.is_empty() || other.is_empty() || other == Self::point(0.0) {
+            return Self::EMPTY;
+        }
+        let quotient = |flags, l, r| crate::f64::div(flags, l, r).0;
+        if other.lo < 0.0 && 0.0 < other.hi || self.contains(0.0) && other.contains(0.0) {
+            Self::ENTIRE
+        } else if other.lo == 0.0 {
+            // Only the positive part of the divisor counts.
+            if self.lo > 0.0 {
+                Self::new(quotient(DOWN, self.lo, other.hi), f64::INFINITY)
+            } else {
+                Self::new(f64::NEG_INFINITY, quotient(UP, self.hi, other.hi))
+            }
+        } else if other.hi == 0.0 {
+            if self.lo > 0.0 {
+                Self::new(f64::NEG_INFINITY, quotient(UP, self.lo, other.lo))
+            } else {
+                Self::new(quotient(DOWN, self.hi, other.lo), f64::INFINITY)
+            }
+        } else {
+            // Infinities over infinities only arise next to finite
+            // quotients of the same sign, which the other corners bound.
+            self.corners(other, f64::NAN, quotient)
+        }
+    }
+}
+
+#[cfg(feature = "convert")]
+/// The magnitude from which the double extended trigonometric functions
+/// give up.
+const TRIG_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+#[cfg(feature = "convert")]
+#[inline]
+fn extend(x: f64) -> F80 {
+    crate::f80::from_double(Flags::new(), x).0
+}
+
+#[cfg(feature = "convert")]
+/// An enclosure of `f(x)` from its value rounded to double extended, which
+/// lies within an ulp of that format of the exact one, so one of `f64` past
+/// the roundings of it to `f64` down and up: the value itself if no
+/// rounding took place.
+#[inline]
+fn extended(x: f64, f: impl Fn(&soft::Env, F80) -> (F80, u32)) -> Interval {
+    let flags = Flags::new();
+    let (y, exc) = f(&crate::f80::env(flags), extend(x));
+    let exact = !Status::from_soft(exc).inexact();
+    let lo = crate::f80::to_double(DOWN, y).0;
+    let hi = crate::f80::to_double(UP, y).0;
+    if exact {
+        Interval { lo, hi }
+    } else {
+        Interval {
+            lo: lo.next_down(),
+            hi: hi.next_up(),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Reductions of iterators under a rounding mode, for values that don't
+//! already sit in a slice.
+
+use crate::{slice, Flags, Rounding, Status};
+
+/// The number of values summed at once, so the environment is set up once
+/// for every chunk rather than for every value.
+const CHUNK: usize = 64;
+
+/// Sums and products of iterators, from left to right like the slice
+/// reductions, so that rounding up or down bounds the exact result.
+pub trait RoundedIterator<T>: Iterator<Item = T> {
+    /// Sums the values, giving positive zero for none.
+    fn rounded_sum(self, rounding: Rounding) -> (T, Status);
+
+    /// Multiplies the values, giving one for none.
+    fn rounded_product(self, rounding: Rounding) -> (T, Status);
+}
+
+impl<I: Iterator<Item = f32>> RoundedIterator<f32> for I {
+    fn rounded_sum(mut self, rounding: Rounding) -> (f32, Status) {
+        let flags = Flags::new().with_rounding(rounding);
+        let Some(mut sum) = self.next() else {
+            return (0.0, Status::empty());
+        };
+        let mut status = Status::empty();
+        let mut chunk = [0.0; CHUNK];
+        loop {
+            let len = chunk
+                .iter_mut()
+                .zip(&mut self)
+                .map(|(slot, value)| *slot = value)
+                .count();
+            if len == 0 {
+                return (sum, status);
+            }
+            let (partial, chunk_status) = slice::imp::f32_sum(flags, sum, &chunk[..len]);
+            sum = partial;
+            status = status.or(chunk_status);
+        }
+    }
+
+    fn rounded_product(mut self, rounding: Rounding) -> (f32, Status) {
+        let flags = Flags::new().with_rounding(rounding);
+        let Some(first) = self.next() else {
+            return (1.0, Status::empty());
+        };
+        self.fold((first, Status::empty()), |(product, status), value| {
+            let (product, value_status) = crate::f32::mul(flags, product, value);
+            (product, status.or(value_status))
+        })
+    }
+}
+
+impl<I: Iterator<Item = f64>> RoundedIterator<f64> for I {
+    fn rounded_sum(mut self, rounding: Rounding) -> (f64, Status) {
+        let flags = Flags::new().with_rounding(rounding);
+        let Some(mut sum) = self.next() else {
+            return (0.0, Status::empty());
+        };
+        let mut status = Status::empty();
+        let mut chunk = [0.0; CHUNK];
+        loop {
+            let len = chunk
+                .iter_mut()
+                .zip(&mut self)
+                .map(|(slot, value)| *slot = value)
+                .count();
+            if len == 0 {
+                return (sum, status);
+            }
+            let (partial, chunk_status) = slice::imp::f64_sum(flags, sum, &chunk[..len]);
+            sum = partial;
+            status = status.or(chunk_status);
+        }
+    }
+
+    fn rounded_product(mut self, rounding: Rounding) -> (f64, Status) {
+        let flags = Flags::new().with_rounding(rounding);
+        let Some(first) = self.next() else {
+            return (1.0, Status::empty());
+        };
+        self.fold((first, Status::empty()), |(product, status), value| {
+            let (product, value_status) = crate::f64::mul(flags, product, value);
+            (product, status.or(value_status))
+        })
+    }
+}
@@ -1,267 +1,142 @@
 #![no_std]
+#![cfg_attr(target_arch = "mips64", feature(asm_experimental_arch))]
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
 
-#[cfg(target_arch = "x86_64")]
-mod x86_imp {
-    use core::arch::{asm, x86_64};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
-    #[repr(u32)]
-    pub enum Rounding {
-        /// Rounds towards zero.
-        Zero = x86_64::_MM_ROUND_TOWARD_ZERO,
-        /// Rounds towards positive infinity.
-        Up = x86_64::_MM_ROUND_UP,
-        /// Rounds towards negative infinity.
-        Down = x86_64::_MM_ROUND_DOWN,
-        /// Rounds towards nearest.
-        Nearest = x86_64::_MM_ROUND_NEAREST,
-    }
-
-    /// The flags set for the operation.
-    #[derive(Clone, Copy)]
-    pub struct Flags {
-        inner: u32,
-    }
-
-    impl Default for Flags {
-        #[inline]
-        fn default() -> Self {
-            Self::new()
-        }
-    }
-
-    impl Flags {
-        #[inline]
-        pub fn new() -> Self {
-            Self {
-                inner: x86_64::_MM_MASK_MASK,
-            }
-        }
-
-        #[inline]
-        pub fn with_rounding(mut self, rounding: Rounding) -> Self {
-            self.set_rounding(rounding);
-            self
-        }
-
-        #[inline]
-        pub fn with_ftz(mut self, enabled: bool) -> Self {
-            self.set_ftz(enabled);
-            self
-        }
-
-        #[inline]
-        pub fn set_rounding(&mut self, rounding: Rounding) {
-            self.inner = (self.inner & !x86_64::_MM_ROUND_MASK) | rounding as u32;
-        }
-
-        #[inline]
-        pub fn rounding(self) -> Rounding {
-            match self.inner & x86_64::_MM_ROUND_MASK {
-                b if b == Rounding::Zero as u32 => Rounding::Zero,
-                b if b == Rounding::Up as u32 => Rounding::Up,
-                b if b == Rounding::Down as u32 => Rounding::Down,
-                _ => Rounding::Nearest,
-            }
-        }
-
-        #[inline]
-        pub fn set_ftz(&mut self, enabled: bool) {
-            self.inner = (self.inner & !x86_64::_MM_FLUSH_ZERO_MASK)
-                | if enabled {
-                    x86_64::_MM_FLUSH_ZERO_ON
-                } else {
-                    x86_64::_MM_FLUSH_ZERO_OFF
-                }
-        }
-
-        #[inline]
-        pub fn ftz(self) -> bool {
-            self.inner & x86_64::_MM_FLUSH_ZERO_MASK != 0
-        }
-    }
-
-    /// The status from the operations.
-    #[derive(Clone, Copy)]
-    pub struct Status {
-        inner: u32,
-    }
-
-    impl Status {
-        pub const OVERFLOW: Self = Self {
-            inner: x86_64::_MM_EXCEPT_OVERFLOW,
-        };
-        pub const UNDERFLOW: Self = Self {
-            inner: x86_64::_MM_EXCEPT_UNDERFLOW,
-        };
-        pub const INEXACT: Self = Self {
-            inner: x86_64::_MM_EXCEPT_INEXACT,
-        };
-        pub const DENORM: Self = Self {
-            inner: x86_64::_MM_EXCEPT_DENORM,
-        };
-        pub const DIV_ZERO: Self = Self {
-            inner: x86_64::_MM_EXCEPT_DIV_ZERO,
-        };
-
-        #[inline]
-        pub fn empty() -> Self {
-            Self { inner: 0 }
-        }
-
-        #[inline]
-        pub fn has_exceptions(self) -> bool {
-            self.inner & x86_64::_MM_EXCEPT_MASK != 0
-        }
-
-        #[inline]
-        pub fn overflow(self) -> bool {
-            self.has(Self::OVERFLOW)
-        }
-
-        #[inline]
-        pub fn underflow(self) -> bool {
-            self.has(Self::UNDERFLOW)
-        }
-
-        #[inline]
-        pub fn inexact(self) -> bool {
-            self.has(Self::INEXACT)
-        }
-
-        #[inline]
-        pub fn denorm(self) -> bool {
-            self.has(Self::DENORM)
-        }
-
-        #[inline]
-        pub fn div_zero(self) -> bool {
-            self.has(Self::DIV_ZERO)
-        }
-
-        #[inline]
-        pub fn has(self, status: Self) -> bool {
-            self.inner & status.inner == status.inner
-        }
-
-        #[inline]
-        pub fn or(self, other: Self) -> Self {
-            Self {
-                inner: self.inner | other.inner,
-            }
-        }
-
-        #[inline]
-        pub fn and(self, other: Self) -> Self {
-            Self {
-                inner: self.inner & other.inner,
-            }
-        }
-    }
-
-    macro_rules! host_op {
-        ($flags:ident; $asm:literal; $($end:tt)* ) => {
-            unsafe {
-                let mut status = 0;
-                asm!(
-                    "ldmxcsr [{flags:r}]",
-                    $asm,
-                    "stmxcsr [{status:r}]",
-                    flags = in(reg) &$flags.inner as *const _,
-                    status = in(reg) &mut status as *mut _,
-                    $($end)*
-                );
-                status
-            }
-        };
-    }
-
-    pub mod f32 {
-        // TODO: not yet implemented
-    }
-
-    pub mod f64 {
-        use super::*;
-
-        #[inline]
-        pub fn add(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
-            let status = host_op!(
-                flags;
-                "addsd {l}, {r}";
-                l = inout(xmm_reg) l,
-                r = in(xmm_reg) r
-            );
-            (l, Status { inner: status })
-        }
-
-        #[inline]
-        pub fn sub(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
-            let status = host_op!(
-                flags;
-                "subsd {l}, {r}";
-                l = inout(xmm_reg) l,
-                r = in(xmm_reg) r
-            );
-            (l, Status { inner: status })
-        }
-
-        #[inline]
-        pub fn mul(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
-            let status = host_op!(
-                flags;
-                "mulsd {l}, {r}";
-                l = inout(xmm_reg) l,
-                r = in(xmm_reg) r,
-            );
-            (l, Status { inner: status })
-        }
-
-        #[inline]
-        pub fn div(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
-            let status = host_op!(
-                flags;
-                "divsd {l}, {r}";
-                l = inout(xmm_reg) l,
-                r = in(xmm_reg) r,
-            );
-            (l, Status { inner: status })
-        }
-
-        #[inline]
-        pub fn madd(flags: Flags, mut a: f64, b: f64, c: f64) -> (f64, Status) {
-            let status = host_op!(
-                flags;
-                "vfmadd213sd {a}, {b}, {c}";
-                a = inout(xmm_reg) a,
-                b = in(xmm_reg) b,
-                c = in(xmm_reg) c,
-            );
-            (a, Status { inner: status })
-        }
-
-        #[inline]
-        pub fn to_single(flags: Flags, mut double: f64) -> (f32, Status) {
-            let status = host_op!(
-                flags;
-                "cvtsd2ss {fp}, {fp}";
-                fp = inout(xmm_reg) double,
-            );
-            (
-                f32::from_bits(double.to_bits() as u32),
-                Status { inner: status },
-            )
-        }
-    }
-}
+#[allow(dead_code)]
+mod soft;
 
 cfg_if::cfg_if!(
-    if #[cfg(target_arch = "x86_64")] {
+    // Miri can't run inline assembly, so it gets the software backend too.
+    if #[cfg(any(feature = "soft-float", miri))] {
+        mod soft_imp;
+        use soft_imp as imp;
+    } else if #[cfg(all(
+        feature = "x87",
+        any(target_arch = "x86_64", target_arch = "x86")
+    ))] {
+        mod x87_imp;
+        use x87_imp as imp;
+    } else if #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "x86"),
+        // Soft-float targets, such as kernels, mustn't touch the SSE state.
+        target_feature = "sse2"
+    ))] {
+        mod x86_imp;
         use x86_imp as imp;
+    } else if #[cfg(all(target_arch = "arm", target_abi = "eabihf"))] {
+        mod arm_imp;
+        use arm_imp as imp;
+    } else if #[cfg(any(
+        // Stable compilers don't expose the float extensions to `cfg`, so
+        // hosted targets, which include them, are assumed to have them.
+        all(
+            target_arch = "riscv64",
+            any(target_feature = "d", not(target_os = "none"))
+        ),
+        all(
+            target_arch = "riscv32",
+            any(target_feature = "d", target_os = "linux")
+        ),
+    ))] {
+        mod riscv_imp;
+        use riscv_imp as imp;
+    } else if #[cfg(all(target_arch = "loongarch64", target_feature = "d"))] {
+        mod loongarch_imp;
+        use loongarch_imp as imp;
+    } else if #[cfg(target_arch = "powerpc64")] {
+        mod powerpc_imp;
+        use powerpc_imp as imp;
+    } else if #[cfg(target_arch = "s390x")] {
+        mod s390x_imp;
+        use s390x_imp as imp;
+    } else if #[cfg(all(target_arch = "mips64", not(target_feature = "soft-float")))] {
+        mod mips64_imp;
+        use mips64_imp as imp;
     } else {
-        mod empty {}
-
-        static_assertions::const_assert!(false, "unsupported architecture for sysfp");
-        use empty as imp;
+        // Architectures without a supported FPU, such as wasm32, perform
+        // every operation in software.
+        mod soft_imp;
+        use soft_imp as imp;
     }
 );
 
+pub mod avx512;
+mod bits;
+mod bounds;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod checked;
+#[cfg(feature = "fma")]
+pub mod complex;
+pub mod consts;
+#[cfg(feature = "fma")]
+pub mod dd;
+pub mod decimal64;
+pub mod direction;
+mod dispatch;
+#[cfg(feature = "f128")]
+pub mod f128;
+pub mod f80;
+mod features;
+pub mod fixed;
+mod fixup;
+mod fpu;
+mod functions;
+#[cfg(feature = "guest")]
+pub mod guest;
+#[cfg(feature = "convert")]
+pub mod half;
+pub mod interval;
+#[cfg(feature = "batch")]
+mod iter;
+#[cfg(feature = "math")]
+pub mod math;
+pub mod nan;
+#[cfg(feature = "num-traits")]
+mod num;
+#[cfg(feature = "outline")]
+mod outline;
+#[cfg(feature = "batch")]
+pub mod packed;
+#[cfg(feature = "posit")]
+pub mod posit;
+pub mod preset;
+#[cfg(feature = "batch")]
+mod program;
+pub mod ps2;
+pub mod quiet;
+pub mod rounded;
+mod sign;
+#[cfg(feature = "batch")]
+mod slice;
+mod sticky;
+#[cfg(feature = "stochastic")]
+pub mod stochastic;
+pub mod table;
+mod transcendental;
+mod trap;
+pub mod trapped;
+pub mod x87;
+
+pub use direction::Direction;
+pub use dispatch::{dispatch, FpOpKind, FpWidth};
+pub use features::{features, Features};
+pub use fixup::{f16, f32, f64, Tininess};
+pub use fpu::{GuestFpu, Isa};
+#[cfg(feature = "convert")]
+pub use half::HalfFormat;
 pub use imp::*;
+#[cfg(feature = "batch")]
+pub use iter::RoundedIterator;
+pub use nan::NanRule;
+pub use preset::Preset;
+#[cfg(feature = "batch")]
+pub use program::{Program, MAX_STEPS};
+#[cfg(feature = "batch")]
+pub use slice::NanPolicy;
+pub use trap::{dispatch_trapping, TrapInfo};
@@ -0,0 +1,79 @@
+//! Correctly rounded elementary functions, rounded under the flags and
+//! raising exceptions as the arithmetic operations do, for offering the
+//! behavior of a C library under `fenv.h` on any backend.
+//!
+//! The functions evaluate in software with a 128-bit significand, to within
+//! 2^-122 of the exact results, finer than the hardest cases to round in
+//! single and double precision need, reducing trigonometric arguments of
+//! any magnitude by enough bits of `2 / π`. Every result is inexact, but
+//! for the exponential, cosine, sine, tangent and arctangent of a zero and
+//! the logarithm of one, and results in the subnormal range also raise
+//! underflow.
+
+use crate::soft::{self, Format};
+use crate::transcendental::{elementary, Function, Trig};
+use crate::{Flags, Status};
+
+#[inline]
+fn evaluate<F: Format>(flags: Flags, function: Function, bits: u64) -> (u64, Status) {
+    let (res, exc) = elementary::<F>(&flags.soft_env(), function, bits);
+    (res, Status::from_soft(exc))
+}
+
+macro_rules! functions {
+    ($float:ident, $format:ty) => {
+        pub mod $float {
+            use super::*;
+
+            #[inline]
+            fn evaluate(flags: Flags, function: Function, x: $float) -> ($float, Status) {
+                let (bits, status) =
+                    super::evaluate::<$format>(flags, function, x.to_bits() as u64);
+                ($float::from_bits(bits as _), status)
+            }
+
+            /// Computes `e^x`.
+            #[inline]
+            pub fn exp(flags: Flags, x: $float) -> ($float, Status) {
+                evaluate(flags, Function::Exp, x)
+            }
+
+            /// Computes the natural logarithm of `x`, raising divide-by-zero
+            /// for zeros and invalid below zero.
+            #[inline]
+            pub fn log(flags: Flags, x: $float) -> ($float, Status) {
+                evaluate(flags, Function::Log, x)
+            }
+
+            /// Computes the sine of `x` in radians, raising invalid for
+            /// infinities.
+            #[inline]
+            pub fn sin(flags: Flags, x: $float) -> ($float, Status) {
+                evaluate(flags, Function::Trig(Trig::Sin), x)
+            }
+
+            /// Computes the cosine of `x` in radians, raising invalid for
+            /// infinities.
+            #[inline]
+            pub fn cos(flags: Flags, x: $float) -> ($float, Status) {
+                evaluate(flags, Function::Trig(Trig::Cos), x)
+            }
+
+            /// Computes the tangent of `x` in radians, raising invalid for
+            /// infinities.
+            #[inline]
+            pub fn tan(flags: Flags, x: $float) -> ($float, Status) {
+                evaluate(flags, Function::Trig(Trig::Tan), x)
+            }
+
+            /// Computes the arctangent of `x`, between `-π / 2` and `π / 2`.
+            #[inline]
+            pub fn atan(flags: Flags, x: $float) -> ($float, Status) {
+                evaluate(flags, Function::Atan, x)
+            }
+        }
+    };
+}
+
+functions!(f32, soft::Binary32);
+functions!(f64, soft::Binary64);
@@ -0,0 +1,225 @@
+//! Operations choosing their NaN results by the rule of an architecture
+//! other than the host's, as emulating its guests needs.
+//!
+//! The operations run as usual and only those returning a NaN run again in
+//! software under the selected rule, so other results cost one check.
+
+use crate::{soft, Flags, Status};
+
+/// How the NaN result of an operation is chosen.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NanRule {
+    /// The first NaN operand is quieted and returned, and invalid operations
+    /// return the negative default NaN, as on x86.
+    FirstOperand,
+    /// The first signaling NaN operand is quieted and returned, else the
+    /// first quiet one, and `madd` considers the addend first, as on ARM.
+    SignalingFirst,
+    /// The positive default NaN is returned regardless of the operands, as
+    /// on RISC-V.
+    Canonical,
+}
+
+impl NanRule {
+    /// The environment running an operation under the rule.
+    #[inline]
+    fn env(self, flags: Flags) -> soft::Env {
+        let mut env = flags.soft_env();
+        let (nan, default_nan_negative, fma_nan_order, fma_inf_zero_nan_invalid) = match self {
+            Self::FirstOperand => (soft::NanRule::FirstOperand, true, [0, 1, 2], false),
+            Self::SignalingFirst => (soft::NanRule::SignalingFirst, false, [2, 0, 1], true),
+            Self::Canonical => (soft::NanRule::Canonical, false, [0, 1, 2], true),
+        };
+        env.sem.nan = nan;
+        env.sem.default_nan_negative = default_nan_negative;
+        env.sem.fma_nan_order = fma_nan_order;
+        env.sem.fma_inf_zero_nan_invalid = fma_inf_zero_nan_invalid;
+        env
+    }
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, rule: NanRule, l: f32, r: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::add(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) =
+            soft::add::<soft::Binary32>(&rule.env(flags), l.to_bits() as u64, r.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, rule: NanRule, l: f32, r: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::sub(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) =
+            soft::sub::<soft::Binary32>(&rule.env(flags), l.to_bits() as u64, r.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, rule: NanRule, l: f32, r: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::mul(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) =
+            soft::mul::<soft::Binary32>(&rule.env(flags), l.to_bits() as u64, r.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, rule: NanRule, l: f32, r: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::div(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) =
+            soft::div::<soft::Binary32>(&rule.env(flags), l.to_bits() as u64, r.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, rule: NanRule, a: f32, b: f32, c: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::madd(flags, a, b, c);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::fma::<soft::Binary32>(
+            &rule.env(flags),
+            a.to_bits() as u64,
+            b.to_bits() as u64,
+            c.to_bits() as u64,
+        );
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, rule: NanRule, x: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::round(flags, x);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::round_int::<soft::Binary32>(&rule.env(flags), x.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, rule: NanRule, x: f32) -> (f32, Status) {
+        let (value, status) = crate::f32::sqrt(flags, x);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::sqrt::<soft::Binary32>(&rule.env(flags), x.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, rule: NanRule, single: f32) -> (f64, Status) {
+        let (value, status) = crate::f32::to_double(flags, single);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::convert::<soft::Binary32, soft::Binary64>(
+            &rule.env(flags),
+            single.to_bits() as u64,
+        );
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, rule: NanRule, l: f64, r: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::add(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::add::<soft::Binary64>(&rule.env(flags), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, rule: NanRule, l: f64, r: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::sub(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::sub::<soft::Binary64>(&rule.env(flags), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, rule: NanRule, l: f64, r: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::mul(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::mul::<soft::Binary64>(&rule.env(flags), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, rule: NanRule, l: f64, r: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::div(flags, l, r);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::div::<soft::Binary64>(&rule.env(flags), l.to_bits(), r.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, rule: NanRule, a: f64, b: f64, c: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::madd(flags, a, b, c);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) =
+            soft::fma::<soft::Binary64>(&rule.env(flags), a.to_bits(), b.to_bits(), c.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, rule: NanRule, x: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::round(flags, x);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::round_int::<soft::Binary64>(&rule.env(flags), x.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, rule: NanRule, x: f64) -> (f64, Status) {
+        let (value, status) = crate::f64::sqrt(flags, x);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) = soft::sqrt::<soft::Binary64>(&rule.env(flags), x.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, rule: NanRule, double: f64) -> (f32, Status) {
+        let (value, status) = crate::f64::to_single(flags, double);
+        if !value.is_nan() {
+            return (value, status);
+        }
+        let (bits, exc) =
+            soft::convert::<soft::Binary64, soft::Binary32>(&rule.env(flags), double.to_bits());
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+}
@@ -0,0 +1,225 @@
+//! The num-traits traits for the `Rounded` wrappers, so they slot into
+//! generic numeric code. Operations the traits provide in terms of others
+//! round by the wrapper's mode and accumulate its status as those do.
+
+use core::num::FpCategory;
+
+use num_traits::float::FloatCore;
+use num_traits::{
+    Bounded, FromPrimitive, Inv, Num, NumCast, One, ParseFloatError, Signed, ToPrimitive, Zero,
+};
+
+use crate::rounded::{Rounded, RoundingMode};
+
+impl<R: RoundingMode> Zero for Rounded<R> {
+    #[inline]
+    fn zero() -> Self {
+        Self::new(0.0)
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.value() == 0.0
+    }
+}
+
+impl<R: RoundingMode> One for Rounded<R> {
+    #[inline]
+    fn one() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Parses as `f64` does, rounding to nearest whatever the mode.
+impl<R: RoundingMode> Num for Rounded<R> {
+    type FromStrRadixErr = ParseFloatError;
+
+    #[inline]
+    fn from_str_radix(str: &str, radix: u32) -> Result<Self, ParseFloatError> {
+        f64::from_str_radix(str, radix).map(Self::new)
+    }
+}
+
+impl<R: RoundingMode> Bounded for Rounded<R> {
+    #[inline]
+    fn min_value() -> Self {
+        Self::new(f64::MIN)
+    }
+
+    #[inline]
+    fn max_value() -> Self {
+        Self::new(f64::MAX)
+    }
+}
+
+impl<R: RoundingMode> Signed for Rounded<R> {
+    #[inline]
+    fn abs(&self) -> Self {
+        if self.value().is_sign_negative() {
+            -*self
+        } else {
+            *self
+        }
+    }
+
+    #[inline]
+    fn abs_sub(&self, other: &Self) -> Self {
+        if *self <= *other {
+            Self::zero()
+        } else {
+            *self - *other
+        }
+    }
+
+    #[inline]
+    fn signum(&self) -> Self {
+        Self::new(Signed::signum(&self.value()))
+    }
+
+    #[inline]
+    fn is_positive(&self) -> bool {
+        Signed::is_positive(&self.value())
+    }
+
+    #[inline]
+    fn is_negative(&self) -> bool {
+        Signed::is_negative(&self.value())
+    }
+}
+
+impl<R: RoundingMode> Inv for Rounded<R> {
+    type Output = Self;
+
+    #[inline]
+    fn inv(self) -> Self {
+        Self::one() / self
+    }
+}
+
+#[cfg(feature = "fma")]
+impl<R: RoundingMode> num_traits::MulAdd for Rounded<R> {
+    type Output = Self;
+
+    #[inline]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        Rounded::mul_add(self, b, c)
+    }
+}
+
+impl<R: RoundingMode> ToPrimitive for Rounded<R> {
+    #[inline]
+    fn to_i64(&self) -> Option<i64> {
+        self.value().to_i64()
+    }
+
+    #[inline]
+    fn to_u64(&self) -> Option<u64> {
+        self.value().to_u64()
+    }
+
+    #[inline]
+    fn to_i128(&self) -> Option<i128> {
+        self.value().to_i128()
+    }
+
+    #[inline]
+    fn to_u128(&self) -> Option<u128> {
+        self.value().to_u128()
+    }
+
+    #[inline]
+    fn to_f64(&self) -> Option<f64> {
+        Some(self.value())
+    }
+}
+
+/// Converts integers rounding by the mode, raising inexact if they don't
+/// fit.
+impl<R: RoundingMode> FromPrimitive for Rounded<R> {
+    #[inline]
+    fn from_i64(int: i64) -> Option<Self> {
+        // Both halves convert exactly, so only the sum rounds.
+        let high = Self::new((int >> 32) as f64 * 4_294_967_296.0);
+        Some(high + Self::new(int as u32 as f64))
+    }
+
+    #[inline]
+    fn from_u64(int: u64) -> Option<Self> {
+        let high = Self::new((int >> 32) as f64 * 4_294_967_296.0);
+        Some(high + Self::new(int as u32 as f64))
+    }
+
+    #[inline]
+    fn from_f64(double: f64) -> Option<Self> {
+        Some(Self::new(double))
+    }
+}
+
+impl<R: RoundingMode> NumCast for Rounded<R> {
+    #[inline]
+    fn from<T: ToPrimitive>(n: T) -> Option<Self> {
+        n.to_f64().map(Self::new)
+    }
+}
+
+impl<R: RoundingMode> FloatCore for Rounded<R> {
+    #[inline]
+    fn infinity() -> Self {
+        Self::new(f64::INFINITY)
+    }
+
+    #[inline]
+    fn neg_infinity() -> Self {
+        Self::new(f64::NEG_INFINITY)
+    }
+
+    #[inline]
+    fn nan() -> Self {
+        Self::new(f64::NAN)
+    }
+
+    #[inline]
+    fn neg_zero() -> Self {
+        Self::new(-0.0)
+    }
+
+    #[inline]
+    fn min_value() -> Self {
+        Self::new(f64::MIN)
+    }
+
+    #[inline]
+    fn min_positive_value() -> Self {
+        Self::new(f64::MIN_POSITIVE)
+    }
+
+    #[inline]
+    fn epsilon() -> Self {
+        Self::new(f64::EPSILON)
+    }
+
+    #[inline]
+    fn max_value() -> Self {
+        Self::new(f64::MAX)
+    }
+
+    #[inline]
+    fn classify(self) -> FpCategory {
+        self.value().classify()
+    }
+
+    #[inline]
+    fn to_degrees(self) -> Self {
+        self * Self::new(180.0 / core::f64::consts::PI)
+    }
+
+    #[inline]
+    fn to_radians(self) -> Self {
+        self * Self::new(core::f64::consts::PI / 180.0)
+    }
+
+    #[inline]
+    fn integer_decode(self) -> (u64, i16, i8) {
+        FloatCore::integer_decode(self.value())
+    }
+}
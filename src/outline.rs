@@ -0,0 +1,473 @@
+//! Out-of-line variants of the scalar operations, for the `outline` feature.
+//!
+//! Each operation becomes an `extern "C"` function that is never inlined,
+//! behind an inlined wrapper keeping the usual signature, so callers pay a
+//! call instead of carrying the environment setup at every site.
+//!
+//! The functions pass values as their bit patterns, as the C calling
+//! convention returns floats on the x87 stack on some targets, which quiets
+//! signaling NaNs.
+
+use crate::{Flags, Status};
+
+pub mod f32 {
+    use super::*;
+
+    pub use crate::imp::f32::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::add(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::sub(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::mul(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::div(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f32, b: f32, c: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::madd(&flags, a.to_bits(), b.to_bits(), c.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::round(&flags, x.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f32) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::sqrt(&flags, x.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, single: f32) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::to_double(&flags, single.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::to_half(&flags, single.to_bits(), &mut status);
+        (bits, status)
+    }
+
+    mod ffi {
+        use super::*;
+
+        #[inline(never)]
+        pub(super) extern "C" fn add(flags: &Flags, l: u32, r: u32, status: &mut Status) -> u32 {
+            let (value, op_status) =
+                crate::imp::f32::add(*flags, f32::from_bits(l), f32::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn sub(flags: &Flags, l: u32, r: u32, status: &mut Status) -> u32 {
+            let (value, op_status) =
+                crate::imp::f32::sub(*flags, f32::from_bits(l), f32::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn mul(flags: &Flags, l: u32, r: u32, status: &mut Status) -> u32 {
+            let (value, op_status) =
+                crate::imp::f32::mul(*flags, f32::from_bits(l), f32::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn div(flags: &Flags, l: u32, r: u32, status: &mut Status) -> u32 {
+            let (value, op_status) =
+                crate::imp::f32::div(*flags, f32::from_bits(l), f32::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline(never)]
+        pub(super) extern "C" fn madd(
+            flags: &Flags,
+            a: u32,
+            b: u32,
+            c: u32,
+            status: &mut Status,
+        ) -> u32 {
+            let (value, op_status) = crate::imp::f32::madd(
+                *flags,
+                f32::from_bits(a),
+                f32::from_bits(b),
+                f32::from_bits(c),
+            );
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn round(flags: &Flags, x: u32, status: &mut Status) -> u32 {
+            let (value, op_status) = crate::imp::f32::round(*flags, f32::from_bits(x));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn sqrt(flags: &Flags, x: u32, status: &mut Status) -> u32 {
+            let (value, op_status) = crate::imp::f32::sqrt(*flags, f32::from_bits(x));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline(never)]
+        pub(super) extern "C" fn to_double(flags: &Flags, single: u32, status: &mut Status) -> u64 {
+            let (value, op_status) = crate::imp::f32::to_double(*flags, f32::from_bits(single));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline(never)]
+        pub(super) extern "C" fn to_half(flags: &Flags, single: u32, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f32::to_half(*flags, f32::from_bits(single));
+            *status = op_status;
+            value
+        }
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    pub use crate::imp::f64::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::add(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::sub(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::mul(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::div(&flags, l.to_bits(), r.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f64, b: f64, c: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::madd(&flags, a.to_bits(), b.to_bits(), c.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::round(&flags, x.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: f64) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::sqrt(&flags, x.to_bits(), &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, double: f64) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::to_single(&flags, double.to_bits(), &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::to_half(&flags, double.to_bits(), &mut status);
+        (bits, status)
+    }
+
+    mod ffi {
+        use super::*;
+
+        #[inline(never)]
+        pub(super) extern "C" fn add(flags: &Flags, l: u64, r: u64, status: &mut Status) -> u64 {
+            let (value, op_status) =
+                crate::imp::f64::add(*flags, f64::from_bits(l), f64::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn sub(flags: &Flags, l: u64, r: u64, status: &mut Status) -> u64 {
+            let (value, op_status) =
+                crate::imp::f64::sub(*flags, f64::from_bits(l), f64::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn mul(flags: &Flags, l: u64, r: u64, status: &mut Status) -> u64 {
+            let (value, op_status) =
+                crate::imp::f64::mul(*flags, f64::from_bits(l), f64::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn div(flags: &Flags, l: u64, r: u64, status: &mut Status) -> u64 {
+            let (value, op_status) =
+                crate::imp::f64::div(*flags, f64::from_bits(l), f64::from_bits(r));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline(never)]
+        pub(super) extern "C" fn madd(
+            flags: &Flags,
+            a: u64,
+            b: u64,
+            c: u64,
+            status: &mut Status,
+        ) -> u64 {
+            let (value, op_status) = crate::imp::f64::madd(
+                *flags,
+                f64::from_bits(a),
+                f64::from_bits(b),
+                f64::from_bits(c),
+            );
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn round(flags: &Flags, x: u64, status: &mut Status) -> u64 {
+            let (value, op_status) = crate::imp::f64::round(*flags, f64::from_bits(x));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn sqrt(flags: &Flags, x: u64, status: &mut Status) -> u64 {
+            let (value, op_status) = crate::imp::f64::sqrt(*flags, f64::from_bits(x));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline(never)]
+        pub(super) extern "C" fn to_single(flags: &Flags, double: u64, status: &mut Status) -> u32 {
+            let (value, op_status) = crate::imp::f64::to_single(*flags, f64::from_bits(double));
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline(never)]
+        pub(super) extern "C" fn to_half(flags: &Flags, double: u64, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f64::to_half(*flags, f64::from_bits(double));
+            *status = op_status;
+            value
+        }
+    }
+}
+
+pub mod f16 {
+    use super::*;
+
+    pub use crate::imp::f16::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::add(&flags, l, r, &mut status);
+        (bits, status)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::sub(&flags, l, r, &mut status);
+        (bits, status)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::mul(&flags, l, r, &mut status);
+        (bits, status)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::div(&flags, l, r, &mut status);
+        (bits, status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: u16, b: u16, c: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::madd(&flags, a, b, c, &mut status);
+        (bits, status)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::round(&flags, x, &mut status);
+        (bits, status)
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: u16) -> (u16, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::sqrt(&flags, x, &mut status);
+        (bits, status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, half: u16) -> (f32, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::to_single(&flags, half, &mut status);
+        (f32::from_bits(bits), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, half: u16) -> (f64, Status) {
+        let mut status = Status::empty();
+        let bits = ffi::to_double(&flags, half, &mut status);
+        (f64::from_bits(bits), status)
+    }
+
+    mod ffi {
+        use super::*;
+
+        #[inline(never)]
+        pub(super) extern "C" fn add(flags: &Flags, l: u16, r: u16, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f16::add(*flags, l, r);
+            *status = op_status;
+            value
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn sub(flags: &Flags, l: u16, r: u16, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f16::sub(*flags, l, r);
+            *status = op_status;
+            value
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn mul(flags: &Flags, l: u16, r: u16, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f16::mul(*flags, l, r);
+            *status = op_status;
+            value
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn div(flags: &Flags, l: u16, r: u16, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f16::div(*flags, l, r);
+            *status = op_status;
+            value
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline(never)]
+        pub(super) extern "C" fn madd(
+            flags: &Flags,
+            a: u16,
+            b: u16,
+            c: u16,
+            status: &mut Status,
+        ) -> u16 {
+            let (value, op_status) = crate::imp::f16::madd(*flags, a, b, c);
+            *status = op_status;
+            value
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn round(flags: &Flags, x: u16, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f16::round(*flags, x);
+            *status = op_status;
+            value
+        }
+
+        #[inline(never)]
+        pub(super) extern "C" fn sqrt(flags: &Flags, x: u16, status: &mut Status) -> u16 {
+            let (value, op_status) = crate::imp::f16::sqrt(*flags, x);
+            *status = op_status;
+            value
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline(never)]
+        pub(super) extern "C" fn to_single(flags: &Flags, half: u16, status: &mut Status) -> u32 {
+            let (value, op_status) = crate::imp::f16::to_single(*flags, half);
+            *status = op_status;
+            value.to_bits()
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline(never)]
+        pub(super) extern "C" fn to_double(flags: &Flags, half: u16, status: &mut Status) -> u64 {
+            let (value, op_status) = crate::imp::f16::to_double(*flags, half);
+            *status = op_status;
+            value.to_bits()
+        }
+    }
+}
@@ -0,0 +1,1013 @@
+//! Packed operations on 128-bit vectors, which set up the environment once
+//! for every lane and combine the status of the lanes.
+//!
+//! These run on the packed SSE instructions on x86, and lane by lane
+//! elsewhere.
+//!
+//! The `_per_lane` variants report the status of every lane instead, which
+//! costs another pass lane by lane only when some lane raised an exception.
+//!
+//! The `_masked` variants only compute the lanes whose bit is set in `mask`,
+//! taking the others from `src` without raising anything for them, so a zero
+//! `src` zeroes them. These use AVX-512 masking where it's enabled.
+//!
+//! With the `portable-simd` feature, `simd` offers them on `core::simd`
+//! vectors and masks too.
+
+use crate::{Flags, Status};
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "x86"),
+        target_feature = "sse2",
+        not(any(feature = "soft-float", feature = "x87", miri))
+    ))] {
+        use crate::imp::packed as imp;
+    } else {
+        use lanes as imp;
+    }
+}
+
+/// Runs `op` on every lane, combining the status of the lanes.
+#[inline]
+pub(crate) fn each_lane<T, const N: usize>(
+    mut op: impl FnMut(usize) -> (T, Status),
+) -> ([T; N], Status) {
+    let mut status = Status::empty();
+    let values = core::array::from_fn(|i| {
+        let (value, lane) = op(i);
+        status = status.or(lane);
+        value
+    });
+    (values, status)
+}
+
+/// Reruns `op` lane by lane to tell the lanes' status apart, unless the
+/// `packed` operation raised no exceptions at all.
+#[inline]
+fn per_lane<T, const N: usize>(
+    packed: ([T; N], Status),
+    mut op: impl FnMut(usize) -> (T, Status),
+) -> ([T; N], [Status; N]) {
+    let (values, status) = packed;
+    if !status.has_exceptions() {
+        return (values, [status; N]);
+    }
+    let mut statuses = [Status::empty(); N];
+    let values = core::array::from_fn(|i| {
+        let (value, lane) = op(i);
+        statuses[i] = lane;
+        value
+    });
+    (values, statuses)
+}
+
+/// The operations run lane by lane on the scalar operations.
+#[allow(dead_code)]
+pub(crate) mod lanes {
+    use super::*;
+
+    #[inline]
+    pub(crate) fn f32x4_add(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        each_lane(|i| crate::f32::add(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_sub(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        each_lane(|i| crate::f32::sub(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_mul(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        each_lane(|i| crate::f32::mul(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_div(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        each_lane(|i| crate::f32::div(flags, l[i], r[i]))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32x4_madd(
+        flags: Flags,
+        a: [f32; 4],
+        b: [f32; 4],
+        c: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| crate::f32::madd(flags, a[i], b[i], c[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_round(flags: Flags, x: [f32; 4]) -> ([f32; 4], Status) {
+        each_lane(|i| crate::f32::round(flags, x[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_add(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        each_lane(|i| crate::f64::add(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_sub(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        each_lane(|i| crate::f64::sub(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_mul(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        each_lane(|i| crate::f64::mul(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_div(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        each_lane(|i| crate::f64::div(flags, l[i], r[i]))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64x2_madd(
+        flags: Flags,
+        a: [f64; 2],
+        b: [f64; 2],
+        c: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| crate::f64::madd(flags, a[i], b[i], c[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_round(flags: Flags, x: [f64; 2]) -> ([f64; 2], Status) {
+        each_lane(|i| crate::f64::round(flags, x[i]))
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_add_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f32::add(flags, l[i], r[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_sub_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f32::sub(flags, l[i], r[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_mul_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f32::mul(flags, l[i], r[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_div_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f32::div(flags, l[i], r[i]),
+        })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32x4_madd_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        a: [f32; 4],
+        b: [f32; 4],
+        c: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f32::madd(flags, a[i], b[i], c[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_round_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        x: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f32::round(flags, x[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_add_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f64::add(flags, l[i], r[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_sub_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f64::sub(flags, l[i], r[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_mul_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f64::mul(flags, l[i], r[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_div_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f64::div(flags, l[i], r[i]),
+        })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64x2_madd_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        a: [f64; 2],
+        b: [f64; 2],
+        c: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f64::madd(flags, a[i], b[i], c[i]),
+        })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_round_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        x: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        each_lane(|i| match mask & 1 << i {
+            0 => (src[i], Status::empty()),
+            _ => crate::f64::round(flags, x[i]),
+        })
+    }
+}
+
+/// Operations on four single-precision lanes.
+pub mod f32x4 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_add(flags, l, r)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_sub(flags, l, r)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_mul(flags, l, r)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_div(flags, l, r)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: [f32; 4], b: [f32; 4], c: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_madd(flags, a, b, c)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_round(flags, x)
+    }
+
+    #[inline]
+    pub fn add_per_lane(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], [Status; 4]) {
+        per_lane(add(flags, l, r), |i| crate::f32::add(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub fn sub_per_lane(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], [Status; 4]) {
+        per_lane(sub(flags, l, r), |i| crate::f32::sub(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub fn mul_per_lane(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], [Status; 4]) {
+        per_lane(mul(flags, l, r), |i| crate::f32::mul(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub fn div_per_lane(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], [Status; 4]) {
+        per_lane(div(flags, l, r), |i| crate::f32::div(flags, l[i], r[i]))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_per_lane(
+        flags: Flags,
+        a: [f32; 4],
+        b: [f32; 4],
+        c: [f32; 4],
+    ) -> ([f32; 4], [Status; 4]) {
+        per_lane(madd(flags, a, b, c), |i| {
+            crate::f32::madd(flags, a[i], b[i], c[i])
+        })
+    }
+
+    #[inline]
+    pub fn round_per_lane(flags: Flags, x: [f32; 4]) -> ([f32; 4], [Status; 4]) {
+        per_lane(round(flags, x), |i| crate::f32::round(flags, x[i]))
+    }
+
+    #[inline]
+    pub fn add_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        imp::f32x4_add_masked(flags, mask, src, l, r)
+    }
+
+    #[inline]
+    pub fn sub_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        imp::f32x4_sub_masked(flags, mask, src, l, r)
+    }
+
+    #[inline]
+    pub fn mul_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        imp::f32x4_mul_masked(flags, mask, src, l, r)
+    }
+
+    #[inline]
+    pub fn div_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        imp::f32x4_div_masked(flags, mask, src, l, r)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        a: [f32; 4],
+        b: [f32; 4],
+        c: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        imp::f32x4_madd_masked(flags, mask, src, a, b, c)
+    }
+
+    #[inline]
+    pub fn round_masked(flags: Flags, mask: u8, src: [f32; 4], x: [f32; 4]) -> ([f32; 4], Status) {
+        imp::f32x4_round_masked(flags, mask, src, x)
+    }
+}
+
+/// Operations on two double-precision lanes.
+pub mod f64x2 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_add(flags, l, r)
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_sub(flags, l, r)
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_mul(flags, l, r)
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_div(flags, l, r)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_madd(flags, a, b, c)
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_round(flags, x)
+    }
+
+    #[inline]
+    pub fn add_per_lane(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], [Status; 2]) {
+        per_lane(add(flags, l, r), |i| crate::f64::add(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub fn sub_per_lane(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], [Status; 2]) {
+        per_lane(sub(flags, l, r), |i| crate::f64::sub(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub fn mul_per_lane(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], [Status; 2]) {
+        per_lane(mul(flags, l, r), |i| crate::f64::mul(flags, l[i], r[i]))
+    }
+
+    #[inline]
+    pub fn div_per_lane(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], [Status; 2]) {
+        per_lane(div(flags, l, r), |i| crate::f64::div(flags, l[i], r[i]))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_per_lane(
+        flags: Flags,
+        a: [f64; 2],
+        b: [f64; 2],
+        c: [f64; 2],
+    ) -> ([f64; 2], [Status; 2]) {
+        per_lane(madd(flags, a, b, c), |i| {
+            crate::f64::madd(flags, a[i], b[i], c[i])
+        })
+    }
+
+    #[inline]
+    pub fn round_per_lane(flags: Flags, x: [f64; 2]) -> ([f64; 2], [Status; 2]) {
+        per_lane(round(flags, x), |i| crate::f64::round(flags, x[i]))
+    }
+
+    #[inline]
+    pub fn add_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        imp::f64x2_add_masked(flags, mask, src, l, r)
+    }
+
+    #[inline]
+    pub fn sub_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        imp::f64x2_sub_masked(flags, mask, src, l, r)
+    }
+
+    #[inline]
+    pub fn mul_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        imp::f64x2_mul_masked(flags, mask, src, l, r)
+    }
+
+    #[inline]
+    pub fn div_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        imp::f64x2_div_masked(flags, mask, src, l, r)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        a: [f64; 2],
+        b: [f64; 2],
+        c: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        imp::f64x2_madd_masked(flags, mask, src, a, b, c)
+    }
+
+    #[inline]
+    pub fn round_masked(flags: Flags, mask: u8, src: [f64; 2], x: [f64; 2]) -> ([f64; 2], Status) {
+        imp::f64x2_round_masked(flags, mask, src, x)
+    }
+}
+
+/// The packed operations on `core::simd` vectors.
+#[cfg(feature = "portable-simd")]
+pub mod simd {
+    use core::simd::{Mask, Simd};
+
+    use crate::{Flags, Status};
+
+    pub mod f32x4 {
+        use super::*;
+        use crate::packed::f32x4 as packed;
+
+        #[inline]
+        pub fn add(flags: Flags, l: Simd<f32, 4>, r: Simd<f32, 4>) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::add(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn sub(flags: Flags, l: Simd<f32, 4>, r: Simd<f32, 4>) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::sub(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn mul(flags: Flags, l: Simd<f32, 4>, r: Simd<f32, 4>) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::mul(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn div(flags: Flags, l: Simd<f32, 4>, r: Simd<f32, 4>) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::div(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(
+            flags: Flags,
+            a: Simd<f32, 4>,
+            b: Simd<f32, 4>,
+            c: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::madd(flags, a.to_array(), b.to_array(), c.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn round(flags: Flags, x: Simd<f32, 4>) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::round(flags, x.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn add_per_lane(
+            flags: Flags,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, [Status; 4]) {
+            let (values, status) = packed::add_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn sub_per_lane(
+            flags: Flags,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, [Status; 4]) {
+            let (values, status) = packed::sub_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn mul_per_lane(
+            flags: Flags,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, [Status; 4]) {
+            let (values, status) = packed::mul_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn div_per_lane(
+            flags: Flags,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, [Status; 4]) {
+            let (values, status) = packed::div_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd_per_lane(
+            flags: Flags,
+            a: Simd<f32, 4>,
+            b: Simd<f32, 4>,
+            c: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, [Status; 4]) {
+            let (values, status) =
+                packed::madd_per_lane(flags, a.to_array(), b.to_array(), c.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn round_per_lane(flags: Flags, x: Simd<f32, 4>) -> (Simd<f32, 4>, [Status; 4]) {
+            let (values, status) = packed::round_per_lane(flags, x.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn add_masked(
+            flags: Flags,
+            mask: Mask<i32, 4>,
+            src: Simd<f32, 4>,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::add_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn sub_masked(
+            flags: Flags,
+            mask: Mask<i32, 4>,
+            src: Simd<f32, 4>,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::sub_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn mul_masked(
+            flags: Flags,
+            mask: Mask<i32, 4>,
+            src: Simd<f32, 4>,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::mul_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn div_masked(
+            flags: Flags,
+            mask: Mask<i32, 4>,
+            src: Simd<f32, 4>,
+            l: Simd<f32, 4>,
+            r: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::div_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd_masked(
+            flags: Flags,
+            mask: Mask<i32, 4>,
+            src: Simd<f32, 4>,
+            a: Simd<f32, 4>,
+            b: Simd<f32, 4>,
+            c: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) = packed::madd_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                a.to_array(),
+                b.to_array(),
+                c.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn round_masked(
+            flags: Flags,
+            mask: Mask<i32, 4>,
+            src: Simd<f32, 4>,
+            x: Simd<f32, 4>,
+        ) -> (Simd<f32, 4>, Status) {
+            let (values, status) =
+                packed::round_masked(flags, mask.to_bitmask() as u8, src.to_array(), x.to_array());
+            (Simd::from_array(values), status)
+        }
+    }
+
+    pub mod f64x2 {
+        use super::*;
+        use crate::packed::f64x2 as packed;
+
+        #[inline]
+        pub fn add(flags: Flags, l: Simd<f64, 2>, r: Simd<f64, 2>) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::add(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn sub(flags: Flags, l: Simd<f64, 2>, r: Simd<f64, 2>) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::sub(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn mul(flags: Flags, l: Simd<f64, 2>, r: Simd<f64, 2>) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::mul(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn div(flags: Flags, l: Simd<f64, 2>, r: Simd<f64, 2>) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::div(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(
+            flags: Flags,
+            a: Simd<f64, 2>,
+            b: Simd<f64, 2>,
+            c: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::madd(flags, a.to_array(), b.to_array(), c.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn round(flags: Flags, x: Simd<f64, 2>) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::round(flags, x.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn add_per_lane(
+            flags: Flags,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, [Status; 2]) {
+            let (values, status) = packed::add_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn sub_per_lane(
+            flags: Flags,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, [Status; 2]) {
+            let (values, status) = packed::sub_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn mul_per_lane(
+            flags: Flags,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, [Status; 2]) {
+            let (values, status) = packed::mul_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn div_per_lane(
+            flags: Flags,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, [Status; 2]) {
+            let (values, status) = packed::div_per_lane(flags, l.to_array(), r.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd_per_lane(
+            flags: Flags,
+            a: Simd<f64, 2>,
+            b: Simd<f64, 2>,
+            c: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, [Status; 2]) {
+            let (values, status) =
+                packed::madd_per_lane(flags, a.to_array(), b.to_array(), c.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn round_per_lane(flags: Flags, x: Simd<f64, 2>) -> (Simd<f64, 2>, [Status; 2]) {
+            let (values, status) = packed::round_per_lane(flags, x.to_array());
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn add_masked(
+            flags: Flags,
+            mask: Mask<i64, 2>,
+            src: Simd<f64, 2>,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::add_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn sub_masked(
+            flags: Flags,
+            mask: Mask<i64, 2>,
+            src: Simd<f64, 2>,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::sub_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn mul_masked(
+            flags: Flags,
+            mask: Mask<i64, 2>,
+            src: Simd<f64, 2>,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::mul_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn div_masked(
+            flags: Flags,
+            mask: Mask<i64, 2>,
+            src: Simd<f64, 2>,
+            l: Simd<f64, 2>,
+            r: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::div_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                l.to_array(),
+                r.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd_masked(
+            flags: Flags,
+            mask: Mask<i64, 2>,
+            src: Simd<f64, 2>,
+            a: Simd<f64, 2>,
+            b: Simd<f64, 2>,
+            c: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) = packed::madd_masked(
+                flags,
+                mask.to_bitmask() as u8,
+                src.to_array(),
+                a.to_array(),
+                b.to_array(),
+                c.to_array(),
+            );
+            (Simd::from_array(values), status)
+        }
+
+        #[inline]
+        pub fn round_masked(
+            flags: Flags,
+            mask: Mask<i64, 2>,
+            src: Simd<f64, 2>,
+            x: Simd<f64, 2>,
+        ) -> (Simd<f64, 2>, Status) {
+            let (values, status) =
+                packed::round_masked(flags, mask.to_bitmask() as u8, src.to_array(), x.to_array());
+            (Simd::from_array(values), status)
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Arithmetic on the 16- and 32-bit posits of the 2022 posit standard, with
+//! two exponent bits, held as their bits.
+//!
+//! Posits have no infinities, a single zero and a single NaR (not a real),
+//! and compare as their bits do as signed integers. Results round to nearest
+//! on the bit pattern, ties to even, and never to zero, NaR or past the
+//! largest posit, so the flags have nothing to apply to and only inexact
+//! results raise `Status::INEXACT`. NaR results of real operands raise
+//! invalid, or divide-by-zero for nonzero numbers divided by zero, and NaR
+//! operands give NaR raising nothing.
+//!
+//! The operations compute exactly and round once. The quire, which the
+//! standard accumulates exact sums of products in, is left out.
+
+use core::cmp::Ordering;
+
+use crate::soft::{self, DIV_ZERO, INEXACT, INVALID};
+use crate::Status;
+
+/// A decoded posit.
+#[derive(Clone, Copy)]
+enum Class {
+    Zero,
+    Nar,
+    /// The value `sig * 2^exp`, with the top bit of `sig` set.
+    Real {
+        sign: bool,
+        exp: i32,
+        sig: u64,
+    },
+}
+
+#[inline]
+fn mask(n: u32) -> u64 {
+    (1 << n) - 1
+}
+
+#[inline]
+fn nar(n: u32) -> u64 {
+    1 << (n - 1)
+}
+
+/// Decodes the `n`-bit posit `x`.
+#[inline]
+fn unpack(n: u32, x: u64) -> Class {
+    if x == 0 {
+        return Class::Zero;
+    }
+    if x == nar(n) {
+        return Class::Nar;
+    }
+    let sign = x & nar(n) != 0;
+    let x = if sign { x.wrapping_neg() & mask(n) } else { x };
+    // The bits after the sign, from the top of the word.
+    let bits = x << (65 - n);
+    let (run, k) = if bits >> 63 != 0 {
+        let run = bits.leading_ones();
+        (run, run as i32 - 1)
+    } else {
+        let run = bits.leading_zeros();
+        (run, -(run as i32))
+    };
+    // The terminating bit and the exponent bits may have been cut off, and
+    // are then zeros.
+    let rest = bits << (run + 1);
+    let scale = 4 * k + (rest >> 62) as i32;
+    let sig = 1 << 63 | (rest << 2) >> 1;
+    Class::Real {
+        sign,
+        exp: scale - 63,
+        sig,
+    }
+}
+
+/// Rounds the nonzero value `sig * 2^exp`, exact but for its lowest bit
+/// standing in for lower ones, to an `n`-bit posit.
+fn round_pack(n: u32, sign: bool, exp: i32, sig: u128, exc: &mut u32) -> u64 {
+    let shift = sig.leading_zeros();
+    let sig = sig << shift;
+    let scale = exp + 127 - shift as i32;
+    let max = 4 * (n as i32 - 2);
+    let bits = if scale > max {
+        *exc |= INEXACT;
+        mask(n - 1)
+    } else if scale < -max {
+        *exc |= INEXACT;
+        1
+    } else {
+        let (k, e) = (scale >> 2, (scale & 3) as u128);
+        // The regime as a run of ones or zeros ended by the opposite bit.
+        let (regime, len) = if k >= 0 {
+            ((1 << (k + 2)) - 2, k as u32 + 2)
+        } else {
+            (1, (-k) as u32 + 1)
+        };
+        let frac = sig << 1;
+        let lost = frac << (126 - len) != 0;
+        let pattern = regime << (128 - len) | e << (126 - len) | frac >> (len + 2);
+        // The bits after the sign and the part below them.
+        let kept = (pattern >> (129 - n)) as u64;
+        let rest = pattern << (n - 1);
+        let half = rest >> 127 != 0;
+        let sticky = rest << 1 != 0 || lost;
+        if half || sticky {
+            *exc |= INEXACT;
+        }
+        kept + (half && (sticky || kept & 1 != 0)) as u64
+    };
+    if sign {
+        bits.wrapping_neg() & mask(n)
+    } else {
+        bits
+    }
+}
+
+#[inline]
+fn result<T>((value, exc): (T, u32)) -> (T, Status) {
+    (value, Status::from_soft(exc))
+}
+
+fn add(n: u32, a: u64, b: u64, negate: bool) -> (u64, Status) {
+    let b = if negate {
+        b.wrapping_neg() & mask(n)
+    } else {
+        b
+    };
+    match (unpack(n, a), unpack(n, b)) {
+        (Class::Nar, _) | (_, Class::Nar) => (nar(n), Status::empty()),
+        (Class::Zero, _) => (b, Status::empty()),
+        (_, Class::Zero) => (a, Status::empty()),
+        (
+            Class::Real {
+                sign: sign_a,
+                exp: exp_a,
+                sig: sig_a,
+            },
+            Class::Real {
+                sign: sign_b,
+                exp: exp_b,
+                sig: sig_b,
+            },
+        ) => {
+            let sum = soft::add_exact(
+                (sign_a, exp_a, sig_a as u128),
+                (sign_b, exp_b, sig_b as u128),
+            );
+            let mut exc = 0;
+            let res = match sum {
+                Some((sign, exp, sig)) => round_pack(n, sign, exp, sig, &mut exc),
+                None => 0,
+            };
+            result((res, exc))
+        }
+    }
+}
+
+fn mul(n: u32, a: u64, b: u64) -> (u64, Status) {
+    match (unpack(n, a), unpack(n, b)) {
+        (Class::Nar, _) | (_, Class::Nar) => (nar(n), Status::empty()),
+        (Class::Zero, _) | (_, Class::Zero) => (0, Status::empty()),
+        (
+            Class::Real {
+                sign: sign_a,
+                exp: exp_a,
+                sig: sig_a,
+            },
+            Class::Real {
+                sign: sign_b,
+                exp: exp_b,
+                sig: sig_b,
+            },
+        ) => {
+            let mut exc = 0;
+            let sig = sig_a as u128 * sig_b as u128;
+            let res = round_pack(n, sign_a != sign_b, exp_a + exp_b, sig, &mut exc);
+            result((res, exc))
+        }
+    }
+}
+
+fn div(n: u32, a: u64, b: u64) -> (u64, Status) {
+    match (unpack(n, a), unpack(n, b)) {
+        (Class::Nar, _) | (_, Class::Nar) => (nar(n), Status::empty()),
+        (Class::Zero, Class::Zero) => (nar(n), Status::from_soft(INVALID)),
+        (_, Class::Zero) => (nar(n), Status::from_soft(DIV_ZERO)),
+        (Class::Zero, _) => (0, Status::empty()),
+        (
+            Class::Real {
+                sign: sign_a,
+                exp: exp_a,
+                sig: sig_a,
+            },
+            Class::Real {
+                sign: sign_b,
+                exp: exp_b,
+                sig: sig_b,
+            },
+        ) => {
+            let mut exc = 0;
+            let num = (sig_a as u128) << 64;
+            let (quo, rem) = (num / sig_b as u128, num % sig_b as u128);
+            let sig = quo << 1 | (rem != 0) as u128;
+            let res = round_pack(n, sign_a != sign_b, exp_a - exp_b - 65, sig, &mut exc);
+            result((res, exc))
+        }
+    }
+}
+
+fn sqrt(n: u32, x: u64) -> (u64, Status) {
+    match unpack(n, x) {
+        Class::Nar => (nar(n), Status::empty()),
+        Class::Zero => (0, Status::empty()),
+        Class::Real { sign: true, .. } => (nar(n), Status::from_soft(INVALID)),
+        Class::Real { exp, sig, .. } => {
+            // An even exponent with the significand in the top bits.
+            let (exp, sig) = if exp & 1 == 0 {
+                (exp - 64, (sig as u128) << 64)
+            } else {
+                (exp - 63, (sig as u128) << 63)
+            };
+            let root = sig.isqrt();
+            let sig = root << 1 | (root * root != sig) as u128;
+            let mut exc = 0;
+            let res = round_pack(n, false, exp / 2 - 1, sig, &mut exc);
+            result((res, exc))
+        }
+    }
+}
+
+#[inline]
+fn compare(n: u32, a: u64, b: u64) -> Ordering {
+    // Sign-extended, NaR being the smallest.
+    let signed = |x: u64| ((x << (64 - n)) as i64) >> (64 - n);
+    signed(a).cmp(&signed(b))
+}
+
+fn from_double(n: u32, x: f64) -> (u64, Status) {
+    if !x.is_finite() {
+        return (nar(n), Status::from_soft(INVALID));
+    }
+    if x == 0.0 {
+        return (0, Status::empty());
+    }
+    let bits = x.to_bits();
+    let biased = (bits >> 52 & 0x7FF) as i32;
+    let frac = bits & ((1 << 52) - 1);
+    let (exp, sig) = if biased == 0 {
+        (-1074, frac)
+    } else {
+        (biased - 1075, frac | 1 << 52)
+    };
+    let mut exc = 0;
+    let res = round_pack(n, x.is_sign_negative(), exp, sig as u128, &mut exc);
+    result((res, exc))
+}
+
+/// The value of a posit of at most 32 bits, which doubles hold exactly.
+fn to_double(n: u32, x: u64) -> f64 {
+    match unpack(n, x) {
+        Class::Zero => 0.0,
+        Class::Nar => f64::NAN,
+        Class::Real { sign, exp, sig } => {
+            let scale = exp + 63;
+            let bits = ((scale + 1023) as u64) << 52 | (sig << 1) >> 12;
+            let value = f64::from_bits(bits);
+            if sign {
+                -value
+            } else {
+                value
+            }
+        }
+    }
+}
+
+macro_rules! posit {
+    ($name:ident, $bits:ty, $n:literal) => {
+        pub mod $name {
+            use super::*;
+
+            pub const ZERO: $bits = 0;
+            pub const ONE: $bits = 1 << ($n - 2);
+            /// Not a real.
+            pub const NAR: $bits = 1 << ($n - 1);
+            /// The largest posit, `2^(4 * (n - 2))`.
+            pub const MAX: $bits = NAR - 1;
+            /// The smallest positive posit, `2^(-4 * (n - 2))`.
+            pub const MIN_POSITIVE: $bits = 1;
+
+            #[inline]
+            pub fn add(l: $bits, r: $bits) -> ($bits, Status) {
+                let (bits, status) = super::add($n, l as u64, r as u64, false);
+                (bits as $bits, status)
+            }
+
+            #[inline]
+            pub fn sub(l: $bits, r: $bits) -> ($bits, Status) {
+                let (bits, status) = super::add($n, l as u64, r as u64, true);
+                (bits as $bits, status)
+            }
+
+            #[inline]
+            pub fn mul(l: $bits, r: $bits) -> ($bits, Status) {
+                let (bits, status) = super::mul($n, l as u64, r as u64);
+                (bits as $bits, status)
+            }
+
+            #[inline]
+            pub fn div(l: $bits, r: $bits) -> ($bits, Status) {
+                let (bits, status) = super::div($n, l as u64, r as u64);
+                (bits as $bits, status)
+            }
+
+            #[inline]
+            pub fn sqrt(x: $bits) -> ($bits, Status) {
+                let (bits, status) = super::sqrt($n, x as u64);
+                (bits as $bits, status)
+            }
+
+            /// Negates `x`, exactly.
+            #[inline]
+            pub fn neg(x: $bits) -> $bits {
+                x.wrapping_neg()
+            }
+
+            /// Compares `l` with `r`, NaR being equal to itself and below
+            /// every real.
+            #[inline]
+            pub fn compare(l: $bits, r: $bits) -> Ordering {
+                super::compare($n, l as u64, r as u64)
+            }
+
+            /// Rounds `double` to the nearest posit, raising invalid for
+            /// infinities and NaNs, which give NaR.
+            #[inline]
+            pub fn from_double(double: f64) -> ($bits, Status) {
+                let (bits, status) = super::from_double($n, double);
+                (bits as $bits, status)
+            }
+
+            /// The value of `x`, exactly, NaR giving a NaN.
+            #[inline]
+            pub fn to_double(x: $bits) -> f64 {
+                super::to_double($n, x as u64)
+            }
+        }
+    };
+}
+
+posit!(p16, u16, 16);
+posit!(p32, u32, 32);
+
+/// Widens a posit16 to a posit32, exactly.
+#[inline]
+pub fn p16_to_p32(x: u16) -> u32 {
+    (x as u32) << 16
+}
+
+/// Rounds a posit32 to the nearest posit16.
+#[inline]
+pub fn p32_to_p16(x: u32) -> (u16, Status) {
+    match unpack(32, x as u64) {
+        Class::Zero => (0, Status::empty()),
+        Class::Nar => (p16::NAR, Status::empty()),
+        Class::Real { sign, exp, sig } => {
+            let mut exc = 0;
+            let bits = round_pack(16, sign, exp, sig as u128, &mut exc);
+            (bits as u16, Status::from_soft(exc))
+        }
+    }
+}
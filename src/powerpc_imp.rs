@@ -0,0 +1,626 @@
+use core::arch::asm;
+
+use crate::{soft, Tininess};
+
+const ROUND_MASK: u32 = 0b11;
+// The non-IEEE mode of the FPSCR flushes in implementation specific ways, so
+// this bit lies in the upper word instead and routes operations through the
+// software implementation.
+const FLUSH_ZERO: u32 = 1 << 31;
+const DENORMALS_ZERO: u32 = 1 << 30;
+const DEFAULT_NAN: u32 = 1 << 29;
+const TININESS_BEFORE: u32 = 1 << 28;
+const TIES_AWAY: u32 = 1 << 27;
+const TO_ODD: u32 = 1 << 26;
+const EXCEPT_MASK: u32 = 0x1F << 25;
+
+/// A rounding mode, laid out as the `u32` of its bits in the flags.
+#[repr(u32)]
+pub enum Rounding {
+    /// Rounds towards zero.
+    Zero = 0b01,
+    /// Rounds towards positive infinity.
+    Up = 0b10,
+    /// Rounds towards negative infinity.
+    Down = 0b11,
+    /// Rounds towards nearest.
+    Nearest = 0b00,
+    /// Rounds towards nearest, ties away from zero, which the hardware lacks.
+    NearestAway = TIES_AWAY,
+    /// Rounds towards zero, setting the last bit of inexact results, which
+    /// the hardware lacks.
+    Odd = TO_ODD,
+}
+
+/// The flags set for the operation.
+///
+/// The layout is stable: a `u32` holding the bits the operations load into
+/// the FPSCR, with the settings it lacks in bits it leaves free.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Flags {
+    inner: u32,
+}
+
+impl Default for Flags {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Flags {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: TININESS_BEFORE,
+        }
+    }
+
+    #[inline]
+    pub const fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.set_rounding(rounding);
+        self
+    }
+
+    #[inline]
+    pub fn with_ftz(mut self, enabled: bool) -> Self {
+        self.set_ftz(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_daz(mut self, enabled: bool) -> Self {
+        self.set_daz(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_default_nan(mut self, enabled: bool) -> Self {
+        self.set_default_nan(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_tininess(mut self, tininess: Tininess) -> Self {
+        self.set_tininess(tininess);
+        self
+    }
+
+    #[inline]
+    pub const fn set_rounding(&mut self, rounding: Rounding) {
+        self.inner = (self.inner & !(ROUND_MASK | TIES_AWAY | TO_ODD)) | rounding as u32;
+    }
+
+    #[inline]
+    pub fn rounding(self) -> Rounding {
+        match self.inner & (ROUND_MASK | TIES_AWAY | TO_ODD) {
+            b if b == Rounding::Zero as u32 => Rounding::Zero,
+            b if b == Rounding::Up as u32 => Rounding::Up,
+            b if b == Rounding::Down as u32 => Rounding::Down,
+            b if b == Rounding::NearestAway as u32 => Rounding::NearestAway,
+            b if b == Rounding::Odd as u32 => Rounding::Odd,
+            _ => Rounding::Nearest,
+        }
+    }
+
+    #[inline]
+    pub fn set_ftz(&mut self, enabled: bool) {
+        self.inner = (self.inner & !FLUSH_ZERO) | if enabled { FLUSH_ZERO } else { 0 }
+    }
+
+    #[inline]
+    pub fn ftz(self) -> bool {
+        self.inner & FLUSH_ZERO != 0
+    }
+
+    #[inline]
+    pub fn set_daz(&mut self, enabled: bool) {
+        self.inner = (self.inner & !DENORMALS_ZERO) | if enabled { DENORMALS_ZERO } else { 0 }
+    }
+
+    #[inline]
+    pub fn daz(self) -> bool {
+        self.inner & DENORMALS_ZERO != 0
+    }
+
+    /// Makes every NaN result the default NaN, regardless of the operands.
+    #[inline]
+    pub fn set_default_nan(&mut self, enabled: bool) {
+        self.inner = (self.inner & !DEFAULT_NAN) | if enabled { DEFAULT_NAN } else { 0 }
+    }
+
+    #[inline]
+    pub fn default_nan(self) -> bool {
+        self.inner & DEFAULT_NAN != 0
+    }
+
+    /// Selects when results are tiny, which the hardware detects
+    /// before rounding.
+    #[inline]
+    pub fn set_tininess(&mut self, tininess: Tininess) {
+        self.inner = (self.inner & !TININESS_BEFORE)
+            | match tininess {
+                Tininess::BeforeRounding => TININESS_BEFORE,
+                Tininess::AfterRounding => 0,
+            }
+    }
+
+    #[inline]
+    pub fn tininess(self) -> Tininess {
+        if self.inner & TININESS_BEFORE != 0 {
+            Tininess::BeforeRounding
+        } else {
+            Tininess::AfterRounding
+        }
+    }
+
+    /// The FPSCR image that selects the rounding mode, disables the traps
+    /// and clears the flags, as the floating-point register `mtfsf` reads.
+    #[inline]
+    fn fpscr(self) -> f64 {
+        f64::from_bits((self.inner & ROUND_MASK) as u64)
+    }
+
+    /// Whether the rounding mode is one the hardware lacks, so that the
+    /// operations run in software.
+    #[inline]
+    pub(crate) fn soft_rounding(self) -> bool {
+        self.inner & (TIES_AWAY | TO_ODD) != 0
+    }
+
+    /// The environment emulating the FPU for operations it can't perform.
+    #[inline]
+    pub(crate) fn soft_env(self) -> soft::Env {
+        soft::Env {
+            mode: match self.rounding() {
+                Rounding::Zero => soft::Mode::Zero,
+                Rounding::Up => soft::Mode::Up,
+                Rounding::Down => soft::Mode::Down,
+                Rounding::Nearest => soft::Mode::Nearest,
+                Rounding::NearestAway => soft::Mode::NearestAway,
+                Rounding::Odd => soft::Mode::Odd,
+            },
+            ftz: self.ftz(),
+            daz: self.daz(),
+            default_nan: self.default_nan(),
+            sem: soft::Semantics {
+                tininess_before_rounding: self.tininess() == Tininess::BeforeRounding,
+                ..soft::Semantics::POWER
+            },
+        }
+    }
+
+    /// `x`, or the default NaN when it is a NaN and the flags ask for one,
+    /// which the hardware doesn't do itself.
+    #[inline]
+    pub(crate) fn result<T: soft::Value>(self, x: T) -> T {
+        if self.default_nan() && x.is_nan() {
+            T::default_nan(&self.soft_env())
+        } else {
+            x
+        }
+    }
+}
+
+/// The status from the operations.
+///
+/// The layout is stable: a `u32` holding the exception bits of the FPSCR.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Status {
+    inner: u32,
+}
+
+impl Status {
+    pub const INVALID: Self = Self { inner: 1 << 29 };
+    pub const OVERFLOW: Self = Self { inner: 1 << 28 };
+    pub const UNDERFLOW: Self = Self { inner: 1 << 27 };
+    pub const INEXACT: Self = Self { inner: 1 << 25 };
+    /// PowerPC doesn't report denormal operands, so this is never raised.
+    pub const DENORM: Self = Self { inner: 1 << 11 };
+    pub const DIV_ZERO: Self = Self { inner: 1 << 26 };
+
+    #[inline]
+    pub fn empty() -> Self {
+        Self { inner: 0 }
+    }
+
+    #[inline]
+    pub fn has_exceptions(self) -> bool {
+        self.inner & EXCEPT_MASK != 0
+    }
+
+    #[inline]
+    pub fn invalid(self) -> bool {
+        self.has(Self::INVALID)
+    }
+
+    #[inline]
+    pub fn overflow(self) -> bool {
+        self.has(Self::OVERFLOW)
+    }
+
+    #[inline]
+    pub fn underflow(self) -> bool {
+        self.has(Self::UNDERFLOW)
+    }
+
+    #[inline]
+    pub fn inexact(self) -> bool {
+        self.has(Self::INEXACT)
+    }
+
+    #[inline]
+    pub fn denorm(self) -> bool {
+        self.has(Self::DENORM)
+    }
+
+    #[inline]
+    pub fn div_zero(self) -> bool {
+        self.has(Self::DIV_ZERO)
+    }
+
+    #[inline]
+    pub fn has(self, status: Self) -> bool {
+        self.inner & status.inner == status.inner
+    }
+
+    #[inline]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            inner: self.inner | other.inner,
+        }
+    }
+
+    #[inline]
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            inner: self.inner & other.inner,
+        }
+    }
+
+    /// Translates exceptions raised by the software implementation.
+    #[inline]
+    pub(crate) fn from_soft(exc: u32) -> Self {
+        [
+            (soft::INVALID, Self::INVALID),
+            (soft::DENORM, Self::DENORM),
+            (soft::DIV_ZERO, Self::DIV_ZERO),
+            (soft::OVERFLOW, Self::OVERFLOW),
+            (soft::UNDERFLOW, Self::UNDERFLOW),
+            (soft::INEXACT, Self::INEXACT),
+        ]
+        .iter()
+        .filter(|(bit, _)| exc & bit != 0)
+        .fold(Self::empty(), |status, &(_, flag)| status.or(flag))
+    }
+}
+
+macro_rules! host_op {
+    ($flags:ident; $asm:literal; $($end:tt)* ) => {
+        unsafe {
+            let status: f64;
+            asm!(
+                "mtfsf 0xff, {flags}",
+                $asm,
+                "mffs {status}",
+                flags = in(freg) $flags.fpscr(),
+                status = out(freg) status,
+                $($end)*
+            );
+            status.to_bits() as u32 & EXCEPT_MASK
+        }
+    };
+}
+
+/// Runs an operation in software when flushing to zero or treating
+/// denormals as zero was requested.
+macro_rules! soft_op {
+    ($flags:ident; $op:path; $ty:ident; $($arg:expr),+) => {
+        if $flags.ftz() || $flags.daz() {
+            let (bits, exc) = $op(&$flags.soft_env(), $($arg.to_bits() as u64),+);
+            return ($ty::from_bits(bits as _), Status::from_soft(exc));
+        }
+    };
+}
+
+/// Whether `madd` runs on a fused instruction.
+pub(crate) const FUSED_MADD: bool = true;
+
+pub mod f32 {
+    use super::*;
+
+    pub use crate::bits::f32::*;
+    pub use crate::bounds::f32::*;
+    pub use crate::functions::f32::*;
+    pub use crate::sign::f32::*;
+    #[cfg(feature = "batch")]
+    pub use crate::slice::f32 as slice;
+    pub use crate::sticky::f32::*;
+
+    #[inline]
+    pub fn add(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::add::<soft::Binary32>; f32; l, r);
+        let status = host_op!(
+            flags;
+            "fadds {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::sub::<soft::Binary32>; f32; l, r);
+        let status = host_op!(
+            flags;
+            "fsubs {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::mul::<soft::Binary32>; f32; l, r);
+        let status = host_op!(
+            flags;
+            "fmuls {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        soft_op!(flags; soft::div::<soft::Binary32>; f32; l, r);
+        let status = host_op!(
+            flags;
+            "fdivs {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, mut a: f32, b: f32, c: f32) -> (f32, Status) {
+        soft_op!(flags; soft::fma::<soft::Binary32>; f32; a, b, c);
+        let status = host_op!(
+            flags;
+            "fmadds {a}, {a}, {b}, {c}";
+            a = inout(freg) a,
+            b = in(freg) b,
+            c = in(freg) c,
+        );
+        (flags.result(a), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f32) -> (f32, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary32>(&flags.soft_env(), x.to_bits() as u64);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, mut x: f32) -> (f32, Status) {
+        soft_op!(flags; soft::sqrt::<soft::Binary32>; f32; x);
+        let status = host_op!(
+            flags;
+            "fsqrts {x}, {x}";
+            x = inout(freg) x,
+        );
+        (flags.result(x), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, single: f32) -> (f64, Status) {
+        soft_op!(flags; soft::convert::<soft::Binary32, soft::Binary64>; f64; single);
+        // Registers hold single precision values in double format already.
+        let double: f64;
+        let status = host_op!(
+            flags;
+            "fmr {double}, {single}";
+            double = out(freg) double,
+            single = in(freg) single,
+        );
+        (flags.result(double), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status) {
+        let (bits, exc) = soft::convert::<soft::Binary32, soft::Binary16>(
+            &flags.soft_env(),
+            single.to_bits() as u64,
+        );
+        (bits as u16, Status::from_soft(exc))
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    pub use crate::bits::f64::*;
+    pub use crate::bounds::f64::*;
+    pub use crate::functions::f64::*;
+    pub use crate::sign::f64::*;
+    #[cfg(feature = "batch")]
+    pub use crate::slice::f64 as slice;
+    pub use crate::sticky::f64::*;
+
+    #[inline]
+    pub fn add(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        soft_op!(flags; soft::add::<soft::Binary64>; f64; l, r);
+        let status = host_op!(
+            flags;
+            "fadd {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        soft_op!(flags; soft::sub::<soft::Binary64>; f64; l, r);
+        let status = host_op!(
+            flags;
+            "fsub {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        soft_op!(flags; soft::mul::<soft::Binary64>; f64; l, r);
+        let status = host_op!(
+            flags;
+            "fmul {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        soft_op!(flags; soft::div::<soft::Binary64>; f64; l, r);
+        let status = host_op!(
+            flags;
+            "fdiv {l}, {l}, {r}";
+            l = inout(freg) l,
+            r = in(freg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, mut a: f64, b: f64, c: f64) -> (f64, Status) {
+        soft_op!(flags; soft::fma::<soft::Binary64>; f64; a, b, c);
+        let status = host_op!(
+            flags;
+            "fmadd {a}, {a}, {b}, {c}";
+            a = inout(freg) a,
+            b = in(freg) b,
+            c = in(freg) c,
+        );
+        (flags.result(a), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: f64) -> (f64, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary64>(&flags.soft_env(), x.to_bits());
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, mut x: f64) -> (f64, Status) {
+        soft_op!(flags; soft::sqrt::<soft::Binary64>; f64; x);
+        let status = host_op!(
+            flags;
+            "fsqrt {x}, {x}";
+            x = inout(freg) x,
+        );
+        (flags.result(x), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, double: f64) -> (f32, Status) {
+        soft_op!(flags; soft::convert::<soft::Binary64, soft::Binary32>; f32; double);
+        let single: f32;
+        let status = host_op!(
+            flags;
+            "frsp {single}, {double}";
+            single = out(freg) single,
+            double = in(freg) double,
+        );
+        (flags.result(single), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary64, soft::Binary16>(&flags.soft_env(), double.to_bits());
+        (bits as u16, Status::from_soft(exc))
+    }
+}
+
+/// Half-precision operations, taking and returning the bit patterns of the
+/// values since `f16` isn't a stable Rust type.
+pub mod f16 {
+    use super::*;
+
+    pub use crate::sticky::f16::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::add::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::sub::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::mul::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::div::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: u16, b: u16, c: u16) -> (u16, Status) {
+        let (bits, exc) =
+            soft::fma::<soft::Binary16>(&flags.soft_env(), a as u64, b as u64, c as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: u16) -> (u16, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary16>(&flags.soft_env(), x as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: u16) -> (u16, Status) {
+        let (bits, exc) = soft::sqrt::<soft::Binary16>(&flags.soft_env(), x as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, half: u16) -> (f32, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary16, soft::Binary32>(&flags.soft_env(), half as u64);
+        (f32::from_bits(bits as _), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, half: u16) -> (f64, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary16, soft::Binary64>(&flags.soft_env(), half as u64);
+        (f64::from_bits(bits as _), Status::from_soft(exc))
+    }
+}
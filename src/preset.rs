@@ -0,0 +1,457 @@
+//! Operations following every corner of IEEE 754 the way a given
+//! architecture does, as emulating its guests needs.
+//!
+//! A `Preset` bundles the NaN results, the sign of the default NaN, the
+//! detection of tininess, what its flush-to-zero control flushes and how
+//! denormal operands and flushed results are reported. The operations run
+//! as usual and only those returning a NaN, raising underflow or taking a
+//! denormal operand run again in software under the preset, so other
+//! results cost a few checks. The `ftz` and `daz` of the flags apply as
+//! set, on any host.
+//!
+//! The conversions to integers run in software, rounding as the flags
+//! select, so truncating instructions such as `cvttsd2si` take
+//! `Rounding::Zero`. NaNs and values out of range give the integers of the
+//! architecture. The legacy MIPS results in `consts::mips` are not among
+//! them, as `Preset::MIPS` follows IEEE 754-2008.
+
+use crate::{soft, Flags, Rounding, Status, Tininess};
+
+/// The floating-point behavior of an architecture.
+#[derive(Clone, Copy)]
+pub struct Preset {
+    sem: soft::Semantics,
+    /// Whether the flush-to-zero control also flushes denormal inputs.
+    flush_inputs: bool,
+    invalid_integer: InvalidInteger,
+}
+
+/// The integers conversions return for NaNs and values out of range.
+#[derive(Clone, Copy)]
+enum InvalidInteger {
+    /// The smallest signed or the largest unsigned integer for both, as on
+    /// x86.
+    Indefinite,
+    /// The nearest integer for values out of range and zero for NaNs.
+    SaturateNanZero,
+    /// The nearest integer for values out of range and the largest one for
+    /// NaNs.
+    SaturateNanLargest,
+    /// The nearest integer for values out of range and the smallest one for
+    /// NaNs.
+    SaturateNanSmallest,
+}
+
+impl Preset {
+    /// SSE and AVX on x86, whose `DAZ` is separate from `FTZ`.
+    pub const X86: Self = Self {
+        sem: soft::Semantics::X86,
+        flush_inputs: false,
+        invalid_integer: InvalidInteger::Indefinite,
+    };
+    /// AArch64 and AArch32 VFP and NEON under the standard handling, whose
+    /// `FZ` flushes both inputs and results.
+    pub const ARMV8: Self = Self {
+        sem: soft::Semantics::ARM,
+        flush_inputs: true,
+        invalid_integer: InvalidInteger::SaturateNanZero,
+    };
+    /// AArch64 under the alternate handling of FEAT_AFP, which follows x86
+    /// and leaves the inputs to `FIZ`.
+    pub const ARMV8_ALTERNATE: Self = Self {
+        sem: soft::Semantics::ARM_ALTERNATE,
+        flush_inputs: false,
+        invalid_integer: InvalidInteger::SaturateNanZero,
+    };
+    pub const LOONGARCH: Self = Self {
+        sem: soft::Semantics::LOONGARCH,
+        flush_inputs: false,
+        invalid_integer: InvalidInteger::SaturateNanZero,
+    };
+    /// MIPS with the NaN encodings of IEEE 754-2008, whose `FS` flushes both
+    /// inputs and results.
+    pub const MIPS: Self = Self {
+        sem: soft::Semantics::MIPS,
+        flush_inputs: true,
+        invalid_integer: InvalidInteger::SaturateNanZero,
+    };
+    /// Power, whose `NI` only flushes results.
+    pub const POWER: Self = Self {
+        sem: soft::Semantics::POWER,
+        flush_inputs: false,
+        invalid_integer: InvalidInteger::SaturateNanSmallest,
+    };
+    pub const RISCV: Self = Self {
+        sem: soft::Semantics::RISCV,
+        flush_inputs: false,
+        invalid_integer: InvalidInteger::SaturateNanLargest,
+    };
+    pub const S390X: Self = Self {
+        sem: soft::Semantics::S390X,
+        flush_inputs: false,
+        invalid_integer: InvalidInteger::SaturateNanSmallest,
+    };
+
+    /// The flags rounding by `rounding`, with the tininess of the
+    /// architecture and its flush-to-zero control set to `flush`.
+    #[inline]
+    pub fn flags(self, rounding: Rounding, flush: bool) -> Flags {
+        Flags::new()
+            .with_rounding(rounding)
+            .with_tininess(self.tininess())
+            .with_ftz(flush)
+            .with_daz(flush && self.flush_inputs)
+    }
+
+    /// When the architecture detects tininess.
+    #[inline]
+    pub fn tininess(self) -> Tininess {
+        if self.sem.tininess_before_rounding {
+            Tininess::BeforeRounding
+        } else {
+            Tininess::AfterRounding
+        }
+    }
+
+    /// The environment running an operation under the preset.
+    #[inline]
+    fn env(self, flags: Flags) -> soft::Env {
+        soft::Env {
+            ftz: flags.ftz(),
+            daz: flags.daz(),
+            sem: soft::Semantics {
+                tininess_before_rounding: flags.tininess() == Tininess::BeforeRounding,
+                ..self.sem
+            },
+            ..flags.soft_env()
+        }
+    }
+}
+
+/// The result of `host`, or of `soft` under `preset` if an operand is
+/// `denormal` or the result may differ from the architecture's.
+#[inline]
+fn finish<T: soft::Value>(
+    flags: Flags,
+    preset: Preset,
+    denormal: bool,
+    host: impl FnOnce() -> (T, Status),
+    soft: impl FnOnce(&soft::Env) -> (u64, u32),
+) -> (T, Status) {
+    if !denormal {
+        let (value, status) = host();
+        if !(value.is_nan() || status.underflow()) {
+            return (value, status);
+        }
+    }
+    let (bits, exc) = soft(&preset.env(flags));
+    (T::from_soft(bits), Status::from_soft(exc))
+}
+
+/// Converts the bits of a value of `F` to an integer from `min` to `max`,
+/// returning the integer of `preset` where that is invalid.
+#[inline]
+fn to_integer<F: soft::Format>(
+    flags: Flags,
+    preset: Preset,
+    bits: u64,
+    min: i128,
+    max: i128,
+) -> (i128, Status) {
+    let (integer, exc) = soft::to_integer::<F>(&preset.env(flags), bits, min, max);
+    let value = match (integer, preset.invalid_integer) {
+        (soft::Integer::Value(value), _) => value,
+        (_, InvalidInteger::Indefinite) if min < 0 => min,
+        (_, InvalidInteger::Indefinite) => max,
+        (soft::Integer::OutOfRange(negative), _) => {
+            if negative {
+                min
+            } else {
+                max
+            }
+        }
+        (soft::Integer::Nan, InvalidInteger::SaturateNanZero) => 0,
+        (soft::Integer::Nan, InvalidInteger::SaturateNanLargest) => max,
+        (soft::Integer::Nan, InvalidInteger::SaturateNanSmallest) => min,
+    };
+    (value, Status::from_soft(exc))
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, preset: Preset, l: f32, r: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f32::add(flags, l, r),
+            |env| soft::add::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, preset: Preset, l: f32, r: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f32::sub(flags, l, r),
+            |env| soft::sub::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, preset: Preset, l: f32, r: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f32::mul(flags, l, r),
+            |env| soft::mul::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, preset: Preset, l: f32, r: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f32::div(flags, l, r),
+            |env| soft::div::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64),
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, preset: Preset, a: f32, b: f32, c: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            a.is_subnormal() || b.is_subnormal() || c.is_subnormal(),
+            || crate::f32::madd(flags, a, b, c),
+            |env| {
+                soft::fma::<soft::Binary32>(
+                    env,
+                    a.to_bits() as u64,
+                    b.to_bits() as u64,
+                    c.to_bits() as u64,
+                )
+            },
+        )
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, preset: Preset, x: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            x.is_subnormal(),
+            || crate::f32::round(flags, x),
+            |env| soft::round_int::<soft::Binary32>(env, x.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, preset: Preset, x: f32) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            x.is_subnormal(),
+            || crate::f32::sqrt(flags, x),
+            |env| soft::sqrt::<soft::Binary32>(env, x.to_bits() as u64),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, preset: Preset, single: f32) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            single.is_subnormal(),
+            || crate::f32::to_double(flags, single),
+            |env| soft::convert::<soft::Binary32, soft::Binary64>(env, single.to_bits() as u64),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, preset: Preset, single: f32) -> (u16, Status) {
+        finish(
+            flags,
+            preset,
+            single.is_subnormal(),
+            || crate::f32::to_half(flags, single),
+            |env| soft::convert::<soft::Binary32, soft::Binary16>(env, single.to_bits() as u64),
+        )
+    }
+
+    #[inline]
+    pub fn to_i32(flags: Flags, preset: Preset, x: f32) -> (i32, Status) {
+        let (min, max) = (i32::MIN as i128, i32::MAX as i128);
+        let (value, status) =
+            to_integer::<soft::Binary32>(flags, preset, x.to_bits() as u64, min, max);
+        (value as i32, status)
+    }
+
+    #[inline]
+    pub fn to_i64(flags: Flags, preset: Preset, x: f32) -> (i64, Status) {
+        let (min, max) = (i64::MIN as i128, i64::MAX as i128);
+        let (value, status) =
+            to_integer::<soft::Binary32>(flags, preset, x.to_bits() as u64, min, max);
+        (value as i64, status)
+    }
+
+    #[inline]
+    pub fn to_u32(flags: Flags, preset: Preset, x: f32) -> (u32, Status) {
+        let (min, max) = (u32::MIN as i128, u32::MAX as i128);
+        let (value, status) =
+            to_integer::<soft::Binary32>(flags, preset, x.to_bits() as u64, min, max);
+        (value as u32, status)
+    }
+
+    #[inline]
+    pub fn to_u64(flags: Flags, preset: Preset, x: f32) -> (u64, Status) {
+        let (min, max) = (u64::MIN as i128, u64::MAX as i128);
+        let (value, status) =
+            to_integer::<soft::Binary32>(flags, preset, x.to_bits() as u64, min, max);
+        (value as u64, status)
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, preset: Preset, l: f64, r: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f64::add(flags, l, r),
+            |env| soft::add::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, preset: Preset, l: f64, r: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f64::sub(flags, l, r),
+            |env| soft::sub::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, preset: Preset, l: f64, r: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f64::mul(flags, l, r),
+            |env| soft::mul::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, preset: Preset, l: f64, r: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            l.is_subnormal() || r.is_subnormal(),
+            || crate::f64::div(flags, l, r),
+            |env| soft::div::<soft::Binary64>(env, l.to_bits(), r.to_bits()),
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, preset: Preset, a: f64, b: f64, c: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            a.is_subnormal() || b.is_subnormal() || c.is_subnormal(),
+            || crate::f64::madd(flags, a, b, c),
+            |env| soft::fma::<soft::Binary64>(env, a.to_bits(), b.to_bits(), c.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, preset: Preset, x: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            x.is_subnormal(),
+            || crate::f64::round(flags, x),
+            |env| soft::round_int::<soft::Binary64>(env, x.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, preset: Preset, x: f64) -> (f64, Status) {
+        finish(
+            flags,
+            preset,
+            x.is_subnormal(),
+            || crate::f64::sqrt(flags, x),
+            |env| soft::sqrt::<soft::Binary64>(env, x.to_bits()),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, preset: Preset, double: f64) -> (f32, Status) {
+        finish(
+            flags,
+            preset,
+            double.is_subnormal(),
+            || crate::f64::to_single(flags, double),
+            |env| soft::convert::<soft::Binary64, soft::Binary32>(env, double.to_bits()),
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, preset: Preset, double: f64) -> (u16, Status) {
+        finish(
+            flags,
+            preset,
+            double.is_subnormal(),
+            || crate::f64::to_half(flags, double),
+            |env| soft::convert::<soft::Binary64, soft::Binary16>(env, double.to_bits()),
+        )
+    }
+
+    #[inline]
+    pub fn to_i32(flags: Flags, preset: Preset, x: f64) -> (i32, Status) {
+        let (min, max) = (i32::MIN as i128, i32::MAX as i128);
+        let (value, status) = to_integer::<soft::Binary64>(flags, preset, x.to_bits(), min, max);
+        (value as i32, status)
+    }
+
+    #[inline]
+    pub fn to_i64(flags: Flags, preset: Preset, x: f64) -> (i64, Status) {
+        let (min, max) = (i64::MIN as i128, i64::MAX as i128);
+        let (value, status) = to_integer::<soft::Binary64>(flags, preset, x.to_bits(), min, max);
+        (value as i64, status)
+    }
+
+    #[inline]
+    pub fn to_u32(flags: Flags, preset: Preset, x: f64) -> (u32, Status) {
+        let (min, max) = (u32::MIN as i128, u32::MAX as i128);
+        let (value, status) = to_integer::<soft::Binary64>(flags, preset, x.to_bits(), min, max);
+        (value as u32, status)
+    }
+
+    #[inline]
+    pub fn to_u64(flags: Flags, preset: Preset, x: f64) -> (u64, Status) {
+        let (min, max) = (u64::MIN as i128, u64::MAX as i128);
+        let (value, status) = to_integer::<soft::Binary64>(flags, preset, x.to_bits(), min, max);
+        (value as u64, status)
+    }
+}
@@ -0,0 +1,275 @@
+//! Short chains of dependent operations run with the environment set up once
+//! around all of them, for fused guest instructions and known expression
+//! shapes.
+//!
+//! ```
+//! use sysfp::{Flags, Program};
+//!
+//! // (2 * 3) + 1
+//! let (value, _) = Program::new().mul(2.0f64, 3.0).add_prev(1.0).finish(Flags::new());
+//! assert_eq!(value, 7.0);
+//! ```
+//!
+//! On x86 the steps run between a single `ldmxcsr` and `stmxcsr`, and step by
+//! step elsewhere.
+
+use crate::{Flags, FpOpKind, Status};
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "x86"),
+        target_feature = "sse2",
+        not(any(feature = "soft-float", feature = "x87", miri))
+    ))] {
+        use crate::imp::program as imp;
+    } else {
+        use steps as imp;
+    }
+}
+
+/// The most steps a program holds.
+pub const MAX_STEPS: usize = 8;
+
+/// An operand of a step.
+#[derive(Clone, Copy)]
+pub(crate) enum Operand<T> {
+    Value(T),
+    /// The result of the step before.
+    Prev,
+}
+
+impl<T: Copy> Operand<T> {
+    #[inline]
+    pub(crate) fn value(self, prev: T) -> T {
+        match self {
+            Self::Value(value) => value,
+            Self::Prev => prev,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Step<T> {
+    pub(crate) kind: FpOpKind,
+    pub(crate) operands: [Operand<T>; 3],
+}
+
+/// A chain of up to `MAX_STEPS` operations, whose `_prev` steps take the
+/// result of the step before as their first operand.
+#[derive(Clone, Copy)]
+pub struct Program<T> {
+    steps: [Step<T>; MAX_STEPS],
+    len: usize,
+}
+
+impl<T: Copy> Default for Program<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy> Program<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            steps: [Step {
+                kind: FpOpKind::Add,
+                operands: [Operand::Prev; 3],
+            }; MAX_STEPS],
+            len: 0,
+        }
+    }
+
+    /// Appends a step, panicking when the program is full or when a step
+    /// refers to a result before there is any.
+    #[inline]
+    fn step(mut self, kind: FpOpKind, operands: [Operand<T>; 3]) -> Self {
+        assert!(self.len < MAX_STEPS, "too many steps");
+        assert!(
+            self.len > 0 || operands.iter().all(|op| matches!(op, Operand::Value(_))),
+            "no previous result"
+        );
+        self.steps[self.len] = Step { kind, operands };
+        self.len += 1;
+        self
+    }
+
+    #[inline]
+    pub fn add(self, l: T, r: T) -> Self {
+        self.step(
+            FpOpKind::Add,
+            [Operand::Value(l), Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn add_prev(self, r: T) -> Self {
+        self.step(
+            FpOpKind::Add,
+            [Operand::Prev, Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn sub(self, l: T, r: T) -> Self {
+        self.step(
+            FpOpKind::Sub,
+            [Operand::Value(l), Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn sub_prev(self, r: T) -> Self {
+        self.step(
+            FpOpKind::Sub,
+            [Operand::Prev, Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn mul(self, l: T, r: T) -> Self {
+        self.step(
+            FpOpKind::Mul,
+            [Operand::Value(l), Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn mul_prev(self, r: T) -> Self {
+        self.step(
+            FpOpKind::Mul,
+            [Operand::Prev, Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn div(self, l: T, r: T) -> Self {
+        self.step(
+            FpOpKind::Div,
+            [Operand::Value(l), Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[inline]
+    pub fn div_prev(self, r: T) -> Self {
+        self.step(
+            FpOpKind::Div,
+            [Operand::Prev, Operand::Value(r), Operand::Value(r)],
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(self, a: T, b: T, c: T) -> Self {
+        self.step(
+            FpOpKind::Madd,
+            [Operand::Value(a), Operand::Value(b), Operand::Value(c)],
+        )
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_prev(self, b: T, c: T) -> Self {
+        self.step(
+            FpOpKind::Madd,
+            [Operand::Prev, Operand::Value(b), Operand::Value(c)],
+        )
+    }
+
+    #[inline]
+    pub fn sqrt(self, x: T) -> Self {
+        self.step(
+            FpOpKind::Sqrt,
+            [Operand::Value(x), Operand::Value(x), Operand::Value(x)],
+        )
+    }
+
+    #[inline]
+    pub fn sqrt_prev(self) -> Self {
+        self.step(FpOpKind::Sqrt, [Operand::Prev; 3])
+    }
+
+    #[inline]
+    pub fn round(self, x: T) -> Self {
+        self.step(
+            FpOpKind::Round,
+            [Operand::Value(x), Operand::Value(x), Operand::Value(x)],
+        )
+    }
+
+    #[inline]
+    pub fn round_prev(self) -> Self {
+        self.step(FpOpKind::Round, [Operand::Prev; 3])
+    }
+}
+
+impl Program<f32> {
+    /// Runs the steps, returning the result of the last one and the status of
+    /// all of them, and panicking when there are none.
+    #[inline]
+    pub fn finish(self, flags: Flags) -> (f32, Status) {
+        assert!(self.len > 0, "no steps");
+        imp::f32_run(flags, &self.steps[..self.len])
+    }
+}
+
+impl Program<f64> {
+    /// Runs the steps, returning the result of the last one and the status of
+    /// all of them, and panicking when there are none.
+    #[inline]
+    pub fn finish(self, flags: Flags) -> (f64, Status) {
+        assert!(self.len > 0, "no steps");
+        imp::f64_run(flags, &self.steps[..self.len])
+    }
+}
+
+/// The steps run one by one on the scalar operations.
+#[allow(dead_code)]
+pub(crate) mod steps {
+    use super::*;
+
+    pub(crate) fn f32_run(flags: Flags, steps: &[Step<f32>]) -> (f32, Status) {
+        let mut prev = 0.0;
+        let mut status = Status::empty();
+        for step in steps {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [a, b, c] = step.operands.map(|op| op.value(prev));
+            let (value, step_status) = match step.kind {
+                FpOpKind::Add => crate::f32::add(flags, a, b),
+                FpOpKind::Sub => crate::f32::sub(flags, a, b),
+                FpOpKind::Mul => crate::f32::mul(flags, a, b),
+                FpOpKind::Div => crate::f32::div(flags, a, b),
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => crate::f32::madd(flags, a, b, c),
+                FpOpKind::Sqrt => crate::f32::sqrt(flags, a),
+                FpOpKind::Round => crate::f32::round(flags, a),
+            };
+            prev = value;
+            status = status.or(step_status);
+        }
+        (prev, status)
+    }
+
+    pub(crate) fn f64_run(flags: Flags, steps: &[Step<f64>]) -> (f64, Status) {
+        let mut prev = 0.0;
+        let mut status = Status::empty();
+        for step in steps {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [a, b, c] = step.operands.map(|op| op.value(prev));
+            let (value, step_status) = match step.kind {
+                FpOpKind::Add => crate::f64::add(flags, a, b),
+                FpOpKind::Sub => crate::f64::sub(flags, a, b),
+                FpOpKind::Mul => crate::f64::mul(flags, a, b),
+                FpOpKind::Div => crate::f64::div(flags, a, b),
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => crate::f64::madd(flags, a, b, c),
+                FpOpKind::Sqrt => crate::f64::sqrt(flags, a),
+                FpOpKind::Round => crate::f64::round(flags, a),
+            };
+            prev = value;
+            status = status.or(step_status);
+        }
+        (prev, status)
+    }
+}
@@ -0,0 +1,230 @@
+//! The single-precision arithmetic of the PlayStation 2 Emotion Engine FPU
+//! and vector units, which departs from IEEE 754.
+//!
+//! The format has no infinities or NaNs: the largest exponent encodes
+//! another binade of normal numbers, up to `MAX`. Denormal operands are
+//! zeros, results round towards zero, results beyond the range clamp to
+//! `MAX` with its sign, raising overflow, and nonzero results below it
+//! become zeros with their sign, raising underflow. Nothing is ever
+//! inexact.
+//!
+//! Values are held as their bits, as the host would turn some of them into
+//! NaNs or infinities. The operations compute in double precision, which
+//! holds every value, sum and product exactly.
+
+use core::cmp::Ordering;
+
+use crate::{Flags, Rounding, Status};
+
+/// The largest magnitude, `(2 - 2^-23) * 2^128`.
+pub const MAX: u32 = 0x7FFF_FFFF;
+
+/// The condition bit of the EE FCR31, which the comparisons set.
+pub const C: u32 = 1 << 23;
+/// The invalid operation flag of the last operation.
+pub const I: u32 = 1 << 17;
+/// The divide-by-zero flag of the last operation.
+pub const D: u32 = 1 << 16;
+/// The overflow flag of the last operation.
+pub const O: u32 = 1 << 15;
+/// The underflow flag of the last operation.
+pub const U: u32 = 1 << 14;
+/// The sticky invalid operation flag.
+pub const SI: u32 = 1 << 6;
+/// The sticky divide-by-zero flag.
+pub const SD: u32 = 1 << 5;
+/// The sticky overflow flag.
+pub const SO: u32 = 1 << 4;
+/// The sticky underflow flag.
+pub const SU: u32 = 1 << 3;
+/// The distance between a flag of the last operation and its sticky flag.
+pub const STICKY_SHIFT: u32 = 11;
+
+const SIGN: u32 = 1 << 31;
+/// Rounding towards zero in double precision before truncating to single
+/// precision gives the result of truncating once.
+const FLAGS: Flags = Flags::new().with_rounding(Rounding::Zero);
+
+#[inline]
+fn biased(x: u32) -> i32 {
+    (x >> 23 & 0xFF) as i32
+}
+
+/// The value of `x`, exactly.
+#[inline]
+fn unpack(x: u32) -> f64 {
+    let sign = ((x & SIGN) as u64) << 32;
+    if biased(x) == 0 {
+        return f64::from_bits(sign);
+    }
+    let exp = (biased(x) - 127 + 1023) as u64;
+    f64::from_bits(sign | exp << 52 | ((x & 0x7F_FFFF) as u64) << 29)
+}
+
+/// `x` truncated to the format, clamped or flushed as it overflows or
+/// underflows.
+#[inline]
+fn pack(x: f64) -> (u32, Status) {
+    let bits = x.to_bits();
+    let sign = (bits >> 32) as u32 & SIGN;
+    if x == 0.0 {
+        return (sign, Status::empty());
+    }
+    let exp = (bits >> 52 & 0x7FF) as i32 - 1023;
+    if exp < -126 {
+        (sign, Status::UNDERFLOW)
+    } else if exp > 128 {
+        (sign | MAX, Status::OVERFLOW)
+    } else {
+        let frac = (bits >> 29) as u32 & 0x7F_FFFF;
+        (sign | ((exp + 127) as u32) << 23 | frac, Status::empty())
+    }
+}
+
+/// `l` and `r` without the bits of the smaller one that fall more than one
+/// place below the larger one, which the adder drops instead of keeping a
+/// sticky bit.
+#[inline]
+fn align(l: u32, r: u32) -> (u32, u32) {
+    let diff = biased(l) - biased(r);
+    match diff {
+        25.. => (l, r & SIGN),
+        1..=24 => (l, r & !0 << (diff - 1)),
+        0 => (l, r),
+        -24..=-1 => (l & !0 << (-diff - 1), r),
+        _ => (l & SIGN, r),
+    }
+}
+
+#[inline]
+pub fn add(l: u32, r: u32) -> (u32, Status) {
+    let (l, r) = align(l, r);
+    pack(crate::f64::add(FLAGS, unpack(l), unpack(r)).0)
+}
+
+#[inline]
+pub fn sub(l: u32, r: u32) -> (u32, Status) {
+    add(l, r ^ SIGN)
+}
+
+#[inline]
+pub fn mul(l: u32, r: u32) -> (u32, Status) {
+    pack(crate::f64::mul(FLAGS, unpack(l), unpack(r)).0)
+}
+
+/// Computes `acc + l * r`, rounding the product first as `madd.s` does.
+#[inline]
+pub fn madd(acc: u32, l: u32, r: u32) -> (u32, Status) {
+    let (product, status) = mul(l, r);
+    let (value, sum) = add(acc, product);
+    (value, status.or(sum))
+}
+
+/// Computes `acc - l * r`, rounding the product first as `msub.s` does.
+#[inline]
+pub fn msub(acc: u32, l: u32, r: u32) -> (u32, Status) {
+    let (product, status) = mul(l, r);
+    let (value, sum) = sub(acc, product);
+    (value, status.or(sum))
+}
+
+/// Divides `l` by `r`. A zero divisor gives `MAX` with the sign of the
+/// quotient, raising invalid if `l` is also zero and divide-by-zero
+/// otherwise.
+#[inline]
+pub fn div(l: u32, r: u32) -> (u32, Status) {
+    if biased(r) == 0 {
+        let status = if biased(l) == 0 {
+            Status::INVALID
+        } else {
+            Status::DIV_ZERO
+        };
+        return ((l ^ r) & SIGN | MAX, status);
+    }
+    pack(crate::f64::div(FLAGS, unpack(l), unpack(r)).0)
+}
+
+/// The square root of the magnitude of `x`, raising invalid if `x` is
+/// negative and not zero.
+#[inline]
+pub fn sqrt(x: u32) -> (u32, Status) {
+    if biased(x) == 0 {
+        return (x & SIGN, Status::empty());
+    }
+    let status = if x & SIGN != 0 {
+        Status::INVALID
+    } else {
+        Status::empty()
+    };
+    let (value, _) = pack(crate::f64::sqrt(FLAGS, unpack(x & !SIGN)).0);
+    (value, status)
+}
+
+/// Divides `l` by the square root of the magnitude of `r`, raising invalid
+/// if `r` is negative. A zero `r` gives `MAX` with the sign of `r`, raising
+/// divide-by-zero. Results beyond the range clamp or flush without raising
+/// anything.
+#[inline]
+pub fn rsqrt(l: u32, r: u32) -> (u32, Status) {
+    if biased(r) == 0 {
+        return (r & SIGN | MAX, Status::DIV_ZERO);
+    }
+    let status = if r & SIGN != 0 {
+        Status::INVALID
+    } else {
+        Status::empty()
+    };
+    let root = crate::f64::sqrt(FLAGS, unpack(r & !SIGN)).0;
+    let (value, _) = pack(crate::f64::div(FLAGS, unpack(l), root).0);
+    (value, status)
+}
+
+/// Compares `l` with `r`, any two zeros being equal.
+#[inline]
+pub fn compare(l: u32, r: u32) -> Ordering {
+    let key = |x: u32| {
+        let magnitude = if biased(x) == 0 { 0 } else { x & !SIGN } as i64;
+        if x & SIGN != 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    };
+    key(l).cmp(&key(r))
+}
+
+/// Converts `x` to an integer, truncating and saturating, as `cvt.w.s`
+/// does.
+#[inline]
+pub fn to_int(x: u32) -> i32 {
+    let value = unpack(x);
+    if value >= 2_147_483_648.0 {
+        i32::MAX
+    } else if value <= -2_147_483_648.0 {
+        i32::MIN
+    } else {
+        value as i32
+    }
+}
+
+/// Converts `int`, truncating, as `cvt.s.w` does.
+#[inline]
+pub fn from_int(int: i32) -> u32 {
+    pack(int as f64).0
+}
+
+/// The FCR31 after an operation raising `status`: the flags of the last
+/// operation hold its exceptions, which the sticky flags accumulate.
+#[inline]
+pub fn update(fcr31: u32, status: Status) -> u32 {
+    let exceptions = [
+        (status.invalid(), I),
+        (status.div_zero(), D),
+        (status.overflow(), O),
+        (status.underflow(), U),
+    ]
+    .iter()
+    .filter(|&&(raised, _)| raised)
+    .fold(0, |bits, &(_, bit)| bits | bit);
+    fcr31 & !(I | D | O | U) | exceptions | exceptions >> STICKY_SHIFT
+}
@@ -0,0 +1,158 @@
+//! Operations that never record exceptions, for callers that don't want to
+//! perturb the status they track.
+//!
+//! With AVX-512 these use the suppress-all-exceptions forms of the
+//! instructions where there are any, otherwise the software implementation
+//! runs them and drops whatever they raise.
+
+use core::cmp::Ordering;
+
+use crate::Flags;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        target_feature = "avx512f",
+        not(any(feature = "soft-float", feature = "x87", miri))
+    ))] {
+        use crate::imp::sae as imp;
+    } else {
+        use soft as imp;
+    }
+}
+
+/// The operations run in software, whose exceptions are dropped.
+pub(crate) mod soft {
+    use super::*;
+    use crate::soft;
+
+    #[inline]
+    pub(crate) fn f32_compare(flags: Flags, l: f32, r: f32) -> Option<Ordering> {
+        soft::compare::<soft::Binary32>(&flags.soft_env(), l.to_bits() as u64, r.to_bits() as u64).0
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_double(flags: Flags, single: f32) -> f64 {
+        let (bits, _) = soft::convert::<soft::Binary32, soft::Binary64>(
+            &flags.soft_env(),
+            single.to_bits() as u64,
+        );
+        f64::from_bits(bits)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_half(flags: Flags, single: f32) -> u16 {
+        let (bits, _) = soft::convert::<soft::Binary32, soft::Binary16>(
+            &flags.soft_env(),
+            single.to_bits() as u64,
+        );
+        bits as u16
+    }
+
+    #[inline]
+    pub(crate) fn f64_compare(flags: Flags, l: f64, r: f64) -> Option<Ordering> {
+        soft::compare::<soft::Binary64>(&flags.soft_env(), l.to_bits(), r.to_bits()).0
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_to_single(flags: Flags, double: f64) -> f32 {
+        let (bits, _) =
+            soft::convert::<soft::Binary64, soft::Binary32>(&flags.soft_env(), double.to_bits());
+        f32::from_bits(bits as u32)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_to_half(flags: Flags, double: f64) -> u16 {
+        let (bits, _) =
+            soft::convert::<soft::Binary64, soft::Binary16>(&flags.soft_env(), double.to_bits());
+        bits as u16
+    }
+
+    #[inline]
+    pub(crate) fn f16_compare(flags: Flags, l: u16, r: u16) -> Option<Ordering> {
+        soft::compare::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64).0
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f16_to_single(flags: Flags, half: u16) -> f32 {
+        let (bits, _) =
+            soft::convert::<soft::Binary16, soft::Binary32>(&flags.soft_env(), half as u64);
+        f32::from_bits(bits as u32)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f16_to_double(flags: Flags, half: u16) -> f64 {
+        let (bits, _) =
+            soft::convert::<soft::Binary16, soft::Binary64>(&flags.soft_env(), half as u64);
+        f64::from_bits(bits)
+    }
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn compare(flags: Flags, l: f32, r: f32) -> Option<Ordering> {
+        imp::f32_compare(flags, l, r)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, single: f32) -> f64 {
+        imp::f32_to_double(flags, single)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> u16 {
+        soft::f32_to_half(flags, single)
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn compare(flags: Flags, l: f64, r: f64) -> Option<Ordering> {
+        imp::f64_compare(flags, l, r)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, double: f64) -> f32 {
+        soft::f64_to_single(flags, double)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> u16 {
+        soft::f64_to_half(flags, double)
+    }
+}
+
+/// Half-precision operations on the bit patterns of the values.
+pub mod f16 {
+    use super::*;
+
+    #[inline]
+    pub fn compare(flags: Flags, l: u16, r: u16) -> Option<Ordering> {
+        soft::f16_compare(flags, l, r)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, half: u16) -> f32 {
+        soft::f16_to_single(flags, half)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, half: u16) -> f64 {
+        soft::f16_to_double(flags, half)
+    }
+}
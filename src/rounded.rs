@@ -0,0 +1,204 @@
+//! Doubles that round every operation on them by a mode fixed in their type,
+//! so ordinary arithmetic expressions run under it.
+//!
+//! Each value carries the exceptions raised computing it, which the
+//! operations accumulate from their operands, so the status of a whole
+//! expression can be read off its result.
+
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+use crate::{Flags, Rounding, Status};
+
+/// A rounding mode, as a type.
+pub trait RoundingMode: Copy {
+    const ROUNDING: Rounding;
+}
+
+macro_rules! modes {
+    ($($(#[$attr:meta])* $name:ident,)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Clone, Copy)]
+            pub struct $name;
+
+            impl RoundingMode for $name {
+                const ROUNDING: Rounding = Rounding::$name;
+            }
+        )*
+    };
+}
+
+modes! {
+    /// Rounds towards nearest, ties to even.
+    Nearest,
+    /// Rounds towards nearest, ties away from zero.
+    NearestAway,
+    /// Rounds towards zero.
+    Zero,
+    /// Rounds towards positive infinity.
+    Up,
+    /// Rounds towards negative infinity.
+    Down,
+    /// Rounds towards zero, setting the last bit of inexact results.
+    Odd,
+}
+
+/// A double whose operations round by `R`, with the exceptions raised
+/// computing it.
+#[derive(Clone, Copy)]
+pub struct Rounded<R: RoundingMode> {
+    value: f64,
+    status: Status,
+    mode: PhantomData<R>,
+}
+
+impl<R: RoundingMode> Rounded<R> {
+    const FLAGS: Flags = Flags::new().with_rounding(R::ROUNDING);
+
+    /// `value`, with no exceptions raised.
+    #[inline]
+    pub fn new(value: f64) -> Self {
+        Self::with_status(value, Status::empty())
+    }
+
+    #[inline]
+    fn with_status(value: f64, status: Status) -> Self {
+        Self {
+            value,
+            status,
+            mode: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn value(self) -> f64 {
+        self.value
+    }
+
+    /// The exceptions raised computing the value.
+    #[inline]
+    pub fn status(self) -> Status {
+        self.status
+    }
+
+    /// The value with the exceptions cleared, for starting a new expression
+    /// from a result.
+    #[inline]
+    pub fn clear_status(self) -> Self {
+        Self::new(self.value)
+    }
+
+    /// Applies `op` to the values under the mode, accumulating the status of
+    /// the operands.
+    #[inline]
+    fn apply(self, other: Self, op: fn(Flags, f64, f64) -> (f64, Status)) -> Self {
+        let (value, status) = op(Self::FLAGS, self.value, other.value);
+        Self::with_status(value, self.status.or(other.status).or(status))
+    }
+
+    #[inline]
+    pub fn sqrt(self) -> Self {
+        let (value, status) = crate::f64::sqrt(Self::FLAGS, self.value);
+        Self::with_status(value, self.status.or(status))
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes `self * b + c` with a single rounding.
+    #[inline]
+    pub fn mul_add(self, b: Self, c: Self) -> Self {
+        let (value, status) = crate::f64::madd(Self::FLAGS, self.value, b.value, c.value);
+        Self::with_status(value, self.status.or(b.status).or(c.status).or(status))
+    }
+}
+
+/// The remainder of truncated division, which is always exact, raising
+/// invalid for an infinite dividend, a zero divisor or a signaling NaN.
+impl<R: RoundingMode> Rem for Rounded<R> {
+    type Output = Self;
+
+    #[inline]
+    fn rem(self, other: Self) -> Self {
+        let signaling = |x: f64| x.is_nan() && x.to_bits() & 1 << 51 == 0;
+        let value = self.value % other.value;
+        let invalid = value.is_nan() && !(self.value.is_nan() || other.value.is_nan())
+            || signaling(self.value)
+            || signaling(other.value);
+        let status = if invalid {
+            Status::INVALID
+        } else {
+            Status::empty()
+        };
+        Self::with_status(value, self.status.or(other.status).or(status))
+    }
+}
+
+impl<R: RoundingMode> RemAssign for Rounded<R> {
+    #[inline]
+    fn rem_assign(&mut self, other: Self) {
+        *self = *self % other;
+    }
+}
+
+impl<R: RoundingMode> From<f64> for Rounded<R> {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Compares the values, whatever exceptions computing them raised.
+impl<R: RoundingMode> PartialEq for Rounded<R> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<R: RoundingMode> PartialOrd for Rounded<R> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<R: RoundingMode> Neg for Rounded<R> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::with_status(-self.value, self.status)
+    }
+}
+
+macro_rules! ops {
+    ($($trait:ident, $method:ident, $assign:ident, $assign_method:ident;)*) => {
+        $(
+            impl<R: RoundingMode> $trait for Rounded<R> {
+                type Output = Self;
+
+                #[inline]
+                fn $method(self, other: Self) -> Self {
+                    self.apply(other, crate::f64::$method)
+                }
+            }
+
+            impl<R: RoundingMode> $assign for Rounded<R> {
+                #[inline]
+                fn $assign_method(&mut self, other: Self) {
+                    *self = self.$method(other);
+                }
+            }
+        )*
+    };
+}
+
+ops! {
+    Add, add, AddAssign, add_assign;
+    Sub, sub, SubAssign, sub_assign;
+    Mul, mul, MulAssign, mul_assign;
+    Div, div, DivAssign, div_assign;
+}
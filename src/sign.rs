@@ -0,0 +1,49 @@
+//! The sign-injection operations of RISC-V's `fsgnj`, `fsgnjn` and
+//! `fsgnjx`, which give the magnitude of `l` a sign taken from `r`.
+//!
+//! These only move bits, so they raise no exceptions and pass NaNs through
+//! unquieted, as moves, negations and absolute values built on them must.
+
+pub(crate) mod f32 {
+    const SIGN: u32 = 1 << 31;
+
+    /// `l` with the sign of `r`.
+    #[inline]
+    pub fn sgnj(l: f32, r: f32) -> f32 {
+        f32::from_bits(l.to_bits() & !SIGN | r.to_bits() & SIGN)
+    }
+
+    /// `l` with the opposite sign of `r`.
+    #[inline]
+    pub fn sgnjn(l: f32, r: f32) -> f32 {
+        f32::from_bits(l.to_bits() & !SIGN | !r.to_bits() & SIGN)
+    }
+
+    /// `l` with the sign of `l` and `r` combined by exclusive or.
+    #[inline]
+    pub fn sgnjx(l: f32, r: f32) -> f32 {
+        f32::from_bits(l.to_bits() ^ r.to_bits() & SIGN)
+    }
+}
+
+pub(crate) mod f64 {
+    const SIGN: u64 = 1 << 63;
+
+    /// `l` with the sign of `r`.
+    #[inline]
+    pub fn sgnj(l: f64, r: f64) -> f64 {
+        f64::from_bits(l.to_bits() & !SIGN | r.to_bits() & SIGN)
+    }
+
+    /// `l` with the opposite sign of `r`.
+    #[inline]
+    pub fn sgnjn(l: f64, r: f64) -> f64 {
+        f64::from_bits(l.to_bits() & !SIGN | !r.to_bits() & SIGN)
+    }
+
+    /// `l` with the sign of `l` and `r` combined by exclusive or.
+    #[inline]
+    pub fn sgnjx(l: f64, r: f64) -> f64 {
+        f64::from_bits(l.to_bits() ^ r.to_bits() & SIGN)
+    }
+}
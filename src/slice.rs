@@ -0,0 +1,1033 @@
+//! Operations over whole slices, which set up the environment once for every
+//! element and combine the status of the elements.
+//!
+//! These run on packed SSE instructions on x86, and element by element
+//! elsewhere. They panic when the slices differ in length.
+//!
+//! With the `rayon` feature, the `par_` variants of the elementwise operations
+//! split the slices between the threads of the rayon pool, each setting up
+//! its own environment, and combine the status of every chunk.
+//!
+//! With the `alloc` feature, the `_all` variants return the results in a new
+//! `Vec` instead of overwriting a slice.
+//!
+//! The `_reproducible` reductions fix the order of their operations
+//! independently of the backend and the number of threads, for results that
+//! are the same everywhere the elementwise operations are.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{soft, Flags, Status};
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "x86"),
+        target_feature = "sse2",
+        not(any(feature = "soft-float", feature = "x87", miri))
+    ))] {
+        pub(crate) use crate::imp::slice as imp;
+    } else {
+        pub(crate) use elements as imp;
+    }
+}
+
+/// How `min` and `max` treat NaNs and zeros.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Returns the later operand unless the earlier one compares strictly
+    /// smaller or larger, as `minsd` and `maxsd` do, so a NaN is dropped
+    /// unless it comes last, and flags any NaN as invalid.
+    Hardware,
+    /// IEEE 754-2019 `minimum` and `maximum`, which propagate NaNs, order
+    /// negative zero below positive zero and flag only signaling NaNs.
+    Propagate,
+}
+
+/// The number of elements every thread takes at once, large enough for the
+/// cost of setting up the environment to vanish.
+#[cfg(feature = "rayon")]
+const PAR_CHUNK: usize = 4096;
+
+/// The number of elements the reproducible reductions take from left to
+/// right before combining the results of the blocks.
+const TREE_BLOCK: usize = 1024;
+
+/// Reduces `blocks` blocks from block `first` on, splitting them at the
+/// largest power of two below their number and combining the halves, which
+/// adds the results of neighbouring blocks pairwise and carries an odd last
+/// one up to the next level.
+fn tree<T, B, C>(first: usize, blocks: usize, block: &B, combine: &C) -> (T, Status)
+where
+    B: Fn(usize) -> (T, Status),
+    C: Fn(T, T) -> (T, Status),
+{
+    if blocks == 1 {
+        return block(first);
+    }
+    let half = 1 << (blocks - 1).ilog2();
+    let (l, l_status) = tree(first, half, block, combine);
+    let (r, r_status) = tree(first + half, blocks - half, block, combine);
+    let (value, status) = combine(l, r);
+    (value, l_status.or(r_status).or(status))
+}
+
+/// Reduces like `tree`, running the halves on the rayon pool.
+#[cfg(feature = "rayon")]
+fn par_tree<T, B, C>(first: usize, blocks: usize, block: &B, combine: &C) -> (T, Status)
+where
+    T: Send,
+    B: Fn(usize) -> (T, Status) + Sync,
+    C: Fn(T, T) -> (T, Status) + Sync,
+{
+    if blocks == 1 {
+        return block(first);
+    }
+    let half = 1 << (blocks - 1).ilog2();
+    let ((l, l_status), (r, r_status)) = rayon::join(
+        || par_tree(first, half, block, combine),
+        || par_tree(first + half, blocks - half, block, combine),
+    );
+    let (value, status) = combine(l, r);
+    (value, l_status.or(r_status).or(status))
+}
+
+/// The elements of block `index` of `src`.
+#[inline]
+fn tree_block<T>(src: &[T], index: usize) -> &[T] {
+    let start = index * TREE_BLOCK;
+    &src[start..src.len().min(start + TREE_BLOCK)]
+}
+
+/// The operations run element by element on the scalar operations.
+#[allow(dead_code)]
+pub(crate) mod elements {
+    use super::*;
+
+    #[inline]
+    pub(crate) fn f32_add_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f32::add(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f32_sub_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f32::sub(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f32_mul_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f32::mul(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f32_div_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f32::div(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32_madd_assign(flags: Flags, dst: &mut [f32], b: &[f32], c: &[f32]) -> Status {
+        let mut status = Status::empty();
+        for ((dst, &b), &c) in dst.iter_mut().zip(b).zip(c) {
+            let (value, element) = crate::f32::madd(flags, *dst, b, c);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f64_add_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f64::add(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f64_sub_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f64::sub(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f64_mul_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f64::mul(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f64_div_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f64::div(flags, *dst, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64_madd_assign(flags: Flags, dst: &mut [f64], b: &[f64], c: &[f64]) -> Status {
+        let mut status = Status::empty();
+        for ((dst, &b), &c) in dst.iter_mut().zip(b).zip(c) {
+            let (value, element) = crate::f64::madd(flags, *dst, b, c);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[inline]
+    pub(crate) fn f32_sum(flags: Flags, first: f32, rest: &[f32]) -> (f32, Status) {
+        let mut sum = first;
+        let mut status = Status::empty();
+        for &value in rest {
+            let (partial, element) = crate::f32::add(flags, sum, value);
+            sum = partial;
+            status = status.or(element);
+        }
+        (sum, status)
+    }
+
+    #[inline]
+    pub(crate) fn f64_sum(flags: Flags, first: f64, rest: &[f64]) -> (f64, Status) {
+        let mut sum = first;
+        let mut status = Status::empty();
+        for &value in rest {
+            let (partial, element) = crate::f64::add(flags, sum, value);
+            sum = partial;
+            status = status.or(element);
+        }
+        (sum, status)
+    }
+
+    pub(crate) fn f64_sum_compensated(
+        flags: Flags,
+        first: f64,
+        rest: &[f64],
+    ) -> (f64, f64, Status) {
+        let mut sum = first;
+        let mut correction = 0.0;
+        let mut status = Status::empty();
+        let mut raise = |(value, element): (f64, Status)| {
+            status = status.or(element);
+            value
+        };
+        for &value in rest {
+            let partial = raise(crate::f64::add(flags, sum, value));
+            // The rounding error of the partial sum, recovered from the
+            // larger addend, which an infinite or NaN sum has no use for.
+            if partial.is_finite() {
+                let (large, small) = if sum.abs() >= value.abs() {
+                    (sum, value)
+                } else {
+                    (value, sum)
+                };
+                let lost = raise(crate::f64::sub(flags, large, partial));
+                let lost = raise(crate::f64::add(flags, lost, small));
+                correction = raise(crate::f64::add(flags, correction, lost));
+            }
+            sum = partial;
+        }
+        if !sum.is_finite() {
+            return (sum, 0.0, status);
+        }
+        let compensated = raise(crate::f64::add(flags, sum, correction));
+        (compensated, correction, status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32_dot(flags: Flags, a: &[f32], b: &[f32]) -> (f32, Status) {
+        let (mut dot, mut status) = crate::f32::mul(flags, a[0], b[0]);
+        for (&a, &b) in a[1..].iter().zip(&b[1..]) {
+            let (partial, element) = crate::f32::madd(flags, a, b, dot);
+            dot = partial;
+            status = status.or(element);
+        }
+        (dot, status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64_dot(flags: Flags, a: &[f64], b: &[f64]) -> (f64, Status) {
+        let (mut dot, mut status) = crate::f64::mul(flags, a[0], b[0]);
+        for (&a, &b) in a[1..].iter().zip(&b[1..]) {
+            let (partial, element) = crate::f64::madd(flags, a, b, dot);
+            dot = partial;
+            status = status.or(element);
+        }
+        (dot, status)
+    }
+
+    #[inline]
+    pub(crate) fn f32_min(flags: Flags, policy: NanPolicy, src: &[f32]) -> (f32, Status) {
+        f32_min_max(flags, policy, false, src)
+    }
+
+    #[inline]
+    pub(crate) fn f32_max(flags: Flags, policy: NanPolicy, src: &[f32]) -> (f32, Status) {
+        f32_min_max(flags, policy, true, src)
+    }
+
+    fn f32_min_max(flags: Flags, policy: NanPolicy, max: bool, src: &[f32]) -> (f32, Status) {
+        let env = flags.soft_env();
+        let ieee = policy == NanPolicy::Propagate;
+        let mut bits = src[0].to_bits() as u64;
+        let mut exc = 0;
+        for &value in &src[1..] {
+            let (picked, element) =
+                soft::min_max::<soft::Binary32>(&env, bits, value.to_bits() as u64, max, ieee);
+            bits = picked;
+            exc |= element;
+        }
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub(crate) fn f64_min(flags: Flags, policy: NanPolicy, src: &[f64]) -> (f64, Status) {
+        f64_min_max(flags, policy, false, src)
+    }
+
+    #[inline]
+    pub(crate) fn f64_max(flags: Flags, policy: NanPolicy, src: &[f64]) -> (f64, Status) {
+        f64_min_max(flags, policy, true, src)
+    }
+
+    fn f64_min_max(flags: Flags, policy: NanPolicy, max: bool, src: &[f64]) -> (f64, Status) {
+        let env = flags.soft_env();
+        let ieee = policy == NanPolicy::Propagate;
+        let mut bits = src[0].to_bits();
+        let mut exc = 0;
+        for &value in &src[1..] {
+            let (picked, element) =
+                soft::min_max::<soft::Binary64>(&env, bits, value.to_bits(), max, ieee);
+            bits = picked;
+            exc |= element;
+        }
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_double(flags: Flags, dst: &mut [f64], src: &[f32]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f32::to_double(flags, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_int(flags: Flags, dst: &mut [i32], src: &[f32]) -> Status {
+        let env = flags.soft_env();
+        let mut exc = 0;
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = soft::to_int::<soft::Binary32>(&env, src.to_bits() as u64);
+            *dst = value;
+            exc |= element;
+        }
+        Status::from_soft(exc)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_from_int(flags: Flags, dst: &mut [f32], src: &[i32]) -> Status {
+        let env = flags.soft_env();
+        let mut exc = 0;
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (bits, element) = soft::from_int::<soft::Binary32>(&env, src);
+            *dst = f32::from_bits(bits as u32);
+            exc |= element;
+        }
+        Status::from_soft(exc)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_to_single(flags: Flags, dst: &mut [f32], src: &[f64]) -> Status {
+        let mut status = Status::empty();
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = crate::f64::to_single(flags, src);
+            *dst = value;
+            status = status.or(element);
+        }
+        status
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_to_int(flags: Flags, dst: &mut [i32], src: &[f64]) -> Status {
+        let env = flags.soft_env();
+        let mut exc = 0;
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (value, element) = soft::to_int::<soft::Binary64>(&env, src.to_bits());
+            *dst = value;
+            exc |= element;
+        }
+        Status::from_soft(exc)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_from_int(flags: Flags, dst: &mut [f64], src: &[i32]) -> Status {
+        let env = flags.soft_env();
+        let mut exc = 0;
+        for (dst, &src) in dst.iter_mut().zip(src) {
+            let (bits, element) = soft::from_int::<soft::Binary64>(&env, src);
+            *dst = f64::from_bits(bits);
+            exc |= element;
+        }
+        Status::from_soft(exc)
+    }
+}
+
+/// Slices of single-precision values.
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_add_assign(flags, dst, src)
+    }
+
+    #[inline]
+    pub fn sub_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_sub_assign(flags, dst, src)
+    }
+
+    #[inline]
+    pub fn mul_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_mul_assign(flags, dst, src)
+    }
+
+    #[inline]
+    pub fn div_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_div_assign(flags, dst, src)
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes `dst * b + c` into `dst`.
+    #[inline]
+    pub fn madd_assign(flags: Flags, dst: &mut [f32], b: &[f32], c: &[f32]) -> Status {
+        assert_eq!(dst.len(), b.len());
+        assert_eq!(dst.len(), c.len());
+        imp::f32_madd_assign(flags, dst, b, c)
+    }
+
+    /// Sums `src` from left to right, as `(src[0] + src[1]) + src[2]` and so
+    /// on, so that rounding up or down bounds the exact sum. An empty slice
+    /// sums to positive zero.
+    #[inline]
+    pub fn sum(flags: Flags, src: &[f32]) -> (f32, Status) {
+        match src.split_first() {
+            Some((&first, rest)) => imp::f32_sum(flags, first, rest),
+            None => (0.0, Status::empty()),
+        }
+    }
+
+    /// Sums `src` exactly and rounds the sum once in the rounding mode, so
+    /// that the result doesn't depend on the order of the elements, and
+    /// rounding up and down gives the tightest bounds on the exact sum. An
+    /// exact zero sum is positive unless every element is a negative zero
+    /// or the rounding is down, and an empty slice sums to positive zero.
+    #[inline]
+    pub fn sum_exact(flags: Flags, src: &[f32]) -> (f32, Status) {
+        if src.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let values = src.iter().map(|value| value.to_bits() as u64);
+        let (bits, exc) = soft::sum_exact::<soft::Binary32>(&flags.soft_env(), values);
+        (f32::from_bits(bits as u32), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes the dot product from left to right, multiplying `a[0] * b[0]`
+    /// and then fusing every other product into it, so that rounding up or
+    /// down bounds the exact dot product. Empty slices give positive zero.
+    #[inline]
+    pub fn dot(flags: Flags, a: &[f32], b: &[f32]) -> (f32, Status) {
+        assert_eq!(a.len(), b.len());
+        if a.is_empty() {
+            return (0.0, Status::empty());
+        }
+        imp::f32_dot(flags, a, b)
+    }
+
+    /// Sums `src` in a fixed order: blocks of 1024 elements from left to
+    /// right, as `sum` does, and then the block sums pairwise, as
+    /// `(s[0] + s[1]) + (s[2] + s[3])` and so on, carrying an odd last sum up
+    /// to the next level. An empty slice sums to positive zero.
+    #[inline]
+    pub fn sum_reproducible(flags: Flags, src: &[f32]) -> (f32, Status) {
+        if src.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| {
+            let block = tree_block(src, index);
+            imp::f32_sum(flags, block[0], &block[1..])
+        };
+        let combine = |l, r| crate::f32::add(flags, l, r);
+        tree(0, src.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes the dot product in the fixed order of `sum_reproducible`,
+    /// with blocks of 1024 products computed as `dot` does. Empty slices
+    /// give positive zero.
+    #[inline]
+    pub fn dot_reproducible(flags: Flags, a: &[f32], b: &[f32]) -> (f32, Status) {
+        assert_eq!(a.len(), b.len());
+        if a.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| imp::f32_dot(flags, tree_block(a, index), tree_block(b, index));
+        let combine = |l, r| crate::f32::add(flags, l, r);
+        tree(0, a.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    /// Finds the smallest element from left to right under `policy`. An
+    /// empty slice gives positive infinity.
+    #[inline]
+    pub fn min(flags: Flags, policy: NanPolicy, src: &[f32]) -> (f32, Status) {
+        if src.is_empty() {
+            return (f32::INFINITY, Status::empty());
+        }
+        imp::f32_min(flags, policy, src)
+    }
+
+    /// Finds the largest element from left to right under `policy`. An empty
+    /// slice gives negative infinity.
+    #[inline]
+    pub fn max(flags: Flags, policy: NanPolicy, src: &[f32]) -> (f32, Status) {
+        if src.is_empty() {
+            return (f32::NEG_INFINITY, Status::empty());
+        }
+        imp::f32_max(flags, policy, src)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts `src` to double precision into `dst`.
+    #[inline]
+    pub fn to_double(flags: Flags, dst: &mut [f64], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_to_double(flags, dst, src)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts `src` to integers in the rounding mode into `dst`. NaNs and
+    /// values out of range become `i32::MIN` and raise invalid, as on x86.
+    #[inline]
+    pub fn to_int(flags: Flags, dst: &mut [i32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_to_int(flags, dst, src)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts the integers in `src` into `dst`.
+    #[inline]
+    pub fn from_int(flags: Flags, dst: &mut [f32], src: &[i32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f32_from_int(flags, dst, src)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_add_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f32_add_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_sub_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f32_sub_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_mul_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f32_mul_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_div_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f32_div_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_madd_assign(flags: Flags, dst: &mut [f32], b: &[f32], c: &[f32]) -> Status {
+        assert_eq!(dst.len(), b.len());
+        assert_eq!(dst.len(), c.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(b.par_chunks(PAR_CHUNK))
+            .zip(c.par_chunks(PAR_CHUNK))
+            .map(|((dst, b), c)| imp::f32_madd_assign(flags, dst, b, c))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Sums `src` like `sum_reproducible`, giving the same result on any
+    /// number of threads.
+    #[inline]
+    pub fn par_sum_reproducible(flags: Flags, src: &[f32]) -> (f32, Status) {
+        if src.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| {
+            let block = tree_block(src, index);
+            imp::f32_sum(flags, block[0], &block[1..])
+        };
+        let combine = |l, r| crate::f32::add(flags, l, r);
+        par_tree(0, src.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(feature = "rayon")]
+    /// Computes the dot product like `dot_reproducible`, giving the same
+    /// result on any number of threads.
+    #[inline]
+    pub fn par_dot_reproducible(flags: Flags, a: &[f32], b: &[f32]) -> (f32, Status) {
+        assert_eq!(a.len(), b.len());
+        if a.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| imp::f32_dot(flags, tree_block(a, index), tree_block(b, index));
+        let combine = |l, r| crate::f32::add(flags, l, r);
+        par_tree(0, a.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn add_all(flags: Flags, l: &[f32], r: &[f32]) -> (Vec<f32>, Status) {
+        let mut values = l.to_vec();
+        let status = add_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn sub_all(flags: Flags, l: &[f32], r: &[f32]) -> (Vec<f32>, Status) {
+        let mut values = l.to_vec();
+        let status = sub_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn mul_all(flags: Flags, l: &[f32], r: &[f32]) -> (Vec<f32>, Status) {
+        let mut values = l.to_vec();
+        let status = mul_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn div_all(flags: Flags, l: &[f32], r: &[f32]) -> (Vec<f32>, Status) {
+        let mut values = l.to_vec();
+        let status = div_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn madd_all(flags: Flags, a: &[f32], b: &[f32], c: &[f32]) -> (Vec<f32>, Status) {
+        let mut values = a.to_vec();
+        let status = madd_assign(flags, &mut values, b, c);
+        (values, status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn to_double_all(flags: Flags, src: &[f32]) -> (Vec<f64>, Status) {
+        let mut status = Status::empty();
+        let values = src
+            .iter()
+            .map(|&value| {
+                let (value, element) = crate::f32::to_double(flags, value);
+                status = status.or(element);
+                value
+            })
+            .collect();
+        (values, status)
+    }
+}
+
+/// Slices of double-precision values.
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_add_assign(flags, dst, src)
+    }
+
+    #[inline]
+    pub fn sub_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_sub_assign(flags, dst, src)
+    }
+
+    #[inline]
+    pub fn mul_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_mul_assign(flags, dst, src)
+    }
+
+    #[inline]
+    pub fn div_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_div_assign(flags, dst, src)
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes `dst * b + c` into `dst`.
+    #[inline]
+    pub fn madd_assign(flags: Flags, dst: &mut [f64], b: &[f64], c: &[f64]) -> Status {
+        assert_eq!(dst.len(), b.len());
+        assert_eq!(dst.len(), c.len());
+        imp::f64_madd_assign(flags, dst, b, c)
+    }
+
+    /// Sums `src` from left to right, as `(src[0] + src[1]) + src[2]` and so
+    /// on, so that rounding up or down bounds the exact sum. An empty slice
+    /// sums to positive zero.
+    #[inline]
+    pub fn sum(flags: Flags, src: &[f64]) -> (f64, Status) {
+        match src.split_first() {
+            Some((&first, rest)) => imp::f64_sum(flags, first, rest),
+            None => (0.0, Status::empty()),
+        }
+    }
+
+    /// Sums `src` exactly and rounds the sum once in the rounding mode, so
+    /// that the result doesn't depend on the order of the elements, and
+    /// rounding up and down gives the tightest bounds on the exact sum. An
+    /// exact zero sum is positive unless every element is a negative zero
+    /// or the rounding is down, and an empty slice sums to positive zero.
+    #[inline]
+    pub fn sum_exact(flags: Flags, src: &[f64]) -> (f64, Status) {
+        if src.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let values = src.iter().map(|value| value.to_bits());
+        let (bits, exc) = soft::sum_exact::<soft::Binary64>(&flags.soft_env(), values);
+        (f64::from_bits(bits), Status::from_soft(exc))
+    }
+
+    /// Sums `src` from left to right with Neumaier's compensation, which
+    /// tracks the rounding error of every partial sum and adds it back at the
+    /// end, so the result is about as accurate as a plain sum carried in
+    /// twice the precision. Also returns that correction, the estimated error
+    /// of the plain sum, which is zero once the sum is infinite or NaN. The
+    /// error terms are exact only rounding to nearest, and the status gathers
+    /// every step's. An empty slice sums to positive zero.
+    #[inline]
+    pub fn sum_compensated(flags: Flags, src: &[f64]) -> (f64, f64, Status) {
+        match src.split_first() {
+            Some((&first, rest)) => elements::f64_sum_compensated(flags, first, rest),
+            None => (0.0, 0.0, Status::empty()),
+        }
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes the dot product from left to right, multiplying `a[0] * b[0]`
+    /// and then fusing every other product into it, so that rounding up or
+    /// down bounds the exact dot product. Empty slices give positive zero.
+    #[inline]
+    pub fn dot(flags: Flags, a: &[f64], b: &[f64]) -> (f64, Status) {
+        assert_eq!(a.len(), b.len());
+        if a.is_empty() {
+            return (0.0, Status::empty());
+        }
+        imp::f64_dot(flags, a, b)
+    }
+
+    /// Sums `src` in a fixed order: blocks of 1024 elements from left to
+    /// right, as `sum` does, and then the block sums pairwise, as
+    /// `(s[0] + s[1]) + (s[2] + s[3])` and so on, carrying an odd last sum up
+    /// to the next level. An empty slice sums to positive zero.
+    #[inline]
+    pub fn sum_reproducible(flags: Flags, src: &[f64]) -> (f64, Status) {
+        if src.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| {
+            let block = tree_block(src, index);
+            imp::f64_sum(flags, block[0], &block[1..])
+        };
+        let combine = |l, r| crate::f64::add(flags, l, r);
+        tree(0, src.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes the dot product in the fixed order of `sum_reproducible`,
+    /// with blocks of 1024 products computed as `dot` does. Empty slices
+    /// give positive zero.
+    #[inline]
+    pub fn dot_reproducible(flags: Flags, a: &[f64], b: &[f64]) -> (f64, Status) {
+        assert_eq!(a.len(), b.len());
+        if a.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| imp::f64_dot(flags, tree_block(a, index), tree_block(b, index));
+        let combine = |l, r| crate::f64::add(flags, l, r);
+        tree(0, a.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    /// Finds the smallest element from left to right under `policy`. An
+    /// empty slice gives positive infinity.
+    #[inline]
+    pub fn min(flags: Flags, policy: NanPolicy, src: &[f64]) -> (f64, Status) {
+        if src.is_empty() {
+            return (f64::INFINITY, Status::empty());
+        }
+        imp::f64_min(flags, policy, src)
+    }
+
+    /// Finds the largest element from left to right under `policy`. An empty
+    /// slice gives negative infinity.
+    #[inline]
+    pub fn max(flags: Flags, policy: NanPolicy, src: &[f64]) -> (f64, Status) {
+        if src.is_empty() {
+            return (f64::NEG_INFINITY, Status::empty());
+        }
+        imp::f64_max(flags, policy, src)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts `src` to single precision into `dst`.
+    #[inline]
+    pub fn to_single(flags: Flags, dst: &mut [f32], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_to_single(flags, dst, src)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts `src` to integers in the rounding mode into `dst`. NaNs and
+    /// values out of range become `i32::MIN` and raise invalid, as on x86.
+    #[inline]
+    pub fn to_int(flags: Flags, dst: &mut [i32], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_to_int(flags, dst, src)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts the integers in `src` into `dst`.
+    #[inline]
+    pub fn from_int(flags: Flags, dst: &mut [f64], src: &[i32]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        imp::f64_from_int(flags, dst, src)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_add_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f64_add_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_sub_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f64_sub_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_mul_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f64_mul_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_div_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        assert_eq!(dst.len(), src.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(src.par_chunks(PAR_CHUNK))
+            .map(|(dst, src)| imp::f64_div_assign(flags, dst, src))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn par_madd_assign(flags: Flags, dst: &mut [f64], b: &[f64], c: &[f64]) -> Status {
+        assert_eq!(dst.len(), b.len());
+        assert_eq!(dst.len(), c.len());
+        dst.par_chunks_mut(PAR_CHUNK)
+            .zip(b.par_chunks(PAR_CHUNK))
+            .zip(c.par_chunks(PAR_CHUNK))
+            .map(|((dst, b), c)| imp::f64_madd_assign(flags, dst, b, c))
+            .reduce(Status::empty, Status::or)
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Sums `src` like `sum_reproducible`, giving the same result on any
+    /// number of threads.
+    #[inline]
+    pub fn par_sum_reproducible(flags: Flags, src: &[f64]) -> (f64, Status) {
+        if src.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| {
+            let block = tree_block(src, index);
+            imp::f64_sum(flags, block[0], &block[1..])
+        };
+        let combine = |l, r| crate::f64::add(flags, l, r);
+        par_tree(0, src.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(feature = "rayon")]
+    /// Computes the dot product like `dot_reproducible`, giving the same
+    /// result on any number of threads.
+    #[inline]
+    pub fn par_dot_reproducible(flags: Flags, a: &[f64], b: &[f64]) -> (f64, Status) {
+        assert_eq!(a.len(), b.len());
+        if a.is_empty() {
+            return (0.0, Status::empty());
+        }
+        let block = |index| imp::f64_dot(flags, tree_block(a, index), tree_block(b, index));
+        let combine = |l, r| crate::f64::add(flags, l, r);
+        par_tree(0, a.len().div_ceil(TREE_BLOCK), &block, &combine)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn add_all(flags: Flags, l: &[f64], r: &[f64]) -> (Vec<f64>, Status) {
+        let mut values = l.to_vec();
+        let status = add_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn sub_all(flags: Flags, l: &[f64], r: &[f64]) -> (Vec<f64>, Status) {
+        let mut values = l.to_vec();
+        let status = sub_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn mul_all(flags: Flags, l: &[f64], r: &[f64]) -> (Vec<f64>, Status) {
+        let mut values = l.to_vec();
+        let status = mul_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn div_all(flags: Flags, l: &[f64], r: &[f64]) -> (Vec<f64>, Status) {
+        let mut values = l.to_vec();
+        let status = div_assign(flags, &mut values, r);
+        (values, status)
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn madd_all(flags: Flags, a: &[f64], b: &[f64], c: &[f64]) -> (Vec<f64>, Status) {
+        let mut values = a.to_vec();
+        let status = madd_assign(flags, &mut values, b, c);
+        (values, status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[cfg(feature = "alloc")]
+    #[inline]
+    pub fn to_single_all(flags: Flags, src: &[f64]) -> (Vec<f32>, Status) {
+        let mut status = Status::empty();
+        let values = src
+            .iter()
+            .map(|&value| {
+                let (value, element) = crate::f64::to_single(flags, value);
+                status = status.or(element);
+                value
+            })
+            .collect();
+        (values, status)
+    }
+}
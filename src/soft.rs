@@ -0,0 +1,2499 @@
+//! Software implementation of IEEE 754 binary floating-point arithmetic.
+//!
+//! Every operation works on raw bit patterns and reports exceptions in the
+//! bit positions used by the x86 MXCSR, which the backends translate into
+//! their own `Status` encodings.
+
+use core::cmp::Ordering;
+
+pub(crate) const INVALID: u32 = 1 << 0;
+pub(crate) const DENORM: u32 = 1 << 1;
+pub(crate) const DIV_ZERO: u32 = 1 << 2;
+pub(crate) const OVERFLOW: u32 = 1 << 3;
+pub(crate) const UNDERFLOW: u32 = 1 << 4;
+pub(crate) const INEXACT: u32 = 1 << 5;
+
+/// The rounding direction applied to inexact results.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    Nearest,
+    NearestAway,
+    Zero,
+    Up,
+    Down,
+    Odd,
+}
+
+/// How the NaN result of an operation with NaN operands is chosen.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NanRule {
+    /// The first NaN operand is quieted and returned.
+    FirstOperand,
+    /// The first signaling NaN operand is quieted and returned, otherwise the
+    /// first quiet NaN operand.
+    SignalingFirst,
+    /// The default NaN is returned regardless of the operands.
+    Canonical,
+    /// The first operand is quieted, as loading it onto the x87 stack does,
+    /// then the quiet NaN with the larger significand is returned, the
+    /// positive one on ties, or the signaling one quieted when there are no
+    /// quiet ones.
+    LargerSignificand,
+}
+
+/// The architecture specific corners of IEEE 754 that a backend emulates.
+#[derive(Clone, Copy)]
+pub(crate) struct Semantics {
+    pub(crate) nan: NanRule,
+    /// Whether the NaN produced by invalid operations has its sign bit set.
+    pub(crate) default_nan_negative: bool,
+    /// Whether tininess is detected before rounding instead of after.
+    pub(crate) tininess_before_rounding: bool,
+    /// The order in which `fma` considers its operands `a * b + c`, as
+    /// indices into `[a, b, c]`, when choosing which NaN to return.
+    pub(crate) fma_nan_order: [usize; 3],
+    /// Whether `fma` with an infinite times zero product is invalid even when
+    /// the addend is a quiet NaN.
+    pub(crate) fma_inf_zero_nan_invalid: bool,
+    /// Whether `DENORM` reports inputs flushed to zero rather than
+    /// denormal operands consumed as-is.
+    pub(crate) denorm_on_flush: bool,
+    /// Whether flushing a tiny result to zero also raises `INEXACT`.
+    pub(crate) flush_inexact: bool,
+    /// Whether `DENORM` is raised as a denormal first operand is loaded,
+    /// before NaNs and invalid operations are considered.
+    pub(crate) denorm_on_load: bool,
+    /// Whether `DENORM` reports denormal half-precision operands, which are
+    /// never flushed.
+    pub(crate) half_denorm: bool,
+    /// Whether results that overflow or are tiny are delivered as enabled
+    /// traps receive them, with their exponent wrapped into range.
+    pub(crate) wrap_exponent: bool,
+}
+
+impl Semantics {
+    pub(crate) const PORTABLE: Self = Self {
+        nan: NanRule::Canonical,
+        default_nan_negative: false,
+        tininess_before_rounding: false,
+        fma_nan_order: [0, 1, 2],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const X86: Self = Self {
+        nan: NanRule::FirstOperand,
+        default_nan_negative: true,
+        tininess_before_rounding: false,
+        fma_nan_order: [1, 0, 2],
+        fma_inf_zero_nan_invalid: false,
+        denorm_on_flush: false,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const X87: Self = Self {
+        nan: NanRule::LargerSignificand,
+        default_nan_negative: true,
+        tininess_before_rounding: false,
+        fma_nan_order: [0, 1, 2],
+        fma_inf_zero_nan_invalid: false,
+        denorm_on_flush: false,
+        flush_inexact: true,
+        denorm_on_load: true,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const ARM: Self = Self {
+        nan: NanRule::SignalingFirst,
+        default_nan_negative: false,
+        tininess_before_rounding: true,
+        fma_nan_order: [2, 0, 1],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: false,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    /// ARM under the alternate handling of FEAT_AFP, which follows x86.
+    pub(crate) const ARM_ALTERNATE: Self = Self {
+        nan: NanRule::FirstOperand,
+        default_nan_negative: true,
+        tininess_before_rounding: false,
+        fma_nan_order: [0, 1, 2],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const LOONGARCH: Self = Self {
+        nan: NanRule::SignalingFirst,
+        default_nan_negative: false,
+        tininess_before_rounding: false,
+        fma_nan_order: [2, 0, 1],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const MIPS: Self = Self {
+        nan: NanRule::SignalingFirst,
+        default_nan_negative: false,
+        tininess_before_rounding: false,
+        fma_nan_order: [0, 1, 2],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const POWER: Self = Self {
+        nan: NanRule::FirstOperand,
+        default_nan_negative: false,
+        tininess_before_rounding: true,
+        fma_nan_order: [0, 2, 1],
+        fma_inf_zero_nan_invalid: false,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const S390X: Self = Self {
+        nan: NanRule::SignalingFirst,
+        default_nan_negative: false,
+        tininess_before_rounding: true,
+        fma_nan_order: [0, 1, 2],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+
+    pub(crate) const RISCV: Self = Self {
+        nan: NanRule::Canonical,
+        default_nan_negative: false,
+        tininess_before_rounding: false,
+        fma_nan_order: [0, 1, 2],
+        fma_inf_zero_nan_invalid: true,
+        denorm_on_flush: true,
+        flush_inexact: true,
+        denorm_on_load: false,
+        half_denorm: false,
+        wrap_exponent: false,
+    };
+}
+
+/// The floating-point environment an operation executes in.
+#[derive(Clone, Copy)]
+pub(crate) struct Env {
+    pub(crate) mode: Mode,
+    /// Flushes tiny results to zero.
+    pub(crate) ftz: bool,
+    /// Treats denormal inputs as zero.
+    pub(crate) daz: bool,
+    /// Replaces every NaN result with the default NaN.
+    pub(crate) default_nan: bool,
+    pub(crate) sem: Semantics,
+}
+
+/// An IEEE 754 binary interchange format of at most 64 bits.
+pub(crate) trait Format {
+    const EXP_BITS: u32;
+    const FRAC_BITS: u32;
+
+    const BITS: u32 = 1 + Self::EXP_BITS + Self::FRAC_BITS;
+    const SIGN: u64 = 1 << (Self::BITS - 1);
+    const EXP_MASK: u64 = ((1 << Self::EXP_BITS) - 1) << Self::FRAC_BITS;
+    const FRAC_MASK: u64 = (1 << Self::FRAC_BITS) - 1;
+    const QUIET: u64 = 1 << (Self::FRAC_BITS - 1);
+    const BIAS: i32 = (1 << (Self::EXP_BITS - 1)) - 1;
+    const EMIN: i32 = 1 - Self::BIAS;
+    const EMAX: i32 = Self::BIAS;
+    /// The precision in bits, including the implicit bit.
+    const PREC: i32 = Self::FRAC_BITS as i32 + 1;
+    /// Whether flush-to-zero and denormals-are-zero apply to the format.
+    const FLUSHABLE: bool = true;
+}
+
+pub(crate) struct Binary16;
+pub(crate) struct Binary32;
+pub(crate) struct Binary64;
+
+// Half-precision denormals are always handled in full regardless of the
+// flushing modes, and only reported where `half_denorm` says so.
+impl Format for Binary16 {
+    const EXP_BITS: u32 = 5;
+    const FRAC_BITS: u32 = 10;
+    const FLUSHABLE: bool = false;
+}
+
+/// The alternative half-precision format of ARM, which spends the encodings
+/// of infinities and NaNs on another binade of normal numbers.
+#[cfg(feature = "convert")]
+struct AlternativeHalf;
+
+#[cfg(feature = "convert")]
+impl Format for AlternativeHalf {
+    const EXP_BITS: u32 = 5;
+    const FRAC_BITS: u32 = 10;
+    const EMAX: i32 = Self::BIAS + 1;
+    const FLUSHABLE: bool = false;
+}
+
+impl Format for Binary32 {
+    const EXP_BITS: u32 = 8;
+    const FRAC_BITS: u32 = 23;
+}
+
+impl Format for Binary64 {
+    const EXP_BITS: u32 = 11;
+    const FRAC_BITS: u32 = 52;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Zero,
+    Finite,
+    Inf,
+    Nan,
+}
+
+/// A decoded operand; finite values equal `sig * 2^exp`.
+#[derive(Clone, Copy)]
+struct Unpacked {
+    sign: bool,
+    class: Class,
+    exp: i32,
+    sig: u64,
+}
+
+impl Unpacked {
+    #[inline]
+    fn zero(sign: bool) -> Self {
+        Self {
+            sign,
+            class: Class::Zero,
+            exp: 0,
+            sig: 0,
+        }
+    }
+}
+
+#[inline]
+fn is_nan<F: Format>(bits: u64) -> bool {
+    bits & F::EXP_MASK == F::EXP_MASK && bits & F::FRAC_MASK != 0
+}
+
+#[inline]
+fn is_snan<F: Format>(bits: u64) -> bool {
+    is_nan::<F>(bits) && bits & F::QUIET == 0
+}
+
+#[inline]
+fn default_nan<F: Format>(env: &Env) -> u64 {
+    let sign = if env.sem.default_nan_negative {
+        F::SIGN
+    } else {
+        0
+    };
+    sign | F::EXP_MASK | F::QUIET
+}
+
+/// A value as the backends hold it, for replacing NaN results of the
+/// hardware with the default NaN.
+pub(crate) trait Value: Copy {
+    fn is_nan(self) -> bool;
+    fn default_nan(env: &Env) -> Self;
+    /// Whether the magnitude is the smallest normal one, which results only
+    /// tiny before rounding round to.
+    fn is_min_normal(self) -> bool;
+    fn from_soft(bits: u64) -> Self;
+}
+
+impl Value for u16 {
+    #[inline]
+    fn is_nan(self) -> bool {
+        is_nan::<Binary16>(self as u64)
+    }
+
+    #[inline]
+    fn default_nan(env: &Env) -> Self {
+        default_nan::<Binary16>(env) as u16
+    }
+
+    #[inline]
+    fn is_min_normal(self) -> bool {
+        self & 0x7FFF == 0x0400
+    }
+
+    #[inline]
+    fn from_soft(bits: u64) -> Self {
+        bits as u16
+    }
+}
+
+impl Value for f32 {
+    #[inline]
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+
+    #[inline]
+    fn default_nan(env: &Env) -> Self {
+        f32::from_bits(default_nan::<Binary32>(env) as u32)
+    }
+
+    #[inline]
+    fn is_min_normal(self) -> bool {
+        self.to_bits() & !(1 << 31) == 0x0080_0000
+    }
+
+    #[inline]
+    fn from_soft(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl Value for f64 {
+    #[inline]
+    fn is_nan(self) -> bool {
+        self.is_nan()
+    }
+
+    #[inline]
+    fn default_nan(env: &Env) -> Self {
+        f64::from_bits(default_nan::<Binary64>(env))
+    }
+
+    #[inline]
+    fn is_min_normal(self) -> bool {
+        self.to_bits() & !(1 << 63) == 1 << 52
+    }
+
+    #[inline]
+    fn from_soft(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
+
+#[inline]
+pub(crate) fn inf<F: Format>(sign: bool) -> u64 {
+    sign_bit::<F>(sign) | F::EXP_MASK
+}
+
+#[inline]
+pub(crate) fn zero<F: Format>(sign: bool) -> u64 {
+    sign_bit::<F>(sign)
+}
+
+#[inline]
+fn sign_bit<F: Format>(sign: bool) -> u64 {
+    if sign {
+        F::SIGN
+    } else {
+        0
+    }
+}
+
+/// Decodes `bits` without looking at the environment.
+#[inline]
+fn unpack<F: Format>(bits: u64) -> Unpacked {
+    let sign = bits & F::SIGN != 0;
+    let biased = ((bits & F::EXP_MASK) >> F::FRAC_BITS) as i32;
+    let frac = bits & F::FRAC_MASK;
+    let (class, exp, sig) = if biased == (1 << F::EXP_BITS) - 1 {
+        (if frac == 0 { Class::Inf } else { Class::Nan }, 0, 0)
+    } else if biased == 0 {
+        if frac == 0 {
+            (Class::Zero, 0, 0)
+        } else {
+            (Class::Finite, F::EMIN - F::FRAC_BITS as i32, frac)
+        }
+    } else {
+        (
+            Class::Finite,
+            biased - F::BIAS - F::FRAC_BITS as i32,
+            frac | 1 << F::FRAC_BITS,
+        )
+    };
+    Unpacked {
+        sign,
+        class,
+        exp,
+        sig,
+    }
+}
+
+/// Decodes an operand, applying denormals-are-zero; returns whether the
+/// operand is a denormal that was consumed as-is.
+#[inline]
+fn operand<F: Format>(env: &Env, bits: u64, exc: &mut u32) -> (Unpacked, bool) {
+    let op = unpack::<F>(bits);
+    if op.class == Class::Finite && op.sig >> F::FRAC_BITS == 0 {
+        if !F::FLUSHABLE {
+            return (op, env.sem.half_denorm);
+        }
+        if env.daz {
+            if env.sem.denorm_on_flush {
+                *exc |= DENORM;
+            }
+            return (Unpacked::zero(op.sign), false);
+        }
+        return (op, !env.sem.denorm_on_flush);
+    }
+    (op, false)
+}
+
+/// Decodes the first operand like `operand`, raising `DENORM` right away
+/// where loading it does.
+#[inline]
+fn first_operand<F: Format>(env: &Env, bits: u64, exc: &mut u32) -> (Unpacked, bool) {
+    let (op, denormal) = operand::<F>(env, bits, exc);
+    if env.sem.denorm_on_load {
+        denormal_operands(denormal, exc);
+    }
+    (op, denormal)
+}
+
+#[cfg(feature = "math")]
+/// The operand of a function evaluated outside this module.
+pub(crate) enum Operand {
+    /// A NaN, with the result it propagates to.
+    Nan(u64),
+    Inf(bool),
+    Zero(bool),
+    /// The value `sig * 2^exp`.
+    Finite(bool, i32, u64),
+}
+
+#[cfg(feature = "math")]
+/// Decodes the operand of a function evaluated outside this module like
+/// the operand of an arithmetic operation.
+pub(crate) fn function_operand<F: Format>(env: &Env, a: u64, exc: &mut u32) -> Operand {
+    let (op, denormal) = first_operand::<F>(env, a, exc);
+    if op.class == Class::Nan {
+        return Operand::Nan(propagate::<F>(env, &[a], exc));
+    }
+    denormal_operands(denormal, exc);
+    match op.class {
+        Class::Inf => Operand::Inf(op.sign),
+        Class::Zero => Operand::Zero(op.sign),
+        _ => Operand::Finite(op.sign, op.exp, op.sig),
+    }
+}
+
+/// Picks the NaN result for an operation whose `ops`, listed in priority
+/// order, contain at least one NaN.
+fn propagate<F: Format>(env: &Env, ops: &[u64], exc: &mut u32) -> u64 {
+    if ops.iter().any(|&op| is_snan::<F>(op)) {
+        *exc |= INVALID;
+    }
+    if env.default_nan {
+        return default_nan::<F>(env);
+    }
+    let pick = match env.sem.nan {
+        NanRule::FirstOperand => ops.iter().copied().find(|&op| is_nan::<F>(op)),
+        NanRule::SignalingFirst => ops
+            .iter()
+            .copied()
+            .find(|&op| is_snan::<F>(op))
+            .or_else(|| ops.iter().copied().find(|&op| is_nan::<F>(op))),
+        NanRule::Canonical => None,
+        NanRule::LargerSignificand => {
+            let first = if is_nan::<F>(ops[0]) {
+                ops[0] | F::QUIET
+            } else {
+                ops[0]
+            };
+            let loaded = || core::iter::once(first).chain(ops[1..].iter().copied());
+            let quiet = loaded().any(|op| is_nan::<F>(op) && !is_snan::<F>(op));
+            loaded()
+                .filter(|&op| is_nan::<F>(op) && !(quiet && is_snan::<F>(op)))
+                .reduce(|best, op| {
+                    let (sig, best_sig) = (op & F::FRAC_MASK, best & F::FRAC_MASK);
+                    if sig > best_sig || sig == best_sig && best & F::SIGN != 0 {
+                        op
+                    } else {
+                        best
+                    }
+                })
+        }
+    };
+    pick.map_or(default_nan::<F>(env), |op| op | F::QUIET)
+}
+
+#[inline]
+pub(crate) fn invalid<F: Format>(env: &Env, exc: &mut u32) -> u64 {
+    *exc |= INVALID;
+    default_nan::<F>(env)
+}
+
+/// Splits `sig` at bit `shift` into the kept part, the half bit and whether
+/// any bit below the half bit is set.
+#[inline]
+fn split(sig: u128, shift: i32) -> (u128, bool, bool) {
+    if shift <= 0 {
+        (sig << -shift, false, false)
+    } else if shift > 128 {
+        (0, false, sig != 0)
+    } else {
+        let kept = if shift == 128 { 0 } else { sig >> shift };
+        let half = (sig >> (shift - 1)) & 1 != 0;
+        let sticky = sig & ((1 << (shift - 1)) - 1) != 0;
+        (kept, half, sticky)
+    }
+}
+
+#[inline]
+fn round_increment(mode: Mode, sign: bool, odd: bool, half: bool, sticky: bool) -> bool {
+    match mode {
+        Mode::Nearest => half && (sticky || odd),
+        Mode::NearestAway => half,
+        Mode::Zero => false,
+        Mode::Up => !sign && (half || sticky),
+        Mode::Down => sign && (half || sticky),
+        Mode::Odd => !odd && (half || sticky),
+    }
+}
+
+/// Rounds the nonzero value `sig * 2^exp` to `prec` bits with an unbounded
+/// exponent range if the result overflows `range` or is tiny, returning the
+/// significand and the exponent of its leading bit wrapped into the range by
+/// three quarters of its width, as enabled traps receive such results.
+/// Tininess raises underflow even for exact results.
+fn wrapped(
+    env: &Env,
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    prec: i32,
+    (emin, emax): (i32, i32),
+    exc: &mut u32,
+) -> Option<(u128, i32)> {
+    let bias = 3 * (emax + 1) / 2;
+    let e = exp + 127 - sig.leading_zeros() as i32;
+    let (mut kept, half, sticky) = split(sig, e - (prec - 1) - exp);
+    let mut top = e;
+    if round_increment(env.mode, sign, kept & 1 != 0, half, sticky) {
+        kept += 1;
+    }
+    if kept >> prec != 0 {
+        kept >>= 1;
+        top += 1;
+    }
+    let tiny = if env.sem.tininess_before_rounding {
+        e < emin
+    } else {
+        top < emin
+    };
+    let top = if top > emax {
+        *exc |= OVERFLOW;
+        top - bias
+    } else if tiny {
+        *exc |= UNDERFLOW;
+        top + bias
+    } else {
+        return None;
+    };
+    if half || sticky {
+        *exc |= INEXACT;
+    }
+    Some((kept, top))
+}
+
+/// Rounds the nonzero value `sig * 2^exp` to the format `F`.
+///
+/// Inexact intermediate results must keep at least two bits below the
+/// rounding position, with the lowest bit set when any lower bits were lost.
+pub(crate) fn round_pack<F: Format>(
+    env: &Env,
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    exc: &mut u32,
+) -> u64 {
+    debug_assert!(sig != 0);
+    if env.sem.wrap_exponent {
+        if let Some((kept, top)) = wrapped(env, sign, exp, sig, F::PREC, (F::EMIN, F::EMAX), exc) {
+            return sign_bit::<F>(sign)
+                | ((top + F::BIAS) as u64) << F::FRAC_BITS
+                | (kept as u64 & F::FRAC_MASK);
+        }
+    }
+    let msb = 127 - sig.leading_zeros() as i32;
+    // The value lies in [2^e, 2^(e + 1)).
+    let e = exp + msb;
+    let qmin = F::EMIN - (F::PREC - 1);
+    let mut q = (e - (F::PREC - 1)).max(qmin);
+
+    let (mut kept, half, sticky) = split(sig, q - exp);
+    let inexact = half || sticky;
+    if round_increment(env.mode, sign, kept & 1 != 0, half, sticky) {
+        kept += 1;
+    }
+
+    let tiny = e < F::EMIN
+        && (env.sem.tininess_before_rounding || e < F::EMIN - 1 || {
+            // Tininess after rounding: rounding to full precision with an
+            // unbounded exponent range must not reach the smallest normal.
+            let (kept, half, sticky) = split(sig, e - (F::PREC - 1) - exp);
+            let up = round_increment(env.mode, sign, kept & 1 != 0, half, sticky);
+            (kept + up as u128) >> F::PREC == 0
+        });
+    if tiny && env.ftz && F::FLUSHABLE {
+        *exc |= UNDERFLOW;
+        if env.sem.flush_inexact {
+            *exc |= INEXACT;
+        }
+        return zero::<F>(sign);
+    }
+    if tiny && inexact {
+        *exc |= UNDERFLOW;
+    }
+    if inexact {
+        *exc |= INEXACT;
+    }
+
+    if kept >> F::PREC != 0 {
+        kept >>= 1;
+        q += 1;
+    }
+    if kept >> (F::PREC - 1) != 0 && q + F::PREC - 1 > F::EMAX {
+        *exc |= OVERFLOW | INEXACT;
+        let to_inf = match env.mode {
+            Mode::Nearest | Mode::NearestAway => true,
+            Mode::Zero | Mode::Odd => false,
+            Mode::Up => !sign,
+            Mode::Down => sign,
+        };
+        return if to_inf {
+            inf::<F>(sign)
+        } else {
+            sign_bit::<F>(sign) | (F::EXP_MASK - (1 << F::FRAC_BITS)) | F::FRAC_MASK
+        };
+    }
+    sign_bit::<F>(sign) + (((q - qmin) as u64) << F::FRAC_BITS) + kept as u64
+}
+
+/// Shifts `sig` right by `shift`, returning whether any set bits were lost.
+#[inline]
+fn shr_lost(sig: u128, shift: i32) -> (u128, bool) {
+    if shift >= 128 {
+        (0, sig != 0)
+    } else {
+        (sig >> shift, sig & ((1 << shift) - 1) != 0)
+    }
+}
+
+/// Adds two exact nonzero values, returning `None` for an exact zero sum.
+pub(crate) fn add_exact(
+    (sign_a, exp_a, sig_a): (bool, i32, u128),
+    (sign_b, exp_b, sig_b): (bool, i32, u128),
+) -> Option<(bool, i32, u128)> {
+    // Place both most significant bits at bit 125, leaving room for a carry.
+    let norm = |exp: i32, sig: u128| {
+        let shift = sig.leading_zeros() as i32 - 2;
+        (exp - shift, sig << shift)
+    };
+    let (exp_a, sig_a) = norm(exp_a, sig_a);
+    let (exp_b, sig_b) = norm(exp_b, sig_b);
+    let ((sign_a, exp_a, sig_a), (sign_b, exp_b, sig_b)) = if (exp_a, sig_a) >= (exp_b, sig_b) {
+        ((sign_a, exp_a, sig_a), (sign_b, exp_b, sig_b))
+    } else {
+        ((sign_b, exp_b, sig_b), (sign_a, exp_a, sig_a))
+    };
+    let (sig_b, lost) = shr_lost(sig_b, exp_a - exp_b);
+    let sig = if sign_a == sign_b {
+        sig_a + sig_b
+    } else {
+        sig_a - sig_b - lost as u128
+    };
+    if sig == 0 {
+        return None;
+    }
+    Some((sign_a, exp_a, sig | lost as u128))
+}
+
+/// The sign of an exact zero sum of operands with the given signs.
+#[inline]
+fn zero_sum_sign(env: &Env, sign_a: bool, sign_b: bool) -> bool {
+    if sign_a == sign_b {
+        sign_a
+    } else {
+        env.mode == Mode::Down
+    }
+}
+
+/// Raises `DENORM` for denormal operands once no NaN was involved.
+#[inline]
+fn denormal_operands(denormal: bool, exc: &mut u32) {
+    if denormal {
+        *exc |= DENORM;
+    }
+}
+
+fn add_impl<F: Format>(env: &Env, a: u64, b: u64, negate: bool) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let (mut ub, db) = operand::<F>(env, b, &mut exc);
+    if ua.class == Class::Nan || ub.class == Class::Nan {
+        return (propagate::<F>(env, &[a, b], &mut exc), exc);
+    }
+    ub.sign ^= negate;
+    let res = match (ua.class, ub.class) {
+        (Class::Inf, Class::Inf) if ua.sign != ub.sign => invalid::<F>(env, &mut exc),
+        _ => {
+            denormal_operands(da || db, &mut exc);
+            match (ua.class, ub.class) {
+                (Class::Inf, _) => inf::<F>(ua.sign),
+                (_, Class::Inf) => inf::<F>(ub.sign),
+                (Class::Zero, Class::Zero) => zero::<F>(zero_sum_sign(env, ua.sign, ub.sign)),
+                (Class::Zero, _) => round_pack::<F>(env, ub.sign, ub.exp, ub.sig as u128, &mut exc),
+                (_, Class::Zero) => round_pack::<F>(env, ua.sign, ua.exp, ua.sig as u128, &mut exc),
+                _ => match add_exact(
+                    (ua.sign, ua.exp, ua.sig as u128),
+                    (ub.sign, ub.exp, ub.sig as u128),
+                ) {
+                    Some((sign, exp, sig)) => round_pack::<F>(env, sign, exp, sig, &mut exc),
+                    None => zero::<F>(env.mode == Mode::Down),
+                },
+            }
+        }
+    };
+    (res, exc)
+}
+
+/// Computes `a + b`.
+pub(crate) fn add<F: Format>(env: &Env, a: u64, b: u64) -> (u64, u32) {
+    add_impl::<F>(env, a, b, false)
+}
+
+/// Computes `a - b`.
+pub(crate) fn sub<F: Format>(env: &Env, a: u64, b: u64) -> (u64, u32) {
+    add_impl::<F>(env, a, b, true)
+}
+
+/// Computes `a * b`.
+pub(crate) fn mul<F: Format>(env: &Env, a: u64, b: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let (ub, db) = operand::<F>(env, b, &mut exc);
+    if ua.class == Class::Nan || ub.class == Class::Nan {
+        return (propagate::<F>(env, &[a, b], &mut exc), exc);
+    }
+    let sign = ua.sign != ub.sign;
+    let res = match (ua.class, ub.class) {
+        (Class::Inf, Class::Zero) | (Class::Zero, Class::Inf) => invalid::<F>(env, &mut exc),
+        (Class::Inf, _) | (_, Class::Inf) => {
+            denormal_operands(da || db, &mut exc);
+            inf::<F>(sign)
+        }
+        _ => {
+            denormal_operands(da || db, &mut exc);
+            if ua.class == Class::Zero || ub.class == Class::Zero {
+                zero::<F>(sign)
+            } else {
+                let sig = ua.sig as u128 * ub.sig as u128;
+                round_pack::<F>(env, sign, ua.exp + ub.exp, sig, &mut exc)
+            }
+        }
+    };
+    (res, exc)
+}
+
+/// Computes `a / b`.
+pub(crate) fn div<F: Format>(env: &Env, a: u64, b: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let (ub, db) = operand::<F>(env, b, &mut exc);
+    if ua.class == Class::Nan || ub.class == Class::Nan {
+        return (propagate::<F>(env, &[a, b], &mut exc), exc);
+    }
+    let sign = ua.sign != ub.sign;
+    let res = match (ua.class, ub.class) {
+        (Class::Inf, Class::Inf) | (Class::Zero, Class::Zero) => invalid::<F>(env, &mut exc),
+        (Class::Inf, _) => {
+            denormal_operands(db, &mut exc);
+            inf::<F>(sign)
+        }
+        (_, Class::Inf) => {
+            denormal_operands(da, &mut exc);
+            zero::<F>(sign)
+        }
+        (_, Class::Zero) => {
+            exc |= DIV_ZERO;
+            inf::<F>(sign)
+        }
+        (Class::Zero, _) => {
+            denormal_operands(db, &mut exc);
+            zero::<F>(sign)
+        }
+        _ => {
+            denormal_operands(da || db, &mut exc);
+            let (exp_a, sig_a) = normalize::<F>(ua.exp, ua.sig);
+            let (exp_b, sig_b) = normalize::<F>(ub.exp, ub.sig);
+            let num = (sig_a as u128) << 64;
+            let quo = num / sig_b as u128;
+            let rem = num % sig_b as u128;
+            let sig = quo | (rem != 0) as u128;
+            round_pack::<F>(env, sign, exp_a - exp_b - 64, sig, &mut exc)
+        }
+    };
+    (res, exc)
+}
+
+/// Shifts a finite significand so its leading bit is the implicit bit.
+#[inline]
+fn normalize<F: Format>(exp: i32, sig: u64) -> (i32, u64) {
+    let shift = sig.leading_zeros() as i32 - (63 - F::FRAC_BITS as i32);
+    (exp - shift, sig << shift)
+}
+
+#[cfg(feature = "fma")]
+/// Computes `a * b + c` with a single rounding.
+pub(crate) fn fma<F: Format>(env: &Env, a: u64, b: u64, c: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = operand::<F>(env, a, &mut exc);
+    let (ub, db) = operand::<F>(env, b, &mut exc);
+    let (uc, dc) = operand::<F>(env, c, &mut exc);
+    let inf_zero = matches!(
+        (ua.class, ub.class),
+        (Class::Inf, Class::Zero) | (Class::Zero, Class::Inf)
+    );
+    if ua.class == Class::Nan || ub.class == Class::Nan || uc.class == Class::Nan {
+        let ops = env.sem.fma_nan_order.map(|i| [a, b, c][i]);
+        let nan = propagate::<F>(env, &ops, &mut exc);
+        if inf_zero && env.sem.fma_inf_zero_nan_invalid {
+            return (invalid::<F>(env, &mut exc), exc);
+        }
+        return (nan, exc);
+    }
+    if inf_zero {
+        return (invalid::<F>(env, &mut exc), exc);
+    }
+    let sign = ua.sign != ub.sign;
+    let prod_inf = ua.class == Class::Inf || ub.class == Class::Inf;
+    let prod_zero = ua.class == Class::Zero || ub.class == Class::Zero;
+    let res = if prod_inf || uc.class == Class::Inf {
+        if prod_inf && uc.class == Class::Inf && sign != uc.sign {
+            invalid::<F>(env, &mut exc)
+        } else {
+            denormal_operands(da || db || dc, &mut exc);
+            inf::<F>(if prod_inf { sign } else { uc.sign })
+        }
+    } else {
+        denormal_operands(da || db || dc, &mut exc);
+        match (prod_zero, uc.class == Class::Zero) {
+            (true, true) => zero::<F>(zero_sum_sign(env, sign, uc.sign)),
+            (true, false) => round_pack::<F>(env, uc.sign, uc.exp, uc.sig as u128, &mut exc),
+            (false, zero_c) => {
+                let prod = (sign, ua.exp + ub.exp, ua.sig as u128 * ub.sig as u128);
+                if zero_c {
+                    round_pack::<F>(env, prod.0, prod.1, prod.2, &mut exc)
+                } else {
+                    match add_exact(prod, (uc.sign, uc.exp, uc.sig as u128)) {
+                        Some((sign, exp, sig)) => round_pack::<F>(env, sign, exp, sig, &mut exc),
+                        None => zero::<F>(env.mode == Mode::Down),
+                    }
+                }
+            }
+        }
+    };
+    (res, exc)
+}
+
+/// Computes the square root of `a`.
+pub(crate) fn sqrt<F: Format>(env: &Env, a: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let res = match ua.class {
+        Class::Nan => propagate::<F>(env, &[a], &mut exc),
+        Class::Zero => zero::<F>(ua.sign),
+        _ if ua.sign => invalid::<F>(env, &mut exc),
+        Class::Inf => inf::<F>(false),
+        Class::Finite => {
+            denormal_operands(da, &mut exc);
+            // Widens the significand to over twice the precision, keeping the
+            // exponent even so it halves exactly.
+            let (exp, sig) = normalize::<F>(ua.exp, ua.sig);
+            let mut shift = 125 - F::PREC;
+            if (exp - shift) % 2 != 0 {
+                shift += 1;
+            }
+            let wide = (sig as u128) << shift;
+            let root = wide.isqrt();
+            let sig = root << 1 | (root * root != wide) as u128;
+            round_pack::<F>(env, false, (exp - shift) / 2 - 1, sig, &mut exc)
+        }
+    };
+    (res, exc)
+}
+
+/// The 256-bit square of `a`, as its high and low halves.
+#[inline]
+fn square(a: u128) -> (u128, u128) {
+    let (high, low) = (a >> 64, a as u64 as u128);
+    let cross = high * low;
+    let (low_sum, carry) = (low * low).overflowing_add(cross << 65);
+    let high_sum = high * high + (cross >> 63) + carry as u128;
+    (high_sum, low_sum)
+}
+
+/// Computes `sqrt(a^2 + b^2)` without intermediate overflow or underflow.
+/// An infinity gives an infinity even with a quiet NaN.
+pub(crate) fn hypot<F: Format>(env: &Env, a: u64, b: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let (ub, db) = operand::<F>(env, b, &mut exc);
+    if is_snan::<F>(a) || is_snan::<F>(b) {
+        return (propagate::<F>(env, &[a, b], &mut exc), exc);
+    }
+    if ua.class == Class::Inf || ub.class == Class::Inf {
+        return (inf::<F>(false), exc);
+    }
+    if ua.class == Class::Nan || ub.class == Class::Nan {
+        return (propagate::<F>(env, &[a, b], &mut exc), exc);
+    }
+    denormal_operands(da || db, &mut exc);
+    let res = match (ua.class, ub.class) {
+        (Class::Zero, Class::Zero) => zero::<F>(false),
+        (Class::Zero, _) => round_pack::<F>(env, false, ub.exp, ub.sig as u128, &mut exc),
+        (_, Class::Zero) => round_pack::<F>(env, false, ua.exp, ua.sig as u128, &mut exc),
+        _ => {
+            // Both significands fill 64 bits, the larger operand's first.
+            let top = |op: Unpacked| {
+                let shift = op.sig.leading_zeros();
+                (op.exp - shift as i32, op.sig << shift)
+            };
+            let ((exp_x, sig_x), (exp_y, sig_y)) = if top(ua) >= top(ub) {
+                (top(ua), top(ub))
+            } else {
+                (top(ub), top(ua))
+            };
+            let diff = exp_x - exp_y;
+            if diff > F::PREC {
+                // The smaller square adds less than 2^-106 to the larger.
+                round_pack::<F>(env, false, exp_x - 2, (sig_x as u128) << 2 | 1, &mut exc)
+            } else {
+                // sqrt((x 2^diff)^2 + y^2) in units of the smaller operand.
+                let (high, low) = square((sig_x as u128) << diff);
+                let (low, carry) = low.overflowing_add(sig_y as u128 * sig_y as u128);
+                let high = high + carry as u128;
+                let bits = 256 - high.leading_zeros() as i32;
+                let shift = if bits <= 128 { 0 } else { (bits - 127) & !1 };
+                let (wide, lost) = match shift {
+                    0 => (low, false),
+                    128 => (high, low != 0),
+                    _ => (
+                        high << (128 - shift) | low >> shift,
+                        low << (128 - shift) != 0,
+                    ),
+                };
+                let root = wide.isqrt();
+                let sig = root << 1 | (root * root != wide || lost) as u128;
+                round_pack::<F>(env, false, exp_y + shift / 2 - 1, sig, &mut exc)
+            }
+        }
+    };
+    (res, exc)
+}
+
+/// The 64-bit limbs of a fixed-point accumulator holding any sum of fewer
+/// than 2^63 doubles exactly, from the smallest denormal up.
+const SUM_LIMBS: usize = 34;
+
+/// Sums `values` exactly and rounds once, an exact zero sum being negative
+/// only for negative zeros or rounding down. There must be at least one
+/// value.
+pub(crate) fn sum_exact<F: Format>(env: &Env, values: impl IntoIterator<Item = u64>) -> (u64, u32) {
+    let mut exc = 0;
+    // In two's complement, with bit 0 standing for the smallest denormal.
+    let mut acc = [0u64; SUM_LIMBS];
+    let mut nan = None;
+    let (mut snan, mut denormal) = (false, false);
+    let (mut pos_inf, mut neg_inf) = (false, false);
+    let mut negative_zeros = true;
+    for bits in values {
+        let (op, den) = operand::<F>(env, bits, &mut exc);
+        denormal |= den;
+        match op.class {
+            Class::Nan => {
+                snan |= is_snan::<F>(bits);
+                nan = nan.or(Some(bits));
+            }
+            Class::Inf if op.sign => neg_inf = true,
+            Class::Inf => pos_inf = true,
+            Class::Zero => negative_zeros &= op.sign,
+            Class::Finite => {
+                negative_zeros = false;
+                let pos = (op.exp - (F::EMIN - F::FRAC_BITS as i32)) as usize;
+                let shifted = (op.sig as u128) << (pos % 64);
+                let mut carry = false;
+                for (i, limb) in acc[pos / 64..].iter_mut().enumerate() {
+                    let part = match i {
+                        0 | 1 => (shifted >> (64 * i)) as u64,
+                        _ if carry => 0,
+                        _ => break,
+                    };
+                    let (value, c1, c2) = if op.sign {
+                        let (value, c1) = limb.overflowing_sub(part);
+                        let (value, c2) = value.overflowing_sub(carry as u64);
+                        (value, c1, c2)
+                    } else {
+                        let (value, c1) = limb.overflowing_add(part);
+                        let (value, c2) = value.overflowing_add(carry as u64);
+                        (value, c1, c2)
+                    };
+                    *limb = value;
+                    carry = c1 || c2;
+                }
+            }
+        }
+    }
+    if let Some(nan) = nan {
+        if snan {
+            exc |= INVALID;
+        }
+        return (propagate::<F>(env, &[nan], &mut exc), exc);
+    }
+    denormal_operands(denormal, &mut exc);
+    if pos_inf && neg_inf {
+        return (invalid::<F>(env, &mut exc), exc);
+    }
+    if pos_inf || neg_inf {
+        return (inf::<F>(neg_inf), exc);
+    }
+    let sign = acc[SUM_LIMBS - 1] >> 63 != 0;
+    if sign {
+        let mut carry = true;
+        for limb in &mut acc {
+            let (value, c) = (!*limb).overflowing_add(carry as u64);
+            *limb = value;
+            carry = c;
+        }
+    }
+    let Some(top) = acc.iter().rposition(|&limb| limb != 0) else {
+        let sign = negative_zeros || env.mode == Mode::Down;
+        return (zero::<F>(sign), exc);
+    };
+    // The top two limbs, with the lowest bit standing in for the rest.
+    let (exp, sig) = match top {
+        0 => (0, acc[0] as u128),
+        _ => {
+            let sig = (acc[top] as u128) << 64 | acc[top - 1] as u128;
+            let sticky = acc[..top - 1].iter().any(|&limb| limb != 0);
+            (64 * (top as i32 - 1), sig | sticky as u128)
+        }
+    };
+    let exp = exp + F::EMIN - F::FRAC_BITS as i32;
+    (round_pack::<F>(env, sign, exp, sig, &mut exc), exc)
+}
+
+/// Rounds `a` to an integral value in the current rounding mode, raising
+/// `INEXACT` when that changes it.
+pub(crate) fn round_int<F: Format>(env: &Env, a: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, _) = first_operand::<F>(env, a, &mut exc);
+    let res = match ua.class {
+        Class::Nan => propagate::<F>(env, &[a], &mut exc),
+        Class::Inf => inf::<F>(ua.sign),
+        Class::Zero => zero::<F>(ua.sign),
+        Class::Finite if ua.exp >= 0 => a,
+        Class::Finite => {
+            let (int, half, sticky) = split(ua.sig as u128, -ua.exp);
+            if half || sticky {
+                exc |= INEXACT;
+            }
+            let int = int + round_increment(env.mode, ua.sign, int & 1 != 0, half, sticky) as u128;
+            if int == 0 {
+                zero::<F>(ua.sign)
+            } else {
+                // Integers below the precision are exact, so this raises
+                // nothing more.
+                round_pack::<F>(env, ua.sign, 0, int, &mut exc)
+            }
+        }
+    };
+    (res, exc)
+}
+
+/// Compares `a` with `b`, raising `INVALID` only for signaling NaNs.
+pub(crate) fn compare<F: Format>(env: &Env, a: u64, b: u64) -> (Option<Ordering>, u32) {
+    let mut exc = 0;
+    if is_nan::<F>(a) || is_nan::<F>(b) {
+        if is_snan::<F>(a) || is_snan::<F>(b) {
+            exc |= INVALID;
+        }
+        return (None, exc);
+    }
+    // Sign and magnitude order like the values once zeros are merged.
+    let mut key = |bits: u64| {
+        let (op, denormal) = operand::<F>(env, bits, &mut exc);
+        denormal_operands(denormal, &mut exc);
+        let mag = if op.class == Class::Zero {
+            0
+        } else {
+            (bits & !F::SIGN) as i64
+        };
+        if op.sign {
+            -mag
+        } else {
+            mag
+        }
+    };
+    let ord = key(a).cmp(&key(b));
+    (Some(ord), exc)
+}
+
+#[cfg(feature = "batch")]
+/// Picks the smaller of `a` and `b`, or the larger with `max`.
+///
+/// With `ieee` this is IEEE 754-2019 `minimum` and `maximum`, which propagate
+/// NaNs and order negative zero below positive zero. Otherwise it follows
+/// `minsd` and `maxsd`, which return `b` unless `a` compares strictly
+/// smaller or larger, and flag any NaN as invalid.
+pub(crate) fn min_max<F: Format>(env: &Env, a: u64, b: u64, max: bool, ieee: bool) -> (u64, u32) {
+    let mut exc = 0;
+    // Denormals-are-zero replaces the operand that is returned as well.
+    let load = |bits: u64, exc: &mut u32| {
+        let (op, denormal) = operand::<F>(env, bits, exc);
+        denormal_operands(denormal, exc);
+        if op.class == Class::Zero {
+            zero::<F>(op.sign)
+        } else {
+            bits
+        }
+    };
+    if is_nan::<F>(a) || is_nan::<F>(b) {
+        if ieee {
+            return (propagate::<F>(env, &[a, b], &mut exc), exc);
+        }
+        let b = if is_nan::<F>(b) { b } else { load(b, &mut 0) };
+        return (b, INVALID);
+    }
+    let (a, b) = (load(a, &mut exc), load(b, &mut exc));
+    let key = |bits: u64| {
+        let mag = (bits & !F::SIGN) as i64;
+        if bits & F::SIGN != 0 {
+            -mag - ieee as i64
+        } else {
+            mag
+        }
+    };
+    let pick_a = if max {
+        key(a) > key(b)
+    } else {
+        key(a) < key(b)
+    };
+    (if pick_a { a } else { b }, exc)
+}
+
+#[cfg(feature = "convert")]
+/// Converts `a` from the format `F` to the format `G`.
+pub(crate) fn convert<F: Format, G: Format>(env: &Env, a: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let res = match ua.class {
+        Class::Nan => {
+            if is_snan::<F>(a) {
+                exc |= INVALID;
+            }
+            if env.default_nan || env.sem.nan == NanRule::Canonical {
+                default_nan::<G>(env)
+            } else {
+                let frac = a & F::FRAC_MASK;
+                let frac = if G::FRAC_BITS >= F::FRAC_BITS {
+                    frac << (G::FRAC_BITS - F::FRAC_BITS)
+                } else {
+                    frac >> (F::FRAC_BITS - G::FRAC_BITS)
+                };
+                sign_bit::<G>(ua.sign) | G::EXP_MASK | G::QUIET | frac
+            }
+        }
+        Class::Inf => inf::<G>(ua.sign),
+        Class::Zero => zero::<G>(ua.sign),
+        Class::Finite => {
+            denormal_operands(da, &mut exc);
+            round_pack::<G>(env, ua.sign, ua.exp, ua.sig as u128, &mut exc)
+        }
+    };
+    (res, exc)
+}
+
+#[cfg(feature = "convert")]
+/// Converts `a` from the format `F` to the alternative half-precision format,
+/// where NaNs become zeros and infinities and results beyond its range
+/// become the largest magnitude, all raising only `INVALID`.
+pub(crate) fn to_alternative_half<F: Format>(env: &Env, a: u64) -> (u64, u32) {
+    const MAX: u64 = 0x7FFF;
+    let mut exc = 0;
+    let (ua, da) = first_operand::<F>(env, a, &mut exc);
+    let sign = sign_bit::<AlternativeHalf>(ua.sign);
+    let res = match ua.class {
+        Class::Nan => {
+            exc |= INVALID;
+            sign
+        }
+        Class::Inf => {
+            exc |= INVALID;
+            sign | MAX
+        }
+        Class::Zero => sign,
+        Class::Finite => {
+            denormal_operands(da, &mut exc);
+            let mut round = 0;
+            let res =
+                round_pack::<AlternativeHalf>(env, ua.sign, ua.exp, ua.sig as u128, &mut round);
+            if round & OVERFLOW != 0 {
+                exc |= INVALID;
+                sign | MAX
+            } else {
+                exc |= round;
+                res
+            }
+        }
+    };
+    (res, exc)
+}
+
+#[cfg(feature = "convert")]
+/// Converts `a` from the alternative half-precision format to the format
+/// `G`, which holds every value exactly.
+pub(crate) fn from_alternative_half<G: Format>(env: &Env, a: u64) -> (u64, u32) {
+    type F = AlternativeHalf;
+    let sign = a & F::SIGN != 0;
+    let biased = ((a & F::EXP_MASK) >> F::FRAC_BITS) as i32;
+    let frac = a & F::FRAC_MASK;
+    if biased == 0 && frac == 0 {
+        return (zero::<G>(sign), 0);
+    }
+    let mut exc = 0;
+    let (exp, sig) = if biased == 0 {
+        if env.sem.half_denorm {
+            exc |= DENORM;
+        }
+        (F::EMIN - F::FRAC_BITS as i32, frac)
+    } else {
+        (
+            biased - F::BIAS - F::FRAC_BITS as i32,
+            frac | 1 << F::FRAC_BITS,
+        )
+    };
+    (round_pack::<G>(env, sign, exp, sig as u128, &mut exc), exc)
+}
+
+#[cfg(feature = "batch")]
+#[cfg(feature = "convert")]
+/// Converts `a` to a 32-bit integer in the rounding mode, returning the
+/// integer indefinite `i32::MIN` for NaNs and values out of range, as
+/// `cvtps2dq` does.
+pub(crate) fn to_int<F: Format>(env: &Env, a: u64) -> (i32, u32) {
+    let mut exc = 0;
+    let (ua, _) = operand::<F>(env, a, &mut exc);
+    match ua.class {
+        Class::Nan | Class::Inf => (i32::MIN, exc | INVALID),
+        Class::Zero => (0, exc),
+        // Every such value is at least 2^32.
+        Class::Finite if ua.exp > 31 => (i32::MIN, exc | INVALID),
+        Class::Finite => {
+            let (int, half, sticky) = split(ua.sig as u128, -ua.exp);
+            let int = int + round_increment(env.mode, ua.sign, int & 1 != 0, half, sticky) as u128;
+            let limit = if ua.sign { 1 << 31 } else { (1 << 31) - 1 };
+            if int > limit {
+                return (i32::MIN, exc | INVALID);
+            }
+            if half || sticky {
+                exc |= INEXACT;
+            }
+            let int = if ua.sign { -(int as i64) } else { int as i64 };
+            (int as i32, exc)
+        }
+    }
+}
+
+#[cfg(feature = "batch")]
+#[cfg(feature = "convert")]
+/// Converts the 32-bit integer `a` to the format `F`.
+pub(crate) fn from_int<F: Format>(env: &Env, a: i32) -> (u64, u32) {
+    let mut exc = 0;
+    if a == 0 {
+        return (zero::<F>(false), exc);
+    }
+    let res = round_pack::<F>(env, a < 0, 0, a.unsigned_abs() as u128, &mut exc);
+    (res, exc)
+}
+
+/// The result of converting to an integer.
+pub(crate) enum Integer {
+    Value(i128),
+    Nan,
+    /// A value out of range, with its sign.
+    OutOfRange(bool),
+}
+
+/// Converts `a` to an integer from `min` to `max` in the rounding mode,
+/// raising invalid for NaNs and values out of range.
+pub(crate) fn to_integer<F: Format>(env: &Env, a: u64, min: i128, max: i128) -> (Integer, u32) {
+    let mut exc = 0;
+    let (ua, _) = operand::<F>(env, a, &mut exc);
+    match ua.class {
+        Class::Nan => (Integer::Nan, exc | INVALID),
+        Class::Inf => (Integer::OutOfRange(ua.sign), exc | INVALID),
+        Class::Zero => (Integer::Value(0), exc),
+        // Every such value is at least 2^65.
+        Class::Finite if ua.exp > 64 => (Integer::OutOfRange(ua.sign), exc | INVALID),
+        Class::Finite => {
+            let (int, half, sticky) = split(ua.sig as u128, -ua.exp);
+            let int = int + round_increment(env.mode, ua.sign, int & 1 != 0, half, sticky) as u128;
+            let int = if ua.sign { -(int as i128) } else { int as i128 };
+            if int < min || int > max {
+                return (Integer::OutOfRange(ua.sign), exc | INVALID);
+            }
+            if half || sticky {
+                exc |= INEXACT;
+            }
+            (Integer::Value(int), exc)
+        }
+    }
+}
+
+/// The x87 double extended format, whose 64-bit significand keeps its
+/// integer bit explicitly, under the x87 rules for its encodings.
+///
+/// Unnormals, pseudo-NaNs and pseudo-infinities, which have an exponent but
+/// no integer bit, are invalid operands, while pseudo-denormals are taken for
+/// the denormals they equal.
+pub(crate) mod extended {
+    use super::*;
+    use crate::f80::F80;
+
+    const INT: u64 = 1 << 63;
+    const QUIET: u64 = 1 << 62;
+    const EXP_MASK: u16 = 0x7FFF;
+    const BIAS: i32 = 16383;
+    const EMIN: i32 = 1 - BIAS;
+    const EMAX: i32 = BIAS;
+    const PREC: i32 = 64;
+
+    #[inline]
+    fn is_unsupported(x: F80) -> bool {
+        x.sign_exp & EXP_MASK != 0 && x.sig & INT == 0
+    }
+
+    #[inline]
+    fn is_nan(x: F80) -> bool {
+        x.sign_exp & EXP_MASK == EXP_MASK && x.sig & INT != 0 && x.sig << 1 != 0
+    }
+
+    #[inline]
+    fn is_snan(x: F80) -> bool {
+        is_nan(x) && x.sig & QUIET == 0
+    }
+
+    #[inline]
+    fn sign_exp(sign: bool, biased: u16) -> u16 {
+        (sign as u16) << 15 | biased
+    }
+
+    #[inline]
+    fn default_nan(env: &Env) -> F80 {
+        F80 {
+            sign_exp: sign_exp(env.sem.default_nan_negative, EXP_MASK),
+            sig: INT | QUIET,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn inf(sign: bool) -> F80 {
+        F80 {
+            sign_exp: sign_exp(sign, EXP_MASK),
+            sig: INT,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn zero(sign: bool) -> F80 {
+        F80 {
+            sign_exp: sign_exp(sign, 0),
+            sig: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn invalid(env: &Env, exc: &mut u32) -> F80 {
+        *exc |= INVALID;
+        default_nan(env)
+    }
+
+    /// Decodes a supported `x`, returning whether it is a denormal.
+    #[inline]
+    fn unpack(x: F80) -> (Unpacked, bool) {
+        let sign = x.sign_exp >> 15 != 0;
+        let biased = x.sign_exp & EXP_MASK;
+        let (class, exp, sig) = if biased == EXP_MASK {
+            (
+                if x.sig << 1 == 0 {
+                    Class::Inf
+                } else {
+                    Class::Nan
+                },
+                0,
+                0,
+            )
+        } else if x.sig == 0 {
+            (Class::Zero, 0, 0)
+        } else {
+            (
+                Class::Finite,
+                biased.max(1) as i32 - BIAS - (PREC - 1),
+                x.sig,
+            )
+        };
+        let unpacked = Unpacked {
+            sign,
+            class,
+            exp,
+            sig,
+        };
+        (unpacked, biased == 0 && x.sig != 0)
+    }
+
+    /// The result of an operation on `ops`, listed in priority order, when
+    /// any is unsupported or a NaN.
+    pub(crate) fn special(env: &Env, ops: &[F80], exc: &mut u32) -> Option<F80> {
+        if ops.iter().any(|&op| is_unsupported(op)) {
+            return Some(invalid(env, exc));
+        }
+        if !ops.iter().any(|&op| is_nan(op)) {
+            return None;
+        }
+        if ops.iter().any(|&op| is_snan(op)) {
+            *exc |= INVALID;
+        }
+        if env.default_nan {
+            return Some(default_nan(env));
+        }
+        // A quiet NaN wins over signaling ones, otherwise the larger
+        // significand does, and the positive one on ties.
+        let quiet = ops.iter().any(|&op| is_nan(op) && !is_snan(op));
+        let pick = ops
+            .iter()
+            .copied()
+            .filter(|&op| is_nan(op) && !(quiet && is_snan(op)))
+            .reduce(|best, op| {
+                let negative = best.sign_exp >> 15 != 0;
+                if op.sig > best.sig || op.sig == best.sig && negative {
+                    op
+                } else {
+                    best
+                }
+            })?;
+        Some(F80 {
+            sign_exp: pick.sign_exp,
+            sig: pick.sig | QUIET,
+        })
+    }
+
+    /// Rounds the nonzero value `sig * 2^exp` to `prec` bits of significand
+    /// within the exponent range of the format.
+    ///
+    /// Denormals keep the bits of the significand field that normals of the
+    /// precision do, so they lose precision from `prec` bits down.
+    pub(crate) fn round_pack(
+        env: &Env,
+        sign: bool,
+        exp: i32,
+        sig: u128,
+        prec: i32,
+        exc: &mut u32,
+    ) -> F80 {
+        debug_assert!(sig != 0);
+        if env.sem.wrap_exponent {
+            if let Some((kept, top)) = super::wrapped(env, sign, exp, sig, prec, (EMIN, EMAX), exc)
+            {
+                return F80 {
+                    sign_exp: sign_exp(sign, (top + BIAS) as u16),
+                    sig: (kept << (PREC - prec)) as u64,
+                };
+            }
+        }
+        let msb = 127 - sig.leading_zeros() as i32;
+        let e = exp + msb;
+        let qmin = EMIN - (prec - 1);
+        let mut q = (e - (prec - 1)).max(qmin);
+
+        let (mut kept, half, sticky) = split(sig, q - exp);
+        let inexact = half || sticky;
+        if round_increment(env.mode, sign, kept & 1 != 0, half, sticky) {
+            kept += 1;
+        }
+
+        let tiny = e < EMIN
+            && (env.sem.tininess_before_rounding || e < EMIN - 1 || {
+                let (kept, half, sticky) = split(sig, e - (prec - 1) - exp);
+                let up = round_increment(env.mode, sign, kept & 1 != 0, half, sticky);
+                (kept + up as u128) >> prec == 0
+            });
+        if tiny && inexact {
+            *exc |= UNDERFLOW;
+        }
+        if inexact {
+            *exc |= INEXACT;
+        }
+
+        if kept >> prec != 0 {
+            kept >>= 1;
+            q += 1;
+        }
+        if kept == 0 {
+            return zero(sign);
+        }
+        let top = 127 - kept.leading_zeros() as i32;
+        if q + top > EMAX {
+            *exc |= OVERFLOW | INEXACT;
+            let to_inf = match env.mode {
+                Mode::Nearest | Mode::NearestAway => true,
+                Mode::Zero | Mode::Odd => false,
+                Mode::Up => !sign,
+                Mode::Down => sign,
+            };
+            return if to_inf {
+                inf(sign)
+            } else {
+                F80 {
+                    sign_exp: sign_exp(sign, EXP_MASK - 1),
+                    sig: !0 << (PREC - prec),
+                }
+            };
+        }
+        if q + top < EMIN {
+            return F80 {
+                sign_exp: sign_exp(sign, 0),
+                sig: (kept << (q - (EMIN - (PREC - 1)))) as u64,
+            };
+        }
+        F80 {
+            sign_exp: sign_exp(sign, (q + top + BIAS) as u16),
+            sig: (kept << (PREC - 1 - top)) as u64,
+        }
+    }
+
+    fn add_impl(env: &Env, a: F80, b: F80, negate: bool, prec: i32) -> (F80, u32) {
+        let mut exc = 0;
+        if let Some(res) = special(env, &[a, b], &mut exc) {
+            return (res, exc);
+        }
+        let (ua, da) = unpack(a);
+        let (mut ub, db) = unpack(b);
+        ub.sign ^= negate;
+        let res = match (ua.class, ub.class) {
+            (Class::Inf, Class::Inf) if ua.sign != ub.sign => invalid(env, &mut exc),
+            _ => {
+                denormal_operands(da || db, &mut exc);
+                match (ua.class, ub.class) {
+                    (Class::Inf, _) => inf(ua.sign),
+                    (_, Class::Inf) => inf(ub.sign),
+                    (Class::Zero, Class::Zero) => zero(zero_sum_sign(env, ua.sign, ub.sign)),
+                    (Class::Zero, _) => {
+                        round_pack(env, ub.sign, ub.exp, ub.sig as u128, prec, &mut exc)
+                    }
+                    (_, Class::Zero) => {
+                        round_pack(env, ua.sign, ua.exp, ua.sig as u128, prec, &mut exc)
+                    }
+                    _ => match add_exact(
+                        (ua.sign, ua.exp, ua.sig as u128),
+                        (ub.sign, ub.exp, ub.sig as u128),
+                    ) {
+                        Some((sign, exp, sig)) => round_pack(env, sign, exp, sig, prec, &mut exc),
+                        None => zero(env.mode == Mode::Down),
+                    },
+                }
+            }
+        };
+        (res, exc)
+    }
+
+    /// Computes `a + b`, rounded to `prec` bits, as are the other operations
+    /// the precision control applies to.
+    pub(crate) fn add(env: &Env, a: F80, b: F80, prec: i32) -> (F80, u32) {
+        add_impl(env, a, b, false, prec)
+    }
+
+    /// Computes `a - b`.
+    pub(crate) fn sub(env: &Env, a: F80, b: F80, prec: i32) -> (F80, u32) {
+        add_impl(env, a, b, true, prec)
+    }
+
+    /// Computes `a * b`.
+    pub(crate) fn mul(env: &Env, a: F80, b: F80, prec: i32) -> (F80, u32) {
+        let mut exc = 0;
+        if let Some(res) = special(env, &[a, b], &mut exc) {
+            return (res, exc);
+        }
+        let (ua, da) = unpack(a);
+        let (ub, db) = unpack(b);
+        let sign = ua.sign != ub.sign;
+        let res = match (ua.class, ub.class) {
+            (Class::Inf, Class::Zero) | (Class::Zero, Class::Inf) => invalid(env, &mut exc),
+            (Class::Inf, _) | (_, Class::Inf) => {
+                denormal_operands(da || db, &mut exc);
+                inf(sign)
+            }
+            (Class::Zero, _) | (_, Class::Zero) => {
+                denormal_operands(da || db, &mut exc);
+                zero(sign)
+            }
+            _ => {
+                denormal_operands(da || db, &mut exc);
+                let sig = ua.sig as u128 * ub.sig as u128;
+                round_pack(env, sign, ua.exp + ub.exp, sig, prec, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    /// Computes `a / b`.
+    pub(crate) fn div(env: &Env, a: F80, b: F80, prec: i32) -> (F80, u32) {
+        let mut exc = 0;
+        if let Some(res) = special(env, &[a, b], &mut exc) {
+            return (res, exc);
+        }
+        let (ua, da) = unpack(a);
+        let (ub, db) = unpack(b);
+        let sign = ua.sign != ub.sign;
+        let res = match (ua.class, ub.class) {
+            (Class::Inf, Class::Inf) | (Class::Zero, Class::Zero) => invalid(env, &mut exc),
+            (Class::Inf, _) => {
+                denormal_operands(db, &mut exc);
+                inf(sign)
+            }
+            (_, Class::Inf) => {
+                denormal_operands(da, &mut exc);
+                zero(sign)
+            }
+            (_, Class::Zero) => {
+                exc |= DIV_ZERO;
+                inf(sign)
+            }
+            (Class::Zero, _) => {
+                denormal_operands(db, &mut exc);
+                zero(sign)
+            }
+            _ => {
+                denormal_operands(da || db, &mut exc);
+                let shift_a = ua.sig.leading_zeros() as i32;
+                let shift_b = ub.sig.leading_zeros() as i32;
+                let (sig_a, sig_b) = ((ua.sig << shift_a) as u128, (ub.sig << shift_b) as u128);
+                // Over 64 quotient bits, then 8 more from the remainder.
+                let num = sig_a << 64;
+                let (quo, rem) = (num / sig_b, num % sig_b);
+                let (low, rem) = ((rem << 8) / sig_b, (rem << 8) % sig_b);
+                let sig = quo << 8 | low | (rem != 0) as u128;
+                let exp = (ua.exp - shift_a) - (ub.exp - shift_b) - 72;
+                round_pack(env, sign, exp, sig, prec, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    /// Computes the square root of `a`.
+    pub(crate) fn sqrt(env: &Env, a: F80, prec: i32) -> (F80, u32) {
+        let mut exc = 0;
+        if let Some(res) = special(env, &[a], &mut exc) {
+            return (res, exc);
+        }
+        let (ua, da) = unpack(a);
+        let res = match ua.class {
+            Class::Zero => zero(ua.sign),
+            _ if ua.sign => invalid(env, &mut exc),
+            Class::Inf => inf(false),
+            _ => {
+                denormal_operands(da, &mut exc);
+                let shift = ua.sig.leading_zeros() as i32;
+                let (exp, sig) = (ua.exp - shift, ua.sig << shift);
+                // The widest even-exponent significand, whose root falls short
+                // of the rounding bits, which digit-by-digit steps then add.
+                let mut widen = 62;
+                if (exp - widen) % 2 != 0 {
+                    widen += 1;
+                }
+                let wide = (sig as u128) << widen;
+                let mut root = wide.isqrt();
+                let mut rem = wide - root * root;
+                for _ in 0..4 {
+                    let trial = 4 * root + 1;
+                    rem *= 4;
+                    root *= 2;
+                    if rem >= trial {
+                        rem -= trial;
+                        root += 1;
+                    }
+                }
+                let sig = root << 1 | (rem != 0) as u128;
+                round_pack(env, false, (exp - widen) / 2 - 5, sig, prec, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    /// Rounds `a` to an integral value in the current rounding mode, raising
+    /// `INEXACT` when that changes it.
+    pub(crate) fn round_int(env: &Env, a: F80) -> (F80, u32) {
+        let mut exc = 0;
+        if let Some(res) = special(env, &[a], &mut exc) {
+            return (res, exc);
+        }
+        let (ua, da) = unpack(a);
+        let res = match ua.class {
+            Class::Inf => inf(ua.sign),
+            Class::Zero => zero(ua.sign),
+            Class::Finite if ua.exp >= 0 => a,
+            _ => {
+                denormal_operands(da, &mut exc);
+                let (int, half, sticky) = split(ua.sig as u128, -ua.exp);
+                if half || sticky {
+                    exc |= INEXACT;
+                }
+                let int =
+                    int + round_increment(env.mode, ua.sign, int & 1 != 0, half, sticky) as u128;
+                if int == 0 {
+                    zero(ua.sign)
+                } else {
+                    round_pack(env, ua.sign, 0, int, PREC, &mut exc)
+                }
+            }
+        };
+        (res, exc)
+    }
+
+    /// Compares `a` with `b`, raising `INVALID` for any NaN unless `quiet`,
+    /// as `fucom` is to `fcom`, and for unsupported encodings.
+    pub(crate) fn compare(a: F80, b: F80, quiet: bool) -> (Option<Ordering>, u32) {
+        let mut exc = 0;
+        if is_unsupported(a) || is_unsupported(b) {
+            return (None, INVALID);
+        }
+        if is_nan(a) || is_nan(b) {
+            if !quiet || is_snan(a) || is_snan(b) {
+                exc |= INVALID;
+            }
+            return (None, exc);
+        }
+        // Pseudo-denormals order with the normals they equal.
+        let key = |x: F80| {
+            let (op, _) = unpack(x);
+            let biased = (x.sign_exp & EXP_MASK).max((x.sig >> 63) as u16);
+            let mag = if op.class == Class::Zero {
+                0
+            } else {
+                (biased as i128) << 64 | x.sig as i128
+            };
+            if op.sign {
+                -mag
+            } else {
+                mag
+            }
+        };
+        denormal_operands(unpack(a).1 || unpack(b).1, &mut exc);
+        (Some(key(a).cmp(&key(b))), exc)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts `a` from the format `F`, which is exact but for NaNs.
+    pub(crate) fn from<F: Format>(env: &Env, a: u64) -> (F80, u32) {
+        let mut exc = 0;
+        let (ua, da) = first_operand::<F>(env, a, &mut exc);
+        let res = match ua.class {
+            Class::Nan => {
+                if super::is_snan::<F>(a) {
+                    exc |= INVALID;
+                }
+                if env.default_nan {
+                    default_nan(env)
+                } else {
+                    F80 {
+                        sign_exp: sign_exp(ua.sign, EXP_MASK),
+                        sig: INT | QUIET | (a & F::FRAC_MASK) << (PREC - 1 - F::FRAC_BITS as i32),
+                    }
+                }
+            }
+            Class::Inf => inf(ua.sign),
+            Class::Zero => zero(ua.sign),
+            Class::Finite => {
+                denormal_operands(da, &mut exc);
+                round_pack(env, ua.sign, ua.exp, ua.sig as u128, PREC, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    #[cfg(feature = "convert")]
+    /// Converts `a` to the format `G`.
+    pub(crate) fn to<G: Format>(env: &Env, a: F80) -> (u64, u32) {
+        let mut exc = 0;
+        if is_unsupported(a) {
+            return (super::invalid::<G>(env, &mut exc), exc);
+        }
+        let (ua, _) = unpack(a);
+        let res = match ua.class {
+            Class::Nan => {
+                if is_snan(a) {
+                    exc |= INVALID;
+                }
+                if env.default_nan {
+                    super::default_nan::<G>(env)
+                } else {
+                    let frac = (a.sig & !INT) >> (PREC - 1 - G::FRAC_BITS as i32);
+                    sign_bit::<G>(ua.sign) | G::EXP_MASK | G::QUIET | frac
+                }
+            }
+            Class::Inf => super::inf::<G>(ua.sign),
+            Class::Zero => super::zero::<G>(ua.sign),
+            Class::Finite => super::round_pack::<G>(env, ua.sign, ua.exp, ua.sig as u128, &mut exc),
+        };
+        (res, exc)
+    }
+}
+
+/// The IEEE 754 binary128 format, whose significand needs twice the width of
+/// the intermediates of the other formats in products and quotients.
+#[cfg(any(feature = "f128", feature = "stochastic"))]
+pub(crate) mod quad {
+    use super::*;
+
+    const FRAC_BITS: u32 = 112;
+    const SIGN: u128 = 1 << 127;
+    const EXP_MASK: u128 = 0x7FFF << FRAC_BITS;
+    const FRAC_MASK: u128 = (1 << FRAC_BITS) - 1;
+    const QUIET: u128 = 1 << (FRAC_BITS - 1);
+    const BIAS: i32 = 16383;
+    const EMIN: i32 = 1 - BIAS;
+    const EMAX: i32 = BIAS;
+    const PREC: i32 = FRAC_BITS as i32 + 1;
+
+    /// A decoded operand; finite values equal `sig * 2^exp`.
+    #[derive(Clone, Copy)]
+    struct Unpacked {
+        sign: bool,
+        class: Class,
+        exp: i32,
+        sig: u128,
+    }
+
+    /// An unsigned 256-bit integer, as the high and low halves.
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    struct Wide(u128, u128);
+
+    impl Wide {
+        /// The exact product of `a` and `b`.
+        #[inline]
+        fn mul(a: u128, b: u128) -> Self {
+            let (a1, a0) = (a >> 64, a as u64 as u128);
+            let (b1, b0) = (b >> 64, b as u64 as u128);
+            let low = a0 * b0;
+            let (cross_a, cross_b) = (a0 * b1, a1 * b0);
+            let mid = (low >> 64) + (cross_a as u64 as u128) + (cross_b as u64 as u128);
+            let high = a1 * b1 + (cross_a >> 64) + (cross_b >> 64) + (mid >> 64);
+            Self(high, mid << 64 | low as u64 as u128)
+        }
+
+        #[inline]
+        fn leading_zeros(self) -> u32 {
+            if self.0 != 0 {
+                self.0.leading_zeros()
+            } else {
+                128 + self.1.leading_zeros()
+            }
+        }
+
+        #[inline]
+        fn shl(self, shift: u32) -> Self {
+            match shift {
+                0 => self,
+                1..=127 => Self(self.0 << shift | self.1 >> (128 - shift), self.1 << shift),
+                _ => Self(self.1 << (shift - 128), 0),
+            }
+        }
+
+        /// Shifts right by `shift`, returning whether any set bits were lost.
+        #[inline]
+        fn shr_lost(self, shift: i32) -> (Self, bool) {
+            match shift {
+                0 => (self, false),
+                1..=127 => {
+                    let lost = self.1 & ((1 << shift) - 1) != 0;
+                    let wide = Self(self.0 >> shift, self.1 >> shift | self.0 << (128 - shift));
+                    (wide, lost)
+                }
+                128..=255 => {
+                    let (low, lost) = shr_lost(self.0, shift - 128);
+                    (Self(0, low), lost || self.1 != 0)
+                }
+                _ => (Self(0, 0), self != Self(0, 0)),
+            }
+        }
+
+        #[inline]
+        fn add(self, other: Self) -> Self {
+            let (low, carry) = self.1.overflowing_add(other.1);
+            Self(self.0 + other.0 + carry as u128, low)
+        }
+
+        #[inline]
+        fn sub(self, other: Self) -> Self {
+            let (low, borrow) = self.1.overflowing_sub(other.1);
+            Self(self.0 - other.0 - borrow as u128, low)
+        }
+
+        /// The nonzero value `self * 2^exp`, below 2^255, narrowed to 128
+        /// bits with its leading bit at bit 126, keeping a sticky bit for the
+        /// rest.
+        #[inline]
+        fn narrow(self, exp: i32) -> (i32, u128) {
+            let shift = self.leading_zeros() as i32 - 1;
+            let wide = self.shl(shift as u32);
+            (exp - shift + 128, wide.0 | (wide.1 != 0) as u128)
+        }
+    }
+
+    #[inline]
+    fn is_nan(bits: u128) -> bool {
+        bits & EXP_MASK == EXP_MASK && bits & FRAC_MASK != 0
+    }
+
+    #[inline]
+    fn is_snan(bits: u128) -> bool {
+        is_nan(bits) && bits & QUIET == 0
+    }
+
+    #[inline]
+    fn sign_bit(sign: bool) -> u128 {
+        (sign as u128) << 127
+    }
+
+    #[inline]
+    fn default_nan(env: &Env) -> u128 {
+        sign_bit(env.sem.default_nan_negative) | EXP_MASK | QUIET
+    }
+
+    #[inline]
+    fn inf(sign: bool) -> u128 {
+        sign_bit(sign) | EXP_MASK
+    }
+
+    #[inline]
+    fn zero(sign: bool) -> u128 {
+        sign_bit(sign)
+    }
+
+    #[inline]
+    fn invalid(env: &Env, exc: &mut u32) -> u128 {
+        *exc |= INVALID;
+        default_nan(env)
+    }
+
+    /// Decodes `bits` without looking at the environment.
+    #[inline]
+    fn unpack(bits: u128) -> Unpacked {
+        let sign = bits & SIGN != 0;
+        let biased = ((bits & EXP_MASK) >> FRAC_BITS) as i32;
+        let frac = bits & FRAC_MASK;
+        let (class, exp, sig) = if biased == 0x7FFF {
+            (if frac == 0 { Class::Inf } else { Class::Nan }, 0, 0)
+        } else if biased == 0 {
+            if frac == 0 {
+                (Class::Zero, 0, 0)
+            } else {
+                (Class::Finite, EMIN - FRAC_BITS as i32, frac)
+            }
+        } else {
+            (
+                Class::Finite,
+                biased - BIAS - FRAC_BITS as i32,
+                frac | 1 << FRAC_BITS,
+            )
+        };
+        Unpacked {
+            sign,
+            class,
+            exp,
+            sig,
+        }
+    }
+
+    /// Decodes an operand, applying denormals-are-zero; returns whether the
+    /// operand is a denormal that was consumed as-is.
+    #[inline]
+    fn operand(env: &Env, bits: u128, exc: &mut u32) -> (Unpacked, bool) {
+        let op = unpack(bits);
+        if op.class == Class::Finite && op.sig >> FRAC_BITS == 0 {
+            if env.daz {
+                if env.sem.denorm_on_flush {
+                    *exc |= DENORM;
+                }
+                let zero = Unpacked {
+                    class: Class::Zero,
+                    exp: 0,
+                    sig: 0,
+                    ..op
+                };
+                return (zero, false);
+            }
+            return (op, !env.sem.denorm_on_flush);
+        }
+        (op, false)
+    }
+
+    /// Decodes the first operand like `operand`, raising `DENORM` right away
+    /// where loading it does.
+    #[inline]
+    fn first_operand(env: &Env, bits: u128, exc: &mut u32) -> (Unpacked, bool) {
+        let (op, denormal) = operand(env, bits, exc);
+        if env.sem.denorm_on_load {
+            denormal_operands(denormal, exc);
+        }
+        (op, denormal)
+    }
+
+    /// Picks the NaN result for an operation whose `ops`, listed in priority
+    /// order, contain at least one NaN.
+    fn propagate(env: &Env, ops: &[u128], exc: &mut u32) -> u128 {
+        if ops.iter().any(|&op| is_snan(op)) {
+            *exc |= INVALID;
+        }
+        if env.default_nan {
+            return default_nan(env);
+        }
+        let pick = match env.sem.nan {
+            NanRule::FirstOperand => ops.iter().copied().find(|&op| is_nan(op)),
+            NanRule::SignalingFirst => ops
+                .iter()
+                .copied()
+                .find(|&op| is_snan(op))
+                .or_else(|| ops.iter().copied().find(|&op| is_nan(op))),
+            NanRule::Canonical => None,
+            NanRule::LargerSignificand => {
+                let first = if is_nan(ops[0]) {
+                    ops[0] | QUIET
+                } else {
+                    ops[0]
+                };
+                let loaded = || core::iter::once(first).chain(ops[1..].iter().copied());
+                let quiet = loaded().any(|op| is_nan(op) && !is_snan(op));
+                loaded()
+                    .filter(|&op| is_nan(op) && !(quiet && is_snan(op)))
+                    .reduce(|best, op| {
+                        let (sig, best_sig) = (op & FRAC_MASK, best & FRAC_MASK);
+                        if sig > best_sig || sig == best_sig && best & SIGN != 0 {
+                            op
+                        } else {
+                            best
+                        }
+                    })
+            }
+        };
+        pick.map_or(default_nan(env), |op| op | QUIET)
+    }
+
+    /// Rounds the nonzero value `sig * 2^exp` to the format, with the same
+    /// needs of inexact intermediates as the other formats.
+    fn round_pack(env: &Env, sign: bool, exp: i32, sig: u128, exc: &mut u32) -> u128 {
+        debug_assert!(sig != 0);
+        let msb = 127 - sig.leading_zeros() as i32;
+        let e = exp + msb;
+        let qmin = EMIN - (PREC - 1);
+        let mut q = (e - (PREC - 1)).max(qmin);
+
+        let (mut kept, half, sticky) = split(sig, q - exp);
+        let inexact = half || sticky;
+        if round_increment(env.mode, sign, kept & 1 != 0, half, sticky) {
+            kept += 1;
+        }
+
+        let tiny = e < EMIN
+            && (env.sem.tininess_before_rounding || e < EMIN - 1 || {
+                let (kept, half, sticky) = split(sig, e - (PREC - 1) - exp);
+                let up = round_increment(env.mode, sign, kept & 1 != 0, half, sticky);
+                (kept + up as u128) >> PREC == 0
+            });
+        if tiny && env.ftz {
+            *exc |= UNDERFLOW;
+            if env.sem.flush_inexact {
+                *exc |= INEXACT;
+            }
+            return zero(sign);
+        }
+        if tiny && inexact {
+            *exc |= UNDERFLOW;
+        }
+        if inexact {
+            *exc |= INEXACT;
+        }
+
+        if kept >> PREC != 0 {
+            kept >>= 1;
+            q += 1;
+        }
+        if kept >> (PREC - 1) != 0 && q + PREC - 1 > EMAX {
+            *exc |= OVERFLOW | INEXACT;
+            let to_inf = match env.mode {
+                Mode::Nearest | Mode::NearestAway => true,
+                Mode::Zero | Mode::Odd => false,
+                Mode::Up => !sign,
+                Mode::Down => sign,
+            };
+            return if to_inf {
+                inf(sign)
+            } else {
+                sign_bit(sign) | (EXP_MASK - (1 << FRAC_BITS)) | FRAC_MASK
+            };
+        }
+        sign_bit(sign) + (((q - qmin) as u128) << FRAC_BITS) + kept
+    }
+
+    /// Adds two exact nonzero values like `add_exact`, over the full width
+    /// of products.
+    fn add_exact_wide(
+        (sign_a, exp_a, sig_a): (bool, i32, Wide),
+        (sign_b, exp_b, sig_b): (bool, i32, Wide),
+    ) -> Option<(bool, i32, u128)> {
+        let norm = |exp: i32, sig: Wide| {
+            let shift = sig.leading_zeros() as i32 - 2;
+            (exp - shift, sig.shl(shift as u32))
+        };
+        let (exp_a, sig_a) = norm(exp_a, sig_a);
+        let (exp_b, sig_b) = norm(exp_b, sig_b);
+        let ((sign_a, exp_a, sig_a), (sign_b, exp_b, sig_b)) = if (exp_a, sig_a) >= (exp_b, sig_b) {
+            ((sign_a, exp_a, sig_a), (sign_b, exp_b, sig_b))
+        } else {
+            ((sign_b, exp_b, sig_b), (sign_a, exp_a, sig_a))
+        };
+        let (sig_b, lost) = sig_b.shr_lost(exp_a - exp_b);
+        let lost = Wide(0, lost as u128);
+        let sig = if sign_a == sign_b {
+            sig_a.add(sig_b)
+        } else {
+            sig_a.sub(sig_b).sub(lost)
+        };
+        if sig == Wide(0, 0) {
+            return None;
+        }
+        let (exp, sig) = Wide(sig.0, sig.1 | lost.1).narrow(exp_a);
+        Some((sign_a, exp, sig))
+    }
+
+    fn add_impl(env: &Env, a: u128, b: u128, negate: bool) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, da) = first_operand(env, a, &mut exc);
+        let (mut ub, db) = operand(env, b, &mut exc);
+        if ua.class == Class::Nan || ub.class == Class::Nan {
+            return (propagate(env, &[a, b], &mut exc), exc);
+        }
+        ub.sign ^= negate;
+        let res = match (ua.class, ub.class) {
+            (Class::Inf, Class::Inf) if ua.sign != ub.sign => invalid(env, &mut exc),
+            _ => {
+                denormal_operands(da || db, &mut exc);
+                match (ua.class, ub.class) {
+                    (Class::Inf, _) => inf(ua.sign),
+                    (_, Class::Inf) => inf(ub.sign),
+                    (Class::Zero, Class::Zero) => zero(zero_sum_sign(env, ua.sign, ub.sign)),
+                    (Class::Zero, _) => round_pack(env, ub.sign, ub.exp, ub.sig, &mut exc),
+                    (_, Class::Zero) => round_pack(env, ua.sign, ua.exp, ua.sig, &mut exc),
+                    // The significands leave the twelve bits below them
+                    // that `add_exact` needs.
+                    _ => match add_exact((ua.sign, ua.exp, ua.sig), (ub.sign, ub.exp, ub.sig)) {
+                        Some((sign, exp, sig)) => round_pack(env, sign, exp, sig, &mut exc),
+                        None => zero(env.mode == Mode::Down),
+                    },
+                }
+            }
+        };
+        (res, exc)
+    }
+
+    pub(crate) fn add(env: &Env, a: u128, b: u128) -> (u128, u32) {
+        add_impl(env, a, b, false)
+    }
+
+    pub(crate) fn sub(env: &Env, a: u128, b: u128) -> (u128, u32) {
+        add_impl(env, a, b, true)
+    }
+
+    pub(crate) fn mul(env: &Env, a: u128, b: u128) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, da) = first_operand(env, a, &mut exc);
+        let (ub, db) = operand(env, b, &mut exc);
+        if ua.class == Class::Nan || ub.class == Class::Nan {
+            return (propagate(env, &[a, b], &mut exc), exc);
+        }
+        let sign = ua.sign != ub.sign;
+        let res = match (ua.class, ub.class) {
+            (Class::Inf, Class::Zero) | (Class::Zero, Class::Inf) => invalid(env, &mut exc),
+            (Class::Inf, _) | (_, Class::Inf) => {
+                denormal_operands(da || db, &mut exc);
+                inf(sign)
+            }
+            _ => {
+                denormal_operands(da || db, &mut exc);
+                if ua.class == Class::Zero || ub.class == Class::Zero {
+                    zero(sign)
+                } else {
+                    let (exp, sig) = Wide::mul(ua.sig, ub.sig).narrow(ua.exp + ub.exp);
+                    round_pack(env, sign, exp, sig, &mut exc)
+                }
+            }
+        };
+        (res, exc)
+    }
+
+    /// Shifts a finite significand so its leading bit is the implicit bit.
+    #[inline]
+    fn normalize(exp: i32, sig: u128) -> (i32, u128) {
+        let shift = sig.leading_zeros() as i32 - (127 - FRAC_BITS as i32);
+        (exp - shift, sig << shift)
+    }
+
+    #[cfg(feature = "f128")]
+    pub(crate) fn div(env: &Env, a: u128, b: u128) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, da) = first_operand(env, a, &mut exc);
+        let (ub, db) = operand(env, b, &mut exc);
+        if ua.class == Class::Nan || ub.class == Class::Nan {
+            return (propagate(env, &[a, b], &mut exc), exc);
+        }
+        let sign = ua.sign != ub.sign;
+        let res = match (ua.class, ub.class) {
+            (Class::Inf, Class::Inf) | (Class::Zero, Class::Zero) => invalid(env, &mut exc),
+            (Class::Inf, _) => {
+                denormal_operands(db, &mut exc);
+                inf(sign)
+            }
+            (_, Class::Inf) => {
+                denormal_operands(da, &mut exc);
+                zero(sign)
+            }
+            (_, Class::Zero) => {
+                exc |= DIV_ZERO;
+                inf(sign)
+            }
+            (Class::Zero, _) => {
+                denormal_operands(db, &mut exc);
+                zero(sign)
+            }
+            _ => {
+                denormal_operands(da || db, &mut exc);
+                let (exp_a, sig_a) = normalize(ua.exp, ua.sig);
+                let (exp_b, sig_b) = normalize(ub.exp, ub.sig);
+                // Long division, to a quotient of at least 116 bits.
+                let (mut quo, mut rem) = (0u128, sig_a);
+                for _ in 0..117 {
+                    quo <<= 1;
+                    if rem >= sig_b {
+                        rem -= sig_b;
+                        quo |= 1;
+                    }
+                    rem <<= 1;
+                }
+                let sig = quo << 1 | (rem != 0) as u128;
+                round_pack(env, sign, exp_a - exp_b - 117, sig, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    #[cfg(feature = "fma")]
+    /// Computes `a * b + c` with a single rounding.
+    pub(crate) fn fma(env: &Env, a: u128, b: u128, c: u128) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, da) = operand(env, a, &mut exc);
+        let (ub, db) = operand(env, b, &mut exc);
+        let (uc, dc) = operand(env, c, &mut exc);
+        let inf_zero = matches!(
+            (ua.class, ub.class),
+            (Class::Inf, Class::Zero) | (Class::Zero, Class::Inf)
+        );
+        if ua.class == Class::Nan || ub.class == Class::Nan || uc.class == Class::Nan {
+            let ops = env.sem.fma_nan_order.map(|i| [a, b, c][i]);
+            let nan = propagate(env, &ops, &mut exc);
+            if inf_zero && env.sem.fma_inf_zero_nan_invalid {
+                return (invalid(env, &mut exc), exc);
+            }
+            return (nan, exc);
+        }
+        if inf_zero {
+            return (invalid(env, &mut exc), exc);
+        }
+        let sign = ua.sign != ub.sign;
+        let prod_inf = ua.class == Class::Inf || ub.class == Class::Inf;
+        let prod_zero = ua.class == Class::Zero || ub.class == Class::Zero;
+        let res = if prod_inf || uc.class == Class::Inf {
+            if prod_inf && uc.class == Class::Inf && sign != uc.sign {
+                invalid(env, &mut exc)
+            } else {
+                denormal_operands(da || db || dc, &mut exc);
+                inf(if prod_inf { sign } else { uc.sign })
+            }
+        } else {
+            denormal_operands(da || db || dc, &mut exc);
+            match (prod_zero, uc.class == Class::Zero) {
+                (true, true) => zero(zero_sum_sign(env, sign, uc.sign)),
+                (true, false) => round_pack(env, uc.sign, uc.exp, uc.sig, &mut exc),
+                (false, zero_c) => {
+                    let prod = (sign, ua.exp + ub.exp, Wide::mul(ua.sig, ub.sig));
+                    if zero_c {
+                        let (exp, sig) = prod.2.narrow(prod.1);
+                        round_pack(env, sign, exp, sig, &mut exc)
+                    } else {
+                        match add_exact_wide(prod, (uc.sign, uc.exp, Wide(0, uc.sig))) {
+                            Some((sign, exp, sig)) => round_pack(env, sign, exp, sig, &mut exc),
+                            None => zero(env.mode == Mode::Down),
+                        }
+                    }
+                }
+            }
+        };
+        (res, exc)
+    }
+
+    #[cfg(feature = "f128")]
+    pub(crate) fn sqrt(env: &Env, a: u128) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, da) = first_operand(env, a, &mut exc);
+        let res = match ua.class {
+            Class::Nan => propagate(env, &[a], &mut exc),
+            Class::Zero => zero(ua.sign),
+            _ if ua.sign => invalid(env, &mut exc),
+            Class::Inf => inf(false),
+            Class::Finite => {
+                denormal_operands(da, &mut exc);
+                let (mut exp, mut sig) = normalize(ua.exp, ua.sig);
+                if exp % 2 != 0 {
+                    exp -= 1;
+                    sig <<= 1;
+                }
+                // The root of the significand, then digit-by-digit steps to
+                // at least 116 bits.
+                let mut root = sig.isqrt();
+                let mut rem = sig - root * root;
+                for _ in 0..59 {
+                    let trial = 4 * root + 1;
+                    rem *= 4;
+                    root *= 2;
+                    if rem >= trial {
+                        rem -= trial;
+                        root += 1;
+                    }
+                }
+                let sig = root << 1 | (rem != 0) as u128;
+                round_pack(env, false, exp / 2 - 60, sig, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    #[cfg(feature = "f128")]
+    /// Rounds `a` to an integral value in the current rounding mode, raising
+    /// `INEXACT` when that changes it.
+    pub(crate) fn round_int(env: &Env, a: u128) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, _) = first_operand(env, a, &mut exc);
+        let res = match ua.class {
+            Class::Nan => propagate(env, &[a], &mut exc),
+            Class::Inf => inf(ua.sign),
+            Class::Zero => zero(ua.sign),
+            Class::Finite if ua.exp >= 0 => a,
+            Class::Finite => {
+                let (int, half, sticky) = split(ua.sig, -ua.exp);
+                if half || sticky {
+                    exc |= INEXACT;
+                }
+                let int =
+                    int + round_increment(env.mode, ua.sign, int & 1 != 0, half, sticky) as u128;
+                if int == 0 {
+                    zero(ua.sign)
+                } else {
+                    round_pack(env, ua.sign, 0, int, &mut exc)
+                }
+            }
+        };
+        (res, exc)
+    }
+
+    #[cfg(feature = "f128")]
+    #[cfg(feature = "convert")]
+    /// Converts `a` from the format `F`, which is exact but for NaNs.
+    pub(crate) fn from<F: Format>(env: &Env, a: u64) -> (u128, u32) {
+        let mut exc = 0;
+        let (ua, da) = super::first_operand::<F>(env, a, &mut exc);
+        let res = match ua.class {
+            Class::Nan => {
+                if super::is_snan::<F>(a) {
+                    exc |= INVALID;
+                }
+                if env.default_nan || env.sem.nan == NanRule::Canonical {
+                    default_nan(env)
+                } else {
+                    let frac = ((a & F::FRAC_MASK) as u128) << (FRAC_BITS - F::FRAC_BITS);
+                    sign_bit(ua.sign) | EXP_MASK | QUIET | frac
+                }
+            }
+            Class::Inf => inf(ua.sign),
+            Class::Zero => zero(ua.sign),
+            Class::Finite => {
+                denormal_operands(da, &mut exc);
+                round_pack(env, ua.sign, ua.exp, ua.sig as u128, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+
+    #[cfg(feature = "f128")]
+    #[cfg(feature = "convert")]
+    /// Converts `a` to the format `G`.
+    pub(crate) fn to<G: Format>(env: &Env, a: u128) -> (u64, u32) {
+        let mut exc = 0;
+        let (ua, da) = first_operand(env, a, &mut exc);
+        let res = match ua.class {
+            Class::Nan => {
+                if is_snan(a) {
+                    exc |= INVALID;
+                }
+                if env.default_nan || env.sem.nan == NanRule::Canonical {
+                    super::default_nan::<G>(env)
+                } else {
+                    let frac = ((a & FRAC_MASK) >> (FRAC_BITS - G::FRAC_BITS)) as u64;
+                    super::sign_bit::<G>(ua.sign) | G::EXP_MASK | G::QUIET | frac
+                }
+            }
+            Class::Inf => super::inf::<G>(ua.sign),
+            Class::Zero => super::zero::<G>(ua.sign),
+            Class::Finite => {
+                denormal_operands(da, &mut exc);
+                super::round_pack::<G>(env, ua.sign, ua.exp, ua.sig, &mut exc)
+            }
+        };
+        (res, exc)
+    }
+}
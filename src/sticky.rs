@@ -0,0 +1,212 @@
+//! The operations ORing their exceptions into a `Status` they are handed,
+//! the way guest sticky-flag registers accumulate them.
+
+pub(crate) mod f32 {
+    use crate::{Flags, Status};
+
+    #[inline]
+    pub fn add_sticky(flags: Flags, sticky: &mut Status, l: f32, r: f32) -> f32 {
+        let (value, status) = crate::f32::add(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn sub_sticky(flags: Flags, sticky: &mut Status, l: f32, r: f32) -> f32 {
+        let (value, status) = crate::f32::sub(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn mul_sticky(flags: Flags, sticky: &mut Status, l: f32, r: f32) -> f32 {
+        let (value, status) = crate::f32::mul(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn div_sticky(flags: Flags, sticky: &mut Status, l: f32, r: f32) -> f32 {
+        let (value, status) = crate::f32::div(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_sticky(flags: Flags, sticky: &mut Status, a: f32, b: f32, c: f32) -> f32 {
+        let (value, status) = crate::f32::madd(flags, a, b, c);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn round_sticky(flags: Flags, sticky: &mut Status, x: f32) -> f32 {
+        let (value, status) = crate::f32::round(flags, x);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn sqrt_sticky(flags: Flags, sticky: &mut Status, x: f32) -> f32 {
+        let (value, status) = crate::f32::sqrt(flags, x);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double_sticky(flags: Flags, sticky: &mut Status, single: f32) -> f64 {
+        let (value, status) = crate::f32::to_double(flags, single);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half_sticky(flags: Flags, sticky: &mut Status, single: f32) -> u16 {
+        let (value, status) = crate::f32::to_half(flags, single);
+        *sticky = sticky.or(status);
+        value
+    }
+}
+
+pub(crate) mod f64 {
+    use crate::{Flags, Status};
+
+    #[inline]
+    pub fn add_sticky(flags: Flags, sticky: &mut Status, l: f64, r: f64) -> f64 {
+        let (value, status) = crate::f64::add(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn sub_sticky(flags: Flags, sticky: &mut Status, l: f64, r: f64) -> f64 {
+        let (value, status) = crate::f64::sub(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn mul_sticky(flags: Flags, sticky: &mut Status, l: f64, r: f64) -> f64 {
+        let (value, status) = crate::f64::mul(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn div_sticky(flags: Flags, sticky: &mut Status, l: f64, r: f64) -> f64 {
+        let (value, status) = crate::f64::div(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_sticky(flags: Flags, sticky: &mut Status, a: f64, b: f64, c: f64) -> f64 {
+        let (value, status) = crate::f64::madd(flags, a, b, c);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn round_sticky(flags: Flags, sticky: &mut Status, x: f64) -> f64 {
+        let (value, status) = crate::f64::round(flags, x);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn sqrt_sticky(flags: Flags, sticky: &mut Status, x: f64) -> f64 {
+        let (value, status) = crate::f64::sqrt(flags, x);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single_sticky(flags: Flags, sticky: &mut Status, double: f64) -> f32 {
+        let (value, status) = crate::f64::to_single(flags, double);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half_sticky(flags: Flags, sticky: &mut Status, double: f64) -> u16 {
+        let (value, status) = crate::f64::to_half(flags, double);
+        *sticky = sticky.or(status);
+        value
+    }
+}
+
+pub(crate) mod f16 {
+    use crate::{Flags, Status};
+
+    #[inline]
+    pub fn add_sticky(flags: Flags, sticky: &mut Status, l: u16, r: u16) -> u16 {
+        let (value, status) = crate::f16::add(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn sub_sticky(flags: Flags, sticky: &mut Status, l: u16, r: u16) -> u16 {
+        let (value, status) = crate::f16::sub(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn mul_sticky(flags: Flags, sticky: &mut Status, l: u16, r: u16) -> u16 {
+        let (value, status) = crate::f16::mul(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn div_sticky(flags: Flags, sticky: &mut Status, l: u16, r: u16) -> u16 {
+        let (value, status) = crate::f16::div(flags, l, r);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd_sticky(flags: Flags, sticky: &mut Status, a: u16, b: u16, c: u16) -> u16 {
+        let (value, status) = crate::f16::madd(flags, a, b, c);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn round_sticky(flags: Flags, sticky: &mut Status, x: u16) -> u16 {
+        let (value, status) = crate::f16::round(flags, x);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[inline]
+    pub fn sqrt_sticky(flags: Flags, sticky: &mut Status, x: u16) -> u16 {
+        let (value, status) = crate::f16::sqrt(flags, x);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single_sticky(flags: Flags, sticky: &mut Status, half: u16) -> f32 {
+        let (value, status) = crate::f16::to_single(flags, half);
+        *sticky = sticky.or(status);
+        value
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double_sticky(flags: Flags, sticky: &mut Status, half: u16) -> f64 {
+        let (value, status) = crate::f16::to_double(flags, half);
+        *sticky = sticky.or(status);
+        value
+    }
+}
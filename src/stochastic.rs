@@ -0,0 +1,269 @@
+//! Operations rounding stochastically: an inexact result rounds away from
+//! zero with probability proportional to its distance from the value towards
+//! zero, and towards zero otherwise, as low-precision training uses so that
+//! rounding errors cancel on average.
+//!
+//! The caller supplies the randomness as a generator of uniform 64-bit
+//! words, drawn once for each inexact result and never for exact ones, so a
+//! seeded generator reproduces a computation. The probability is exact to
+//! 2^-64 for conversions and to 2^-29 for single-precision and 2^-60 for
+//! double-precision arithmetic, which is computed rounded to odd in a wider
+//! format before rounding stochastically.
+//!
+//! The flags apply but for the rounding mode, which the stochastic rounding
+//! replaces, and flushing to zero, which doesn't apply. Results of a
+//! magnitude reaching past the largest finite value by an ulp become
+//! infinities, raising overflow and inexact, and inexact results below the
+//! normal range before rounding raise underflow. NaNs, infinities and zeros
+//! are returned as under rounding to nearest.
+
+use crate::soft::{self, Format};
+use crate::{Flags, Rounding, Status};
+
+/// Rounds `sig * 2^exp`, nonzero but for a lost lowest bit standing in for
+/// a lower part, to `F` stochastically.
+fn round<F: Format>(
+    sign: bool,
+    exp: i32,
+    sig: u128,
+    rng: &mut impl FnMut() -> u64,
+) -> (u64, Status) {
+    let sign_bit = if sign { F::SIGN } else { 0 };
+    // The value lies in [2^e, 2^(e + 1)), and rounds to a multiple of 2^q.
+    let e = exp + 127 - sig.leading_zeros() as i32;
+    let qmin = F::EMIN - (F::PREC - 1);
+    let mut q = (e - (F::PREC - 1)).max(qmin);
+    let shift = q - exp;
+    // The part below 2^q, as whether it is nonzero and its top 64 bits as a
+    // fraction of 2^q.
+    let (mut kept, lost, fraction) = match shift {
+        ..=0 => (sig << -shift, false, 0),
+        1..=127 => {
+            let rest = sig << (128 - shift);
+            (sig >> shift, rest != 0, (rest >> 64) as u64)
+        }
+        128..=191 => (0, true, (sig >> (shift - 64)) as u64),
+        _ => (0, true, 0),
+    };
+    let mut exc = 0;
+    if lost {
+        exc |= soft::INEXACT;
+        if e < F::EMIN {
+            exc |= soft::UNDERFLOW;
+        }
+        if rng() < fraction {
+            kept += 1;
+        }
+    }
+    if kept >> F::PREC != 0 {
+        kept >>= 1;
+        q += 1;
+    }
+    if kept >> (F::PREC - 1) != 0 && q + F::PREC - 1 > F::EMAX {
+        return (
+            sign_bit | F::EXP_MASK,
+            Status::from_soft(exc | soft::OVERFLOW | soft::INEXACT),
+        );
+    }
+    let bits = sign_bit + (((q - qmin) as u64) << F::FRAC_BITS) + kept as u64;
+    (bits, Status::from_soft(exc))
+}
+
+/// Decodes a finite nonzero `x` of `F` as its sign, exponent and significand.
+#[inline]
+fn unpack<F: Format>(x: u64) -> (bool, i32, u128) {
+    let biased = ((x & F::EXP_MASK) >> F::FRAC_BITS) as i32;
+    let frac = (x & F::FRAC_MASK) as u128;
+    let sig = if biased == 0 {
+        frac
+    } else {
+        frac | 1 << F::FRAC_BITS
+    };
+    let exp = biased.max(1) - F::BIAS - F::FRAC_BITS as i32;
+    (x & F::SIGN != 0, exp, sig)
+}
+
+/// The flags computing the results that don't round.
+#[inline]
+fn exact(flags: Flags) -> Flags {
+    flags
+        .with_rounding(Rounding::Nearest)
+        .with_ftz(false)
+        .with_daz(false)
+}
+
+/// The environment of the wider computation.
+#[inline]
+fn odd(flags: Flags) -> soft::Env {
+    soft::Env {
+        mode: soft::Mode::Odd,
+        ftz: false,
+        daz: false,
+        ..flags.soft_env()
+    }
+}
+
+const QUAD_FRAC_BITS: u32 = 112;
+const QUAD_BIAS: i32 = 16383;
+
+/// Widens `x` to binary128 exactly, leaving NaNs as they are.
+#[inline]
+fn widen(x: f64) -> u128 {
+    let bits = x.to_bits();
+    let sign = ((bits >> 63) as u128) << 127;
+    if bits << 1 == 0 {
+        return sign;
+    }
+    if !x.is_finite() {
+        let frac = (bits & soft::Binary64::FRAC_MASK) as u128;
+        return sign | 0x7FFF << QUAD_FRAC_BITS | frac << (QUAD_FRAC_BITS - 52);
+    }
+    let (_, exp, sig) = unpack::<soft::Binary64>(bits);
+    let shift = sig.leading_zeros() - (127 - QUAD_FRAC_BITS);
+    let biased = (exp - shift as i32 + QUAD_BIAS + QUAD_FRAC_BITS as i32) as u128;
+    sign | biased << QUAD_FRAC_BITS | (sig << shift) & ((1 << QUAD_FRAC_BITS) - 1)
+}
+
+/// Rounds the binary128 result of a wider computation to double precision,
+/// or returns `None` if it is not finite and nonzero.
+#[inline]
+fn narrow(x: u128, rng: &mut impl FnMut() -> u64) -> Option<(f64, Status)> {
+    let biased = (x >> QUAD_FRAC_BITS) as i32 & 0x7FFF;
+    if biased == 0x7FFF || x << 1 == 0 {
+        return None;
+    }
+    // Products and sums of doubles are never below the normal range here.
+    let sig = x & ((1 << QUAD_FRAC_BITS) - 1) | 1 << QUAD_FRAC_BITS;
+    let exp = biased - QUAD_BIAS - QUAD_FRAC_BITS as i32;
+    let (bits, status) = round::<soft::Binary64>(x >> 127 != 0, exp, sig, rng);
+    Some((f64::from_bits(bits), status))
+}
+
+pub mod f32 {
+    use super::*;
+
+    /// Rounds the double-precision result of a wider computation, or
+    /// computes `exact` if it is not finite and nonzero.
+    #[inline]
+    fn finish(
+        (wide, _): (u64, u32),
+        rng: &mut impl FnMut() -> u64,
+        exact: impl FnOnce() -> (f32, Status),
+    ) -> (f32, Status) {
+        if !f64::from_bits(wide).is_finite() || wide << 1 == 0 {
+            return exact();
+        }
+        let (sign, exp, sig) = unpack::<soft::Binary64>(wide);
+        let (bits, status) = round::<soft::Binary32>(sign, exp, sig, rng);
+        (f32::from_bits(bits as u32), status)
+    }
+
+    #[inline]
+    pub fn add(flags: Flags, rng: &mut impl FnMut() -> u64, l: f32, r: f32) -> (f32, Status) {
+        let wide =
+            soft::add::<soft::Binary64>(&odd(flags), (l as f64).to_bits(), (r as f64).to_bits());
+        finish(wide, rng, || crate::f32::add(exact(flags), l, r))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, rng: &mut impl FnMut() -> u64, l: f32, r: f32) -> (f32, Status) {
+        let wide =
+            soft::sub::<soft::Binary64>(&odd(flags), (l as f64).to_bits(), (r as f64).to_bits());
+        finish(wide, rng, || crate::f32::sub(exact(flags), l, r))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, rng: &mut impl FnMut() -> u64, l: f32, r: f32) -> (f32, Status) {
+        let wide =
+            soft::mul::<soft::Binary64>(&odd(flags), (l as f64).to_bits(), (r as f64).to_bits());
+        finish(wide, rng, || crate::f32::mul(exact(flags), l, r))
+    }
+
+    /// Computes `a * b + c` with a single stochastic rounding.
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(
+        flags: Flags,
+        rng: &mut impl FnMut() -> u64,
+        a: f32,
+        b: f32,
+        c: f32,
+    ) -> (f32, Status) {
+        let wide = soft::fma::<soft::Binary64>(
+            &odd(flags),
+            (a as f64).to_bits(),
+            (b as f64).to_bits(),
+            (c as f64).to_bits(),
+        );
+        finish(wide, rng, || crate::f32::madd(exact(flags), a, b, c))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, rng: &mut impl FnMut() -> u64, single: f32) -> (u16, Status) {
+        if !single.is_finite() || single == 0.0 {
+            return crate::f32::to_half(exact(flags), single);
+        }
+        let (sign, exp, sig) = unpack::<soft::Binary32>(single.to_bits() as u64);
+        let (bits, status) = round::<soft::Binary16>(sign, exp, sig, rng);
+        (bits as u16, status)
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, rng: &mut impl FnMut() -> u64, l: f64, r: f64) -> (f64, Status) {
+        let (wide, _) = soft::quad::add(&odd(flags), widen(l), widen(r));
+        narrow(wide, rng).unwrap_or_else(|| crate::f64::add(exact(flags), l, r))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, rng: &mut impl FnMut() -> u64, l: f64, r: f64) -> (f64, Status) {
+        let (wide, _) = soft::quad::sub(&odd(flags), widen(l), widen(r));
+        narrow(wide, rng).unwrap_or_else(|| crate::f64::sub(exact(flags), l, r))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, rng: &mut impl FnMut() -> u64, l: f64, r: f64) -> (f64, Status) {
+        let (wide, _) = soft::quad::mul(&odd(flags), widen(l), widen(r));
+        narrow(wide, rng).unwrap_or_else(|| crate::f64::mul(exact(flags), l, r))
+    }
+
+    /// Computes `a * b + c` with a single stochastic rounding.
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(
+        flags: Flags,
+        rng: &mut impl FnMut() -> u64,
+        a: f64,
+        b: f64,
+        c: f64,
+    ) -> (f64, Status) {
+        let (wide, _) = soft::quad::fma(&odd(flags), widen(a), widen(b), widen(c));
+        narrow(wide, rng).unwrap_or_else(|| crate::f64::madd(exact(flags), a, b, c))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, rng: &mut impl FnMut() -> u64, double: f64) -> (f32, Status) {
+        if !double.is_finite() || double == 0.0 {
+            return crate::f64::to_single(exact(flags), double);
+        }
+        let (sign, exp, sig) = unpack::<soft::Binary64>(double.to_bits());
+        let (bits, status) = round::<soft::Binary32>(sign, exp, sig, rng);
+        (f32::from_bits(bits as u32), status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_half(flags: Flags, rng: &mut impl FnMut() -> u64, double: f64) -> (u16, Status) {
+        if !double.is_finite() || double == 0.0 {
+            return crate::f64::to_half(exact(flags), double);
+        }
+        let (sign, exp, sig) = unpack::<soft::Binary64>(double.to_bits());
+        let (bits, status) = round::<soft::Binary16>(sign, exp, sig, rng);
+        (bits as u16, status)
+    }
+}
@@ -0,0 +1,93 @@
+//! A table of the binary operations on bit patterns, so interpreters and JIT
+//! runtimes can dispatch on an index rather than match on the operation.
+//!
+//! Operands narrower than 64 bits are taken from the low bits, and results are
+//! zero extended.
+
+use crate::{Flags, Status};
+
+/// A binary operation on the bit patterns of its operands.
+pub type BinaryOp = fn(Flags, u64, u64) -> (u64, Status);
+
+pub const F16_ADD: usize = 0;
+pub const F16_SUB: usize = 1;
+pub const F16_MUL: usize = 2;
+pub const F16_DIV: usize = 3;
+pub const F32_ADD: usize = 4;
+pub const F32_SUB: usize = 5;
+pub const F32_MUL: usize = 6;
+pub const F32_DIV: usize = 7;
+pub const F64_ADD: usize = 8;
+pub const F64_SUB: usize = 9;
+pub const F64_MUL: usize = 10;
+pub const F64_DIV: usize = 11;
+
+/// The binary operations, indexed by the constants above.
+pub const BINARY: [BinaryOp; 12] = [
+    f16_add, f16_sub, f16_mul, f16_div, f32_add, f32_sub, f32_mul, f32_div, f64_add, f64_sub,
+    f64_mul, f64_div,
+];
+
+fn f16_add(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f16::add(flags, l as u16, r as u16);
+    (value as u64, status)
+}
+
+fn f16_sub(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f16::sub(flags, l as u16, r as u16);
+    (value as u64, status)
+}
+
+fn f16_mul(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f16::mul(flags, l as u16, r as u16);
+    (value as u64, status)
+}
+
+fn f16_div(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f16::div(flags, l as u16, r as u16);
+    (value as u64, status)
+}
+
+fn f32_add(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) =
+        crate::f32::add(flags, f32::from_bits(l as u32), f32::from_bits(r as u32));
+    (value.to_bits() as u64, status)
+}
+
+fn f32_sub(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) =
+        crate::f32::sub(flags, f32::from_bits(l as u32), f32::from_bits(r as u32));
+    (value.to_bits() as u64, status)
+}
+
+fn f32_mul(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) =
+        crate::f32::mul(flags, f32::from_bits(l as u32), f32::from_bits(r as u32));
+    (value.to_bits() as u64, status)
+}
+
+fn f32_div(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) =
+        crate::f32::div(flags, f32::from_bits(l as u32), f32::from_bits(r as u32));
+    (value.to_bits() as u64, status)
+}
+
+fn f64_add(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f64::add(flags, f64::from_bits(l), f64::from_bits(r));
+    (value.to_bits(), status)
+}
+
+fn f64_sub(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f64::sub(flags, f64::from_bits(l), f64::from_bits(r));
+    (value.to_bits(), status)
+}
+
+fn f64_mul(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f64::mul(flags, f64::from_bits(l), f64::from_bits(r));
+    (value.to_bits(), status)
+}
+
+fn f64_div(flags: Flags, l: u64, r: u64) -> (u64, Status) {
+    let (value, status) = crate::f64::div(flags, f64::from_bits(l), f64::from_bits(r));
+    (value.to_bits(), status)
+}
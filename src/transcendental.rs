@@ -0,0 +1,864 @@
+//! The x87 transcendental instructions on the double extended format,
+//! evaluated in a 128-bit significand wide enough that the rounded results
+//! are correct but for arguments closer to a rounding boundary than that.
+//!
+//! Intermediate results truncate, and differences borrow from the bits they
+//! drop, so a result within a term of an exact value, such as the sine of a
+//! tiny argument, stays below it in magnitude, where the last bit every
+//! inexact result sets before rounding keeps it.
+
+use crate::f80::F80;
+use crate::soft::extended::{inf, invalid, round_pack, special, zero};
+#[cfg(feature = "math")]
+use crate::soft::{self, Format, Operand};
+use crate::soft::{Env, DENORM, DIV_ZERO};
+
+/// A nonzero value `sig * 2^exp` with the top bit of `sig` set, or zero.
+#[derive(Clone, Copy)]
+struct Wide {
+    sign: bool,
+    exp: i32,
+    sig: u128,
+}
+
+const FRAC_PI_2: Wide = Wide {
+    sign: false,
+    exp: -127,
+    sig: 0xC90F_DAA2_2168_C234_C4C6_628B_80DC_1CD1,
+};
+const FRAC_PI_4: Wide = Wide {
+    exp: -128,
+    ..FRAC_PI_2
+};
+const PI: Wide = Wide {
+    exp: -126,
+    ..FRAC_PI_2
+};
+const FRAC_2_PI: Wide = Wide {
+    sign: false,
+    exp: -128,
+    sig: 0xA2F9_836E_4E44_1529_FC27_57D1_F534_DDC0,
+};
+const LN_2: Wide = Wide {
+    sign: false,
+    exp: -128,
+    sig: 0xB172_17F7_D1CF_79AB_C9E3_B398_03F2_F6AF,
+};
+#[cfg(any(feature = "convert", feature = "math"))]
+/// `ln 2` in 192 bits, as three parts of 64 bits whose products with small
+/// integers are exact.
+const LN_2_PARTS: [Wide; 3] = [
+    Wide {
+        sign: false,
+        exp: -128,
+        sig: 0xB172_17F7_D1CF_79AB << 64,
+    },
+    Wide {
+        sign: false,
+        exp: -192,
+        sig: 0xC9E3_B398_03F2_F6AF << 64,
+    },
+    Wide {
+        sign: false,
+        exp: -257,
+        sig: 0x81E6_864C_E531_6C5A << 64,
+    },
+];
+const LOG2_E: Wide = Wide {
+    sign: false,
+    exp: -127,
+    sig: 0xB8AA_3B29_5C17_F0BB_BE87_FED0_691D_3E88,
+};
+const ONE: Wide = Wide {
+    sign: false,
+    exp: -127,
+    sig: 1 << 127,
+};
+
+/// The high half of the 256-bit product of `a` and `b`.
+#[inline]
+fn mul_hi(a: u128, b: u128) -> u128 {
+    let (ah, al) = (a >> 64, a as u64 as u128);
+    let (bh, bl) = (b >> 64, b as u64 as u128);
+    let (ll, lh, hl, hh) = (al * bl, al * bh, ah * bl, ah * bh);
+    let mid = (ll >> 64) + (lh as u64 as u128) + (hl as u64 as u128);
+    hh + (lh >> 64) + (hl >> 64) + (mid >> 64)
+}
+
+impl Wide {
+    const ZERO: Self = Self {
+        sign: false,
+        exp: 0,
+        sig: 0,
+    };
+
+    #[inline]
+    fn new(sign: bool, exp: i32, sig: u128) -> Self {
+        if sig == 0 {
+            return Self { sign, ..Self::ZERO };
+        }
+        let shift = sig.leading_zeros();
+        Self {
+            sign,
+            exp: exp - shift as i32,
+            sig: sig << shift,
+        }
+    }
+
+    #[inline]
+    fn from_int(n: i64) -> Self {
+        Self::new(n < 0, 0, n.unsigned_abs() as u128)
+    }
+
+    /// Decodes a finite, supported `x`.
+    #[inline]
+    fn from_f80(x: F80) -> Self {
+        let biased = (x.sign_exp & 0x7FFF).max(1) as i32;
+        Self::new(x.is_sign_negative(), biased - 16383 - 63, x.sig as u128)
+    }
+
+    /// Rounds to the format, which is exact only if `exact`.
+    #[inline]
+    fn to_f80(self, env: &Env, exact: bool, exc: &mut u32) -> F80 {
+        if self.sig == 0 {
+            return zero(self.sign);
+        }
+        let sig = self.sig | !exact as u128;
+        round_pack(env, self.sign, self.exp, sig, 64, exc)
+    }
+
+    #[cfg(feature = "math")]
+    /// Rounds a value that is zero or inexact to `F`.
+    #[inline]
+    fn to_format<F: Format>(self, env: &Env, exc: &mut u32) -> u64 {
+        if self.sig == 0 {
+            return soft::zero::<F>(self.sign);
+        }
+        let sig = self.sig | 1;
+        soft::round_pack::<F>(env, self.sign, self.exp, sig, exc)
+    }
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self {
+            sign: !self.sign,
+            ..self
+        }
+    }
+
+    #[inline]
+    fn abs(self) -> Self {
+        Self {
+            sign: false,
+            ..self
+        }
+    }
+
+    #[inline]
+    fn scale(self, n: i32) -> Self {
+        Self {
+            exp: self.exp + n,
+            ..self
+        }
+    }
+
+    /// Whether the magnitude is below that of `other`.
+    #[inline]
+    fn below(self, other: Self) -> bool {
+        other.sig != 0 && (self.sig == 0 || (self.exp, self.sig) < (other.exp, other.sig))
+    }
+
+    /// Whether adding the value to `sum` leaves it unchanged.
+    #[inline]
+    fn negligible(self, sum: Self) -> bool {
+        self.sig == 0 || (sum.exp as i64 - self.exp as i64) > 130
+    }
+
+    fn add(self, other: Self) -> Self {
+        if self.sig == 0 {
+            return other;
+        }
+        if other.sig == 0 {
+            return self;
+        }
+        let (x, y) = if other.below(self) {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let shift = x.exp as i64 - y.exp as i64;
+        let (ys, lost) = if shift >= 128 {
+            (0, true)
+        } else {
+            (y.sig >> shift, y.sig & ((1 << shift) - 1) != 0)
+        };
+        if x.sign == y.sign {
+            let (sum, carry) = x.sig.overflowing_add(ys);
+            if carry {
+                return Self::new(x.sign, x.exp + 1, sum >> 1 | 1 << 127);
+            }
+            Self::new(x.sign, x.exp, sum)
+        } else {
+            Self::new(x.sign, x.exp, x.sig - ys - lost as u128)
+        }
+    }
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        self.add(other.neg())
+    }
+
+    fn mul(self, other: Self) -> Self {
+        let sign = self.sign != other.sign;
+        if self.sig == 0 || other.sig == 0 {
+            return Self::new(sign, 0, 0);
+        }
+        Self::new(
+            sign,
+            self.exp + other.exp + 128,
+            mul_hi(self.sig, other.sig),
+        )
+    }
+
+    /// Divides by the nonzero `other`.
+    fn div(self, other: Self) -> Self {
+        let sign = self.sign != other.sign;
+        if self.sig == 0 {
+            return Self::new(sign, 0, 0);
+        }
+        let (mut rem, div) = (self.sig, other.sig);
+        let mut quo = 0;
+        if rem >= div {
+            rem -= div;
+            quo = 1;
+        }
+        for _ in 0..127 {
+            let carry = rem >> 127 != 0;
+            rem <<= 1;
+            quo <<= 1;
+            if carry || rem >= div {
+                rem = rem.wrapping_sub(div);
+                quo |= 1;
+            }
+        }
+        Self::new(sign, self.exp - other.exp - 127, quo)
+    }
+
+    /// The square root of a positive value, by Newton's method from above.
+    fn sqrt(self) -> Self {
+        let mut root = Self::new(false, (self.exp + 129).div_euclid(2), 1);
+        for _ in 0..9 {
+            root = root.add(self.div(root)).scale(-1);
+        }
+        root
+    }
+
+    /// The nearest integer, ties away from zero, for magnitudes below 2^63,
+    /// whose exponents are negative.
+    #[inline]
+    fn round_int(self) -> i64 {
+        let shift = -self.exp;
+        let mag = if shift > 128 {
+            0
+        } else {
+            (((self.sig >> (shift - 1)) + 1) >> 1) as i64
+        };
+        if self.sign {
+            -mag
+        } else {
+            mag
+        }
+    }
+}
+
+/// The sine and cosine of `r`, of magnitude below one.
+fn sin_cos(r: Wide) -> (Wide, Wide) {
+    let r2 = r.mul(r);
+    let (mut sin, mut cos) = (r, ONE);
+    let (mut sin_term, mut cos_term) = (r, ONE);
+    for n in 1..64 {
+        let k = 2 * n as i64;
+        cos_term = cos_term.mul(r2).div(Wide::from_int((k - 1) * k)).neg();
+        sin_term = sin_term.mul(r2).div(Wide::from_int(k * (k + 1))).neg();
+        let done = sin_term.negligible(sin) && cos_term.negligible(cos);
+        cos = cos.add(cos_term);
+        sin = sin.add(sin_term);
+        if done {
+            break;
+        }
+    }
+    (sin, cos)
+}
+
+/// `e^t - 1` for `t` of magnitude below one.
+fn exp_m1(t: Wide) -> Wide {
+    let (mut sum, mut term) = (t, t);
+    for n in 2..64 {
+        term = term.mul(t).div(Wide::from_int(n));
+        let done = term.negligible(sum);
+        sum = sum.add(term);
+        if done {
+            break;
+        }
+    }
+    sum
+}
+
+/// The arctangent of `z`, between zero and one.
+fn atan(z: Wide) -> Wide {
+    // Three halvings of the angle bring `z` below tan(pi / 32).
+    let mut z = z;
+    for _ in 0..3 {
+        z = z.div(ONE.add(ONE.add(z.mul(z)).sqrt()));
+    }
+    let z2 = z.mul(z);
+    let (mut sum, mut power) = (z, z);
+    for n in 1..64 {
+        power = power.mul(z2).neg();
+        let term = power.div(Wide::from_int(2 * n + 1));
+        let done = term.negligible(sum);
+        sum = sum.add(term);
+        if done {
+            break;
+        }
+    }
+    sum.scale(3)
+}
+
+/// The base-2 logarithm of a positive finite `x`, and whether it is exact.
+fn log2(x: Wide) -> (Wide, bool) {
+    // x = m * 2^e with m in [1/sqrt(2), sqrt(2)).
+    let mut e = x.exp as i64 + 127;
+    let mut m = x.scale(-(e as i32));
+    if m.sig > 0xB504_F333_F9DE_6484_597D_89B3_754A_BE9F {
+        m = m.scale(-1);
+        e += 1;
+    }
+    if m.sig == 1 << 127 && m.exp == -127 {
+        return (Wide::from_int(e), true);
+    }
+    // ln(m) = 2 atanh(s) for s = (m - 1) / (m + 1).
+    let s = m.sub(ONE).div(m.add(ONE));
+    let s2 = s.mul(s);
+    let (mut sum, mut power) = (s, s);
+    for n in 1..64 {
+        power = power.mul(s2);
+        let term = power.div(Wide::from_int(2 * n + 1));
+        let done = term.negligible(sum);
+        sum = sum.add(term);
+        if done {
+            break;
+        }
+    }
+    (Wide::from_int(e).add(sum.scale(1).mul(LOG2_E)), false)
+}
+
+type Big = [u64; 8];
+
+/// `π / 2` in 331 bits, or `2^-330` times this.
+const FRAC_PI_2_BITS: [u64; 6] = [
+    0x5043_CC71_A026_EF7C,
+    0x5DF5_31D8_9CD9_128A,
+    0x1270_4453_3E63_A010,
+    0x3314_5C06_E0E6_8948,
+    0x7ED5_110B_4611_A626,
+    0x648,
+];
+/// The 66-bit `π` of the x87 halved, or `2^-65` times this.
+const FRAC_PI_2_X87: [u64; 2] = [0x243F_6A88_85A3_08D3, 0x3];
+
+fn big_mul(limbs: &[u64], k: u64) -> Big {
+    let mut out = [0; 8];
+    let mut carry = 0;
+    for (i, &limb) in limbs.iter().enumerate() {
+        let product = limb as u128 * k as u128 + carry;
+        out[i] = product as u64;
+        carry = product >> 64;
+    }
+    out[limbs.len()] = carry as u64;
+    out
+}
+
+fn big_sub(a: &Big, b: &Big) -> Big {
+    let mut out = [0; 8];
+    let mut borrow = false;
+    for i in 0..8 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        out[i] = d;
+        borrow = b1 || b2;
+    }
+    out
+}
+
+/// The value `big * 2^scale`, truncated.
+fn big_wide(sign: bool, big: &Big, scale: i32) -> Wide {
+    let Some(top) = (0..8).rev().find(|&i| big[i] != 0) else {
+        return Wide::ZERO;
+    };
+    if top == 0 {
+        return Wide::new(sign, scale, big[0] as u128);
+    }
+    let shift = big[top].leading_zeros();
+    let high = (big[top] as u128) << 64 | big[top - 1] as u128;
+    let next = if top >= 2 { big[top - 2] } else { 0 };
+    let sig = if shift == 0 {
+        high
+    } else {
+        high << shift | (next >> (64 - shift)) as u128
+    };
+    Wide::new(sign, 64 * (top as i32 - 1) - shift as i32 + scale, sig)
+}
+
+/// Reduces a finite `x` of magnitude at least `π / 4` and below 2^63 by the
+/// nearest multiple `k` of `π / 2`, returning `k % 4` and the remainder.
+fn reduce(x: Wide, hardware: bool) -> (u32, Wide) {
+    let (limbs, bits): (&[u64], i32) = if hardware {
+        (&FRAC_PI_2_X87, 65)
+    } else {
+        (&FRAC_PI_2_BITS, 330)
+    };
+    let k = x.abs().mul(FRAC_2_PI).round_int() as u64;
+    // |x| * 2^bits is an integer, as the significand of `x` sits in its top
+    // 64 bits with an exponent of at least -64.
+    let shift = (x.exp + 64 + bits) as u32;
+    let mut scaled = [0; 8];
+    let (limb, shifted) = ((shift / 64) as usize, (x.sig >> 64) << (shift % 64));
+    scaled[limb] = shifted as u64;
+    scaled[limb + 1] = (shifted >> 64) as u64;
+    let multiple = big_mul(limbs, k);
+    let remainder = if (0..8)
+        .rev()
+        .map(|i| scaled[i].cmp(&multiple[i]))
+        .find(|o| o.is_ne())
+        != Some(core::cmp::Ordering::Less)
+    {
+        big_wide(false, &big_sub(&scaled, &multiple), -bits)
+    } else {
+        big_wide(true, &big_sub(&multiple, &scaled), -bits)
+    };
+    (k as u32 & 3, remainder)
+}
+
+#[cfg(feature = "math")]
+/// `2 / π` in 1344 bits, or `2^-1344` times this.
+const FRAC_2_PI_BITS: [u64; 21] = [
+    0xA9E3_9161_5EE6_1B08,
+    0xF0CF_BC20_9AF4_361D,
+    0x5603_3046_FC7B_6BAB,
+    0x6BFB_5FB1_1F8D_5D08,
+    0x3D07_39F7_8A52_92EA,
+    0x7527_BAC7_EBE5_F17B,
+    0x4F46_3F66_9E5F_EA2D,
+    0x6D36_7ECF_27CB_09B7,
+    0xEF2F_118B_5A0A_6D1F,
+    0x1FF8_97FF_DE05_980F,
+    0x9C84_5F8B_BDF9_283B,
+    0x3991_D639_8353_39F4,
+    0xE99C_7026_B45F_7E41,
+    0xE882_35F5_2EBB_4484,
+    0xFE1D_EB1C_B129_A73E,
+    0x0649_2EEA_09D1_921C,
+    0xB724_6E3A_424D_D2E0,
+    0xFE51_63AB_DEBB_C561,
+    0xDB62_9599_3C43_9041,
+    0xFC27_57D1_F534_DDC0,
+    0xA2F9_836E_4E44_1529,
+];
+
+#[cfg(feature = "math")]
+/// Reduces a finite `x` of magnitude at least 2^63 and below 2^1024 like
+/// `reduce`, multiplying it by the bits of `2 / π` that leave the product
+/// modulo 4 with 256 bits after the point.
+fn reduce_large(x: Wide) -> (u32, Wide) {
+    // |x| = m * 2^q, and the window holds 2^(q + 320) * 2 / π modulo 2^384.
+    let (m, q) = ((x.sig >> 64) as u64, x.exp + 64);
+    let shift = (1024 - q) as usize;
+    let limb = |i: usize| FRAC_2_PI_BITS.get(i).copied().unwrap_or(0);
+    let mut window = [0; 6];
+    for (i, bits) in window.iter_mut().enumerate() {
+        let (index, offset) = (shift / 64 + i, shift % 64);
+        *bits = if offset == 0 {
+            limb(index)
+        } else {
+            limb(index) >> offset | limb(index + 1) << (64 - offset)
+        };
+    }
+    let product = big_mul(&window, m);
+    let mut frac = [0; 8];
+    frac[..5].copy_from_slice(&product[..5]);
+    let k = product[5] as u32;
+    let (k, remainder) = if frac[4] >> 63 == 0 {
+        (k, big_wide(false, &frac, -320))
+    } else {
+        let mut one = [0; 8];
+        one[5] = 1;
+        (
+            k.wrapping_add(1),
+            big_wide(true, &big_sub(&one, &frac), -320),
+        )
+    };
+    (k & 3, remainder.mul(FRAC_PI_2))
+}
+
+/// Which of the sine, cosine and tangent `trig` computes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trig {
+    Sin,
+    Cos,
+    Tan,
+}
+
+/// Computes the sine, cosine or tangent of `x`, reducing it by the 66-bit
+/// `π` of the x87 if `hardware`. Returns `None` for finite magnitudes of
+/// 2^63 and over, which the x87 leaves as they are, setting C2.
+pub(crate) fn trig(env: &Env, x: F80, trig: Trig, hardware: bool) -> Option<(F80, u32)> {
+    let mut exc = 0;
+    if let Some(res) = special(env, &[x], &mut exc) {
+        return Some((res, exc));
+    }
+    let biased = x.sign_exp & 0x7FFF;
+    if biased == 0x7FFF {
+        return Some((invalid(env, &mut exc), exc));
+    }
+    if biased >= 16383 + 63 {
+        return None;
+    }
+    if x.sig == 0 {
+        let res = match trig {
+            Trig::Cos => F80::ONE,
+            _ => x,
+        };
+        return Some((res, exc));
+    }
+    if biased == 0 {
+        exc |= DENORM;
+    }
+    let wide = Wide::from_f80(x);
+    let (k, r) = if wide.below(FRAC_PI_4) {
+        (0, wide.abs())
+    } else {
+        reduce(wide, hardware)
+    };
+    let res = reduced(trig, wide.sign, k, r);
+    Some((res.to_f80(env, false, &mut exc), exc))
+}
+
+/// Computes the sine, cosine or tangent of `x` from the reduction of `|x|`
+/// to `k π/2 + r`, with `k` modulo 4.
+fn reduced(trig: Trig, sign: bool, k: u32, r: Wide) -> Wide {
+    let (sin, cos) = sin_cos(r);
+    let res = match (trig, k) {
+        (Trig::Sin, 0) => sin,
+        (Trig::Sin, 1) => cos,
+        (Trig::Sin, 2) => sin.neg(),
+        (Trig::Sin, _) => cos.neg(),
+        (Trig::Cos, 0) => cos,
+        (Trig::Cos, 1) => sin.neg(),
+        (Trig::Cos, 2) => cos.neg(),
+        (Trig::Cos, _) => sin,
+        (Trig::Tan, 0 | 2) => sin.div(cos),
+        (Trig::Tan, _) => cos.div(sin).neg(),
+    };
+    match trig {
+        Trig::Cos => res,
+        _ if sign => res.neg(),
+        _ => res,
+    }
+}
+
+/// Computes the angle of the point `(x, y)`, as `fpatan` does of `st(0)` and
+/// `st(1)`.
+pub(crate) fn atan2(env: &Env, y: F80, x: F80) -> (F80, u32) {
+    let mut exc = 0;
+    if let Some(res) = special(env, &[y, x], &mut exc) {
+        return (res, exc);
+    }
+    if [y, x]
+        .iter()
+        .any(|op| op.sign_exp & 0x7FFF == 0 && op.sig != 0)
+    {
+        exc |= DENORM;
+    }
+    let (y_inf, x_inf) = (y.sign_exp & 0x7FFF == 0x7FFF, x.sign_exp & 0x7FFF == 0x7FFF);
+    let (sign, west) = (y.is_sign_negative(), x.is_sign_negative());
+    let angle = if y.sig == 0 || (x_inf && !y_inf) {
+        if !west {
+            return (zero(sign), exc);
+        }
+        PI
+    } else if y_inf {
+        match (x_inf, west) {
+            (false, _) => FRAC_PI_2,
+            (true, false) => FRAC_PI_4,
+            (true, true) => PI.mul(Wide::from_int(3)).scale(-2),
+        }
+    } else if x.sig == 0 {
+        FRAC_PI_2
+    } else {
+        let (y, x) = (Wide::from_f80(y).abs(), Wide::from_f80(x).abs());
+        let angle = if y.below(x) {
+            atan(y.div(x))
+        } else {
+            FRAC_PI_2.sub(atan(x.div(y)))
+        };
+        if west {
+            PI.sub(angle)
+        } else {
+            angle
+        }
+    };
+    let angle = if sign { angle.neg() } else { angle };
+    (angle.to_f80(env, false, &mut exc), exc)
+}
+
+/// Computes `2^x - 1`, as `f2xm1` does, which the x87 only defines for `x`
+/// between -1 and 1.
+pub(crate) fn exp2_m1(env: &Env, x: F80) -> (F80, u32) {
+    let mut exc = 0;
+    if let Some(res) = special(env, &[x], &mut exc) {
+        return (res, exc);
+    }
+    let biased = x.sign_exp & 0x7FFF;
+    if x.sig == 0 {
+        return (x, exc);
+    }
+    if biased == 0x7FFF {
+        let res = if x.is_sign_negative() {
+            F80 {
+                sign_exp: 0xBFFF,
+                ..F80::ONE
+            }
+        } else {
+            x
+        };
+        return (res, exc);
+    }
+    if biased == 0 {
+        exc |= DENORM;
+    }
+    let wide = Wide::from_f80(x);
+    if biased >= 16383 + 15 {
+        // Far beyond the range of the format, or within an ulp of -1.
+        let res = if wide.sign {
+            Wide::new(true, -128, u128::MAX)
+        } else {
+            Wide::new(false, 1 << 15, 1)
+        };
+        return (res.to_f80(env, false, &mut exc), exc);
+    }
+    let k = wide.round_int();
+    let frac = wide.sub(Wide::from_int(k));
+    let (res, exact) = if frac.sig == 0 {
+        // 2^k - 1, whose low bits stand in for the parts beyond 128 bits.
+        let res = match k {
+            0..=127 => Wide::new(false, 0, (1u128 << k) - 1),
+            128.. => Wide::new(false, k as i32 - 128, u128::MAX),
+            -127..=-1 => Wide::new(true, k as i32, (1u128 << -k) - 1),
+            _ => Wide::new(true, -128, u128::MAX),
+        };
+        (res, (-127..=127).contains(&k))
+    } else {
+        let m1 = exp_m1(frac.mul(LN_2));
+        let res = if k == 0 {
+            m1
+        } else {
+            m1.add(ONE).scale(k as i32).sub(ONE)
+        };
+        (res, false)
+    };
+    (res.to_f80(env, exact, &mut exc), exc)
+}
+
+/// Computes `y * log2(x)`, as `fyl2x` does of `st(1)` and `st(0)`.
+pub(crate) fn y_log2_x(env: &Env, y: F80, x: F80) -> (F80, u32) {
+    let mut exc = 0;
+    if let Some(res) = special(env, &[y, x], &mut exc) {
+        return (res, exc);
+    }
+    let (y_inf, x_inf) = (y.sign_exp & 0x7FFF == 0x7FFF, x.sign_exp & 0x7FFF == 0x7FFF);
+    let (y_zero, x_zero) = (y.sig == 0, x.sig == 0);
+    let sign = y.is_sign_negative();
+    if x.is_sign_negative() && !x_zero || y_zero && (x_zero || x_inf) {
+        return (invalid(env, &mut exc), exc);
+    }
+    let log = (!x_zero && !x_inf).then(|| log2(Wide::from_f80(x)));
+    if y_inf && matches!(log, Some((l, _)) if l.sig == 0) {
+        return (invalid(env, &mut exc), exc);
+    }
+    if x_zero {
+        // Division by zero takes the place of a denormal `y`.
+        if !y_inf {
+            exc |= DIV_ZERO;
+        }
+        return (inf(!sign), exc);
+    }
+    if [y, x]
+        .iter()
+        .any(|op| op.sign_exp & 0x7FFF == 0 && op.sig != 0)
+    {
+        exc |= DENORM;
+    }
+    let res = match log {
+        None => inf(sign),
+        Some((l, _)) if y_inf => inf(sign != l.sign),
+        Some((l, _)) if y_zero || l.sig == 0 => zero(sign != l.sign),
+        Some((l, exact)) => Wide::from_f80(y).mul(l).to_f80(env, exact, &mut exc),
+    };
+    (res, exc)
+}
+
+#[cfg(feature = "convert")]
+/// Computes `e^x`, correctly rounded.
+pub(crate) fn exp(env: &Env, x: F80) -> (F80, u32) {
+    let mut exc = 0;
+    if let Some(res) = special(env, &[x], &mut exc) {
+        return (res, exc);
+    }
+    let biased = x.sign_exp & 0x7FFF;
+    if x.sig == 0 {
+        return (F80::ONE, exc);
+    }
+    if biased == 0x7FFF {
+        let res = if x.is_sign_negative() { zero(false) } else { x };
+        return (res, exc);
+    }
+    if biased == 0 {
+        exc |= DENORM;
+    }
+    let res = exp_of(Wide::from_f80(x));
+    (res.to_f80(env, false, &mut exc), exc)
+}
+
+#[cfg(any(feature = "convert", feature = "math"))]
+/// `e^x` for a nonzero `x`.
+fn exp_of(x: Wide) -> Wide {
+    if x.exp + 127 >= 15 {
+        // Far beyond the range of the formats either way.
+        return if x.sign {
+            Wide::new(false, -(1 << 15), 1)
+        } else {
+            Wide::new(false, 1 << 15, 1)
+        };
+    }
+    // e^x = 2^k * e^r for x = k ln 2 + r, subtracting each part of k ln 2
+    // exactly or nearly so.
+    let k = x.mul(LOG2_E).round_int();
+    let n = Wide::from_int(k);
+    let r = LN_2_PARTS.iter().fold(x, |r, &part| r.sub(n.mul(part)));
+    exp_m1(r).add(ONE).scale(k as i32)
+}
+
+#[cfg(feature = "convert")]
+/// Computes the natural logarithm of a positive finite `x`, correctly
+/// rounded.
+pub(crate) fn ln(env: &Env, x: F80) -> (F80, u32) {
+    let mut exc = 0;
+    if x.sign_exp & 0x7FFF == 0 {
+        exc |= DENORM;
+    }
+    // Only the logarithm of one, zero, is exact.
+    let (log, _) = log2(Wide::from_f80(x));
+    (log.mul(LN_2).to_f80(env, false, &mut exc), exc)
+}
+
+#[cfg(feature = "convert")]
+/// The quadrant of a finite nonzero `x` of magnitude below 2^63, the floor
+/// of `x / (π / 2)` modulo 4.
+pub(crate) fn quadrant(x: F80) -> u32 {
+    let wide = Wide::from_f80(x);
+    if wide.below(FRAC_PI_4) {
+        return if wide.sign { 3 } else { 0 };
+    }
+    // |x| = k π/2 + r, so r below zero puts |x| in the quadrant below k.
+    let (k, r) = reduce(wide, false);
+    match (wide.sign, r.sign && r.sig != 0) {
+        (false, false) => k,
+        (false, true) => k.wrapping_sub(1) & 3,
+        (true, false) if r.sig == 0 => k.wrapping_neg() & 3,
+        (true, false) => 3u32.wrapping_sub(k) & 3,
+        (true, true) => k.wrapping_neg() & 3,
+    }
+}
+
+#[cfg(feature = "math")]
+/// The functions `elementary` computes.
+#[derive(Clone, Copy)]
+pub(crate) enum Function {
+    Exp,
+    Log,
+    Trig(Trig),
+    Atan,
+}
+
+#[cfg(feature = "math")]
+/// Computes `function` of `a` in the format `F`, rounded and raising
+/// exceptions as the arithmetic operations do.
+pub(crate) fn elementary<F: Format>(env: &Env, function: Function, a: u64) -> (u64, u32) {
+    let mut exc = 0;
+    let one = (F::BIAS as u64) << F::FRAC_BITS;
+    let x = match soft::function_operand::<F>(env, a, &mut exc) {
+        Operand::Nan(res) => return (res, exc),
+        Operand::Inf(sign) => {
+            let res = match function {
+                Function::Exp if sign => soft::zero::<F>(false),
+                Function::Log if sign => soft::invalid::<F>(env, &mut exc),
+                Function::Trig(_) => soft::invalid::<F>(env, &mut exc),
+                Function::Atan => {
+                    let angle = if sign { FRAC_PI_2.neg() } else { FRAC_PI_2 };
+                    angle.to_format::<F>(env, &mut exc)
+                }
+                _ => soft::inf::<F>(false),
+            };
+            return (res, exc);
+        }
+        Operand::Zero(sign) => {
+            let res = match function {
+                Function::Exp | Function::Trig(Trig::Cos) => one,
+                Function::Log => {
+                    exc |= DIV_ZERO;
+                    soft::inf::<F>(true)
+                }
+                _ => soft::zero::<F>(sign),
+            };
+            return (res, exc);
+        }
+        Operand::Finite(sign, exp, sig) => Wide::new(sign, exp, sig as u128),
+    };
+    // Within 2^-60 of zero, the cube term of the tangent and arctangent is
+    // below the accuracy of the series that would otherwise compute them.
+    let tiny = x.exp + 127 < -60;
+    let cube = || x.mul(x).mul(x).div(Wide::from_int(3));
+    // Only the results of zeros and the logarithm of one, zero, are exact.
+    let res = match function {
+        Function::Exp => exp_of(x),
+        Function::Log if x.sign => return (soft::invalid::<F>(env, &mut exc), exc),
+        Function::Log => log2(x).0.mul(LN_2),
+        Function::Trig(Trig::Tan) if tiny => x.add(cube()),
+        Function::Trig(trig) => {
+            let (k, r) = if x.below(FRAC_PI_4) {
+                (0, x.abs())
+            } else if x.exp + 127 < 63 {
+                reduce(x, false)
+            } else {
+                reduce_large(x)
+            };
+            reduced(trig, x.sign, k, r)
+        }
+        Function::Atan if tiny => x.sub(cube()),
+        Function::Atan => {
+            let z = x.abs();
+            let angle = if ONE.below(z) {
+                FRAC_PI_2.sub(atan(ONE.div(z)))
+            } else {
+                atan(z)
+            };
+            if x.sign {
+                angle.neg()
+            } else {
+                angle
+            }
+        }
+    };
+    (res.to_format::<F>(env, &mut exc), exc)
+}
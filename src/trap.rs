@@ -0,0 +1,41 @@
+//! Operations that stop at enabled exceptions, describing the operation so
+//! an emulator can deliver a precise floating-point exception to its guest.
+
+use crate::{dispatch, Flags, FpOpKind, FpWidth, Status};
+
+/// An operation that raised an enabled exception.
+#[derive(Clone, Copy)]
+pub struct TrapInfo {
+    pub kind: FpOpKind,
+    pub width: FpWidth,
+    /// The bit patterns of the operands, as given to `dispatch`.
+    pub operands: [u64; 3],
+    /// Every exception the operation raised, not only the enabled ones.
+    pub status: Status,
+    /// The result the operation would have delivered with its exceptions
+    /// disabled.
+    pub result: u64,
+}
+
+/// Runs `kind` as `dispatch` does, returning what the guest needs to take a
+/// trap instead if it raised any exception in `enabled`.
+#[inline]
+pub fn dispatch_trapping(
+    kind: FpOpKind,
+    width: FpWidth,
+    flags: Flags,
+    enabled: Status,
+    operands: [u64; 3],
+) -> Result<(u64, Status), TrapInfo> {
+    let (result, status) = dispatch(kind, width, flags, operands);
+    if status.and(enabled).has_exceptions() {
+        return Err(TrapInfo {
+            kind,
+            width,
+            operands,
+            status,
+            result,
+        });
+    }
+    Ok((result, status))
+}
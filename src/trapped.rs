@@ -0,0 +1,168 @@
+//! Operations delivering the results that enabled overflow and underflow
+//! traps receive, as on the x87, SPARC and Alpha, for emulating precise
+//! traps.
+//!
+//! A result that overflows or is tiny is rounded to the precision with an
+//! unbounded exponent range and its exponent is wrapped back into range, by
+//! 192 for single precision, 1536 for double precision and 24576 for double
+//! extended, raising overflow or underflow, the latter even for exact
+//! results, and inexact where rounding lost bits. Flushing doesn't apply to
+//! such results. Other results are those of the plain operations.
+
+use crate::f80::{Precision, F80};
+use crate::{soft, Flags, Status};
+
+/// The environment delivering wrapped results.
+#[inline]
+fn env(env: soft::Env) -> soft::Env {
+    soft::Env {
+        sem: soft::Semantics {
+            wrap_exponent: true,
+            ..env.sem
+        },
+        ..env
+    }
+}
+
+/// `result`, or the result of `soft` delivering wrapped results if `result`
+/// overflowed or may have been tiny.
+#[inline]
+fn finish<T: soft::Value>(
+    flags: Flags,
+    (value, status): (T, Status),
+    subnormal: bool,
+    soft: impl FnOnce(&soft::Env) -> (u64, u32),
+) -> (T, Status) {
+    if !(status.overflow() || status.underflow() || subnormal) {
+        return (value, status);
+    }
+    let (bits, exc) = soft(&env(flags.soft_env()));
+    (T::from_soft(bits), Status::from_soft(exc))
+}
+
+pub mod f32 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let result = crate::f32::add(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::add::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64)
+        })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let result = crate::f32::sub(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::sub::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64)
+        })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let result = crate::f32::mul(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::mul::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64)
+        })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f32, r: f32) -> (f32, Status) {
+        let result = crate::f32::div(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::div::<soft::Binary32>(env, l.to_bits() as u64, r.to_bits() as u64)
+        })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f32, b: f32, c: f32) -> (f32, Status) {
+        let result = crate::f32::madd(flags, a, b, c);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::fma::<soft::Binary32>(
+                env,
+                a.to_bits() as u64,
+                b.to_bits() as u64,
+                c.to_bits() as u64,
+            )
+        })
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let result = crate::f64::add(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::add::<soft::Binary64>(env, l.to_bits(), r.to_bits())
+        })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let result = crate::f64::sub(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::sub::<soft::Binary64>(env, l.to_bits(), r.to_bits())
+        })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let result = crate::f64::mul(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::mul::<soft::Binary64>(env, l.to_bits(), r.to_bits())
+        })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: f64, r: f64) -> (f64, Status) {
+        let result = crate::f64::div(flags, l, r);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::div::<soft::Binary64>(env, l.to_bits(), r.to_bits())
+        })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: f64, b: f64, c: f64) -> (f64, Status) {
+        let result = crate::f64::madd(flags, a, b, c);
+        finish(flags, result, result.0.is_subnormal(), |env| {
+            soft::fma::<soft::Binary64>(env, a.to_bits(), b.to_bits(), c.to_bits())
+        })
+    }
+}
+
+pub mod f80 {
+    use super::*;
+
+    #[inline]
+    pub fn add(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+        let (value, exc) =
+            soft::extended::add(&env(crate::f80::env(flags)), l, r, precision.bits());
+        (value, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+        let (value, exc) =
+            soft::extended::sub(&env(crate::f80::env(flags)), l, r, precision.bits());
+        (value, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+        let (value, exc) =
+            soft::extended::mul(&env(crate::f80::env(flags)), l, r, precision.bits());
+        (value, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, precision: Precision, l: F80, r: F80) -> (F80, Status) {
+        let (value, exc) =
+            soft::extended::div(&env(crate::f80::env(flags)), l, r, precision.bits());
+        (value, Status::from_soft(exc))
+    }
+}
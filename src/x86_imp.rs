@@ -0,0 +1,2882 @@
+use core::arch::asm;
+#[cfg(target_arch = "x86")]
+use core::arch::x86 as arch;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64 as arch;
+
+use crate::{features, soft, Features, Tininess};
+
+const DENORMALS_ZERO: u32 = 1 << 6;
+const DEFAULT_NAN: u32 = 1 << 0;
+const TININESS_BEFORE: u32 = 1 << 1;
+const TIES_AWAY: u32 = 1 << 2;
+const TO_ODD: u32 = 1 << 3;
+
+/// A rounding mode, laid out as the `u32` of its bits in the flags.
+#[repr(u32)]
+pub enum Rounding {
+    /// Rounds towards zero.
+    Zero = arch::_MM_ROUND_TOWARD_ZERO,
+    /// Rounds towards positive infinity.
+    Up = arch::_MM_ROUND_UP,
+    /// Rounds towards negative infinity.
+    Down = arch::_MM_ROUND_DOWN,
+    /// Rounds towards nearest.
+    Nearest = arch::_MM_ROUND_NEAREST,
+    /// Rounds towards nearest, ties away from zero, which SSE lacks.
+    NearestAway = 1 << 16,
+    /// Rounds towards zero, setting the last bit of inexact results, which
+    /// SSE lacks.
+    Odd = 1 << 17,
+}
+
+/// The flags set for the operation.
+///
+/// The layout is stable: the MXCSR value the operations load, followed by
+/// the settings MXCSR lacks, as two `u32`s.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct Flags {
+    inner: u32,
+    // The modes only the software tracks, kept apart from `inner`, which is
+    // loaded into MXCSR as it is.
+    modes: u32,
+}
+
+impl Default for Flags {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Flags {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            inner: arch::_MM_MASK_MASK,
+            modes: 0,
+        }
+    }
+
+    #[inline]
+    pub const fn with_rounding(mut self, rounding: Rounding) -> Self {
+        self.set_rounding(rounding);
+        self
+    }
+
+    #[inline]
+    pub fn with_ftz(mut self, enabled: bool) -> Self {
+        self.set_ftz(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_daz(mut self, enabled: bool) -> Self {
+        self.set_daz(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_default_nan(mut self, enabled: bool) -> Self {
+        self.set_default_nan(enabled);
+        self
+    }
+
+    #[inline]
+    pub fn with_tininess(mut self, tininess: Tininess) -> Self {
+        self.set_tininess(tininess);
+        self
+    }
+
+    #[inline]
+    pub const fn set_rounding(&mut self, rounding: Rounding) {
+        let soft = match rounding {
+            Rounding::NearestAway => TIES_AWAY,
+            Rounding::Odd => TO_ODD,
+            _ => 0,
+        };
+        self.inner =
+            (self.inner & !arch::_MM_ROUND_MASK) | (rounding as u32 & arch::_MM_ROUND_MASK);
+        self.modes = (self.modes & !(TIES_AWAY | TO_ODD)) | soft;
+    }
+
+    #[inline]
+    pub fn rounding(self) -> Rounding {
+        if self.modes & TIES_AWAY != 0 {
+            return Rounding::NearestAway;
+        }
+        if self.modes & TO_ODD != 0 {
+            return Rounding::Odd;
+        }
+        match self.inner & arch::_MM_ROUND_MASK {
+            b if b == Rounding::Zero as u32 => Rounding::Zero,
+            b if b == Rounding::Up as u32 => Rounding::Up,
+            b if b == Rounding::Down as u32 => Rounding::Down,
+            _ => Rounding::Nearest,
+        }
+    }
+
+    #[inline]
+    pub fn set_ftz(&mut self, enabled: bool) {
+        self.inner = (self.inner & !arch::_MM_FLUSH_ZERO_MASK)
+            | if enabled {
+                arch::_MM_FLUSH_ZERO_ON
+            } else {
+                arch::_MM_FLUSH_ZERO_OFF
+            }
+    }
+
+    #[inline]
+    pub fn ftz(self) -> bool {
+        self.inner & arch::_MM_FLUSH_ZERO_MASK != 0
+    }
+
+    #[inline]
+    pub fn set_daz(&mut self, enabled: bool) {
+        self.inner = (self.inner & !DENORMALS_ZERO) | if enabled { DENORMALS_ZERO } else { 0 }
+    }
+
+    #[inline]
+    pub fn daz(self) -> bool {
+        self.inner & DENORMALS_ZERO != 0
+    }
+
+    /// Makes every NaN result the default NaN, regardless of the operands.
+    #[inline]
+    pub fn set_default_nan(&mut self, enabled: bool) {
+        self.modes = (self.modes & !DEFAULT_NAN) | if enabled { DEFAULT_NAN } else { 0 }
+    }
+
+    #[inline]
+    pub fn default_nan(self) -> bool {
+        self.modes & DEFAULT_NAN != 0
+    }
+
+    /// Selects when results are tiny, which the hardware detects
+    /// after rounding.
+    #[inline]
+    pub fn set_tininess(&mut self, tininess: Tininess) {
+        self.modes = (self.modes & !TININESS_BEFORE)
+            | match tininess {
+                Tininess::BeforeRounding => TININESS_BEFORE,
+                Tininess::AfterRounding => 0,
+            }
+    }
+
+    #[inline]
+    pub fn tininess(self) -> Tininess {
+        if self.modes & TININESS_BEFORE != 0 {
+            Tininess::BeforeRounding
+        } else {
+            Tininess::AfterRounding
+        }
+    }
+
+    /// Whether the rounding mode is one the hardware lacks, so that the
+    /// operations run in software.
+    #[inline]
+    pub(crate) fn soft_rounding(self) -> bool {
+        self.modes & (TIES_AWAY | TO_ODD) != 0
+    }
+
+    #[cfg(feature = "batch")]
+    /// Whether the flags select a mode the packed instructions lack, such as
+    /// detecting tininess before rounding, so that the packed, slice and
+    /// program operations run the scalar ones instead.
+    #[inline]
+    pub(crate) fn needs_soft(self) -> bool {
+        self.soft_rounding() || self.default_nan() || self.tininess() == Tininess::BeforeRounding
+    }
+
+    /// The environment emulating SSE for operations it lacks.
+    #[inline]
+    pub(crate) fn soft_env(self) -> soft::Env {
+        soft::Env {
+            mode: match self.rounding() {
+                Rounding::Zero => soft::Mode::Zero,
+                Rounding::Up => soft::Mode::Up,
+                Rounding::Down => soft::Mode::Down,
+                Rounding::Nearest => soft::Mode::Nearest,
+                Rounding::NearestAway => soft::Mode::NearestAway,
+                Rounding::Odd => soft::Mode::Odd,
+            },
+            ftz: self.ftz(),
+            daz: self.daz(),
+            default_nan: self.default_nan(),
+            sem: soft::Semantics {
+                tininess_before_rounding: self.tininess() == Tininess::BeforeRounding,
+                half_denorm: cfg!(target_feature = "avx512fp16"),
+                ..soft::Semantics::X86
+            },
+        }
+    }
+
+    /// `x`, or the default NaN when it is a NaN and the flags ask for one,
+    /// which the hardware doesn't do itself.
+    #[inline]
+    pub(crate) fn result<T: soft::Value>(self, x: T) -> T {
+        if self.default_nan() && x.is_nan() {
+            T::default_nan(&self.soft_env())
+        } else {
+            x
+        }
+    }
+}
+
+/// The status from the operations.
+///
+/// The layout is stable: a `u32` holding the exception bits of MXCSR.
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct Status {
+    inner: u32,
+}
+
+impl Status {
+    pub const INVALID: Self = Self {
+        inner: arch::_MM_EXCEPT_INVALID,
+    };
+    pub const OVERFLOW: Self = Self {
+        inner: arch::_MM_EXCEPT_OVERFLOW,
+    };
+    pub const UNDERFLOW: Self = Self {
+        inner: arch::_MM_EXCEPT_UNDERFLOW,
+    };
+    pub const INEXACT: Self = Self {
+        inner: arch::_MM_EXCEPT_INEXACT,
+    };
+    pub const DENORM: Self = Self {
+        inner: arch::_MM_EXCEPT_DENORM,
+    };
+    pub const DIV_ZERO: Self = Self {
+        inner: arch::_MM_EXCEPT_DIV_ZERO,
+    };
+
+    #[inline]
+    pub fn empty() -> Self {
+        Self { inner: 0 }
+    }
+
+    #[inline]
+    pub fn has_exceptions(self) -> bool {
+        self.inner & arch::_MM_EXCEPT_MASK != 0
+    }
+
+    #[inline]
+    pub fn invalid(self) -> bool {
+        self.has(Self::INVALID)
+    }
+
+    #[inline]
+    pub fn overflow(self) -> bool {
+        self.has(Self::OVERFLOW)
+    }
+
+    #[inline]
+    pub fn underflow(self) -> bool {
+        self.has(Self::UNDERFLOW)
+    }
+
+    #[inline]
+    pub fn inexact(self) -> bool {
+        self.has(Self::INEXACT)
+    }
+
+    #[inline]
+    pub fn denorm(self) -> bool {
+        self.has(Self::DENORM)
+    }
+
+    #[inline]
+    pub fn div_zero(self) -> bool {
+        self.has(Self::DIV_ZERO)
+    }
+
+    #[inline]
+    pub fn has(self, status: Self) -> bool {
+        self.inner & status.inner == status.inner
+    }
+
+    #[inline]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            inner: self.inner | other.inner,
+        }
+    }
+
+    #[inline]
+    pub fn and(self, other: Self) -> Self {
+        Self {
+            inner: self.inner & other.inner,
+        }
+    }
+
+    /// Translates exceptions raised by the software implementation, which
+    /// uses the MXCSR layout.
+    #[inline]
+    pub(crate) fn from_soft(exc: u32) -> Self {
+        Self { inner: exc }
+    }
+}
+
+#[cfg(feature = "fma")]
+/// Whether `vfmadd` can be used, as older CPUs lack it.
+#[inline]
+fn has_fma() -> bool {
+    cfg!(target_feature = "fma") || features().has(Features::FMA)
+}
+
+/// Whether `roundss` and `roundsd` can be used, as older CPUs lack them.
+#[inline]
+fn has_sse41() -> bool {
+    cfg!(target_feature = "sse4.1") || features().has(Features::SSE4_1)
+}
+
+/// Whether `vfixupimmss` and `vfixupimmsd` can be used.
+#[inline]
+fn has_avx512f() -> bool {
+    cfg!(target_feature = "avx512f") || features().has(Features::AVX512F)
+}
+
+/// Whether `vrangess` and `vrangesd` can be used.
+#[inline]
+fn has_avx512dq() -> bool {
+    cfg!(target_feature = "avx512dq") || features().has(Features::AVX512DQ)
+}
+
+/// Picks the VEX encoding of an instruction when AVX is enabled, so AVX code
+/// around the operation doesn't stall on state transitions.
+#[cfg(target_feature = "avx")]
+macro_rules! encoding {
+    ($legacy:literal, $vex:literal) => {
+        $vex
+    };
+}
+
+#[cfg(not(target_feature = "avx"))]
+macro_rules! encoding {
+    ($legacy:literal, $vex:literal) => {
+        $legacy
+    };
+}
+
+macro_rules! host_op {
+    ($flags:ident; $($asm:expr),+; $($end:tt)* ) => {
+        unsafe {
+            let mut status = 0;
+            asm!(
+                encoding!("ldmxcsr [{flags}]", "vldmxcsr [{flags}]"),
+                $($asm,)+
+                encoding!("stmxcsr [{status}]", "vstmxcsr [{status}]"),
+                flags = in(reg) &$flags.inner as *const _,
+                status = in(reg) &mut status as *mut _,
+                $($end)*
+            );
+            status
+        }
+    };
+}
+
+/// Tracks the MXCSR an emulator has loaded for its guest against the flags
+/// the guest last requested, so that MXCSR is only rewritten when they
+/// differ, as the write serializes the pipeline.
+///
+/// JIT-generated code loads the value `switch` returns itself, and
+/// interpreters call `load`. The modes only the software tracks don't take
+/// part, as MXCSR doesn't hold them.
+#[derive(Clone, Copy)]
+pub struct DirtyEnv {
+    requested: Flags,
+    loaded: Option<u32>,
+}
+
+impl Default for DirtyEnv {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirtyEnv {
+    /// Requests the default flags, with nothing known to be loaded.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            requested: Flags::new(),
+            loaded: None,
+        }
+    }
+
+    /// Records that the guest requested `flags`.
+    #[inline]
+    pub fn request(&mut self, flags: Flags) {
+        self.requested = flags;
+    }
+
+    #[inline]
+    pub fn requested(&self) -> Flags {
+        self.requested
+    }
+
+    /// Whether MXCSR has to be rewritten before running guest operations.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.loaded != Some(self.requested.inner)
+    }
+
+    /// The MXCSR value to load if it differs from the loaded one, which is
+    /// then taken to be loaded.
+    #[inline]
+    pub fn switch(&mut self) -> Option<u32> {
+        if !self.is_dirty() {
+            return None;
+        }
+        self.loaded = Some(self.requested.inner);
+        self.loaded
+    }
+
+    /// Forgets the loaded value, as after host code has written MXCSR.
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.loaded = None;
+    }
+
+    /// Loads the requested flags into MXCSR if they differ from the loaded
+    /// ones, clearing the status flags.
+    ///
+    /// # Safety
+    ///
+    /// The compiler assumes the default environment, so only code that
+    /// doesn't observe the rounding, flushing or exceptions may run until
+    /// MXCSR is restored.
+    #[inline]
+    pub unsafe fn load(&mut self) {
+        if let Some(mxcsr) = self.switch() {
+            unsafe {
+                asm!(
+                    encoding!("ldmxcsr [{mxcsr}]", "vldmxcsr [{mxcsr}]"),
+                    mxcsr = in(reg) &mxcsr as *const _,
+                );
+            }
+        }
+    }
+}
+
+/// Whether `madd` runs on a fused instruction where the CPU has one.
+pub(crate) const FUSED_MADD: bool = true;
+
+pub mod f32 {
+    use super::*;
+
+    pub use crate::bits::f32::*;
+    pub use crate::bounds::f32::*;
+    pub use crate::functions::f32::*;
+    pub use crate::sign::f32::*;
+    #[cfg(feature = "batch")]
+    pub use crate::slice::f32 as slice;
+    pub use crate::sticky::f32::*;
+
+    #[inline]
+    pub fn add(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("addss {l}, {r}", "vaddss {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("subss {l}, {r}", "vsubss {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("mulss {l}, {r}", "vmulss {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, mut l: f32, r: f32) -> (f32, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("divss {l}, {r}", "vdivss {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, mut a: f32, b: f32, c: f32) -> (f32, Status) {
+        if !has_fma() {
+            let (bits, exc) = soft::fma::<soft::Binary32>(
+                &flags.soft_env(),
+                a.to_bits() as u64,
+                b.to_bits() as u64,
+                c.to_bits() as u64,
+            );
+            return (f32::from_bits(bits as u32), Status::from_soft(exc));
+        }
+        let status = host_op!(
+            flags;
+            "vfmadd213ss {a}, {b}, {c}";
+            a = inout(xmm_reg) a,
+            b = in(xmm_reg) b,
+            c = in(xmm_reg) c,
+        );
+        (flags.result(a), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, mut x: f32) -> (f32, Status) {
+        if !has_sse41() {
+            let (bits, exc) =
+                soft::round_int::<soft::Binary32>(&flags.soft_env(), x.to_bits() as u64);
+            return (f32::from_bits(bits as u32), Status::from_soft(exc));
+        }
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            encoding!("roundss {x}, {x}, 4", "vroundss {x}, {x}, {x}, 4");
+            x = inout(xmm_reg) x,
+        );
+        (flags.result(x), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, mut x: f32) -> (f32, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("sqrtss {x}, {x}", "vsqrtss {x}, {x}, {x}");
+            x = inout(xmm_reg) x,
+        );
+        (flags.result(x), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, single: f32) -> (f64, Status) {
+        let mut double = f64::from_bits(single.to_bits() as u64);
+        let status = host_op!(
+            flags;
+            encoding!("cvtss2sd {fp}, {fp}", "vcvtss2sd {fp}, {fp}, {fp}");
+            fp = inout(xmm_reg) double,
+        );
+        (flags.result(double), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[cfg(target_feature = "avx512fp16")]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status) {
+        let mut half = single;
+        let status = host_op!(
+            flags;
+            "vcvtss2sh {fp}, {fp}, {fp}";
+            fp = inout(xmm_reg) half,
+        );
+        (
+            flags.result(half.to_bits() as u16),
+            Status { inner: status },
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[cfg(not(target_feature = "avx512fp16"))]
+    #[inline]
+    pub fn to_half(flags: Flags, single: f32) -> (u16, Status) {
+        let (bits, exc) = soft::convert::<soft::Binary32, soft::Binary16>(
+            &flags.soft_env(),
+            single.to_bits() as u64,
+        );
+        (bits as u16, Status::from_soft(exc))
+    }
+}
+
+pub mod f64 {
+    use super::*;
+
+    pub use crate::bits::f64::*;
+    pub use crate::bounds::f64::*;
+    pub use crate::functions::f64::*;
+    pub use crate::sign::f64::*;
+    #[cfg(feature = "batch")]
+    pub use crate::slice::f64 as slice;
+    pub use crate::sticky::f64::*;
+
+    #[inline]
+    pub fn add(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("addsd {l}, {r}", "vaddsd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("subsd {l}, {r}", "vsubsd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("mulsd {l}, {r}", "vmulsd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, mut l: f64, r: f64) -> (f64, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("divsd {l}, {r}", "vdivsd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) r,
+        );
+        (flags.result(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, mut a: f64, b: f64, c: f64) -> (f64, Status) {
+        if !has_fma() {
+            let (bits, exc) = soft::fma::<soft::Binary64>(
+                &flags.soft_env(),
+                a.to_bits(),
+                b.to_bits(),
+                c.to_bits(),
+            );
+            return (f64::from_bits(bits), Status::from_soft(exc));
+        }
+        let status = host_op!(
+            flags;
+            "vfmadd213sd {a}, {b}, {c}";
+            a = inout(xmm_reg) a,
+            b = in(xmm_reg) b,
+            c = in(xmm_reg) c,
+        );
+        (flags.result(a), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, mut x: f64) -> (f64, Status) {
+        if !has_sse41() {
+            let (bits, exc) = soft::round_int::<soft::Binary64>(&flags.soft_env(), x.to_bits());
+            return (f64::from_bits(bits), Status::from_soft(exc));
+        }
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            encoding!("roundsd {x}, {x}, 4", "vroundsd {x}, {x}, {x}, 4");
+            x = inout(xmm_reg) x,
+        );
+        (flags.result(x), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, mut x: f64) -> (f64, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("sqrtsd {x}, {x}", "vsqrtsd {x}, {x}, {x}");
+            x = inout(xmm_reg) x,
+        );
+        (flags.result(x), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, mut double: f64) -> (f32, Status) {
+        let status = host_op!(
+            flags;
+            encoding!("cvtsd2ss {fp}, {fp}", "vcvtsd2ss {fp}, {fp}, {fp}");
+            fp = inout(xmm_reg) double,
+        );
+        (
+            flags.result(f32::from_bits(double.to_bits() as u32)),
+            Status { inner: status },
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[cfg(target_feature = "avx512fp16")]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status) {
+        let mut half = double;
+        let status = host_op!(
+            flags;
+            "vcvtsd2sh {fp}, {fp}, {fp}";
+            fp = inout(xmm_reg) half,
+        );
+        (
+            flags.result(half.to_bits() as u16),
+            Status { inner: status },
+        )
+    }
+
+    #[cfg(feature = "convert")]
+    #[cfg(not(target_feature = "avx512fp16"))]
+    #[inline]
+    pub fn to_half(flags: Flags, double: f64) -> (u16, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary64, soft::Binary16>(&flags.soft_env(), double.to_bits());
+        (bits as u16, Status::from_soft(exc))
+    }
+}
+
+/// Half-precision operations, taking and returning the bit patterns of the
+/// values since `f16` isn't a stable Rust type.
+///
+/// AVX512-FP16 handles denormal halves in full regardless of the flushing
+/// modes, but unlike F16C reports them as `DENORM`.
+#[cfg(target_feature = "avx512fp16")]
+pub mod f16 {
+    use super::*;
+
+    pub use crate::sticky::f16::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut l = f32::from_bits(l as u32);
+        let status = host_op!(
+            flags;
+            "vaddsh {l}, {l}, {r}";
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) f32::from_bits(r as u32),
+        );
+        (flags.result(l.to_bits() as u16), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut l = f32::from_bits(l as u32);
+        let status = host_op!(
+            flags;
+            "vsubsh {l}, {l}, {r}";
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) f32::from_bits(r as u32),
+        );
+        (flags.result(l.to_bits() as u16), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut l = f32::from_bits(l as u32);
+        let status = host_op!(
+            flags;
+            "vmulsh {l}, {l}, {r}";
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) f32::from_bits(r as u32),
+        );
+        (flags.result(l.to_bits() as u16), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let mut l = f32::from_bits(l as u32);
+        let status = host_op!(
+            flags;
+            "vdivsh {l}, {l}, {r}";
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) f32::from_bits(r as u32),
+        );
+        (flags.result(l.to_bits() as u16), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: u16, b: u16, c: u16) -> (u16, Status) {
+        let mut a = f32::from_bits(a as u32);
+        let status = host_op!(
+            flags;
+            "vfmadd213sh {a}, {b}, {c}";
+            a = inout(xmm_reg) a,
+            b = in(xmm_reg) f32::from_bits(b as u32),
+            c = in(xmm_reg) f32::from_bits(c as u32),
+        );
+        (flags.result(a.to_bits() as u16), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: u16) -> (u16, Status) {
+        let mut x = f32::from_bits(x as u32);
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            "vrndscalesh {x}, {x}, {x}, 4";
+            x = inout(xmm_reg) x,
+        );
+        (flags.result(x.to_bits() as u16), Status { inner: status })
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: u16) -> (u16, Status) {
+        let mut x = f32::from_bits(x as u32);
+        let status = host_op!(
+            flags;
+            "vsqrtsh {x}, {x}, {x}";
+            x = inout(xmm_reg) x,
+        );
+        (flags.result(x.to_bits() as u16), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, half: u16) -> (f32, Status) {
+        let mut single = f32::from_bits(half as u32);
+        let status = host_op!(
+            flags;
+            "vcvtsh2ss {fp}, {fp}, {fp}";
+            fp = inout(xmm_reg) single,
+        );
+        (flags.result(single), Status { inner: status })
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, half: u16) -> (f64, Status) {
+        let mut double = f64::from_bits(half as u64);
+        let status = host_op!(
+            flags;
+            "vcvtsh2sd {fp}, {fp}, {fp}";
+            fp = inout(xmm_reg) double,
+        );
+        (flags.result(double), Status { inner: status })
+    }
+}
+
+/// Half-precision operations, taking and returning the bit patterns of the
+/// values since `f16` isn't a stable Rust type.
+#[cfg(not(target_feature = "avx512fp16"))]
+pub mod f16 {
+    use super::*;
+
+    pub use crate::sticky::f16::*;
+
+    #[inline]
+    pub fn add(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::add::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sub(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::sub::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn mul(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::mul::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn div(flags: Flags, l: u16, r: u16) -> (u16, Status) {
+        let (bits, exc) = soft::div::<soft::Binary16>(&flags.soft_env(), l as u64, r as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub fn madd(flags: Flags, a: u16, b: u16, c: u16) -> (u16, Status) {
+        let (bits, exc) =
+            soft::fma::<soft::Binary16>(&flags.soft_env(), a as u64, b as u64, c as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn round(flags: Flags, x: u16) -> (u16, Status) {
+        let (bits, exc) = soft::round_int::<soft::Binary16>(&flags.soft_env(), x as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[inline]
+    pub fn sqrt(flags: Flags, x: u16) -> (u16, Status) {
+        let (bits, exc) = soft::sqrt::<soft::Binary16>(&flags.soft_env(), x as u64);
+        (bits as u16, Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_single(flags: Flags, half: u16) -> (f32, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary16, soft::Binary32>(&flags.soft_env(), half as u64);
+        (f32::from_bits(bits as _), Status::from_soft(exc))
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub fn to_double(flags: Flags, half: u16) -> (f64, Status) {
+        let (bits, exc) =
+            soft::convert::<soft::Binary16, soft::Binary64>(&flags.soft_env(), half as u64);
+        (f64::from_bits(bits as _), Status::from_soft(exc))
+    }
+}
+
+#[cfg(feature = "batch")]
+/// The packed operations, on SSE registers holding every lane.
+pub(crate) mod packed {
+    use core::mem::transmute;
+
+    use super::*;
+    use crate::packed::lanes;
+
+    // The lanes have the layout of the registers.
+
+    #[inline]
+    fn from_f32x4(lanes: [f32; 4]) -> arch::__m128 {
+        unsafe { transmute::<[f32; 4], arch::__m128>(lanes) }
+    }
+
+    #[inline]
+    fn to_f32x4(reg: arch::__m128) -> [f32; 4] {
+        unsafe { transmute::<arch::__m128, [f32; 4]>(reg) }
+    }
+
+    #[inline]
+    fn from_f64x2(lanes: [f64; 2]) -> arch::__m128d {
+        unsafe { transmute::<[f64; 2], arch::__m128d>(lanes) }
+    }
+
+    #[inline]
+    fn to_f64x2(reg: arch::__m128d) -> [f64; 2] {
+        unsafe { transmute::<arch::__m128d, [f64; 2]>(reg) }
+    }
+
+    // The assembler swaps the operands of commutative VEX instructions where
+    // that allows a shorter encoding, changing which NaN they return, unless
+    // the long encoding is forced with `{vex3}`.
+
+    #[inline]
+    pub(crate) fn f32x4_add(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_add(flags, l, r);
+        }
+        let mut l = from_f32x4(l);
+        let status = host_op!(
+            flags;
+            encoding!("addps {l}, {r}", "{{vex3}} vaddps {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_sub(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_sub(flags, l, r);
+        }
+        let mut l = from_f32x4(l);
+        let status = host_op!(
+            flags;
+            encoding!("subps {l}, {r}", "vsubps {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_mul(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_mul(flags, l, r);
+        }
+        let mut l = from_f32x4(l);
+        let status = host_op!(
+            flags;
+            encoding!("mulps {l}, {r}", "{{vex3}} vmulps {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_div(flags: Flags, l: [f32; 4], r: [f32; 4]) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_div(flags, l, r);
+        }
+        let mut l = from_f32x4(l);
+        let status = host_op!(
+            flags;
+            encoding!("divps {l}, {r}", "vdivps {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32x4_madd(
+        flags: Flags,
+        a: [f32; 4],
+        b: [f32; 4],
+        c: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() || !has_fma() {
+            return lanes::f32x4_madd(flags, a, b, c);
+        }
+        let mut a = from_f32x4(a);
+        let status = host_op!(
+            flags;
+            "vfmadd213ps {a}, {b}, {c}";
+            a = inout(xmm_reg) a,
+            b = in(xmm_reg) from_f32x4(b),
+            c = in(xmm_reg) from_f32x4(c),
+        );
+        (to_f32x4(a), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f32x4_round(flags: Flags, x: [f32; 4]) -> ([f32; 4], Status) {
+        if flags.needs_soft() || !has_sse41() {
+            return lanes::f32x4_round(flags, x);
+        }
+        let mut x = from_f32x4(x);
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            encoding!("roundps {x}, {x}, 4", "vroundps {x}, {x}, 4");
+            x = inout(xmm_reg) x,
+        );
+        (to_f32x4(x), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_add(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_add(flags, l, r);
+        }
+        let mut l = from_f64x2(l);
+        let status = host_op!(
+            flags;
+            encoding!("addpd {l}, {r}", "{{vex3}} vaddpd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_sub(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_sub(flags, l, r);
+        }
+        let mut l = from_f64x2(l);
+        let status = host_op!(
+            flags;
+            encoding!("subpd {l}, {r}", "vsubpd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_mul(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_mul(flags, l, r);
+        }
+        let mut l = from_f64x2(l);
+        let status = host_op!(
+            flags;
+            encoding!("mulpd {l}, {r}", "{{vex3}} vmulpd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(l), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_div(flags: Flags, l: [f64; 2], r: [f64; 2]) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_div(flags, l, r);
+        }
+        let mut l = from_f64x2(l);
+        let status = host_op!(
+            flags;
+            encoding!("divpd {l}, {r}", "vdivpd {l}, {l}, {r}");
+            l = inout(xmm_reg) l,
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(l), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64x2_madd(
+        flags: Flags,
+        a: [f64; 2],
+        b: [f64; 2],
+        c: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() || !has_fma() {
+            return lanes::f64x2_madd(flags, a, b, c);
+        }
+        let mut a = from_f64x2(a);
+        let status = host_op!(
+            flags;
+            "vfmadd213pd {a}, {b}, {c}";
+            a = inout(xmm_reg) a,
+            b = in(xmm_reg) from_f64x2(b),
+            c = in(xmm_reg) from_f64x2(c),
+        );
+        (to_f64x2(a), Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64x2_round(flags: Flags, x: [f64; 2]) -> ([f64; 2], Status) {
+        if flags.needs_soft() || !has_sse41() {
+            return lanes::f64x2_round(flags, x);
+        }
+        let mut x = from_f64x2(x);
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            encoding!("roundpd {x}, {x}, 4", "vroundpd {x}, {x}, 4");
+            x = inout(xmm_reg) x,
+        );
+        (to_f64x2(x), Status { inner: status })
+    }
+
+    #[cfg(not(target_feature = "avx512vl"))]
+    pub(crate) use lanes::{
+        f32x4_add_masked, f32x4_div_masked, f32x4_mul_masked, f32x4_round_masked, f32x4_sub_masked,
+        f64x2_add_masked, f64x2_div_masked, f64x2_mul_masked, f64x2_round_masked, f64x2_sub_masked,
+    };
+    #[cfg(all(feature = "fma", not(target_feature = "avx512vl")))]
+    pub(crate) use lanes::{f32x4_madd_masked, f64x2_madd_masked};
+
+    // Masked off lanes take `src` and raise nothing, and the merging forms of
+    // the instructions take them from the destination.
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f32x4_add_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_add_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f32x4(src);
+        let status = host_op!(
+            flags;
+            "vaddps {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f32x4(l),
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f32x4_sub_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_sub_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f32x4(src);
+        let status = host_op!(
+            flags;
+            "vsubps {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f32x4(l),
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f32x4_mul_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_mul_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f32x4(src);
+        let status = host_op!(
+            flags;
+            "vmulps {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f32x4(l),
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f32x4_div_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        l: [f32; 4],
+        r: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_div_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f32x4(src);
+        let status = host_op!(
+            flags;
+            "vdivps {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f32x4(l),
+            r = in(xmm_reg) from_f32x4(r),
+        );
+        (to_f32x4(src), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f32x4_madd_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        a: [f32; 4],
+        b: [f32; 4],
+        c: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_madd_masked(flags, mask, src, a, b, c);
+        }
+        let mut src = from_f32x4(src);
+        // The fused forms overwrite an operand, so the lanes are computed in
+        // `a` before being merged into `src`.
+        let status = host_op!(
+            flags;
+            "vfmadd213ps {a}{{{mask}}}, {b}, {c}",
+            "vmovaps {src}{{{mask}}}, {a}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            a = inout(xmm_reg) from_f32x4(a) => _,
+            b = in(xmm_reg) from_f32x4(b),
+            c = in(xmm_reg) from_f32x4(c),
+        );
+        (to_f32x4(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f32x4_round_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f32; 4],
+        x: [f32; 4],
+    ) -> ([f32; 4], Status) {
+        if flags.needs_soft() {
+            return lanes::f32x4_round_masked(flags, mask, src, x);
+        }
+        let mut src = from_f32x4(src);
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            "vrndscaleps {src}{{{mask}}}, {x}, 4";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            x = in(xmm_reg) from_f32x4(x),
+        );
+        (to_f32x4(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f64x2_add_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_add_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f64x2(src);
+        let status = host_op!(
+            flags;
+            "vaddpd {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f64x2(l),
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f64x2_sub_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_sub_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f64x2(src);
+        let status = host_op!(
+            flags;
+            "vsubpd {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f64x2(l),
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f64x2_mul_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_mul_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f64x2(src);
+        let status = host_op!(
+            flags;
+            "vmulpd {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f64x2(l),
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f64x2_div_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        l: [f64; 2],
+        r: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_div_masked(flags, mask, src, l, r);
+        }
+        let mut src = from_f64x2(src);
+        let status = host_op!(
+            flags;
+            "vdivpd {src}{{{mask}}}, {l}, {r}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            l = in(xmm_reg) from_f64x2(l),
+            r = in(xmm_reg) from_f64x2(r),
+        );
+        (to_f64x2(src), Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f64x2_madd_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        a: [f64; 2],
+        b: [f64; 2],
+        c: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_madd_masked(flags, mask, src, a, b, c);
+        }
+        let mut src = from_f64x2(src);
+        // The fused forms overwrite an operand, so the lanes are computed in
+        // `a` before being merged into `src`.
+        let status = host_op!(
+            flags;
+            "vfmadd213pd {a}{{{mask}}}, {b}, {c}",
+            "vmovapd {src}{{{mask}}}, {a}";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            a = inout(xmm_reg) from_f64x2(a) => _,
+            b = in(xmm_reg) from_f64x2(b),
+            c = in(xmm_reg) from_f64x2(c),
+        );
+        (to_f64x2(src), Status { inner: status })
+    }
+
+    #[cfg(target_feature = "avx512vl")]
+    #[inline]
+    pub(crate) fn f64x2_round_masked(
+        flags: Flags,
+        mask: u8,
+        src: [f64; 2],
+        x: [f64; 2],
+    ) -> ([f64; 2], Status) {
+        if flags.needs_soft() {
+            return lanes::f64x2_round_masked(flags, mask, src, x);
+        }
+        let mut src = from_f64x2(src);
+        // Rounds in the mode of MXCSR, reporting inexact results.
+        let status = host_op!(
+            flags;
+            "vrndscalepd {src}{{{mask}}}, {x}, 4";
+            src = inout(xmm_reg) src,
+            mask = in(kreg) mask,
+            x = in(xmm_reg) from_f64x2(x),
+        );
+        (to_f64x2(src), Status { inner: status })
+    }
+}
+
+#[cfg(feature = "batch")]
+/// The slice operations, looping over packed instructions with the
+/// environment set up once.
+pub(crate) mod slice {
+    use super::*;
+    use crate::slice::{elements, NanPolicy};
+
+    #[inline]
+    pub(crate) fn f32_add_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_add_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {l}, [{dst}]", "vmovups {l}, [{dst}]"),
+            encoding!("movups {r}, [{src}]", "vmovups {r}, [{src}]"),
+            encoding!("addps {l}, {r}", "{{vex3}} vaddps {l}, {l}, {r}"),
+            encoding!("movups [{dst}], {l}", "vmovups [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_add_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f32_sub_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_sub_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {l}, [{dst}]", "vmovups {l}, [{dst}]"),
+            encoding!("movups {r}, [{src}]", "vmovups {r}, [{src}]"),
+            encoding!("subps {l}, {r}", "vsubps {l}, {l}, {r}"),
+            encoding!("movups [{dst}], {l}", "vmovups [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_sub_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f32_mul_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_mul_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {l}, [{dst}]", "vmovups {l}, [{dst}]"),
+            encoding!("movups {r}, [{src}]", "vmovups {r}, [{src}]"),
+            encoding!("mulps {l}, {r}", "{{vex3}} vmulps {l}, {l}, {r}"),
+            encoding!("movups [{dst}], {l}", "vmovups [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_mul_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f32_div_assign(flags: Flags, dst: &mut [f32], src: &[f32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_div_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {l}, [{dst}]", "vmovups {l}, [{dst}]"),
+            encoding!("movups {r}, [{src}]", "vmovups {r}, [{src}]"),
+            encoding!("divps {l}, {r}", "vdivps {l}, {l}, {r}"),
+            encoding!("movups [{dst}], {l}", "vmovups [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_div_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32_madd_assign(flags: Flags, dst: &mut [f32], b: &[f32], c: &[f32]) -> Status {
+        if flags.needs_soft() || !has_fma() {
+            return elements::f32_madd_assign(flags, dst, b, c);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            "vmovups {a}, [{dst}]",
+            "vmovups {b}, [{b_ptr}]",
+            "vfmadd213ps {a}, {b}, [{c_ptr}]",
+            "vmovups [{dst}], {a}",
+            "add {dst}, 16",
+            "add {b_ptr}, 16",
+            "add {c_ptr}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            b_ptr = inout(reg) b.as_ptr() => _,
+            c_ptr = inout(reg) c.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            a = out(xmm_reg) _,
+            b = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_madd_assign(flags, &mut dst[done..], &b[done..], &c[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f64_add_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_add_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {l}, [{dst}]", "vmovupd {l}, [{dst}]"),
+            encoding!("movupd {r}, [{src}]", "vmovupd {r}, [{src}]"),
+            encoding!("addpd {l}, {r}", "{{vex3}} vaddpd {l}, {l}, {r}"),
+            encoding!("movupd [{dst}], {l}", "vmovupd [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_add_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f64_sub_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_sub_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {l}, [{dst}]", "vmovupd {l}, [{dst}]"),
+            encoding!("movupd {r}, [{src}]", "vmovupd {r}, [{src}]"),
+            encoding!("subpd {l}, {r}", "vsubpd {l}, {l}, {r}"),
+            encoding!("movupd [{dst}], {l}", "vmovupd [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_sub_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f64_mul_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_mul_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {l}, [{dst}]", "vmovupd {l}, [{dst}]"),
+            encoding!("movupd {r}, [{src}]", "vmovupd {r}, [{src}]"),
+            encoding!("mulpd {l}, {r}", "{{vex3}} vmulpd {l}, {l}, {r}"),
+            encoding!("movupd [{dst}], {l}", "vmovupd [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_mul_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f64_div_assign(flags: Flags, dst: &mut [f64], src: &[f64]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_div_assign(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {l}, [{dst}]", "vmovupd {l}, [{dst}]"),
+            encoding!("movupd {r}, [{src}]", "vmovupd {r}, [{src}]"),
+            encoding!("divpd {l}, {r}", "vdivpd {l}, {l}, {r}"),
+            encoding!("movupd [{dst}], {l}", "vmovupd [{dst}], {l}"),
+            "add {dst}, 16",
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            l = out(xmm_reg) _,
+            r = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_div_assign(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64_madd_assign(flags: Flags, dst: &mut [f64], b: &[f64], c: &[f64]) -> Status {
+        if flags.needs_soft() || !has_fma() {
+            return elements::f64_madd_assign(flags, dst, b, c);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            "vmovupd {a}, [{dst}]",
+            "vmovupd {b}, [{b_ptr}]",
+            "vfmadd213pd {a}, {b}, [{c_ptr}]",
+            "vmovupd [{dst}], {a}",
+            "add {dst}, 16",
+            "add {b_ptr}, 16",
+            "add {c_ptr}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            b_ptr = inout(reg) b.as_ptr() => _,
+            c_ptr = inout(reg) c.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            a = out(xmm_reg) _,
+            b = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_madd_assign(flags, &mut dst[done..], &b[done..], &c[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[inline]
+    pub(crate) fn f32_sum(flags: Flags, first: f32, rest: &[f32]) -> (f32, Status) {
+        if flags.needs_soft() {
+            return elements::f32_sum(flags, first, rest);
+        }
+        let mut sum = first;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("addss {sum}, [{src}]", "vaddss {sum}, {sum}, [{src}]"),
+            "add {src}, 4",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            sum = inout(xmm_reg) sum,
+            src = inout(reg) rest.as_ptr() => _,
+            n = inout(reg) rest.len() => _,
+        );
+        (sum, Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64_sum(flags: Flags, first: f64, rest: &[f64]) -> (f64, Status) {
+        if flags.needs_soft() {
+            return elements::f64_sum(flags, first, rest);
+        }
+        let mut sum = first;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("addsd {sum}, [{src}]", "vaddsd {sum}, {sum}, [{src}]"),
+            "add {src}, 8",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            sum = inout(xmm_reg) sum,
+            src = inout(reg) rest.as_ptr() => _,
+            n = inout(reg) rest.len() => _,
+        );
+        (sum, Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f32_min(flags: Flags, policy: NanPolicy, src: &[f32]) -> (f32, Status) {
+        let chunks = src.len() / 4;
+        if policy == NanPolicy::Propagate || chunks == 0 {
+            return elements::f32_min(flags, policy, src);
+        }
+        let acc: f32;
+        let status = host_op!(
+            flags;
+            encoding!("movups {acc}, [{src}]", "vmovups {acc}, [{src}]"),
+            "add {src}, 16",
+            "dec {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {t}, [{src}]", "vmovups {t}, [{src}]"),
+            encoding!("minps {acc}, {t}", "vminps {acc}, {acc}, {t}"),
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:",
+            encoding!("movaps {t}, {acc}", "vmovaps {t}, {acc}"),
+            encoding!("movhlps {t}, {acc}", "vmovhlps {t}, {acc}, {acc}"),
+            encoding!("minps {acc}, {t}", "vminps {acc}, {acc}, {t}"),
+            encoding!("pshufd {t}, {acc}, 0x55", "vpshufd {t}, {acc}, 0x55"),
+            encoding!("minss {acc}, {t}", "vminss {acc}, {acc}, {t}"),
+            "test {tail}, {tail}",
+            "jz 5f",
+            "4:",
+            encoding!("minss {acc}, [{src}]", "vminss {acc}, {acc}, [{src}]"),
+            "add {src}, 4",
+            "dec {tail}",
+            "jnz 4b",
+            "5:";
+            acc = out(xmm_reg) acc,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            tail = inout(reg) src.len() % 4 => _,
+            t = out(xmm_reg) _,
+        );
+        let status = Status { inner: status };
+        // The lanes only pick the same element as going from left to right
+        // when no NaN or zero can tie.
+        if status.invalid() || acc == 0.0 {
+            return elements::f32_min(flags, policy, src);
+        }
+        (acc, status)
+    }
+
+    #[inline]
+    pub(crate) fn f32_max(flags: Flags, policy: NanPolicy, src: &[f32]) -> (f32, Status) {
+        let chunks = src.len() / 4;
+        if policy == NanPolicy::Propagate || chunks == 0 {
+            return elements::f32_max(flags, policy, src);
+        }
+        let acc: f32;
+        let status = host_op!(
+            flags;
+            encoding!("movups {acc}, [{src}]", "vmovups {acc}, [{src}]"),
+            "add {src}, 16",
+            "dec {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {t}, [{src}]", "vmovups {t}, [{src}]"),
+            encoding!("maxps {acc}, {t}", "vmaxps {acc}, {acc}, {t}"),
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:",
+            encoding!("movaps {t}, {acc}", "vmovaps {t}, {acc}"),
+            encoding!("movhlps {t}, {acc}", "vmovhlps {t}, {acc}, {acc}"),
+            encoding!("maxps {acc}, {t}", "vmaxps {acc}, {acc}, {t}"),
+            encoding!("pshufd {t}, {acc}, 0x55", "vpshufd {t}, {acc}, 0x55"),
+            encoding!("maxss {acc}, {t}", "vmaxss {acc}, {acc}, {t}"),
+            "test {tail}, {tail}",
+            "jz 5f",
+            "4:",
+            encoding!("maxss {acc}, [{src}]", "vmaxss {acc}, {acc}, [{src}]"),
+            "add {src}, 4",
+            "dec {tail}",
+            "jnz 4b",
+            "5:";
+            acc = out(xmm_reg) acc,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            tail = inout(reg) src.len() % 4 => _,
+            t = out(xmm_reg) _,
+        );
+        let status = Status { inner: status };
+        // The lanes only pick the same element as going from left to right
+        // when no NaN or zero can tie.
+        if status.invalid() || acc == 0.0 {
+            return elements::f32_max(flags, policy, src);
+        }
+        (acc, status)
+    }
+
+    #[inline]
+    pub(crate) fn f64_min(flags: Flags, policy: NanPolicy, src: &[f64]) -> (f64, Status) {
+        let chunks = src.len() / 2;
+        if policy == NanPolicy::Propagate || chunks == 0 {
+            return elements::f64_min(flags, policy, src);
+        }
+        let acc: f64;
+        let status = host_op!(
+            flags;
+            encoding!("movupd {acc}, [{src}]", "vmovupd {acc}, [{src}]"),
+            "add {src}, 16",
+            "dec {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {t}, [{src}]", "vmovupd {t}, [{src}]"),
+            encoding!("minpd {acc}, {t}", "vminpd {acc}, {acc}, {t}"),
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:",
+            encoding!("movaps {t}, {acc}", "vmovaps {t}, {acc}"),
+            encoding!("movhlps {t}, {acc}", "vmovhlps {t}, {acc}, {acc}"),
+            encoding!("minsd {acc}, {t}", "vminsd {acc}, {acc}, {t}"),
+            "test {tail}, {tail}",
+            "jz 4f",
+            encoding!("minsd {acc}, [{src}]", "vminsd {acc}, {acc}, [{src}]"),
+            "4:";
+            acc = out(xmm_reg) acc,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            tail = in(reg) src.len() % 2,
+            t = out(xmm_reg) _,
+        );
+        let status = Status { inner: status };
+        // The lanes only pick the same element as going from left to right
+        // when no NaN or zero can tie.
+        if status.invalid() || acc == 0.0 {
+            return elements::f64_min(flags, policy, src);
+        }
+        (acc, status)
+    }
+
+    #[inline]
+    pub(crate) fn f64_max(flags: Flags, policy: NanPolicy, src: &[f64]) -> (f64, Status) {
+        let chunks = src.len() / 2;
+        if policy == NanPolicy::Propagate || chunks == 0 {
+            return elements::f64_max(flags, policy, src);
+        }
+        let acc: f64;
+        let status = host_op!(
+            flags;
+            encoding!("movupd {acc}, [{src}]", "vmovupd {acc}, [{src}]"),
+            "add {src}, 16",
+            "dec {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {t}, [{src}]", "vmovupd {t}, [{src}]"),
+            encoding!("maxpd {acc}, {t}", "vmaxpd {acc}, {acc}, {t}"),
+            "add {src}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:",
+            encoding!("movaps {t}, {acc}", "vmovaps {t}, {acc}"),
+            encoding!("movhlps {t}, {acc}", "vmovhlps {t}, {acc}, {acc}"),
+            encoding!("maxsd {acc}, {t}", "vmaxsd {acc}, {acc}, {t}"),
+            "test {tail}, {tail}",
+            "jz 4f",
+            encoding!("maxsd {acc}, [{src}]", "vmaxsd {acc}, {acc}, [{src}]"),
+            "4:";
+            acc = out(xmm_reg) acc,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            tail = in(reg) src.len() % 2,
+            t = out(xmm_reg) _,
+        );
+        let status = Status { inner: status };
+        // The lanes only pick the same element as going from left to right
+        // when no NaN or zero can tie.
+        if status.invalid() || acc == 0.0 {
+            return elements::f64_max(flags, policy, src);
+        }
+        (acc, status)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_double(flags: Flags, dst: &mut [f64], src: &[f32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_to_double(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("cvtps2pd {x}, qword ptr [{src}]", "vcvtps2pd {x}, qword ptr [{src}]"),
+            encoding!("movupd [{dst}], {x}", "vmovupd [{dst}], {x}"),
+            "add {src}, 8",
+            "add {dst}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            x = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f32_to_double(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_int(flags: Flags, dst: &mut [i32], src: &[f32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_to_int(flags, dst, src);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movups {x}, [{src}]", "vmovups {x}, [{src}]"),
+            encoding!("cvtps2dq {x}, {x}", "vcvtps2dq {x}, {x}"),
+            encoding!("movdqu [{dst}], {x}", "vmovdqu [{dst}], {x}"),
+            "add {src}, 16",
+            "add {dst}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            x = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_to_int(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_from_int(flags: Flags, dst: &mut [f32], src: &[i32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f32_from_int(flags, dst, src);
+        }
+        let chunks = dst.len() / 4;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movdqu {x}, [{src}]", "vmovdqu {x}, [{src}]"),
+            encoding!("cvtdq2ps {x}, {x}", "vcvtdq2ps {x}, {x}"),
+            encoding!("movups [{dst}], {x}", "vmovups [{dst}], {x}"),
+            "add {src}, 16",
+            "add {dst}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            x = out(xmm_reg) _,
+        );
+        let done = chunks * 4;
+        let rest = elements::f32_from_int(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_to_single(flags: Flags, dst: &mut [f32], src: &[f64]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_to_single(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {x}, [{src}]", "vmovupd {x}, [{src}]"),
+            encoding!("cvtpd2ps {x}, {x}", "vcvtpd2ps {x}, {x}"),
+            encoding!("movlps [{dst}], {x}", "vmovlps [{dst}], {x}"),
+            "add {src}, 16",
+            "add {dst}, 8",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            x = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_to_single(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_to_int(flags: Flags, dst: &mut [i32], src: &[f64]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_to_int(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("movupd {x}, [{src}]", "vmovupd {x}, [{src}]"),
+            encoding!("cvtpd2dq {x}, {x}", "vcvtpd2dq {x}, {x}"),
+            encoding!("movq [{dst}], {x}", "vmovq [{dst}], {x}"),
+            "add {src}, 16",
+            "add {dst}, 8",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            x = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_to_int(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f64_from_int(flags: Flags, dst: &mut [f64], src: &[i32]) -> Status {
+        if flags.needs_soft() {
+            return elements::f64_from_int(flags, dst, src);
+        }
+        let chunks = dst.len() / 2;
+        let status = host_op!(
+            flags;
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            encoding!("cvtdq2pd {x}, qword ptr [{src}]", "vcvtdq2pd {x}, qword ptr [{src}]"),
+            encoding!("movupd [{dst}], {x}", "vmovupd [{dst}], {x}"),
+            "add {src}, 8",
+            "add {dst}, 16",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dst = inout(reg) dst.as_mut_ptr() => _,
+            src = inout(reg) src.as_ptr() => _,
+            n = inout(reg) chunks => _,
+            x = out(xmm_reg) _,
+        );
+        let done = chunks * 2;
+        let rest = elements::f64_from_int(flags, &mut dst[done..], &src[done..]);
+        Status { inner: status }.or(rest)
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f32_dot(flags: Flags, a: &[f32], b: &[f32]) -> (f32, Status) {
+        if flags.needs_soft() || !has_fma() {
+            return elements::f32_dot(flags, a, b);
+        }
+        let mut dot = a[0];
+        // Every product is fused like `madd(a, b, dot)`, so the NaNs picked
+        // are those of the scalar operations.
+        let status = host_op!(
+            flags;
+            "vmulss {dot}, {dot}, {b0}",
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            "vmovss {x}, [{a}]",
+            "vmovss {y}, [{b}]",
+            "vfmadd213ss {x}, {y}, {dot}",
+            "vmovaps {dot}, {x}",
+            "add {a}, 4",
+            "add {b}, 4",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dot = inout(xmm_reg) dot,
+            b0 = in(xmm_reg) b[0],
+            a = inout(reg) a[1..].as_ptr() => _,
+            b = inout(reg) b[1..].as_ptr() => _,
+            n = inout(reg) a.len() - 1 => _,
+            x = out(xmm_reg) _,
+            y = out(xmm_reg) _,
+        );
+        (dot, Status { inner: status })
+    }
+
+    #[cfg(feature = "fma")]
+    #[inline]
+    pub(crate) fn f64_dot(flags: Flags, a: &[f64], b: &[f64]) -> (f64, Status) {
+        if flags.needs_soft() || !has_fma() {
+            return elements::f64_dot(flags, a, b);
+        }
+        let mut dot = a[0];
+        // Every product is fused like `madd(a, b, dot)`, so the NaNs picked
+        // are those of the scalar operations.
+        let status = host_op!(
+            flags;
+            "vmulsd {dot}, {dot}, {b0}",
+            "test {n}, {n}",
+            "jz 3f",
+            "2:",
+            "vmovsd {x}, [{a}]",
+            "vmovsd {y}, [{b}]",
+            "vfmadd213sd {x}, {y}, {dot}",
+            "vmovapd {dot}, {x}",
+            "add {a}, 8",
+            "add {b}, 8",
+            "dec {n}",
+            "jnz 2b",
+            "3:";
+            dot = inout(xmm_reg) dot,
+            b0 = in(xmm_reg) b[0],
+            a = inout(reg) a[1..].as_ptr() => _,
+            b = inout(reg) b[1..].as_ptr() => _,
+            n = inout(reg) a.len() - 1 => _,
+            x = out(xmm_reg) _,
+            y = out(xmm_reg) _,
+        );
+        (dot, Status { inner: status })
+    }
+}
+
+#[cfg(feature = "batch")]
+/// The programs, run with MXCSR set up once around every step.
+pub(crate) mod program {
+    use super::*;
+    use crate::program::Step;
+    use crate::FpOpKind;
+
+    // Kept out of line so no code of the caller runs in the environment.
+    #[inline(never)]
+    pub(crate) fn f32_run(flags: Flags, steps: &[Step<f32>]) -> (f32, Status) {
+        if flags.needs_soft() {
+            return crate::program::steps::f32_run(flags, steps);
+        }
+        let env = flags.soft_env();
+        let mut soft_status = 0;
+        unsafe {
+            asm!(
+                encoding!("ldmxcsr [{flags}]", "vldmxcsr [{flags}]"),
+                flags = in(reg) &flags.inner as *const _,
+            );
+        }
+        let mut prev = 0.0;
+        for step in steps {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [mut a, b, c] = step.operands.map(|op| op.value(prev));
+            match step.kind {
+                FpOpKind::Add => unsafe {
+                    asm!(
+                        encoding!("addss {a}, {b}", "vaddss {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                FpOpKind::Sub => unsafe {
+                    asm!(
+                        encoding!("subss {a}, {b}", "vsubss {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                FpOpKind::Mul => unsafe {
+                    asm!(
+                        encoding!("mulss {a}, {b}", "vmulss {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                FpOpKind::Div => unsafe {
+                    asm!(
+                        encoding!("divss {a}, {b}", "vdivss {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd if has_fma() => unsafe {
+                    asm!(
+                        "vfmadd213ss {a}, {b}, {c}",
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                        c = in(xmm_reg) c,
+                    );
+                },
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => {
+                    let (bits, exc) = soft::fma::<soft::Binary32>(
+                        &env,
+                        a.to_bits() as u64,
+                        b.to_bits() as u64,
+                        c.to_bits() as u64,
+                    );
+                    a = f32::from_bits(bits as u32);
+                    soft_status |= exc;
+                }
+                FpOpKind::Sqrt => unsafe {
+                    asm!(
+                        encoding!("sqrtss {a}, {a}", "vsqrtss {a}, {a}, {a}"),
+                        a = inout(xmm_reg) a,
+                    );
+                },
+                FpOpKind::Round if has_sse41() => unsafe {
+                    asm!(
+                        encoding!("roundss {a}, {a}, 4", "vroundss {a}, {a}, {a}, 4"),
+                        a = inout(xmm_reg) a,
+                    );
+                },
+                FpOpKind::Round => {
+                    let (bits, exc) = soft::round_int::<soft::Binary32>(&env, a.to_bits() as u64);
+                    a = f32::from_bits(bits as u32);
+                    soft_status |= exc;
+                }
+            }
+            prev = a;
+        }
+        let mut status = 0;
+        unsafe {
+            asm!(
+                encoding!("stmxcsr [{status}]", "vstmxcsr [{status}]"),
+                status = in(reg) &mut status as *mut _,
+            );
+        }
+        (
+            prev,
+            Status { inner: status }.or(Status::from_soft(soft_status)),
+        )
+    }
+
+    // Kept out of line so no code of the caller runs in the environment.
+    #[inline(never)]
+    pub(crate) fn f64_run(flags: Flags, steps: &[Step<f64>]) -> (f64, Status) {
+        if flags.needs_soft() {
+            return crate::program::steps::f64_run(flags, steps);
+        }
+        let env = flags.soft_env();
+        let mut soft_status = 0;
+        unsafe {
+            asm!(
+                encoding!("ldmxcsr [{flags}]", "vldmxcsr [{flags}]"),
+                flags = in(reg) &flags.inner as *const _,
+            );
+        }
+        let mut prev = 0.0;
+        for step in steps {
+            #[cfg_attr(not(feature = "fma"), allow(unused_variables))]
+            let [mut a, b, c] = step.operands.map(|op| op.value(prev));
+            match step.kind {
+                FpOpKind::Add => unsafe {
+                    asm!(
+                        encoding!("addsd {a}, {b}", "vaddsd {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                FpOpKind::Sub => unsafe {
+                    asm!(
+                        encoding!("subsd {a}, {b}", "vsubsd {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                FpOpKind::Mul => unsafe {
+                    asm!(
+                        encoding!("mulsd {a}, {b}", "vmulsd {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                FpOpKind::Div => unsafe {
+                    asm!(
+                        encoding!("divsd {a}, {b}", "vdivsd {a}, {a}, {b}"),
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                    );
+                },
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd if has_fma() => unsafe {
+                    asm!(
+                        "vfmadd213sd {a}, {b}, {c}",
+                        a = inout(xmm_reg) a,
+                        b = in(xmm_reg) b,
+                        c = in(xmm_reg) c,
+                    );
+                },
+                #[cfg(feature = "fma")]
+                FpOpKind::Madd => {
+                    let (bits, exc) =
+                        soft::fma::<soft::Binary64>(&env, a.to_bits(), b.to_bits(), c.to_bits());
+                    a = f64::from_bits(bits);
+                    soft_status |= exc;
+                }
+                FpOpKind::Sqrt => unsafe {
+                    asm!(
+                        encoding!("sqrtsd {a}, {a}", "vsqrtsd {a}, {a}, {a}"),
+                        a = inout(xmm_reg) a,
+                    );
+                },
+                FpOpKind::Round if has_sse41() => unsafe {
+                    asm!(
+                        encoding!("roundsd {a}, {a}, 4", "vroundsd {a}, {a}, {a}, 4"),
+                        a = inout(xmm_reg) a,
+                    );
+                },
+                FpOpKind::Round => {
+                    let (bits, exc) = soft::round_int::<soft::Binary64>(&env, a.to_bits());
+                    a = f64::from_bits(bits);
+                    soft_status |= exc;
+                }
+            }
+            prev = a;
+        }
+        let mut status = 0;
+        unsafe {
+            asm!(
+                encoding!("stmxcsr [{status}]", "vstmxcsr [{status}]"),
+                status = in(reg) &mut status as *mut _,
+            );
+        }
+        (
+            prev,
+            Status { inner: status }.or(Status::from_soft(soft_status)),
+        )
+    }
+}
+
+#[cfg(target_feature = "avx512f")]
+/// Runs an operation with the rounding mode encoded in the instruction.
+macro_rules! embedded_op {
+    ($rounding:ident, $soft:expr; $asm:literal; $($end:tt)* ) => {
+        unsafe {
+            match $rounding {
+                Rounding::Zero => asm!(concat!($asm, ", {{rz-sae}}"), $($end)*),
+                Rounding::Up => asm!(concat!($asm, ", {{ru-sae}}"), $($end)*),
+                Rounding::Down => asm!(concat!($asm, ", {{rd-sae}}"), $($end)*),
+                Rounding::Nearest => asm!(concat!($asm, ", {{rn-sae}}"), $($end)*),
+                Rounding::NearestAway | Rounding::Odd => return $soft(Flags::new().with_rounding($rounding)).0,
+            }
+        }
+    };
+}
+
+/// Operations with AVX-512 embedded rounding, which leave MXCSR untouched.
+///
+/// Embedded rounding suppresses all exceptions, so no status is returned,
+/// and flushing follows whatever MXCSR currently holds. The rounding modes it
+/// lacks run in software without flushing.
+#[cfg(target_feature = "avx512f")]
+pub mod embedded {
+    pub mod f32 {
+        use super::super::*;
+
+        #[inline]
+        pub fn add(rounding: Rounding, mut l: f32, r: f32) -> f32 {
+            embedded_op!(
+                rounding, |flags| crate::f32::add(flags, l, r);
+                "vaddss {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[inline]
+        pub fn sub(rounding: Rounding, mut l: f32, r: f32) -> f32 {
+            embedded_op!(
+                rounding, |flags| crate::f32::sub(flags, l, r);
+                "vsubss {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[inline]
+        pub fn mul(rounding: Rounding, mut l: f32, r: f32) -> f32 {
+            embedded_op!(
+                rounding, |flags| crate::f32::mul(flags, l, r);
+                "vmulss {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[inline]
+        pub fn div(rounding: Rounding, mut l: f32, r: f32) -> f32 {
+            embedded_op!(
+                rounding, |flags| crate::f32::div(flags, l, r);
+                "vdivss {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(rounding: Rounding, mut a: f32, b: f32, c: f32) -> f32 {
+            embedded_op!(
+                rounding, |flags| crate::f32::madd(flags, a, b, c);
+                "vfmadd213ss {a}, {b}, {c}";
+                a = inout(xmm_reg) a,
+                b = in(xmm_reg) b,
+                c = in(xmm_reg) c,
+            );
+            a
+        }
+    }
+
+    pub mod f64 {
+        use super::super::*;
+
+        #[inline]
+        pub fn add(rounding: Rounding, mut l: f64, r: f64) -> f64 {
+            embedded_op!(
+                rounding, |flags| crate::f64::add(flags, l, r);
+                "vaddsd {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[inline]
+        pub fn sub(rounding: Rounding, mut l: f64, r: f64) -> f64 {
+            embedded_op!(
+                rounding, |flags| crate::f64::sub(flags, l, r);
+                "vsubsd {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[inline]
+        pub fn mul(rounding: Rounding, mut l: f64, r: f64) -> f64 {
+            embedded_op!(
+                rounding, |flags| crate::f64::mul(flags, l, r);
+                "vmulsd {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[inline]
+        pub fn div(rounding: Rounding, mut l: f64, r: f64) -> f64 {
+            embedded_op!(
+                rounding, |flags| crate::f64::div(flags, l, r);
+                "vdivsd {l}, {l}, {r}";
+                l = inout(xmm_reg) l,
+                r = in(xmm_reg) r,
+            );
+            l
+        }
+
+        #[cfg(feature = "fma")]
+        #[inline]
+        pub fn madd(rounding: Rounding, mut a: f64, b: f64, c: f64) -> f64 {
+            embedded_op!(
+                rounding, |flags| crate::f64::madd(flags, a, b, c);
+                "vfmadd213sd {a}, {b}, {c}";
+                a = inout(xmm_reg) a,
+                b = in(xmm_reg) b,
+                c = in(xmm_reg) c,
+            );
+            a
+        }
+
+        #[cfg(feature = "convert")]
+        #[inline]
+        pub fn to_single(rounding: Rounding, double: f64) -> f32 {
+            let mut single = f32::from_bits(double.to_bits() as u32);
+            embedded_op!(
+                rounding, |flags| crate::f64::to_single(flags, double);
+                "vcvtsd2ss {single}, {single}, {double}";
+                single = inout(xmm_reg) single,
+                double = in(xmm_reg) double,
+            );
+            single
+        }
+    }
+}
+
+/// The suppress-all-exceptions forms backing the `quiet` operations.
+#[cfg(target_feature = "avx512f")]
+pub(crate) mod sae {
+    use core::cmp::Ordering;
+
+    use super::*;
+
+    /// Orders the flags set by `vucomiss` and `vucomisd`.
+    #[inline]
+    fn ordering(unordered: u8, below: u8, equal: u8) -> Option<Ordering> {
+        match (unordered, below, equal) {
+            (0, 0, 0) => Some(Ordering::Greater),
+            (0, _, 0) => Some(Ordering::Less),
+            (0, _, _) => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn f32_compare(flags: Flags, l: f32, r: f32) -> Option<Ordering> {
+        if flags.inner & DENORMALS_ZERO != 0 {
+            return crate::quiet::soft::f32_compare(flags, l, r);
+        }
+        let (unordered, below, equal): (u8, u8, u8);
+        unsafe {
+            asm!(
+                "vucomiss {l}, {r}, {{sae}}",
+                "setp {unordered}",
+                "setb {below}",
+                "sete {equal}",
+                l = in(xmm_reg) l,
+                r = in(xmm_reg) r,
+                unordered = out(reg_byte) unordered,
+                below = out(reg_byte) below,
+                equal = out(reg_byte) equal,
+                options(pure, nomem, nostack),
+            );
+        }
+        ordering(unordered, below, equal)
+    }
+
+    #[inline]
+    pub(crate) fn f64_compare(flags: Flags, l: f64, r: f64) -> Option<Ordering> {
+        if flags.inner & DENORMALS_ZERO != 0 {
+            return crate::quiet::soft::f64_compare(flags, l, r);
+        }
+        let (unordered, below, equal): (u8, u8, u8);
+        unsafe {
+            asm!(
+                "vucomisd {l}, {r}, {{sae}}",
+                "setp {unordered}",
+                "setb {below}",
+                "sete {equal}",
+                l = in(xmm_reg) l,
+                r = in(xmm_reg) r,
+                unordered = out(reg_byte) unordered,
+                below = out(reg_byte) below,
+                equal = out(reg_byte) equal,
+                options(pure, nomem, nostack),
+            );
+        }
+        ordering(unordered, below, equal)
+    }
+
+    #[cfg(feature = "convert")]
+    #[inline]
+    pub(crate) fn f32_to_double(flags: Flags, single: f32) -> f64 {
+        if flags.inner & DENORMALS_ZERO != 0 || flags.default_nan() {
+            return crate::quiet::soft::f32_to_double(flags, single);
+        }
+        let mut double = f64::from_bits(single.to_bits() as u64);
+        unsafe {
+            asm!(
+                "vcvtss2sd {double}, {double}, {single}, {{sae}}",
+                double = inout(xmm_reg) double,
+                single = in(xmm_reg) single,
+                options(pure, nomem, nostack),
+            );
+        }
+        double
+    }
+}
+
+/// Calls `$op` with the immediate `$imm` as its constant parameter, as the
+/// instructions encode it, matching the four bits of a nibble or the eight of
+/// a byte.
+macro_rules! with_imm {
+    (nibble $imm:expr, $op:ident $args:tt) => {{
+        let imm: u8 = $imm;
+        with_imm!(@low imm, 0, $op $args; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15)
+    }};
+    (byte $imm:expr, $op:ident $args:tt) => {{
+        let imm: u8 = $imm;
+        match imm >> 4 {
+            0 => with_imm!(@high imm, 0, $op $args),
+            1 => with_imm!(@high imm, 1, $op $args),
+            2 => with_imm!(@high imm, 2, $op $args),
+            3 => with_imm!(@high imm, 3, $op $args),
+            4 => with_imm!(@high imm, 4, $op $args),
+            5 => with_imm!(@high imm, 5, $op $args),
+            6 => with_imm!(@high imm, 6, $op $args),
+            7 => with_imm!(@high imm, 7, $op $args),
+            8 => with_imm!(@high imm, 8, $op $args),
+            9 => with_imm!(@high imm, 9, $op $args),
+            10 => with_imm!(@high imm, 10, $op $args),
+            11 => with_imm!(@high imm, 11, $op $args),
+            12 => with_imm!(@high imm, 12, $op $args),
+            13 => with_imm!(@high imm, 13, $op $args),
+            14 => with_imm!(@high imm, 14, $op $args),
+            _ => with_imm!(@high imm, 15, $op $args),
+        }
+    }};
+    (@high $imm:ident, $high:literal, $op:ident $args:tt) => {
+        with_imm!(@low $imm, $high, $op $args; 0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15)
+    };
+    (@low $imm:ident, $high:literal, $op:ident $args:tt; $($low:literal)*) => {
+        match $imm & 0xF {
+            $($low => $op::<{ $high << 4 | $low }> $args,)*
+            _ => unreachable!(),
+        }
+    };
+}
+
+/// `vfixupimm` and `vrange`, falling back to software where the CPU lacks
+/// them.
+pub(crate) mod avx512 {
+    use super::*;
+    use crate::avx512::{soft, FixupTable, RangeOp, RangeSign};
+
+    #[inline]
+    fn fixupimmss<const IMM: u8>(flags: Flags, mut dest: f32, src: f32, table: f32) -> (f32, u32) {
+        let status = host_op!(
+            flags;
+            "vfixupimmss {dest}, {src}, {table}, {imm}";
+            dest = inout(xmm_reg) dest,
+            src = in(xmm_reg) src,
+            table = in(xmm_reg) table,
+            imm = const IMM,
+        );
+        (dest, status)
+    }
+
+    #[inline]
+    fn fixupimmsd<const IMM: u8>(flags: Flags, mut dest: f64, src: f64, table: f64) -> (f64, u32) {
+        let status = host_op!(
+            flags;
+            "vfixupimmsd {dest}, {src}, {table}, {imm}";
+            dest = inout(xmm_reg) dest,
+            src = in(xmm_reg) src,
+            table = in(xmm_reg) table,
+            imm = const IMM,
+        );
+        (dest, status)
+    }
+
+    #[inline]
+    fn rangess<const IMM: u8>(flags: Flags, l: f32, r: f32) -> (f32, u32) {
+        let value;
+        let status = host_op!(
+            flags;
+            "vrangess {value}, {l}, {r}, {imm}";
+            value = out(xmm_reg) value,
+            l = in(xmm_reg) l,
+            r = in(xmm_reg) r,
+            imm = const IMM,
+        );
+        (value, status)
+    }
+
+    #[inline]
+    fn rangesd<const IMM: u8>(flags: Flags, l: f64, r: f64) -> (f64, u32) {
+        let value;
+        let status = host_op!(
+            flags;
+            "vrangesd {value}, {l}, {r}, {imm}";
+            value = out(xmm_reg) value,
+            l = in(xmm_reg) l,
+            r = in(xmm_reg) r,
+            imm = const IMM,
+        );
+        (value, status)
+    }
+
+    /// The immediate of `vrange`, the operation in its low two bits and the
+    /// sign in the next two.
+    #[inline]
+    fn range_imm(op: RangeOp, sign: RangeSign) -> u8 {
+        op as u8 | (sign as u8) << 2
+    }
+
+    #[inline]
+    pub(crate) fn f32_fixup(flags: Flags, dest: f32, src: f32, table: FixupTable) -> (f32, Status) {
+        if !has_avx512f() {
+            return soft::f32_fixup(flags, dest, src, table);
+        }
+        let bits = f32::from_bits(table.table());
+        let (value, status) = with_imm!(byte table.imm8(), fixupimmss(flags, dest, src, bits));
+        (value, Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f32_range(
+        flags: Flags,
+        l: f32,
+        r: f32,
+        op: RangeOp,
+        sign: RangeSign,
+    ) -> (f32, Status) {
+        if !has_avx512dq() {
+            return soft::f32_range(flags, l, r, op, sign);
+        }
+        let (value, status) = with_imm!(nibble range_imm(op, sign), rangess(flags, l, r));
+        (value, Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64_fixup(flags: Flags, dest: f64, src: f64, table: FixupTable) -> (f64, Status) {
+        if !has_avx512f() {
+            return soft::f64_fixup(flags, dest, src, table);
+        }
+        let bits = f64::from_bits(table.table() as u64);
+        let (value, status) = with_imm!(byte table.imm8(), fixupimmsd(flags, dest, src, bits));
+        (value, Status { inner: status })
+    }
+
+    #[inline]
+    pub(crate) fn f64_range(
+        flags: Flags,
+        l: f64,
+        r: f64,
+        op: RangeOp,
+        sign: RangeSign,
+    ) -> (f64, Status) {
+        if !has_avx512dq() {
+            return soft::f64_range(flags, l, r, op, sign);
+        }
+        let (value, status) = with_imm!(nibble range_imm(op, sign), rangesd(flags, l, r));
+        (value, Status { inner: status })
+    }
+}
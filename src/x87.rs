@@ -0,0 +1,614 @@
+//! The architectural state of an x87 FPU, for emulators: the register
+//! stack with its tags, and the control and status words, driven by the
+//! `f80` operations, which take the rounding mode and precision control of
+//! the control word.
+//!
+//! Instructions run as the hardware runs them. Stack overflows and
+//! underflows raise invalid with the stack fault bit, delivering the default
+//! NaN when invalid is masked, and an unmasked invalid, denormal or
+//! divide-by-zero exception leaves the stack as it was, as does an unmasked
+//! overflow or underflow storing to memory. Any unmasked exception sets the
+//! error summary and busy bits for the emulator to raise `#MF` at the next
+//! waiting instruction. C1 reports the direction of stack faults, and
+//! otherwise whether an inexact result was rounded up in magnitude, which
+//! takes running the operation again towards zero.
+
+pub use crate::f80::Precision;
+
+use core::cmp::Ordering;
+
+use crate::direction;
+use crate::f80::{self, Accuracy, F80};
+use crate::{Flags, Rounding, Status, Tininess};
+
+const INVALID: u16 = 1 << 0;
+const DENORM: u16 = 1 << 1;
+const DIV_ZERO: u16 = 1 << 2;
+const OVERFLOW: u16 = 1 << 3;
+const UNDERFLOW: u16 = 1 << 4;
+const EXCEPTIONS: u16 = 0x3F;
+const STACK_FAULT: u16 = 1 << 6;
+const ERROR_SUMMARY: u16 = 1 << 7;
+const C0: u16 = 1 << 8;
+const C1: u16 = 1 << 9;
+const C2: u16 = 1 << 10;
+const TOP_SHIFT: u32 = 11;
+const TOP_MASK: u16 = 7 << TOP_SHIFT;
+const C3: u16 = 1 << 14;
+const BUSY: u16 = 1 << 15;
+
+const CF: u32 = 1 << 0;
+const PF: u32 = 1 << 2;
+const ZF: u32 = 1 << 6;
+
+/// The condition codes a comparison sets, in their places in the status
+/// word: none for greater, C0 for less, C3 for equal and C3, C2 and C0 for
+/// unordered.
+#[inline]
+pub fn condition_codes(ordering: Option<Ordering>) -> u16 {
+    match ordering {
+        Some(Ordering::Greater) => 0,
+        Some(Ordering::Less) => C0,
+        Some(Ordering::Equal) => C3,
+        None => C3 | C2 | C0,
+    }
+}
+
+/// The EFLAGS bits `fcomi` sets, as ZF, PF and CF in the pattern of C3, C2
+/// and C0 in `condition_codes`.
+#[inline]
+pub fn eflags(ordering: Option<Ordering>) -> u32 {
+    match ordering {
+        Some(Ordering::Greater) => 0,
+        Some(Ordering::Less) => CF,
+        Some(Ordering::Equal) => ZF,
+        None => ZF | PF | CF,
+    }
+}
+
+/// The tag of a register.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tag {
+    Valid = 0,
+    Zero = 1,
+    /// A NaN, infinity, denormal or unsupported encoding.
+    Special = 2,
+    Empty = 3,
+}
+
+/// An x87 FPU.
+#[derive(Clone)]
+pub struct Fpu {
+    // Indexed by physical register, which `st(i)` maps onto from `top`.
+    regs: [F80; 8],
+    // One bit per physical register.
+    empty: u8,
+    control: u16,
+    status: u16,
+}
+
+impl Default for Fpu {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fpu {
+    /// The state `fninit` leaves: an empty stack, every exception masked,
+    /// rounding to nearest at extended precision.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            regs: [F80::ZERO; 8],
+            empty: 0xFF,
+            control: 0x037F,
+            status: 0,
+        }
+    }
+
+    #[inline]
+    pub fn control_word(&self) -> u16 {
+        self.control
+    }
+
+    /// Loads the control word, as `fldcw` does, which raises the error
+    /// summary for pending exceptions it unmasks.
+    #[inline]
+    pub fn set_control_word(&mut self, control: u16) {
+        // The reserved bit 6 always reads as set.
+        self.control = control | 0x0040;
+        self.summarize();
+    }
+
+    #[inline]
+    pub fn status_word(&self) -> u16 {
+        self.status
+    }
+
+    #[inline]
+    pub fn set_status_word(&mut self, status: u16) {
+        self.status = status;
+    }
+
+    /// The full tag word, as `fstenv` stores it, classifying every register
+    /// that isn't empty.
+    pub fn tag_word(&self) -> u16 {
+        (0..8).fold(0, |tags, reg| tags | (self.tag(reg) as u16) << (2 * reg))
+    }
+
+    /// Loads the tag word, as `fldenv` does, which only takes which registers
+    /// are empty from it.
+    #[inline]
+    pub fn set_tag_word(&mut self, tags: u16) {
+        self.empty = (0..8).fold(0, |empty, reg| {
+            empty | ((tags >> (2 * reg) & 3 == Tag::Empty as u16) as u8) << reg
+        });
+    }
+
+    /// The abridged tag word `fxsave` stores, with a bit set for every
+    /// register that isn't empty.
+    #[inline]
+    pub fn abridged_tag_word(&self) -> u8 {
+        !self.empty
+    }
+
+    #[inline]
+    pub fn set_abridged_tag_word(&mut self, tags: u8) {
+        self.empty = !tags;
+    }
+
+    /// The tag of the physical register `reg`, which `st(i)` is when
+    /// `reg == (top + i) % 8`.
+    pub fn tag(&self, reg: usize) -> Tag {
+        let x = self.regs[reg & 7];
+        let biased = x.sign_exp & 0x7FFF;
+        if self.empty >> (reg & 7) & 1 != 0 {
+            Tag::Empty
+        } else if biased == 0 && x.sig == 0 {
+            Tag::Zero
+        } else if biased == 0 || biased == 0x7FFF || x.is_unsupported() {
+            Tag::Special
+        } else {
+            Tag::Valid
+        }
+    }
+
+    /// The physical register that is `st(0)`.
+    #[inline]
+    pub fn top(&self) -> usize {
+        (self.status >> TOP_SHIFT & 7) as usize
+    }
+
+    #[inline]
+    fn set_top(&mut self, top: usize) {
+        self.status = self.status & !TOP_MASK | ((top & 7) as u16) << TOP_SHIFT;
+    }
+
+    #[inline]
+    fn reg(&self, i: usize) -> usize {
+        (self.top() + i) & 7
+    }
+
+    /// The rounding mode of the control word.
+    pub fn rounding(&self) -> Rounding {
+        match self.control >> 10 & 3 {
+            0 => Rounding::Nearest,
+            1 => Rounding::Down,
+            2 => Rounding::Up,
+            _ => Rounding::Zero,
+        }
+    }
+
+    /// The precision control of the control word, where the reserved
+    /// encoding acts as extended.
+    pub fn precision(&self) -> Precision {
+        match self.control >> 8 & 3 {
+            0 => Precision::Single,
+            2 => Precision::Double,
+            _ => Precision::Extended,
+        }
+    }
+
+    /// The flags operations take under the control word.
+    #[inline]
+    pub fn flags(&self) -> Flags {
+        Flags::new()
+            .with_rounding(self.rounding())
+            .with_tininess(Tininess::AfterRounding)
+    }
+
+    /// Whether an unmasked exception is pending.
+    #[inline]
+    pub fn exception_pending(&self) -> bool {
+        self.status & ERROR_SUMMARY != 0
+    }
+
+    /// Clears the exceptions, the stack fault and the busy bit, as `fnclex`
+    /// does.
+    #[inline]
+    pub fn clear_exceptions(&mut self) {
+        self.status &= !(EXCEPTIONS | STACK_FAULT | ERROR_SUMMARY | BUSY);
+    }
+
+    #[inline]
+    fn summarize(&mut self) {
+        if self.status & !self.control & EXCEPTIONS != 0 {
+            self.status |= ERROR_SUMMARY | BUSY;
+        } else {
+            self.status &= !(ERROR_SUMMARY | BUSY);
+        }
+    }
+
+    /// Records the exceptions of `status` in the status word.
+    #[inline]
+    pub fn raise(&mut self, status: Status) {
+        self.status |= f80::status_word(status);
+        self.summarize();
+    }
+
+    /// Records `status` for an instruction, with C1 set if `rounded_up`,
+    /// returning whether it completes, which it doesn't for unmasked invalid,
+    /// denormal and divide-by-zero exceptions.
+    #[inline]
+    fn complete(&mut self, status: Status, rounded_up: bool) -> bool {
+        self.status = self.status & !C1 | if rounded_up { C1 } else { 0 };
+        self.raise(status);
+        f80::status_word(status) & !self.control & (INVALID | DENORM | DIV_ZERO) == 0
+    }
+
+    /// Runs `op` under the flags of the control word, returning also whether
+    /// it rounded the result up in magnitude.
+    #[inline]
+    fn run<T: PartialEq>(&self, op: impl Fn(Flags) -> (T, Status)) -> (T, Status, bool) {
+        let flags = self.flags();
+        let (value, status) = op(flags);
+        let rounded_up = direction::away_from_zero(flags, status, &value, || {
+            op(flags.with_rounding(Rounding::Zero)).0
+        });
+        (value, status, rounded_up)
+    }
+
+    /// Raises a stack overflow or underflow, returning whether invalid is
+    /// masked so the instruction delivers the default NaN.
+    #[inline]
+    fn stack_fault(&mut self, overflow: bool) -> bool {
+        self.status = self.status & !C1 | INVALID | STACK_FAULT | if overflow { C1 } else { 0 };
+        self.summarize();
+        self.control & INVALID != 0
+    }
+
+    /// Whether `st(i)` is empty.
+    #[inline]
+    pub fn is_empty(&self, i: usize) -> bool {
+        self.empty >> self.reg(i) & 1 != 0
+    }
+
+    /// The value of `st(i)`, whatever its tag.
+    #[inline]
+    pub fn st(&self, i: usize) -> F80 {
+        self.regs[self.reg(i)]
+    }
+
+    /// Writes `st(i)`, marking it full.
+    #[inline]
+    pub fn set_st(&mut self, i: usize, value: F80) {
+        let reg = self.reg(i);
+        self.regs[reg] = value;
+        self.empty &= !(1 << reg);
+    }
+
+    /// Marks `st(i)` empty, as `ffree` does.
+    #[inline]
+    pub fn free(&mut self, i: usize) {
+        self.empty |= 1 << self.reg(i);
+    }
+
+    /// Rotates the stack so `st(1)` becomes `st(0)`, as `fincstp` does,
+    /// without touching the tags.
+    #[inline]
+    pub fn increment_top(&mut self) {
+        self.status &= !C1;
+        self.set_top(self.top() + 1);
+    }
+
+    /// Rotates the stack the other way, as `fdecstp` does.
+    #[inline]
+    pub fn decrement_top(&mut self) {
+        self.status &= !C1;
+        self.set_top(self.top() + 7);
+    }
+
+    /// Pushes `value` without raising anything, overwriting `st(7)` if it is
+    /// full.
+    #[inline]
+    pub fn push(&mut self, value: F80) {
+        self.set_top(self.top() + 7);
+        self.set_st(0, value);
+    }
+
+    /// Pops `st(0)` without raising anything, whether or not it is empty.
+    #[inline]
+    pub fn pop(&mut self) -> F80 {
+        let value = self.st(0);
+        self.free(0);
+        self.set_top(self.top() + 1);
+        value
+    }
+
+    /// Pushes the result of loading a value, as `fld` does, as in
+    /// `fpu.load(f80::from_double(fpu.flags(), x))`.
+    pub fn load(&mut self, (value, status): (F80, Status)) {
+        if !self.is_empty(7) {
+            if self.stack_fault(true) {
+                self.push(F80::NAN);
+            }
+        } else if self.complete(status, false) {
+            self.push(value);
+        }
+    }
+
+    /// Pushes a copy of `st(i)`, as `fld st(i)` does.
+    pub fn load_st(&mut self, i: usize) {
+        if self.is_empty(i) {
+            if self.stack_fault(false) && self.is_empty(7) {
+                self.push(F80::NAN);
+            }
+        } else {
+            self.load((self.st(i), Status::empty()));
+        }
+    }
+
+    /// Stores `op(st(0))`, popping it if `pop`, as `fst` and `fstp` do to
+    /// memory, as in `fpu.store(true, f80::to_double)`. Returns `None` if the
+    /// instruction doesn't complete, when nothing is to be stored, which an
+    /// unmasked overflow or underflow also leaves.
+    pub fn store<T: PartialEq>(
+        &mut self,
+        pop: bool,
+        op: impl Fn(Flags, F80) -> (T, Status),
+    ) -> Option<T> {
+        let value = if self.is_empty(0) {
+            if !self.stack_fault(false) {
+                return None;
+            }
+            op(self.flags(), F80::NAN).0
+        } else {
+            let x = self.st(0);
+            let (value, status, rounded_up) = self.run(|flags| op(flags, x));
+            if f80::status_word(status) & !self.control & (OVERFLOW | UNDERFLOW) != 0 {
+                // Nothing is written, so nothing is reported as rounded.
+                let unrounded = Status::INVALID
+                    .or(Status::DENORM)
+                    .or(Status::DIV_ZERO)
+                    .or(Status::OVERFLOW)
+                    .or(Status::UNDERFLOW);
+                self.complete(status.and(unrounded), false);
+                return None;
+            }
+            if !self.complete(status, rounded_up) {
+                return None;
+            }
+            value
+        };
+        if pop {
+            self.pop();
+        }
+        Some(value)
+    }
+
+    /// Exchanges `st(0)` with `st(i)`, as `fxch` does, where an empty
+    /// operand reads as the default NaN.
+    pub fn exchange(&mut self, i: usize) {
+        if self.is_empty(0) || self.is_empty(i) {
+            if !self.stack_fault(false) {
+                return;
+            }
+            for j in [0, i] {
+                if self.is_empty(j) {
+                    self.set_st(j, F80::NAN);
+                }
+            }
+        } else {
+            self.status &= !C1;
+        }
+        let (a, b) = (self.st(0), self.st(i));
+        self.set_st(0, b);
+        self.set_st(i, a);
+    }
+
+    /// Replaces `st(i)` with `op(st(i))`, as `fsqrt` does to `st(0)` with
+    /// `f80::sqrt`, and `frndint` with `|flags, _, x| f80::round(flags, x)`.
+    pub fn unary(&mut self, i: usize, op: impl Fn(Flags, Precision, F80) -> (F80, Status)) {
+        if self.is_empty(i) {
+            if self.stack_fault(false) {
+                self.set_st(i, F80::NAN);
+            }
+            return;
+        }
+        let (precision, x) = (self.precision(), self.st(i));
+        let (value, status, rounded_up) = self.run(|flags| op(flags, precision, x));
+        if self.complete(status, rounded_up) {
+            self.set_st(i, value);
+        }
+    }
+
+    /// Replaces `st(dst)` with `op(st(dst), st(src))`, popping the stack
+    /// after if `pop`, as in `fpu.binary(i, 0, true, f80::sub)` for
+    /// `fsubp st(i), st`.
+    pub fn binary(
+        &mut self,
+        dst: usize,
+        src: usize,
+        pop: bool,
+        op: impl Fn(Flags, Precision, F80, F80) -> (F80, Status),
+    ) {
+        if self.is_empty(dst) || self.is_empty(src) {
+            if !self.stack_fault(false) {
+                return;
+            }
+            self.set_st(dst, F80::NAN);
+        } else {
+            let (precision, l, r) = (self.precision(), self.st(dst), self.st(src));
+            let (value, status, rounded_up) = self.run(|flags| op(flags, precision, l, r));
+            if !self.complete(status, rounded_up) {
+                return;
+            }
+            self.set_st(dst, value);
+        }
+        if pop {
+            self.pop();
+        }
+    }
+
+    /// Replaces `st(0)` with `op(st(0), value)` for an operand loaded from
+    /// memory, as in `fpu.binary_load(f80::from_double(fpu.flags(), x),
+    /// f80::add)` for `fadd m64`.
+    pub fn binary_load(
+        &mut self,
+        (value, status): (F80, Status),
+        op: impl Fn(Flags, Precision, F80, F80) -> (F80, Status),
+    ) {
+        if self.is_empty(0) {
+            if self.stack_fault(false) {
+                self.set_st(0, F80::NAN);
+            }
+            return;
+        }
+        if !self.complete(status, false) {
+            return;
+        }
+        let (precision, l) = (self.precision(), self.st(0));
+        let (result, status, rounded_up) = self.run(|flags| op(flags, precision, l, value));
+        if self.complete(status, rounded_up) {
+            self.set_st(0, result);
+        }
+    }
+
+    /// The ordering of `st(0)` against `r`, or `None` if the comparison
+    /// doesn't complete, where a stack underflow is unordered.
+    fn ordering(&mut self, r: Option<F80>, quiet: bool) -> Option<Option<Ordering>> {
+        let Some(r) = r.filter(|_| !self.is_empty(0)) else {
+            return self.stack_fault(false).then_some(None);
+        };
+        let (ordering, status) = if quiet {
+            f80::compare_quiet(self.st(0), r)
+        } else {
+            f80::compare(self.st(0), r)
+        };
+        self.complete(status, false).then_some(ordering)
+    }
+
+    /// Compares `st(0)` with `st(i)` into the condition codes, popping the
+    /// stack `pops` times after, as `fcom`, `fcomp` and `fcompp` do, or
+    /// `fucom` and the like with `quiet`.
+    pub fn compare(&mut self, i: usize, quiet: bool, pops: usize) {
+        let r = (!self.is_empty(i)).then(|| self.st(i));
+        if let Some(ordering) = self.ordering(r, quiet) {
+            self.set_condition(condition_codes(ordering));
+            for _ in 0..pops {
+                self.pop();
+            }
+        }
+    }
+
+    /// Compares `st(0)` with an operand loaded from memory, popping the stack
+    /// after if `pop`, as `fcom m64` does with `f80::from_double`, or `ftst`
+    /// with `(F80::ZERO, Status::empty())`.
+    pub fn compare_load(&mut self, (value, status): (F80, Status), quiet: bool, pop: bool) {
+        if !self.is_empty(0) && !self.complete(status, false) {
+            return;
+        }
+        if let Some(ordering) = self.ordering(Some(value), quiet) {
+            self.set_condition(condition_codes(ordering));
+            if pop {
+                self.pop();
+            }
+        }
+    }
+
+    /// Compares `st(0)` with `st(i)`, popping the stack after if `pop`, as
+    /// `fcomi` and `fcomip` do, or `fucomi` and `fucomip` with `quiet`.
+    /// Returns the ZF, PF and CF bits to set in EFLAGS, clearing the rest of
+    /// them, or `None` if the comparison doesn't complete, when EFLAGS keep
+    /// their values.
+    pub fn compare_eflags(&mut self, i: usize, quiet: bool, pop: bool) -> Option<u32> {
+        let r = (!self.is_empty(i)).then(|| self.st(i));
+        let ordering = self.ordering(r, quiet)?;
+        if pop {
+            self.pop();
+        }
+        Some(eflags(ordering))
+    }
+
+    /// Replaces `st(0)` with `op(st(0))` for a trigonometric function,
+    /// pushing one after if `push_one`, or sets C2 if `op` leaves the operand
+    /// out of range.
+    fn trig(&mut self, push_one: bool, op: impl Fn(Flags, F80) -> Option<(F80, Status)>) {
+        if self.is_empty(0) || push_one && !self.is_empty(7) {
+            if self.stack_fault(!self.is_empty(0)) {
+                self.set_st(0, F80::NAN);
+                if push_one {
+                    self.push(F80::NAN);
+                }
+            }
+            return;
+        }
+        let (flags, x) = (self.flags(), self.st(0));
+        match op(flags, x) {
+            None => self.status = self.status & !C1 | C2,
+            Some((value, status)) => {
+                self.status &= !C2;
+                let rounded_up = direction::away_from_zero(flags, status, &value, || {
+                    op(flags.with_rounding(Rounding::Zero), x).map_or(value, |(value, _)| value)
+                });
+                if self.complete(status, rounded_up) {
+                    self.set_st(0, value);
+                    // A NaN result takes the place of the one.
+                    if push_one {
+                        self.push(if value.is_nan() { value } else { F80::ONE });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces `st(0)` with its sine, as `fsin` does.
+    pub fn sin(&mut self, accuracy: Accuracy) {
+        self.trig(false, |flags, x| f80::sin(flags, accuracy, x));
+    }
+
+    /// Replaces `st(0)` with its cosine, as `fcos` does.
+    pub fn cos(&mut self, accuracy: Accuracy) {
+        self.trig(false, |flags, x| f80::cos(flags, accuracy, x));
+    }
+
+    /// Replaces `st(0)` with its tangent and pushes one, as `fptan` does.
+    pub fn tan(&mut self, accuracy: Accuracy) {
+        self.trig(true, |flags, x| f80::tan(flags, accuracy, x));
+    }
+
+    /// Replaces `st(1)` with the angle of `(st(0), st(1))` and pops the
+    /// stack, as `fpatan` does.
+    pub fn atan2(&mut self) {
+        self.binary(1, 0, true, |flags, _, y, x| f80::atan2(flags, y, x));
+    }
+
+    /// Replaces `st(0)` with `2^st(0) - 1`, as `f2xm1` does.
+    pub fn exp2_m1(&mut self) {
+        self.unary(0, |flags, _, x| f80::exp2_m1(flags, x));
+    }
+
+    /// Replaces `st(1)` with `st(1) * log2(st(0))` and pops the stack, as
+    /// `fyl2x` does.
+    pub fn y_log2_x(&mut self) {
+        self.binary(1, 0, true, |flags, _, y, x| f80::y_log2_x(flags, y, x));
+    }
+
+    /// Sets the condition codes C0, C2 and C3 from `codes`, whose bits are
+    /// in their places in the status word, clearing C1.
+    #[inline]
+    pub fn set_condition(&mut self, codes: u16) {
+        self.status = self.status & !(C0 | C1 | C2 | C3) | codes & (C0 | C2 | C3);
+    }
+}
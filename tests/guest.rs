@@ -0,0 +1,77 @@
+#![cfg(feature = "guest")]
+
+use sysfp::guest::{ppc, sh4, sparc};
+use sysfp::GuestFpu;
+
+#[test]
+fn sh4_propagates_quiet_nans() {
+    let mut fpu = GuestFpu::<sh4::Sh4>::new(0);
+    let quiet = f32::from_bits(0x7FBF_FFFF);
+    assert_eq!(fpu.f32_add(quiet, 1.0).to_bits(), 0x7FBF_FFFF);
+    assert_eq!(fpu.registers() & (sh4::CAUSE | sh4::FLAGS), 0);
+    let quiet = f64::from_bits(0x7FF0_0000_0000_0001);
+    assert_eq!(fpu.f64_mul(2.0, quiet).to_bits(), 0x7FF0_0000_0000_0001);
+    assert_eq!(fpu.registers() & (sh4::CAUSE | sh4::FLAGS), 0);
+}
+
+#[test]
+fn sh4_default_nans() {
+    let mut fpu = GuestFpu::<sh4::Sh4>::new(0);
+    let inf = f32::INFINITY;
+    assert_eq!(fpu.f32_sub(inf, inf).to_bits(), 0x7FBF_FFFF);
+    assert_eq!(fpu.registers() >> sh4::CAUSE_SHIFT & sh4::V, sh4::V);
+    let signaling = f32::from_bits(0x7FC0_0000);
+    let mut fpu = GuestFpu::<sh4::Sh4>::new(0);
+    assert_eq!(fpu.f32_add(signaling, 1.0).to_bits(), 0x7FBF_FFFF);
+    assert_eq!(fpu.registers() >> sh4::FLAGS_SHIFT & sh4::V, sh4::V);
+    assert_eq!(fpu.f64_sqrt(-1.0).to_bits(), 0x7FF7_FFFF_FFFF_FFFF);
+}
+
+#[test]
+fn sparc_propagates_quiet_nans() {
+    let mut fpu = GuestFpu::<sparc::Sparc>::new(0);
+    let (first, second) = (f32::from_bits(0x7FC0_0001), f32::from_bits(0xFFC0_0002));
+    assert_eq!(fpu.f32_add(first, second).to_bits(), 0xFFC0_0002);
+    assert_eq!(fpu.f32_add(first, 1.0).to_bits(), 0x7FC0_0001);
+    assert_eq!(fpu.registers() & (sparc::CEXC | sparc::AEXC), 0);
+    let signaling = f64::from_bits(0x7FF0_0000_0000_0001);
+    let quiet = f64::from_bits(0x7FF8_0000_0000_0002);
+    assert_eq!(
+        fpu.f64_add(signaling, quiet).to_bits(),
+        0x7FF8_0000_0000_0001
+    );
+    assert_eq!(fpu.registers() & sparc::NV, sparc::NV);
+}
+
+#[test]
+fn sparc_default_nans() {
+    let mut fpu = GuestFpu::<sparc::Sparc>::new(0);
+    let inf = f32::INFINITY;
+    assert_eq!(fpu.f32_sub(inf, inf).to_bits(), 0x7FFF_FFFF);
+    assert_eq!(fpu.f64_sqrt(-1.0).to_bits(), 0x7FFF_FFFF_FFFF_FFFF);
+    assert_eq!(fpu.registers() >> sparc::AEXC_SHIFT & sparc::NV, sparc::NV);
+}
+
+#[test]
+fn ppc_default_nans() {
+    let mut fpu = GuestFpu::<ppc::PowerPc>::new(0);
+    let inf = f64::INFINITY;
+    assert_eq!(fpu.f64_sub(inf, inf).to_bits(), 0x7FF8_0000_0000_0000);
+    assert_eq!(fpu.registers() & ppc::VXISI, ppc::VXISI);
+    assert_eq!(fpu.f64_sqrt(-1.0).to_bits(), 0x7FF8_0000_0000_0000);
+    assert_eq!(
+        fpu.f32_sub(f32::INFINITY, f32::INFINITY).to_bits(),
+        0x7FC0_0000
+    );
+}
+
+#[cfg(feature = "fma")]
+#[test]
+fn ppc_madd_propagates_the_addend_nan() {
+    let mut fpu = GuestFpu::<ppc::PowerPc>::new(0);
+    let (c, b) = (
+        f64::from_bits(0x7FF8_0000_0000_0001),
+        f64::from_bits(0x7FF8_0000_0000_0002),
+    );
+    assert_eq!(fpu.f64_madd(1.0, c, b).to_bits(), 0x7FF8_0000_0000_0002);
+}
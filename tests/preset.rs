@@ -0,0 +1,35 @@
+use sysfp::preset::{self, Preset};
+use sysfp::{Flags, Rounding};
+
+#[test]
+fn invalid_conversions_follow_the_architecture() {
+    let flags = Flags::new();
+    let nan = f64::NAN;
+    assert_eq!(preset::f64::to_i32(flags, Preset::X86, nan).0, i32::MIN);
+    assert_eq!(preset::f64::to_i32(flags, Preset::X86, 1e10).0, i32::MIN);
+    assert_eq!(preset::f64::to_u32(flags, Preset::X86, -1.0).0, u32::MAX);
+    assert_eq!(preset::f64::to_i32(flags, Preset::ARMV8, nan).0, 0);
+    assert_eq!(preset::f64::to_i32(flags, Preset::ARMV8, 1e10).0, i32::MAX);
+    assert_eq!(preset::f64::to_i64(flags, Preset::RISCV, nan).0, i64::MAX);
+    assert_eq!(preset::f64::to_u64(flags, Preset::RISCV, -1.0).0, 0);
+    assert_eq!(
+        preset::f32::to_i32(flags, Preset::POWER, f32::NAN).0,
+        i32::MIN
+    );
+    assert_eq!(preset::f32::to_u32(flags, Preset::POWER, f32::NAN).0, 0);
+    let (value, status) = preset::f64::to_i64(flags, Preset::S390X, -f64::INFINITY);
+    assert_eq!(value, i64::MIN);
+    assert!(status.invalid() && !status.inexact());
+}
+
+#[test]
+fn conversions_round_as_the_flags_select() {
+    let flags = Flags::new();
+    let (value, status) = preset::f64::to_i32(flags, Preset::X86, 2.5);
+    assert_eq!(value, 2);
+    assert!(status.inexact() && !status.invalid());
+    let flags = flags.with_rounding(Rounding::Zero);
+    assert_eq!(preset::f64::to_i32(flags, Preset::X86, -2.5).0, -2);
+    assert_eq!(preset::f32::to_u32(flags, Preset::ARMV8, -0.5).0, 0);
+    assert!(!preset::f32::to_u32(flags, Preset::ARMV8, -0.5).1.invalid());
+}
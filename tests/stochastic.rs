@@ -0,0 +1,15 @@
+#![cfg(feature = "stochastic")]
+
+use sysfp::{stochastic, Flags};
+
+#[test]
+fn overflow_is_inexact() {
+    let flags = Flags::new();
+    let mut rng = || 0;
+    let (value, status) = stochastic::f32::mul(flags, &mut rng, f32::MAX, 4.0);
+    assert!(value.is_infinite());
+    assert!(status.overflow() && status.inexact());
+    let (value, status) = stochastic::f64::mul(flags, &mut rng, f64::MAX, 4.0);
+    assert!(value.is_infinite());
+    assert!(status.overflow() && status.inexact());
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "convert")]
+
+use sysfp::f80::{self, F80};
+use sysfp::x87::Fpu;
+
+const C1: u16 = 1 << 9;
+
+#[test]
+fn c1_reports_rounding_up() {
+    let mut fpu = Fpu::new();
+    fpu.load(f80::from_double(fpu.flags(), 1.0));
+    fpu.load(f80::from_double(fpu.flags(), 3.0));
+    fpu.binary(1, 0, true, f80::div);
+    assert_eq!(fpu.status_word() & C1, C1);
+    fpu.load(f80::from_double(fpu.flags(), 2.0));
+    fpu.binary(1, 0, true, f80::mul);
+    assert_eq!(fpu.status_word() & C1, 0);
+}
+
+#[test]
+fn unmasked_overflow_leaves_the_store() {
+    let mut fpu = Fpu::new();
+    fpu.set_control_word(0x0377);
+    fpu.push(F80 {
+        sign_exp: 0x4400,
+        sig: 1 << 63,
+    });
+    assert!(fpu.store(true, f80::to_single).is_none());
+    assert!(!fpu.is_empty(0));
+    assert_eq!(fpu.status_word() & 0x3F, 1 << 3);
+}